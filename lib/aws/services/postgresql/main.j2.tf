@@ -28,6 +28,34 @@ data "aws_security_group" "selected" {
   }
 }
 
+{%- if publicly_accessible %}
+# workers security group is open to any host, a publicly accessible instance only gets its own one instead
+resource "aws_security_group" "allowed_cidrs" {
+  name = "${var.postgresql_identifier}-allowed-cidrs"
+  description = "Allowed CIDRs of ${var.postgresql_identifier}"
+  vpc_id = data.aws_vpc.selected.id
+
+  ingress {
+    description = "Kubernetes workers"
+    from_port = var.port
+    to_port = var.port
+    protocol = "tcp"
+    security_groups = [data.aws_security_group.selected.id]
+  }
+
+  ingress {
+    description = "Allowed CIDRs"
+    from_port = var.port
+    to_port = var.port
+    protocol = "tcp"
+    cidr_blocks = [for cidr in var.allowed_cidrs : cidr if length(regexall(":", cidr)) == 0]
+    ipv6_cidr_blocks = [for cidr in var.allowed_cidrs : cidr if length(regexall(":", cidr)) > 0]
+  }
+
+  tags = local.postgres_database_tags
+}
+{%- endif %}
+
 data "aws_iam_role" "rds_enhanced_monitoring" {
   name = "qovery-rds-enhanced-monitoring-${var.kubernetes_cluster_id}"
 }
@@ -85,6 +113,9 @@ resource "aws_db_instance" "postgresql_instance" {
   allocated_storage = var.disk_size
   name = var.database_name
   storage_type = var.storage_type
+  {%- if database_disk_iops %}
+  iops = {{ database_disk_iops }}
+  {%- endif %}
   username = var.username
   engine_version = var.postgresql_version
   engine = "postgres"
@@ -93,7 +124,11 @@ resource "aws_db_instance" "postgresql_instance" {
 
   # Network
   db_subnet_group_name = data.aws_subnet_ids.k8s_subnet_ids.id
+  {%- if publicly_accessible %}
+  vpc_security_group_ids = [aws_security_group.allowed_cidrs.id]
+  {%- else %}
   vpc_security_group_ids = data.aws_security_group.selected.*.id
+  {%- endif %}
   publicly_accessible = var.publicly_accessible
   multi_az = var.multi_az
 
@@ -12,6 +12,12 @@ variable "port" {
   type = number
 }
 
+variable "allowed_cidrs" {
+  description = "CIDRs allowed to reach the instance when publicly accessible"
+  default = {{ allowed_cidrs | json_encode() }}
+  type = list(string)
+}
+
 variable "disk_size" {
   description = "disk instance size"
   default = "{{ database_disk_size_in_gib }}"
@@ -20,8 +20,8 @@ provider "scaleway" {
   access_key      = "{{ scaleway_access_key }}"
   secret_key      = "{{ scaleway_secret_key }}"
   project_id	  = "{{ scaleway_project_id }}"
-  zone            = "{{ zone }}"
-  region          = "{{ region }}"
+  zone            = "{{ database_zone | default(value=zone) }}"
+  region          = "{{ database_region | default(value=region) }}"
 }
 
 data "scaleway_k8s_cluster" "kubernetes_cluster" {
@@ -8,13 +8,13 @@ variable "cluster_name" {
 
 variable "region" {
   description = "SCW region to store terraform state and lock"
-  default     = "{{ region }}"
+  default     = "{{ database_region | default(value=region) }}"
   type        = string
 }
 
 variable "zone" {
   description = "SCW zone to store terraform state and lock"
-  default     = "{{ zone }}"
+  default     = "{{ database_zone | default(value=zone) }}"
   type        = string
 }
 
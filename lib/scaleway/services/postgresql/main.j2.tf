@@ -16,11 +16,12 @@ locals {
 {%- if publicly_accessible != false %}
 resource "scaleway_rdb_acl" "main" {
   instance_id = scaleway_rdb_instance.postgresql_instance.id
-  # TODO(benjaminch): Allow only Scaleway's private traffic
+  {%- for allowed_cidr in allowed_cidrs %}
   acl_rules {
-    ip = "0.0.0.0/0"
-    description = "accessible from any host"
+    ip = "{{ allowed_cidr }}"
+    description = "allowed CIDR"
   }
+  {%- endfor %}
   depends_on = [
     scaleway_rdb_instance.postgresql_instance
   ]
@@ -28,6 +29,8 @@ resource "scaleway_rdb_acl" "main" {
 {% else %}
 resource "scaleway_rdb_acl" "main" {
   instance_id = scaleway_rdb_instance.postgresql_instance.id
+  # TODO(benjaminch): Allow only Scaleway's private traffic
+  # autoscaled Kapsule workers reach the instance through its public endpoint, without any fixed CIDR to restrict to
   acl_rules {
     ip = "0.0.0.0/0"
     description = "accessible from any host"
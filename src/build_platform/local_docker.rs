@@ -13,7 +13,7 @@ use crate::build_platform::{Build, BuildError, BuildPlatform, Credentials, Kind}
 use crate::cmd::command;
 use crate::cmd::command::CommandError::Killed;
 use crate::cmd::command::{CommandKiller, QoveryCommand};
-use crate::cmd::docker::{BuildResult, ContainerImage, DockerError};
+use crate::cmd::docker::{BuildCacheBackend, BuildResult, ContainerImage, DockerError};
 use crate::events::{EngineEvent, EventMessage, ToTransmitter, Transmitter};
 use crate::fs::workspace_directory;
 use crate::git;
@@ -23,6 +23,13 @@ use crate::io_models::{
 use crate::logger::Logger;
 
 const BUILD_DURATION_TIMEOUT_SEC: u64 = 30 * 60;
+/// Overrides the build duration timeout, in seconds (set by the qovery-engine chart).
+pub const BUILD_DURATION_TIMEOUT_ENV_VAR: &str = "QOVERY_BUILD_TIMEOUT_SECONDS";
+
+/// Build cache backend, `local` or `registry` (set by the qovery-engine chart).
+pub const BUILD_CACHE_BACKEND_ENV_VAR: &str = "QOVERY_BUILD_CACHE_BACKEND";
+/// Image reference the build cache is pushed to and pulled from, with the `registry` backend.
+pub const BUILD_CACHE_REGISTRY_REF_ENV_VAR: &str = "QOVERY_BUILD_CACHE_REGISTRY_REF";
 
 /// https://buildpacks.io/
 const BUILDPACKS_BUILDERS: [&str; 1] = [
@@ -32,6 +39,45 @@ const BUILDPACKS_BUILDERS: [&str; 1] = [
     //"paketobuildpacks/builder:base",
 ];
 
+fn build_duration_timeout() -> Duration {
+    let timeout_sec = env::var(BUILD_DURATION_TIMEOUT_ENV_VAR)
+        .ok()
+        .and_then(|timeout_sec| timeout_sec.parse::<u64>().ok())
+        .filter(|timeout_sec| *timeout_sec > 0)
+        .unwrap_or(BUILD_DURATION_TIMEOUT_SEC);
+
+    Duration::from_secs(timeout_sec)
+}
+
+/// Returns the build cache backend of an image, each image gets its own local directory or registry tag.
+fn build_cache_backend(
+    backend: Option<String>,
+    registry_ref: Option<String>,
+    workspace_root_dir: &str,
+    image_name: &str,
+) -> Result<Option<BuildCacheBackend>, String> {
+    let cache_name = image_name.replace('/', "-");
+    match backend.as_deref() {
+        None | Some("") => Ok(None),
+        Some("local") => Ok(Some(BuildCacheBackend::Local(
+            Path::new(workspace_root_dir).join("build-cache").join(cache_name),
+        ))),
+        Some("registry") => match registry_ref {
+            Some(reference) if !reference.is_empty() => {
+                Ok(Some(BuildCacheBackend::Registry(format!("{}:{}", reference, cache_name))))
+            }
+            _ => Err(format!(
+                "{} must be set with the `registry` build cache backend",
+                BUILD_CACHE_REGISTRY_REF_ENV_VAR
+            )),
+        },
+        Some(backend) => Err(format!(
+            "Unknown build cache backend `{}`, expected `local` or `registry`",
+            backend
+        )),
+    }
+}
+
 /// use Docker in local
 pub struct LocalDocker {
     context: Context,
@@ -201,16 +247,25 @@ impl LocalDocker {
             .map(|(k, v)| (k.as_str(), v.as_str()))
             .collect();
 
+        let build_cache_backend = build_cache_backend(
+            env::var(BUILD_CACHE_BACKEND_ENV_VAR).ok(),
+            env::var(BUILD_CACHE_REGISTRY_REF_ENV_VAR).ok(),
+            self.context.workspace_root_dir(),
+            &build.image.name(),
+        )
+        .map_err(|msg| BuildError::InvalidConfig(build.image.application_id.clone(), msg))?;
+
         let exit_status = self.context.docker.build(
             Path::new(dockerfile_complete_path),
             Path::new(into_dir_docker_style),
             &image_to_build,
             &env_vars,
             &image_cache,
+            build_cache_backend.as_ref(),
             true,
             &mut |line| log_info(line),
             &mut |line| log_info(line),
-            &CommandKiller::from(Duration::from_secs(BUILD_DURATION_TIMEOUT_SEC), is_task_canceled),
+            &CommandKiller::from(build_duration_timeout(), is_task_canceled),
         );
 
         match exit_status {
@@ -307,7 +362,7 @@ impl LocalDocker {
 
             // buildpacks build
             let mut cmd = QoveryCommand::new("pack", &buildpacks_args, &self.get_docker_host_envs());
-            let cmd_killer = CommandKiller::from(Duration::from_secs(BUILD_DURATION_TIMEOUT_SEC), is_task_canceled);
+            let cmd_killer = CommandKiller::from(build_duration_timeout(), is_task_canceled);
             exit_status = cmd.exec_with_abort(
                 &mut |line| {
                     self.logger.log(EngineEvent::Info(
@@ -561,3 +616,39 @@ impl ToTransmitter for LocalDocker {
         Transmitter::BuildPlatform(self.id().to_string(), self.name().to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::build_platform::local_docker::build_cache_backend;
+    use crate::cmd::docker::BuildCacheBackend;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_build_cache_backend() {
+        // execute & verify: only the cached image is used by default
+        assert_eq!(build_cache_backend(None, None, "/workspace", "qovery/app"), Ok(None));
+
+        // execute & verify: each image has its own cache
+        assert_eq!(
+            build_cache_backend(Some("local".to_string()), None, "/workspace", "qovery/app"),
+            Ok(Some(BuildCacheBackend::Local(PathBuf::from(
+                "/workspace/build-cache/qovery-app"
+            ))))
+        );
+        assert_eq!(
+            build_cache_backend(
+                Some("registry".to_string()),
+                Some("registry.acme.com/qovery/build-cache".to_string()),
+                "/workspace",
+                "qovery/app"
+            ),
+            Ok(Some(BuildCacheBackend::Registry(
+                "registry.acme.com/qovery/build-cache:qovery-app".to_string()
+            )))
+        );
+
+        // execute & verify: invalid configurations
+        assert!(build_cache_backend(Some("registry".to_string()), None, "/workspace", "qovery/app").is_err());
+        assert!(build_cache_backend(Some("s3".to_string()), None, "/workspace", "qovery/app").is_err());
+    }
+}
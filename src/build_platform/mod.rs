@@ -44,7 +44,7 @@ pub fn to_engine_error(event_details: EventDetails, err: BuildError) -> EngineEr
     }
 }
 
-pub trait BuildPlatform: ToTransmitter + Listen {
+pub trait BuildPlatform: ToTransmitter + Listen + Send + Sync {
     fn context(&self) -> &Context;
     fn kind(&self) -> Kind;
     fn id(&self) -> &str;
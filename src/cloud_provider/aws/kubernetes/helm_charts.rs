@@ -4,7 +4,10 @@ use crate::cloud_provider::helm::{
     ChartPayload, ChartSetValue, ChartValuesGenerated, ClusterAgentContext, CommonChart, CoreDNSConfigChart, HelmChart,
     HelmChartNamespaces, PrometheusOperatorConfigChart, ShellAgentContext,
 };
-use crate::cloud_provider::qovery::{get_qovery_app_version, EngineLocation, QoveryAgent, QoveryAppName, QoveryEngine};
+use crate::cloud_provider::qovery::{
+    get_qovery_app_version, EngineLocation, QoveryAgent, QoveryAppName, QoveryEngine, TokenSource,
+};
+use crate::cloud_provider::utilities::validate_non_nil_uuid;
 use crate::cmd::kubectl::{kubectl_delete_crash_looping_pods, kubectl_exec_get_daemonset, kubectl_exec_with_output};
 use crate::errors::{CommandError, ErrorMessageVerbosity};
 use semver::Version;
@@ -63,6 +66,9 @@ pub fn aws_helm_charts(
     kubernetes_config: &Path,
     envs: &[(String, String)],
 ) -> Result<Vec<Vec<Box<dyn HelmChart>>>, CommandError> {
+    validate_non_nil_uuid("organization_long_id", &chart_config_prerequisites.organization_long_id)?;
+    validate_non_nil_uuid("cluster_long_id", &chart_config_prerequisites.cluster_long_id)?;
+
     let content_file = match File::open(&qovery_terraform_config_file) {
         Ok(x) => x,
         Err(e) => {
@@ -996,7 +1002,12 @@ datasources:
 
     let qovery_agent_version: QoveryAgent = get_qovery_app_version(
         QoveryAppName::Agent,
-        &chart_config_prerequisites.infra_options.agent_version_controller_token,
+        &TokenSource::Literal(
+            chart_config_prerequisites
+                .infra_options
+                .agent_version_controller_token
+                .clone(),
+        ),
         &chart_config_prerequisites.infra_options.qovery_api_url,
         &chart_config_prerequisites.cluster_id,
     )?;
@@ -1069,7 +1080,12 @@ datasources:
 
     let qovery_engine_version: QoveryEngine = get_qovery_app_version(
         QoveryAppName::Engine,
-        &chart_config_prerequisites.infra_options.engine_version_controller_token,
+        &TokenSource::Literal(
+            chart_config_prerequisites
+                .infra_options
+                .engine_version_controller_token
+                .clone(),
+        ),
         &chart_config_prerequisites.infra_options.qovery_api_url,
         &chart_config_prerequisites.cluster_id,
     )?;
@@ -1242,6 +1258,10 @@ impl HelmChart for AwsVpcCniChart {
         &self.chart_info
     }
 
+    fn get_chart_info_mut(&mut self) -> &mut ChartInfo {
+        &mut self.chart_info
+    }
+
     fn pre_exec(
         &self,
         kubernetes_config: &Path,
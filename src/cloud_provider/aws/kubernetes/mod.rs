@@ -16,7 +16,9 @@ use crate::cloud_provider::aws::kubernetes::node::AwsInstancesType;
 use crate::cloud_provider::aws::kubernetes::roles::get_default_roles_to_create;
 use crate::cloud_provider::aws::regions::{AwsRegion, AwsZones};
 use crate::cloud_provider::environment::Environment;
-use crate::cloud_provider::helm::{deploy_charts_levels, ChartInfo};
+use crate::cloud_provider::helm::{
+    deploy_charts_levels, get_charts_levels_estimated_duration, ChartInfo, ChartsDeploymentOptions,
+};
 use crate::cloud_provider::kubernetes::{
     is_kubernetes_upgrade_required, send_progress_on_long_task, uninstall_cert_manager, Kind, Kubernetes,
     KubernetesNodesType, KubernetesUpgradeStatus, ProviderOptions,
@@ -739,11 +741,22 @@ impl EKS {
         )
         .map_err(|e| EngineError::new_helm_charts_setup_error(event_details.clone(), e))?;
 
+        let message = format!(
+            "Deploying charts, estimated duration is up to {} minutes",
+            get_charts_levels_estimated_duration(&helm_charts_to_deploy).as_secs() / 60
+        );
+        self.send_to_customer(&message, &listeners_helper);
+        self.logger()
+            .log(EngineEvent::Info(event_details.clone(), EventMessage::new_from_safe(message)));
+
         deploy_charts_levels(
             kubeconfig_path,
             &credentials_environment_variables,
             helm_charts_to_deploy,
-            self.context.is_dry_run_deploy(),
+            &ChartsDeploymentOptions {
+                dry_run: self.context.is_dry_run_deploy(),
+                ..Default::default()
+            },
         )
         .map_err(|e| EngineError::new_helm_charts_deploy_error(event_details.clone(), e))
     }
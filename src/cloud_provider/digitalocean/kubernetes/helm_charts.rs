@@ -1,15 +1,34 @@
+use crate::build_platform::local_docker::BUILD_DURATION_TIMEOUT_ENV_VAR;
 use crate::cloud_provider::digitalocean::kubernetes::DoksOptions;
 use crate::cloud_provider::helm::{
-    get_chart_for_cluster_agent, get_chart_for_shell_agent, get_engine_helm_action_from_location, ChartInfo,
-    ChartSetValue, ChartValuesGenerated, ClusterAgentContext, CommonChart, CoreDNSConfigChart, HelmChart,
-    HelmChartNamespaces, PrometheusOperatorConfigChart, ShellAgentContext,
+    escape_chart_set_string, get_chart_for_cluster_agent, get_chart_for_shell_agent,
+    get_engine_helm_action_from_location, get_string_map_values, get_tolerations_values, ChartInfo, ChartPayload,
+    ChartSetValue, ChartValuesGenerated, ClusterAgentContext, CommonChart, CoreDNSConfigChart, ExternalDnsPolicy,
+    HelmAction, HelmChart, HelmChartNamespaces, ImagePullPolicy, PrometheusOperatorConfigChart, ResourceSpec,
+    ShellAgentContext, Toleration,
 };
-use crate::cloud_provider::qovery::{get_qovery_app_version, EngineLocation, QoveryAgent, QoveryAppName, QoveryEngine};
+use crate::cloud_provider::qovery::{
+    get_qovery_app_version, EngineLocation, QoveryAgent, QoveryAppName, QoveryEngine, TokenSource,
+};
+use crate::cloud_provider::utilities::validate_non_nil_uuid;
+use crate::cloud_provider::Kind as CloudProviderKind;
+use crate::cmd::helm::{to_command_error, Helm};
 use crate::errors::CommandError;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use retry::delay::Fixed;
+use retry::Error::Operation;
+use retry::OperationResult;
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::BufReader;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DigitalOceanQoveryTerraformConfig {
@@ -20,6 +39,333 @@ pub struct DigitalOceanQoveryTerraformConfig {
     pub loki_storage_config_do_space_bucket_name: String,
 }
 
+/// Default prometheus-adapter replicas, at least 2 are required for the pod disruption budget to be effective.
+pub const DEFAULT_PROMETHEUS_ADAPTER_REPLICAS: u32 = 2;
+
+/// Default nginx-ingress controller replicas, a single replica would make the ingress a single point of failure.
+pub const DEFAULT_INGRESS_CONTROLLER_REPLICAS: u32 = 2;
+
+/// Maximum nginx-ingress controller replicas the autoscaler can reach, raised to the minimum when it's higher.
+pub const INGRESS_CONTROLLER_MAX_REPLICAS: u32 = 4;
+
+/// Default loki service name and port, promtail, qovery-agent and grafana reach loki through them.
+pub const DEFAULT_LOKI_SERVICE_NAME: &str = "loki";
+pub const DEFAULT_LOKI_PORT: u16 = 3100;
+
+/// Release name of the whoami application deployed to smoke test ingress, DNS and TLS after bootstrap.
+pub const BOOTSTRAP_CANARY_CHART_NAME: &str = "bootstrap-canary";
+/// Timeout of each bootstrap canary probe request, a hanging request would otherwise block the probe retries.
+const BOOTSTRAP_CANARY_PROBE_TIMEOUT_SECONDS: u64 = 10;
+
+/// qovery-engine builds timeout, image builds being way longer than the engine chart deployment itself.
+pub const DEFAULT_ENGINE_BUILD_TIMEOUT_SECONDS: u32 = 30 * 60;
+
+/// Charts expected to be alone in their namespace with charts of their own group, see `conflicting_chart_namespaces`.
+pub const CHART_NAMESPACE_ISOLATION_GROUPS: [&[&str]; 4] = [
+    &[
+        "kube-prometheus-stack",
+        "prometheus-adapter",
+        "kube-state-metrics",
+        "grafana",
+    ],
+    &["cert-manager", "cert-manager-configs"],
+    &["nginx-ingress"],
+    &["qovery-agent", "qovery-engine"],
+];
+
+/// Where NATS CA certificate is mounted in qovery-engine container, as set in the chart values.
+const QOVERY_ENGINE_NATS_CA_MOUNT_PATH: &str = "/etc/qovery/nats";
+
+/// Length of the grafana admin password generated when none is set.
+pub const GRAFANA_ADMIN_PASSWORD_LENGTH: usize = 32;
+
+/// Recursive nameservers used by cert-manager DNS01 self-check when no provider specific ones are set.
+pub const DEFAULT_DNS01_RECURSIVE_NAMESERVERS: [&str; 2] = ["1.1.1.1:53", "8.8.8.8:53"];
+
+/// Let's Encrypt production and staging ACME directories.
+pub const LETS_ENCRYPT_ACME_DIRECTORIES: [&str; 2] = [
+    "https://acme-v02.api.letsencrypt.org/directory",
+    "https://acme-staging-v02.api.letsencrypt.org/directory",
+];
+
+/// Nginx ingress load balancer connection draining: the load balancer quickly stops sending traffic to a stopping
+/// controller (health checks), which is given time to complete in flight connections before being killed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DoLoadBalancerDraining {
+    pub draining_timeout_seconds: u32,
+    pub healthcheck_interval_seconds: u32,
+    pub healthcheck_unhealthy_threshold: u32,
+}
+
+impl Default for DoLoadBalancerDraining {
+    fn default() -> Self {
+        DoLoadBalancerDraining {
+            draining_timeout_seconds: 30,
+            healthcheck_interval_seconds: 3,
+            healthcheck_unhealthy_threshold: 2,
+        }
+    }
+}
+
+/// Loki deployment mode, simple scalable splits the read and write paths to scale them independently.
+/// https://grafana.com/docs/loki/latest/fundamentals/architecture/deployment-modes/
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LokiMode {
+    /// Monolithic: a single loki release runs every component.
+    Monolithic,
+    /// SimpleScalable: a write release (distributor, ingester) and a read release (querier, query frontend).
+    SimpleScalable,
+}
+
+impl Default for LokiMode {
+    fn default() -> Self {
+        LokiMode::Monolithic
+    }
+}
+
+/// Pod affinity of loki pods to the pods matching `match_labels` (ex: log heavy workloads), within `topology_key` domains.
+/// Co-locating loki with the pods shipping most logs lowers cross-node shipping latency on large clusters, but loki is
+/// no longer scheduled on the least loaded nodes and may be kept pending when the affinity is required.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LokiAffinity {
+    pub match_labels: BTreeMap<String, String>,
+    pub topology_key: String,
+    /// Preferred affinity weight (1 to 100), the affinity is required when not set
+    pub preferred_weight: Option<u32>,
+}
+
+/// Read and write targets are only available from loki 2.4.
+pub const LOKI_SIMPLE_SCALABLE_IMAGE_TAG: &str = "2.4.2";
+pub const LOKI_SIMPLE_SCALABLE_REPLICAS: u32 = 2;
+const LOKI_MEMBERLIST_PORT: u16 = 7946;
+
+/// qovery-agent liveness probe, the agent is restarted by Kubernetes once its health endpoint failed `failure_threshold`
+/// times in a row (ex: the agent is wedged because loki is unreachable).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QoveryAgentLivenessProbe {
+    pub path: String,
+    pub port: u16,
+    pub initial_delay_seconds: u32,
+    pub period_seconds: u32,
+    pub timeout_seconds: u32,
+    pub failure_threshold: u32,
+}
+
+impl Default for QoveryAgentLivenessProbe {
+    fn default() -> Self {
+        QoveryAgentLivenessProbe {
+            path: "/health".to_string(),
+            port: 80,
+            initial_delay_seconds: 30,
+            period_seconds: 30,
+            timeout_seconds: 5,
+            failure_threshold: 3,
+        }
+    }
+}
+
+/// qovery-engine image build cache backend, a registry cache being shared by every engine pod.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BuildCacheConfig {
+    /// Local: cache is kept on the engine pod disk.
+    Local,
+    /// Registry: cache is pushed to and pulled from an image reference (ex: `registry.acme.com/qovery/build-cache`).
+    Registry { reference: String },
+}
+
+/// Credentials of a DNS provider managed by an additional external-dns release.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum ExternalDnsProviderCredentials {
+    Cloudflare {
+        api_token: String,
+        email: String,
+    },
+    Route53 {
+        access_key_id: String,
+        secret_access_key: String,
+        region: String,
+    },
+}
+
+impl ExternalDnsProviderCredentials {
+    pub fn provider_name(&self) -> &str {
+        match self {
+            ExternalDnsProviderCredentials::Cloudflare { .. } => "cloudflare",
+            ExternalDnsProviderCredentials::Route53 { .. } => "aws",
+        }
+    }
+}
+
+/// DNS provider managing records of the given domains, in addition to the main `external_dns_provider`
+/// (ex: apex domain on Cloudflare and sub zones on Route53).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExternalDnsProviderConfig {
+    pub domain_filters: Vec<String>,
+    pub credentials: ExternalDnsProviderCredentials,
+}
+
+/// Kubernetes service port raw TCP/UDP traffic is passed through to by nginx-ingress.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ServiceRef {
+    pub namespace: String,
+    pub name: String,
+    pub port: u16,
+}
+
+/// Secret, in prometheus namespace, holding the basic auth credentials of a remote write endpoint.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RemoteWriteBasicAuth {
+    pub secret_name: String,
+    pub username_key: String,
+    pub password_key: String,
+}
+
+/// Remote endpoint (ex: Cortex, Thanos) prometheus samples are written to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RemoteWriteConfig {
+    pub url: String,
+    pub basic_auth: Option<RemoteWriteBasicAuth>,
+}
+
+/// qovery-engine chart options.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DoksEngineOptions {
+    pub node_selector: BTreeMap<String, String>,
+    pub tolerations: Vec<Toleration>,
+    pub build_resources: ResourceSpec,
+    pub build_timeout_seconds: u32,
+    pub build_cache_backend: Option<BuildCacheConfig>,
+    pub docker_hub_mirror: Option<String>,
+    pub image_registry: Option<String>,
+    pub image_pull_policy: ImagePullPolicy,
+    pub extra_env: BTreeMap<String, String>,
+    pub nats_tls_enabled: bool,
+    pub nats_ca_cert: Option<String>,
+}
+
+impl Default for DoksEngineOptions {
+    fn default() -> Self {
+        DoksEngineOptions {
+            node_selector: BTreeMap::new(),
+            tolerations: vec![],
+            build_resources: default_engine_build_resources(),
+            build_timeout_seconds: DEFAULT_ENGINE_BUILD_TIMEOUT_SECONDS,
+            build_cache_backend: None,
+            docker_hub_mirror: None,
+            image_registry: None,
+            image_pull_policy: ImagePullPolicy::default(),
+            extra_env: BTreeMap::new(),
+            nats_tls_enabled: false,
+            nats_ca_cert: None,
+        }
+    }
+}
+
+/// Monitoring charts (prometheus, loki, promtail, grafana, qovery-agent) options.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DoksMonitoringOptions {
+    pub prometheus_adapter_replicas: u32,
+    /// Labels added to the default ones (cluster id and region), which can't be overridden.
+    pub prometheus_external_labels: BTreeMap<String, String>,
+    pub prometheus_remote_write: Vec<RemoteWriteConfig>,
+    pub loki_service_name: String,
+    pub loki_port: u16,
+    pub loki_deployment_mode: LokiMode,
+    pub loki_affinity: Option<LokiAffinity>,
+    pub promtail_drop_labels: Vec<String>,
+    /// Tolerations of system charts which have to run on every node (promtail).
+    pub system_chart_tolerations: Vec<Toleration>,
+    /// A random password is generated (then kept) when empty.
+    pub grafana_admin_password: String,
+    pub grafana_ingress_host: Option<String>,
+    pub qovery_agent_liveness_probe: Option<QoveryAgentLivenessProbe>,
+}
+
+impl Default for DoksMonitoringOptions {
+    fn default() -> Self {
+        DoksMonitoringOptions {
+            prometheus_adapter_replicas: DEFAULT_PROMETHEUS_ADAPTER_REPLICAS,
+            prometheus_external_labels: BTreeMap::new(),
+            prometheus_remote_write: vec![],
+            loki_service_name: DEFAULT_LOKI_SERVICE_NAME.to_string(),
+            loki_port: DEFAULT_LOKI_PORT,
+            loki_deployment_mode: LokiMode::default(),
+            loki_affinity: None,
+            promtail_drop_labels: vec![],
+            system_chart_tolerations: default_system_chart_tolerations(),
+            grafana_admin_password: "".to_string(),
+            grafana_ingress_host: None,
+            qovery_agent_liveness_probe: None,
+        }
+    }
+}
+
+/// Nginx ingress controller chart options.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DoksIngressOptions {
+    pub controller_replicas: u32,
+    pub enable_default_backend: bool,
+    pub default_backend_image: Option<String>,
+    pub load_balancer_draining: DoLoadBalancerDraining,
+    pub tcp_services: BTreeMap<u16, ServiceRef>,
+    pub udp_services: BTreeMap<u16, ServiceRef>,
+    pub deploy_bootstrap_canary: bool,
+}
+
+impl Default for DoksIngressOptions {
+    fn default() -> Self {
+        DoksIngressOptions {
+            controller_replicas: DEFAULT_INGRESS_CONTROLLER_REPLICAS,
+            enable_default_backend: true,
+            default_backend_image: None,
+            load_balancer_draining: DoLoadBalancerDraining::default(),
+            tcp_services: BTreeMap::new(),
+            udp_services: BTreeMap::new(),
+            deploy_bootstrap_canary: false,
+        }
+    }
+}
+
+/// DNS charts (external-dns, coredns, cert-manager DNS01 solver) options.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DoksDnsOptions {
+    pub external_dns_policy: ExternalDnsPolicy,
+    pub external_dns_providers: Vec<ExternalDnsProviderConfig>,
+    pub dns01_self_check_nameservers: BTreeMap<String, Vec<String>>,
+    pub coredns_stub_domains: BTreeMap<String, Vec<String>>,
+    pub coredns_auto_reload: bool,
+    /// cert-manager can be skipped when TLS is terminated outside of the cluster or by the user own cert-manager.
+    pub deploy_cert_manager: bool,
+}
+
+impl Default for DoksDnsOptions {
+    fn default() -> Self {
+        DoksDnsOptions {
+            external_dns_policy: ExternalDnsPolicy::default(),
+            external_dns_providers: vec![],
+            dns01_self_check_nameservers: BTreeMap::new(),
+            coredns_stub_domains: BTreeMap::new(),
+            coredns_auto_reload: true,
+            deploy_cert_manager: true,
+        }
+    }
+}
+
+/// Cluster maintenance charts (k8s-token-rotate, digital-mobius) options.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct DoksMaintenanceOptions {
+    pub token_rotate_schedule_cron: Option<String>,
+    pub digital_mobius_image_tag: Option<String>,
+}
+
 pub struct ChartsConfigPrerequisites {
     pub organization_id: String,
     pub organization_long_id: uuid::Uuid,
@@ -28,7 +374,7 @@ pub struct ChartsConfigPrerequisites {
     pub do_cluster_id: String,
     pub region: String,
     pub cluster_name: String,
-    pub cloud_provider: String,
+    pub cloud_provider: CloudProviderKind,
     pub test_cluster: bool,
     pub do_token: String,
     pub do_space_access_id: String,
@@ -52,6 +398,7 @@ pub struct ChartsConfigPrerequisites {
 }
 
 impl ChartsConfigPrerequisites {
+    /// Returns charts configuration, chart options being read from the cluster options.
     pub fn new(
         organization_id: String,
         organization_long_id: uuid::Uuid,
@@ -60,28 +407,29 @@ impl ChartsConfigPrerequisites {
         do_cluster_id: String,
         region: String,
         cluster_name: String,
-        cloud_provider: String,
+        cloud_provider: CloudProviderKind,
         test_cluster: bool,
         do_token: String,
         do_space_access_id: String,
         do_space_secret_key: String,
         do_space_bucket_kubeconfig: String,
         do_space_kubeconfig_filename: String,
-        qovery_engine_location: EngineLocation,
         ff_log_history_enabled: bool,
         ff_metrics_history_enabled: bool,
         managed_dns_name: String,
         managed_dns_helm_format: String,
         managed_dns_resolvers_terraform_format: String,
         external_dns_provider: String,
-        dns_email_report: String,
         acme_url: String,
         cloudflare_email: String,
         cloudflare_api_token: String,
         disable_pleco: bool,
         infra_options: DoksOptions,
-    ) -> Self {
-        ChartsConfigPrerequisites {
+    ) -> Result<Self, CommandError> {
+        validate_non_nil_uuid("organization_long_id", &organization_long_id)?;
+        validate_non_nil_uuid("cluster_long_id", &cluster_long_id)?;
+
+        Ok(ChartsConfigPrerequisites {
             organization_id,
             organization_long_id,
             cluster_id,
@@ -96,28 +444,53 @@ impl ChartsConfigPrerequisites {
             do_space_secret_key,
             do_space_bucket_kubeconfig,
             do_space_kubeconfig_filename,
-            qovery_engine_location,
+            qovery_engine_location: infra_options.qovery_engine_location.clone(),
             ff_log_history_enabled,
             ff_metrics_history_enabled,
             managed_dns_name,
             managed_dns_helm_format,
             managed_dns_resolvers_terraform_format,
             external_dns_provider,
-            dns_email_report,
+            dns_email_report: infra_options.tls_email_report.clone(),
             acme_url,
             cloudflare_email,
             cloudflare_api_token,
             disable_pleco,
             infra_options,
-        }
+        })
+    }
+
+    /// Returns prometheus external labels, the default ones (cluster id and region) taking precedence.
+    pub fn prometheus_external_labels(&self) -> BTreeMap<String, String> {
+        let mut prometheus_external_labels = self.infra_options.monitoring.prometheus_external_labels.clone();
+        prometheus_external_labels.extend(default_prometheus_external_labels(&self.cluster_id, &self.region));
+        prometheus_external_labels
+    }
+
+    /// ServiceMonitors are only scraped by the prometheus deployed along with metrics history, they would be orphaned
+    /// otherwise.
+    pub fn enable_service_monitors(&self) -> bool {
+        self.ff_metrics_history_enabled
     }
 }
 
+/// ServiceMonitor toggle value of each chart exposing metrics.
+const SERVICE_MONITOR_VALUE_KEYS: [(&str, &str); 4] = [
+    ("kube-state-metrics", "prometheus.monitor.enabled"),
+    ("cert-manager", "prometheus.servicemonitor.enabled"),
+    ("promtail", "serviceMonitor.enabled"),
+    ("loki", "serviceMonitor.enabled"),
+];
+
 pub fn do_helm_charts(
     qovery_terraform_config_file: &str,
     chart_config_prerequisites: &ChartsConfigPrerequisites,
     chart_prefix_path: Option<&str>,
 ) -> Result<Vec<Vec<Box<dyn HelmChart>>>, CommandError> {
+    validate_feature_flags(chart_config_prerequisites)?;
+    validate_managed_dns(chart_config_prerequisites)?;
+    validate_acme_url(&chart_config_prerequisites.acme_url)?;
+
     let content_file = match File::open(&qovery_terraform_config_file) {
         Ok(x) => x,
         Err(e) => {
@@ -145,7 +518,22 @@ pub fn do_helm_charts(
     let prometheus_namespace = HelmChartNamespaces::Prometheus;
     let prometheus_internal_url = format!("http://prometheus-operated.{}.svc", prometheus_namespace);
     let loki_namespace = HelmChartNamespaces::Logging;
-    let loki_kube_dns_prefix = format!("loki.{}.svc", loki_namespace);
+    let loki_write_kube_dns_prefix = format!(
+        "{}.{}.svc",
+        loki_write_service_name(
+            chart_config_prerequisites.infra_options.monitoring.loki_deployment_mode,
+            &chart_config_prerequisites.infra_options.monitoring.loki_service_name
+        ),
+        loki_namespace
+    );
+    let loki_read_kube_dns_prefix = format!(
+        "{}.{}.svc",
+        loki_read_service_name(
+            chart_config_prerequisites.infra_options.monitoring.loki_deployment_mode,
+            &chart_config_prerequisites.infra_options.monitoring.loki_service_name
+        ),
+        loki_namespace
+    );
 
     // Qovery storage class
     let q_storage_class = CommonChart {
@@ -156,25 +544,7 @@ pub fn do_helm_charts(
         },
     };
 
-    let coredns_config = CoreDNSConfigChart {
-        chart_info: ChartInfo {
-            name: "coredns".to_string(),
-            path: chart_path("/charts/coredns-config"),
-            values: vec![
-                ChartSetValue {
-                    key: "managed_dns".to_string(),
-                    value: chart_config_prerequisites.managed_dns_helm_format.clone(),
-                },
-                ChartSetValue {
-                    key: "managed_dns_resolvers".to_string(),
-                    value: chart_config_prerequisites
-                        .managed_dns_resolvers_terraform_format
-                        .clone(),
-                },
-            ],
-            ..Default::default()
-        },
-    };
+    let coredns_config = coredns_config_chart(chart_config_prerequisites, chart_prefix)?;
 
     let external_dns = CommonChart {
         chart_info: ChartInfo {
@@ -182,6 +552,7 @@ pub fn do_helm_charts(
             path: chart_path("common/charts/external-dns"),
             values_files: vec![chart_path("chart_values/external-dns.yaml")],
             values: vec![
+                external_dns_policy_value(chart_config_prerequisites.infra_options.dns.external_dns_policy),
                 // resources limits
                 ChartSetValue {
                     key: "resources.limits.cpu".to_string(),
@@ -204,7 +575,7 @@ pub fn do_helm_charts(
         },
     };
 
-    let promtail = CommonChart {
+    let mut promtail = CommonChart {
         chart_info: ChartInfo {
             name: "promtail".to_string(),
             last_breaking_version_requiring_restart: Some(Version::new(0, 24, 0)),
@@ -212,10 +583,6 @@ pub fn do_helm_charts(
             // because of priorityClassName, we need to add it to kube-system
             namespace: HelmChartNamespaces::KubeSystem,
             values: vec![
-                ChartSetValue {
-                    key: "loki.serviceName".to_string(),
-                    value: loki_kube_dns_prefix.clone(),
-                },
                 // it's mandatory to get this class to ensure paused infra will behave properly on restore
                 ChartSetValue {
                     key: "priorityClassName".to_string(),
@@ -242,69 +609,133 @@ pub fn do_helm_charts(
             ..Default::default()
         },
     };
+    promtail.chart_info.values.extend(promtail_loki_values(
+        &loki_write_kube_dns_prefix,
+        chart_config_prerequisites.infra_options.monitoring.loki_port,
+    ));
+    promtail.chart_info.values.extend(promtail_drop_labels_values(
+        &chart_config_prerequisites.infra_options.monitoring.promtail_drop_labels,
+    )?);
+    promtail.chart_info.values.extend(service_monitor_values(
+        "promtail",
+        chart_config_prerequisites.enable_service_monitors(),
+    ));
+    // promtail has to run on every node to collect all logs, tainted ones included
+    promtail
+        .chart_info
+        .values
+        .extend(system_chart_tolerations_values(chart_config_prerequisites));
 
-    let loki = CommonChart {
-        chart_info: ChartInfo {
-            name: "loki".to_string(),
-            path: chart_path("common/charts/loki"),
-            namespace: loki_namespace,
-            values_files: vec![chart_path("chart_values/loki.yaml")],
-            values: vec![
-                ChartSetValue {
-                    key: "config.storage_config.aws.s3forcepathstyle".to_string(),
-                    value: "true".to_string(),
-                },
-                ChartSetValue {
-                    key: "config.storage_config.aws.bucketnames".to_string(),
-                    value: qovery_terraform_config.loki_storage_config_do_space_bucket_name,
-                },
-                ChartSetValue {
-                    key: "config.storage_config.aws.endpoint".to_string(),
-                    value: qovery_terraform_config.loki_storage_config_do_space_host,
-                },
-                ChartSetValue {
-                    key: "config.storage_config.aws.region".to_string(),
-                    value: qovery_terraform_config.loki_storage_config_do_space_region,
-                },
-                ChartSetValue {
-                    key: "config.storage_config.aws.access_key_id".to_string(),
-                    value: qovery_terraform_config.loki_storage_config_do_space_access_id,
-                },
-                ChartSetValue {
-                    key: "config.storage_config.aws.secret_access_key".to_string(),
-                    value: qovery_terraform_config.loki_storage_config_do_space_secret_key,
-                },
-                // DigitalOcean do not support encryption yet
-                // https://docs.digitalocean.com/reference/api/spaces-api/
-                ChartSetValue {
-                    key: "config.storage_config.aws.sse_encryption".to_string(),
-                    value: "false".to_string(),
-                },
-                ChartSetValue {
-                    key: "config.storage_config.aws.insecure".to_string(),
-                    value: "false".to_string(),
-                },
-                // resources limits
-                ChartSetValue {
-                    key: "resources.limits.cpu".to_string(),
-                    value: "100m".to_string(),
-                },
-                ChartSetValue {
-                    key: "resources.requests.cpu".to_string(),
-                    value: "100m".to_string(),
-                },
-                ChartSetValue {
-                    key: "resources.limits.memory".to_string(),
-                    value: "2Gi".to_string(),
-                },
-                ChartSetValue {
-                    key: "resources.requests.memory".to_string(),
-                    value: "1Gi".to_string(),
-                },
-            ],
-            ..Default::default()
+    let mut loki_values = vec![
+        ChartSetValue {
+            key: "service.port".to_string(),
+            value: chart_config_prerequisites
+                .infra_options
+                .monitoring
+                .loki_port
+                .to_string(),
         },
-    };
+        ChartSetValue {
+            key: "config.storage_config.aws.s3forcepathstyle".to_string(),
+            value: "true".to_string(),
+        },
+        ChartSetValue {
+            key: "config.storage_config.aws.bucketnames".to_string(),
+            value: qovery_terraform_config.loki_storage_config_do_space_bucket_name,
+        },
+        ChartSetValue {
+            key: "config.storage_config.aws.endpoint".to_string(),
+            value: qovery_terraform_config.loki_storage_config_do_space_host,
+        },
+        ChartSetValue {
+            key: "config.storage_config.aws.region".to_string(),
+            value: qovery_terraform_config.loki_storage_config_do_space_region,
+        },
+        ChartSetValue {
+            key: "config.storage_config.aws.access_key_id".to_string(),
+            value: qovery_terraform_config.loki_storage_config_do_space_access_id,
+        },
+        ChartSetValue {
+            key: "config.storage_config.aws.secret_access_key".to_string(),
+            value: qovery_terraform_config.loki_storage_config_do_space_secret_key,
+        },
+        // DigitalOcean do not support encryption yet
+        // https://docs.digitalocean.com/reference/api/spaces-api/
+        ChartSetValue {
+            key: "config.storage_config.aws.sse_encryption".to_string(),
+            value: "false".to_string(),
+        },
+        ChartSetValue {
+            key: "config.storage_config.aws.insecure".to_string(),
+            value: "false".to_string(),
+        },
+        // resources limits
+        ChartSetValue {
+            key: "resources.limits.cpu".to_string(),
+            value: "100m".to_string(),
+        },
+        ChartSetValue {
+            key: "resources.requests.cpu".to_string(),
+            value: "100m".to_string(),
+        },
+        ChartSetValue {
+            key: "resources.limits.memory".to_string(),
+            value: "2Gi".to_string(),
+        },
+        ChartSetValue {
+            key: "resources.requests.memory".to_string(),
+            value: "1Gi".to_string(),
+        },
+    ];
+    loki_values.extend(service_monitor_values(
+        "loki",
+        chart_config_prerequisites.enable_service_monitors(),
+    ));
+    if let Some(loki_affinity) = &chart_config_prerequisites.infra_options.monitoring.loki_affinity {
+        loki_values.extend(loki_affinity_values(loki_affinity)?);
+    }
+    let mut loki_charts: Vec<CommonChart> = loki_components(
+        chart_config_prerequisites.infra_options.monitoring.loki_deployment_mode,
+        &chart_config_prerequisites.infra_options.monitoring.loki_service_name,
+        loki_namespace,
+    )
+    .into_iter()
+    .map(|(name, component_values)| {
+        let mut values = loki_values.clone();
+        values.push(ChartSetValue {
+            key: "fullnameOverride".to_string(),
+            value: name.clone(),
+        });
+        values.extend(component_values);
+
+        CommonChart {
+            chart_info: ChartInfo {
+                name,
+                path: chart_path("common/charts/loki"),
+                namespace: loki_namespace,
+                values_files: vec![chart_path("chart_values/loki.yaml")],
+                values,
+                ..Default::default()
+            },
+        }
+    })
+    .collect();
+    // releases of the previous mode are removed when switching mode, uninstalling a missing release does nothing
+    loki_charts.extend(
+        loki_stale_release_names(
+            chart_config_prerequisites.infra_options.monitoring.loki_deployment_mode,
+            &chart_config_prerequisites.infra_options.monitoring.loki_service_name,
+        )
+        .into_iter()
+        .map(|name| CommonChart {
+            chart_info: ChartInfo {
+                name,
+                namespace: loki_namespace,
+                action: HelmAction::Destroy,
+                ..Default::default()
+            },
+        }),
+    );
 
     /*
     let old_prometheus_operator = PrometheusOperatorConfigChart {
@@ -316,7 +747,7 @@ pub fn do_helm_charts(
         },
     };*/
 
-    let kube_prometheus_stack = PrometheusOperatorConfigChart {
+    let mut kube_prometheus_stack = PrometheusOperatorConfigChart {
         chart_info: ChartInfo {
             name: "kube-prometheus-stack".to_string(),
             path: chart_path("/common/charts/kube-prometheus-stack"),
@@ -380,8 +811,21 @@ pub fn do_helm_charts(
             ..Default::default()
         },
     };
+    kube_prometheus_stack.chart_info.values.extend(get_string_map_values(
+        "prometheus.prometheusSpec.externalLabels",
+        &chart_config_prerequisites.prometheus_external_labels(),
+    ));
+    kube_prometheus_stack
+        .chart_info
+        .values
+        .extend(prometheus_remote_write_values(
+            &chart_config_prerequisites
+                .infra_options
+                .monitoring
+                .prometheus_remote_write,
+        )?);
 
-    let prometheus_adapter = CommonChart {
+    let mut prometheus_adapter = CommonChart {
         chart_info: ChartInfo {
             name: "prometheus-adapter".to_string(),
             path: chart_path("common/charts/prometheus-adapter"),
@@ -395,14 +839,6 @@ pub fn do_helm_charts(
                     key: "prometheus.url".to_string(),
                     value: prometheus_internal_url.clone(),
                 },
-                ChartSetValue {
-                    key: "podDisruptionBudget.enabled".to_string(),
-                    value: "true".to_string(),
-                },
-                ChartSetValue {
-                    key: "podDisruptionBudget.maxUnavailable".to_string(),
-                    value: "1".to_string(),
-                },
                 // resources limits
                 ChartSetValue {
                     key: "resources.limits.cpu".to_string(),
@@ -424,6 +860,15 @@ pub fn do_helm_charts(
             ..Default::default()
         },
     };
+    prometheus_adapter
+        .chart_info
+        .values
+        .extend(prometheus_adapter_replicas_values(
+            chart_config_prerequisites
+                .infra_options
+                .monitoring
+                .prometheus_adapter_replicas,
+        )?);
 
     let metrics_server = CommonChart {
         chart_info: ChartInfo {
@@ -452,16 +897,12 @@ pub fn do_helm_charts(
         },
     };
 
-    let kube_state_metrics = CommonChart {
+    let mut kube_state_metrics = CommonChart {
         chart_info: ChartInfo {
             name: "kube-state-metrics".to_string(),
             namespace: HelmChartNamespaces::Prometheus,
             path: chart_path("common/charts/kube-state-metrics"),
             values: vec![
-                ChartSetValue {
-                    key: "prometheus.monitor.enabled".to_string(),
-                    value: "true".to_string(),
-                },
                 ChartSetValue {
                     key: "resources.limits.cpu".to_string(),
                     value: "75m".to_string(),
@@ -483,27 +924,15 @@ pub fn do_helm_charts(
         },
     };
 
-    let grafana_datasources = format!(
-        "
-datasources:
-  datasources.yaml:
-    apiVersion: 1
-    datasources:
-      - name: Prometheus
-        type: prometheus
-        url: \"{}:9090\"
-        access: proxy
-        isDefault: true
-      - name: PromLoki
-        type: prometheus
-        url: \"http://{}.{}.svc:3100/loki\"
-        access: proxy
-        isDefault: false
-      - name: Loki
-        type: loki
-        url: \"http://{}.{}.svc:3100\"
-      ",
-        prometheus_internal_url, &loki.chart_info.name, loki_namespace, &loki.chart_info.name, loki_namespace,
+    kube_state_metrics.chart_info.values.extend(service_monitor_values(
+        "kube-state-metrics",
+        chart_config_prerequisites.enable_service_monitors(),
+    ));
+
+    let grafana_datasources = grafana_datasources(
+        &prometheus_internal_url,
+        &loki_read_kube_dns_prefix,
+        chart_config_prerequisites.infra_options.monitoring.loki_port,
     );
 
     let grafana = CommonChart {
@@ -516,217 +945,116 @@ datasources:
                 filename: "grafana_generated.yaml".to_string(),
                 yaml_content: grafana_datasources,
             }],
+            values: grafana_values(
+                &chart_config_prerequisites
+                    .infra_options
+                    .monitoring
+                    .grafana_admin_password,
+                &chart_config_prerequisites.infra_options.monitoring.grafana_ingress_host,
+            ),
             ..Default::default()
         },
     };
 
-    let cert_manager = CommonChart {
+    let mut nginx_ingress = CommonChart {
         chart_info: ChartInfo {
-            name: "cert-manager".to_string(),
-            path: chart_path("common/charts/cert-manager"),
-            namespace: HelmChartNamespaces::CertManager,
+            name: "nginx-ingress".to_string(),
+            path: chart_path("common/charts/ingress-nginx"),
+            namespace: HelmChartNamespaces::NginxIngress,
+            // Because of NLB, svc can take some time to start
+            timeout_in_seconds: 800,
+            values_files: vec![chart_path("chart_values/nginx-ingress.yaml")],
             values: vec![
+                // Controller resources limits
                 ChartSetValue {
-                    key: "installCRDs".to_string(),
-                    value: "true".to_string(),
+                    key: "controller.resources.limits.cpu".to_string(),
+                    value: "200m".to_string(),
                 },
                 ChartSetValue {
-                    key: "replicaCount".to_string(),
-                    value: "1".to_string(),
+                    key: "controller.resources.requests.cpu".to_string(),
+                    value: "100m".to_string(),
                 },
-                // https://cert-manager.io/docs/configuration/acme/dns01/#setting-nameservers-for-dns01-self-check
                 ChartSetValue {
-                    key: "extraArgs".to_string(),
-                    value: "{--dns01-recursive-nameservers-only,--dns01-recursive-nameservers=1.1.1.1:53\\,8.8.8.8:53}"
-                        .to_string(),
+                    key: "controller.resources.limits.memory".to_string(),
+                    value: "768Mi".to_string(),
                 },
                 ChartSetValue {
-                    key: "prometheus.servicemonitor.enabled".to_string(),
-                    // Due to cycle, prometheus need tls certificate from cert manager, and enabling this will require
-                    // prometheus to be already installed
-                    value: "false".to_string(),
+                    key: "controller.resources.requests.memory".to_string(),
+                    value: "768Mi".to_string(),
                 },
+            ],
+            ..Default::default()
+        },
+    };
+    nginx_ingress.chart_info.values.extend(nginx_ingress_replicas_values(
+        chart_config_prerequisites.infra_options.ingress.controller_replicas,
+    )?);
+    nginx_ingress.chart_info.values.extend(nginx_ingress_passthrough_values(
+        &chart_config_prerequisites.infra_options.ingress.tcp_services,
+        &chart_config_prerequisites.infra_options.ingress.udp_services,
+    )?);
+    nginx_ingress
+        .chart_info
+        .values
+        .extend(nginx_ingress_load_balancer_draining_values(
+            &chart_config_prerequisites.infra_options.ingress.load_balancer_draining,
+        ));
+    nginx_ingress
+        .chart_info
+        .values
+        .extend(nginx_ingress_default_backend_values(
+            chart_config_prerequisites.infra_options.ingress.enable_default_backend,
+            &chart_config_prerequisites.infra_options.ingress.default_backend_image,
+        )?);
+    nginx_ingress
+        .chart_info
+        .values
+        .extend(nginx_ingress_default_certificate_values(
+            chart_config_prerequisites.infra_options.dns.deploy_cert_manager,
+        ));
+
+    let mut digital_mobius = CommonChart {
+        chart_info: ChartInfo {
+            name: "digital-mobius".to_string(),
+            path: chart_path("charts/digital-mobius"),
+            values: vec![
                 ChartSetValue {
-                    key: "prometheus.servicemonitor.prometheusInstance".to_string(),
-                    value: "qovery".to_string(),
+                    key: "environmentVariables.LOG_LEVEL".to_string(),
+                    value: "debug".to_string(),
                 },
-                // resources limits
                 ChartSetValue {
-                    key: "resources.limits.cpu".to_string(),
-                    value: "200m".to_string(),
+                    key: "environmentVariables.DELAY_NODE_CREATION".to_string(),
+                    value: "5m".to_string(),
                 },
                 ChartSetValue {
-                    key: "resources.requests.cpu".to_string(),
-                    value: "100m".to_string(),
+                    key: "environmentVariables.DIGITAL_OCEAN_TOKEN".to_string(),
+                    value: chart_config_prerequisites.do_token.clone(),
                 },
                 ChartSetValue {
-                    key: "resources.limits.memory".to_string(),
-                    value: "1Gi".to_string(),
+                    key: "environmentVariables.DIGITAL_OCEAN_CLUSTER_ID".to_string(),
+                    value: chart_config_prerequisites.do_cluster_id.to_string(),
                 },
                 ChartSetValue {
-                    key: "resources.requests.memory".to_string(),
-                    value: "1Gi".to_string(),
+                    key: "enabledFeatures.disableDryRun".to_string(),
+                    value: "true".to_string(),
                 },
-                // Webhooks resources limits
-                ChartSetValue {
-                    key: "webhook.resources.limits.cpu".to_string(),
-                    value: "200m".to_string(),
-                },
-                ChartSetValue {
-                    key: "webhook.resources.requests.cpu".to_string(),
-                    value: "50m".to_string(),
-                },
-                ChartSetValue {
-                    key: "webhook.resources.limits.memory".to_string(),
-                    value: "128Mi".to_string(),
-                },
-                ChartSetValue {
-                    key: "webhook.resources.requests.memory".to_string(),
-                    value: "128Mi".to_string(),
-                },
-                // Cainjector resources limits
-                ChartSetValue {
-                    key: "cainjector.resources.limits.cpu".to_string(),
-                    value: "500m".to_string(),
-                },
-                ChartSetValue {
-                    key: "cainjector.resources.requests.cpu".to_string(),
-                    value: "100m".to_string(),
-                },
-                ChartSetValue {
-                    key: "cainjector.resources.limits.memory".to_string(),
-                    value: "1Gi".to_string(),
-                },
-                ChartSetValue {
-                    key: "cainjector.resources.requests.memory".to_string(),
-                    value: "1Gi".to_string(),
-                },
-            ],
-            ..Default::default()
-        },
-    };
-
-    let mut cert_manager_config = CommonChart {
-        chart_info: ChartInfo {
-            name: "cert-manager-configs".to_string(),
-            path: chart_path("common/charts/cert-manager-configs"),
-            namespace: HelmChartNamespaces::CertManager,
-            values: vec![
-                ChartSetValue {
-                    key: "externalDnsProvider".to_string(),
-                    value: chart_config_prerequisites.external_dns_provider.clone(),
-                },
-                ChartSetValue {
-                    key: "acme.letsEncrypt.emailReport".to_string(),
-                    value: chart_config_prerequisites.dns_email_report.clone(),
-                },
-                ChartSetValue {
-                    key: "acme.letsEncrypt.acmeUrl".to_string(),
-                    value: chart_config_prerequisites.acme_url.clone(),
-                },
-                ChartSetValue {
-                    key: "managedDns".to_string(),
-                    value: chart_config_prerequisites.managed_dns_helm_format.clone(),
-                },
-            ],
-            ..Default::default()
-        },
-    };
-    if chart_config_prerequisites.external_dns_provider == "cloudflare" {
-        cert_manager_config.chart_info.values.push(ChartSetValue {
-            key: "provider.cloudflare.apiToken".to_string(),
-            value: chart_config_prerequisites.cloudflare_api_token.clone(),
-        });
-        cert_manager_config.chart_info.values.push(ChartSetValue {
-            key: "provider.cloudflare.email".to_string(),
-            value: chart_config_prerequisites.cloudflare_email.clone(),
-        })
-    }
-
-    let nginx_ingress = CommonChart {
-        chart_info: ChartInfo {
-            name: "nginx-ingress".to_string(),
-            path: chart_path("common/charts/ingress-nginx"),
-            namespace: HelmChartNamespaces::NginxIngress,
-            // Because of NLB, svc can take some time to start
-            timeout_in_seconds: 800,
-            values_files: vec![chart_path("chart_values/nginx-ingress.yaml")],
-            values: vec![
-                // Controller resources limits
-                ChartSetValue {
-                    key: "controller.resources.limits.cpu".to_string(),
-                    value: "200m".to_string(),
-                },
-                ChartSetValue {
-                    key: "controller.resources.requests.cpu".to_string(),
-                    value: "100m".to_string(),
-                },
-                ChartSetValue {
-                    key: "controller.resources.limits.memory".to_string(),
-                    value: "768Mi".to_string(),
-                },
-                ChartSetValue {
-                    key: "controller.resources.requests.memory".to_string(),
-                    value: "768Mi".to_string(),
-                },
-                // Default backend resources limits
-                ChartSetValue {
-                    key: "defaultBackend.resources.limits.cpu".to_string(),
-                    value: "20m".to_string(),
-                },
-                ChartSetValue {
-                    key: "defaultBackend.resources.requests.cpu".to_string(),
-                    value: "10m".to_string(),
-                },
-                ChartSetValue {
-                    key: "defaultBackend.resources.limits.memory".to_string(),
-                    value: "32Mi".to_string(),
-                },
-                ChartSetValue {
-                    key: "defaultBackend.resources.requests.memory".to_string(),
-                    value: "32Mi".to_string(),
-                },
-            ],
-            ..Default::default()
-        },
-    };
-
-    let digital_mobius = CommonChart {
-        chart_info: ChartInfo {
-            name: "digital-mobius".to_string(),
-            path: chart_path("charts/digital-mobius"),
-            values: vec![
-                ChartSetValue {
-                    key: "environmentVariables.LOG_LEVEL".to_string(),
-                    value: "debug".to_string(),
-                },
-                ChartSetValue {
-                    key: "environmentVariables.DELAY_NODE_CREATION".to_string(),
-                    value: "5m".to_string(),
-                },
-                ChartSetValue {
-                    key: "environmentVariables.DIGITAL_OCEAN_TOKEN".to_string(),
-                    value: chart_config_prerequisites.do_token.clone(),
-                },
-                ChartSetValue {
-                    key: "environmentVariables.DIGITAL_OCEAN_CLUSTER_ID".to_string(),
-                    value: chart_config_prerequisites.do_cluster_id.to_string(),
-                },
-                ChartSetValue {
-                    key: "enabledFeatures.disableDryRun".to_string(),
-                    value: "true".to_string(),
-                },
-            ],
-            ..Default::default()
-        },
-    };
-
-    let pleco = CommonChart {
-        chart_info: ChartInfo {
-            name: "pleco".to_string(),
-            path: chart_path("common/charts/pleco"),
-            values_files: vec![chart_path("chart_values/pleco-do.yaml")],
-            values: vec![
+            ],
+            ..Default::default()
+        },
+    };
+    digital_mobius.chart_info.values.extend(digital_mobius_image_tag_values(
+        &chart_config_prerequisites
+            .infra_options
+            .maintenance
+            .digital_mobius_image_tag,
+    ));
+
+    let pleco = CommonChart {
+        chart_info: ChartInfo {
+            name: "pleco".to_string(),
+            path: chart_path("common/charts/pleco"),
+            values_files: vec![chart_path("chart_values/pleco-do.yaml")],
+            values: vec![
                 ChartSetValue {
                     key: "environmentVariables.DO_API_TOKEN".to_string(),
                     value: chart_config_prerequisites.do_token.clone(),
@@ -756,7 +1084,7 @@ datasources:
         },
     };
 
-    let k8s_token_rotate = CommonChart {
+    let mut k8s_token_rotate = CommonChart {
         chart_info: ChartInfo {
             name: "k8s-token-rotate".to_string(),
             path: chart_path("charts/do-k8s-token-rotate"),
@@ -793,6 +1121,18 @@ datasources:
             ..Default::default()
         },
     };
+    if let Some(schedule) = &chart_config_prerequisites
+        .infra_options
+        .maintenance
+        .token_rotate_schedule_cron
+    {
+        validate_cron_expression(schedule)?;
+        k8s_token_rotate.chart_info.values.push(ChartSetValue {
+            key: "schedule".to_string(),
+            // commas have to be escaped for helm --set
+            value: schedule.replace(',', "\\,"),
+        });
+    }
 
     let cluster_agent_context = ClusterAgentContext {
         api_url: &chart_config_prerequisites.infra_options.qovery_api_url,
@@ -818,7 +1158,12 @@ datasources:
 
     let qovery_agent_version: QoveryAgent = get_qovery_app_version(
         QoveryAppName::Agent,
-        &chart_config_prerequisites.infra_options.agent_version_controller_token,
+        &TokenSource::Literal(
+            chart_config_prerequisites
+                .infra_options
+                .agent_version_controller_token
+                .clone(),
+        ),
         &chart_config_prerequisites.infra_options.qovery_api_url,
         &chart_config_prerequisites.cluster_id,
     )?;
@@ -858,7 +1203,10 @@ datasources:
                 },
                 ChartSetValue {
                     key: "environmentVariables.LOKI_URL".to_string(),
-                    value: format!("http://{}.cluster.local:3100", loki_kube_dns_prefix),
+                    value: qovery_agent_loki_url(
+                        &loki_read_kube_dns_prefix,
+                        chart_config_prerequisites.infra_options.monitoring.loki_port,
+                    ),
                 },
                 // resources limits
                 ChartSetValue {
@@ -882,6 +1230,16 @@ datasources:
         },
     };
 
+    qovery_agent
+        .chart_info
+        .values
+        .extend(qovery_agent_liveness_probe_values(
+            &chart_config_prerequisites
+                .infra_options
+                .monitoring
+                .qovery_agent_liveness_probe,
+        )?);
+
     if chart_config_prerequisites.ff_log_history_enabled {
         qovery_agent.chart_info.values.push(ChartSetValue {
             key: "environmentVariables.FEATURES".to_string(),
@@ -891,12 +1249,17 @@ datasources:
 
     let qovery_engine_version: QoveryEngine = get_qovery_app_version(
         QoveryAppName::Engine,
-        &chart_config_prerequisites.infra_options.engine_version_controller_token,
+        &TokenSource::Literal(
+            chart_config_prerequisites
+                .infra_options
+                .engine_version_controller_token
+                .clone(),
+        ),
         &chart_config_prerequisites.infra_options.qovery_api_url,
         &chart_config_prerequisites.cluster_id,
     )?;
 
-    let qovery_engine = CommonChart {
+    let mut qovery_engine = CommonChart {
         chart_info: ChartInfo {
             name: "qovery-engine".to_string(),
             action: get_engine_helm_action_from_location(&chart_config_prerequisites.qovery_engine_location),
@@ -935,18 +1298,6 @@ datasources:
                         .qovery_nats_password
                         .to_string(),
                 },
-                ChartSetValue {
-                    key: "environmentVariables.ORGANIZATION".to_string(),
-                    value: chart_config_prerequisites.organization_id.clone(),
-                },
-                ChartSetValue {
-                    key: "environmentVariables.CLOUD_PROVIDER".to_string(),
-                    value: "do".to_string(),
-                },
-                ChartSetValue {
-                    key: "environmentVariables.REGION".to_string(),
-                    value: chart_config_prerequisites.region.clone(),
-                },
                 ChartSetValue {
                     key: "environmentVariables.LIB_ROOT_DIR".to_string(),
                     value: "/home/qovery/lib".to_string(),
@@ -972,27 +1323,54 @@ datasources:
                     key: "engineResources.requests.memory".to_string(),
                     value: "512Mi".to_string(),
                 },
-                // build resources limits
-                ChartSetValue {
-                    key: "buildResources.limits.cpu".to_string(),
-                    value: "1".to_string(),
-                },
-                ChartSetValue {
-                    key: "buildResources.requests.cpu".to_string(),
-                    value: "500m".to_string(),
-                },
-                ChartSetValue {
-                    key: "buildResources.limits.memory".to_string(),
-                    value: "4Gi".to_string(),
-                },
-                ChartSetValue {
-                    key: "buildResources.requests.memory".to_string(),
-                    value: "4Gi".to_string(),
-                },
             ],
             ..Default::default()
         },
     };
+    qovery_engine
+        .chart_info
+        .values
+        .extend(qovery_engine_scheduling_values(chart_config_prerequisites));
+    // build resources limits
+    qovery_engine
+        .chart_info
+        .values
+        .extend(qovery_engine_build_resources_values(
+            &chart_config_prerequisites.infra_options.engine.build_resources,
+        )?);
+    qovery_engine
+        .chart_info
+        .values
+        .extend(qovery_engine_docker_hub_mirror_values(
+            &chart_config_prerequisites.infra_options.engine.docker_hub_mirror,
+        )?);
+    qovery_engine.chart_info.values.extend(qovery_engine_image_values(
+        &chart_config_prerequisites.infra_options.engine.image_registry,
+        chart_config_prerequisites.infra_options.engine.image_pull_policy,
+    ));
+    qovery_engine.chart_info.values.extend(qovery_engine_build_cache_values(
+        &chart_config_prerequisites.infra_options.engine.build_cache_backend,
+    )?);
+    qovery_engine
+        .chart_info
+        .values
+        .extend(qovery_engine_build_timeout_values(
+            chart_config_prerequisites.infra_options.engine.build_timeout_seconds,
+        )?);
+    qovery_engine
+        .chart_info
+        .values
+        .extend(qovery_engine_location_env_values(chart_config_prerequisites));
+    qovery_engine.chart_info.values.extend(qovery_engine_nats_tls_values(
+        chart_config_prerequisites.infra_options.engine.nats_tls_enabled,
+        &chart_config_prerequisites.infra_options.engine.nats_ca_cert,
+    )?);
+    // extra env must come last, so engine managed variables can be checked for collisions
+    let qovery_engine_extra_env = qovery_engine_extra_env_values(
+        &chart_config_prerequisites.infra_options.engine.extra_env,
+        &qovery_engine.chart_info.values,
+    );
+    qovery_engine.chart_info.values.extend(qovery_engine_extra_env);
 
     let container_registry_secret = CommonChart {
         chart_info: ChartInfo {
@@ -1039,16 +1417,29 @@ datasources:
     // chart deployment order matters!!!
     let level_1: Vec<Box<dyn HelmChart>> = vec![Box::new(q_storage_class), Box::new(coredns_config)];
 
-    let mut level_2: Vec<Box<dyn HelmChart>> = vec![Box::new(container_registry_secret), Box::new(cert_manager)];
+    let mut level_2: Vec<Box<dyn HelmChart>> = vec![Box::new(container_registry_secret)];
 
     let mut level_3: Vec<Box<dyn HelmChart>> = vec![];
 
     let mut level_4: Vec<Box<dyn HelmChart>> = vec![Box::new(metrics_server), Box::new(external_dns)];
+    let external_dns_providers = external_dns_providers_charts(chart_config_prerequisites, chart_prefix)?;
+    level_4.push(Box::new(ExternalDnsProvidersCleanupChart {
+        chart_info: ChartInfo {
+            name: "external-dns-providers-cleanup".to_string(),
+            ..Default::default()
+        },
+        configured_release_names: external_dns_providers
+            .iter()
+            .map(|chart| chart.chart_info.name.clone())
+            .collect(),
+    }));
+    for external_dns_chart in external_dns_providers {
+        level_4.push(Box::new(external_dns_chart));
+    }
 
     let mut level_5: Vec<Box<dyn HelmChart>> = vec![Box::new(nginx_ingress)];
 
     let mut level_6: Vec<Box<dyn HelmChart>> = vec![
-        Box::new(cert_manager_config),
         Box::new(qovery_agent),
         Box::new(cluster_agent),
         Box::new(shell_agent),
@@ -1057,6 +1448,11 @@ datasources:
         Box::new(k8s_token_rotate),
     ];
 
+    if let Some((cert_manager, cert_manager_config)) = cert_manager_charts(chart_config_prerequisites, chart_prefix)? {
+        level_2.push(Box::new(cert_manager));
+        level_6.push(Box::new(cert_manager_config));
+    }
+
     // observability
     if chart_config_prerequisites.ff_metrics_history_enabled {
         level_2.push(Box::new(kube_prometheus_stack));
@@ -1065,7 +1461,9 @@ datasources:
     }
     if chart_config_prerequisites.ff_log_history_enabled {
         level_3.push(Box::new(promtail));
-        level_4.push(Box::new(loki));
+        for loki in loki_charts {
+            level_4.push(Box::new(loki));
+        }
     }
 
     if chart_config_prerequisites.ff_metrics_history_enabled || chart_config_prerequisites.ff_log_history_enabled {
@@ -1077,6 +1475,2904 @@ datasources:
         level_5.push(Box::new(pleco));
     }
 
+    let mut charts_levels = vec![level_1, level_2, level_3, level_4, level_5, level_6];
+
+    // bootstrap canary, requires ingress, dns and certificates to be ready
+    if let Some(bootstrap_canary) = bootstrap_canary_chart(chart_config_prerequisites, chart_prefix) {
+        charts_levels.push(vec![Box::new(bootstrap_canary)]);
+    }
+
     info!("charts configuration preparation finished");
-    Ok(vec![level_1, level_2, level_3, level_4, level_5, level_6])
+    Ok(charts_levels)
+}
+
+/// Returns default prometheus external labels, identifying the cluster metrics come from once federated.
+pub fn default_prometheus_external_labels(cluster_id: &str, region: &str) -> BTreeMap<String, String> {
+    let mut labels = BTreeMap::new();
+    labels.insert("cluster_id".to_string(), cluster_id.to_string());
+    labels.insert("region".to_string(), region.to_string());
+    labels
+}
+
+/// Checks enabled features are consistent with the provided configuration, to fail early instead of during charts deployment.
+pub fn validate_feature_flags(chart_config_prerequisites: &ChartsConfigPrerequisites) -> Result<(), CommandError> {
+    // loki stores logs in DigitalOcean Spaces
+    if chart_config_prerequisites.ff_log_history_enabled
+        && (chart_config_prerequisites.do_space_access_id.is_empty()
+            || chart_config_prerequisites.do_space_secret_key.is_empty())
+    {
+        return Err(CommandError::new_from_safe_message(
+            "Logs history is enabled but DigitalOcean Spaces credentials are missing.".to_string(),
+        ));
+    }
+
+    // grafana is deployed as soon as logs or metrics history is enabled
+    if (chart_config_prerequisites.ff_log_history_enabled || chart_config_prerequisites.ff_metrics_history_enabled)
+        && (chart_config_prerequisites.infra_options.grafana_admin_user.is_empty()
+            || chart_config_prerequisites
+                .infra_options
+                .monitoring
+                .grafana_admin_password
+                .is_empty())
+    {
+        return Err(CommandError::new_from_safe_message(
+            "Logs or metrics history is enabled but Grafana admin credentials are missing.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Ensures managed DNS domain and resolvers, consumed as is by coredns and cert-manager charts, are well formed.
+pub fn validate_managed_dns(chart_config_prerequisites: &ChartsConfigPrerequisites) -> Result<(), CommandError> {
+    let domains = parse_braced_list("managed DNS", &chart_config_prerequisites.managed_dns_helm_format)?;
+    for domain in domains {
+        let is_valid_domain = domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+        if !is_valid_domain {
+            return Err(CommandError::new_from_safe_message(format!(
+                "Managed DNS `{}` is not a valid domain name.",
+                domain
+            )));
+        }
+    }
+
+    let resolvers = parse_braced_list(
+        "managed DNS resolvers",
+        &chart_config_prerequisites.managed_dns_resolvers_terraform_format,
+    )?;
+    for resolver in resolvers {
+        if IpAddr::from_str(resolver).is_err() {
+            return Err(CommandError::new_from_safe_message(format!(
+                "Managed DNS resolver `{}` is not a valid IP address.",
+                resolver
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensures ACME URL given to cert-manager is a known Let's Encrypt directory, or at least looks like an ACME directory.
+pub fn validate_acme_url(acme_url: &str) -> Result<(), CommandError> {
+    if LETS_ENCRYPT_ACME_DIRECTORIES.contains(&acme_url) {
+        return Ok(());
+    }
+
+    match Url::parse(acme_url) {
+        Ok(url) if url.scheme() == "https" && url.host_str().is_some() && url.path().ends_with("/directory") => Ok(()),
+        _ => Err(CommandError::new_from_safe_message(format!(
+            "ACME URL `{}` is not valid, an https URL to an ACME directory (ending with `/directory`) is expected.",
+            acme_url
+        ))),
+    }
+}
+
+/// Returns prometheus remote write values, endpoints being http(s) URLs.
+fn prometheus_remote_write_values(remote_writes: &[RemoteWriteConfig]) -> Result<Vec<ChartSetValue>, CommandError> {
+    let mut values = vec![];
+
+    for (index, remote_write) in remote_writes.iter().enumerate() {
+        match Url::parse(&remote_write.url) {
+            Ok(url) if (url.scheme() == "https" || url.scheme() == "http") && url.host_str().is_some() => {}
+            _ => {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "Prometheus remote write URL `{}` is not valid, an http(s) URL is expected.",
+                    remote_write.url
+                )))
+            }
+        }
+
+        let prefix = format!("prometheus.prometheusSpec.remoteWrite[{}]", index);
+        values.push(ChartSetValue {
+            key: format!("{}.url", prefix),
+            value: remote_write.url.clone(),
+        });
+
+        if let Some(basic_auth) = &remote_write.basic_auth {
+            values.extend(vec![
+                ChartSetValue {
+                    key: format!("{}.basicAuth.username.name", prefix),
+                    value: basic_auth.secret_name.clone(),
+                },
+                ChartSetValue {
+                    key: format!("{}.basicAuth.username.key", prefix),
+                    value: basic_auth.username_key.clone(),
+                },
+                ChartSetValue {
+                    key: format!("{}.basicAuth.password.name", prefix),
+                    value: basic_auth.secret_name.clone(),
+                },
+                ChartSetValue {
+                    key: format!("{}.basicAuth.password.key", prefix),
+                    value: basic_auth.password_key.clone(),
+                },
+            ]);
+        }
+    }
+
+    Ok(values)
+}
+
+/// Parses a `{item1,item2}` formatted list, expecting at least one item.
+fn parse_braced_list<'a>(name: &str, value: &'a str) -> Result<Vec<&'a str>, CommandError> {
+    let items = match value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+        Some(items) => items,
+        None => {
+            return Err(CommandError::new_from_safe_message(format!(
+                "{} `{}` is malformed, expected format is `{{item1,item2}}`.",
+                name, value
+            )))
+        }
+    };
+
+    let items = items.split(',').map(|item| item.trim()).collect::<Vec<_>>();
+    if items.iter().any(|item| item.is_empty()) {
+        return Err(CommandError::new_from_safe_message(format!(
+            "{} `{}` should contain at least one item and no empty ones.",
+            name, value
+        )));
+    }
+
+    Ok(items)
+}
+
+/// Returns prometheus-adapter replicas and pod disruption budget values, keeping them consistent:
+/// the pod disruption budget is only enabled when more than one replica is running.
+fn prometheus_adapter_replicas_values(replicas: u32) -> Result<Vec<ChartSetValue>, CommandError> {
+    if replicas < 1 {
+        return Err(CommandError::new_from_safe_message(format!(
+            "Invalid prometheus-adapter replicas count `{}`, at least 1 replica is required.",
+            replicas
+        )));
+    }
+
+    let mut values = vec![
+        ChartSetValue {
+            key: "replicas".to_string(),
+            value: replicas.to_string(),
+        },
+        ChartSetValue {
+            key: "podDisruptionBudget.enabled".to_string(),
+            value: (replicas > 1).to_string(),
+        },
+    ];
+
+    if replicas > 1 {
+        values.push(ChartSetValue {
+            key: "podDisruptionBudget.maxUnavailable".to_string(),
+            value: "1".to_string(),
+        });
+    }
+
+    Ok(values)
+}
+
+/// Returns cert-manager extra args for DNS01 self-check, using the nameservers set for the DNS provider in use
+/// (`ip:port` format) or the default recursive nameservers.
+fn cert_manager_dns01_extra_args_value(
+    dns_provider: &str,
+    nameservers_by_provider: &BTreeMap<String, Vec<String>>,
+) -> Result<ChartSetValue, CommandError> {
+    for (provider, nameservers) in nameservers_by_provider {
+        if nameservers.is_empty() {
+            return Err(CommandError::new_from_safe_message(format!(
+                "DNS01 self-check nameservers for provider `{}` requires at least one nameserver.",
+                provider
+            )));
+        }
+
+        for nameserver in nameservers {
+            if SocketAddr::from_str(nameserver).is_err() {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "DNS01 self-check nameserver `{}` for provider `{}` is invalid, expected format is `ip:port`.",
+                    nameserver, provider
+                )));
+            }
+        }
+    }
+
+    let nameservers = match nameservers_by_provider.get(dns_provider) {
+        Some(nameservers) => nameservers.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
+        None => DEFAULT_DNS01_RECURSIVE_NAMESERVERS.to_vec(),
+    };
+
+    Ok(ChartSetValue {
+        key: "extraArgs".to_string(),
+        value: format!(
+            "{{--dns01-recursive-nameservers-only,--dns01-recursive-nameservers={}}}",
+            nameservers.join("\\,")
+        ),
+    })
+}
+
+/// Returns CoreDNS stub domains values, each zone being forwarded to its resolvers (`ip:port` format).
+fn coredns_stub_domains_values(
+    stub_domains: &BTreeMap<String, Vec<String>>,
+) -> Result<Vec<ChartSetValue>, CommandError> {
+    let mut values = vec![];
+
+    for (zone, resolvers) in stub_domains {
+        if zone.is_empty() || resolvers.is_empty() {
+            return Err(CommandError::new_from_safe_message(format!(
+                "CoreDNS stub domain `{}` requires a zone and at least one resolver.",
+                zone
+            )));
+        }
+
+        for resolver in resolvers {
+            if SocketAddr::from_str(resolver).is_err() {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "CoreDNS stub domain `{}` resolver `{}` is invalid, expected format is `ip:port`.",
+                    zone, resolver
+                )));
+            }
+        }
+
+        values.push(ChartSetValue {
+            key: format!("stub_domains.{}", escape_chart_set_string(zone)),
+            value: format!("{{{}}}", resolvers.join(",")),
+        });
+    }
+
+    Ok(values)
+}
+
+/// Returns CoreDNS config chart, forwarding managed domains to their resolvers.
+fn coredns_config_chart(
+    chart_config_prerequisites: &ChartsConfigPrerequisites,
+    chart_prefix: &str,
+) -> Result<CoreDNSConfigChart, CommandError> {
+    let mut coredns_config = CoreDNSConfigChart {
+        chart_info: ChartInfo {
+            name: "coredns".to_string(),
+            path: format!("{}/{}", chart_prefix, "/charts/coredns-config"),
+            values: vec![
+                ChartSetValue {
+                    key: "managed_dns".to_string(),
+                    value: chart_config_prerequisites.managed_dns_helm_format.clone(),
+                },
+                ChartSetValue {
+                    key: "managed_dns_resolvers".to_string(),
+                    value: chart_config_prerequisites
+                        .managed_dns_resolvers_terraform_format
+                        .clone(),
+                },
+            ],
+            ..Default::default()
+        },
+    };
+    coredns_config.chart_info.values.extend(coredns_stub_domains_values(
+        &chart_config_prerequisites.infra_options.dns.coredns_stub_domains,
+    )?);
+    coredns_config.chart_info.values.extend(coredns_auto_reload_values(
+        chart_config_prerequisites.infra_options.dns.coredns_auto_reload,
+    ));
+
+    Ok(coredns_config)
+}
+
+/// Returns whether CoreDNS `reload` plugin is enabled, otherwise config changes only take effect on CoreDNS restart.
+fn coredns_auto_reload_values(auto_reload: bool) -> Vec<ChartSetValue> {
+    vec![ChartSetValue {
+        key: "auto_reload".to_string(),
+        value: auto_reload.to_string(),
+    }]
+}
+
+/// Returns qovery-engine default build resources.
+pub fn default_engine_build_resources() -> ResourceSpec {
+    ResourceSpec {
+        cpu_request: "500m".to_string(),
+        cpu_limit: "1".to_string(),
+        memory_request: "4Gi".to_string(),
+        memory_limit: "4Gi".to_string(),
+    }
+}
+
+/// Returns tolerations applied to system daemonsets by default: every taint is tolerated.
+pub fn default_system_chart_tolerations() -> Vec<Toleration> {
+    vec![Toleration {
+        key: "".to_string(),
+        operator: "Exists".to_string(),
+        value: None,
+        effect: None,
+    }]
+}
+
+/// Returns loki pod affinity values.
+fn loki_affinity_values(loki_affinity: &LokiAffinity) -> Result<Vec<ChartSetValue>, CommandError> {
+    if loki_affinity.match_labels.is_empty() || loki_affinity.topology_key.is_empty() {
+        return Err(CommandError::new_from_safe_message(
+            "Loki affinity requires labels to match and a topology key.".to_string(),
+        ));
+    }
+
+    let (affinity_term, mut values) = match loki_affinity.preferred_weight {
+        Some(weight) if !(1..=100).contains(&weight) => {
+            return Err(CommandError::new_from_safe_message(format!(
+                "Invalid loki affinity weight `{}`, it must be between 1 and 100.",
+                weight
+            )))
+        }
+        Some(weight) => {
+            let preferred = "affinity.podAffinity.preferredDuringSchedulingIgnoredDuringExecution[0]";
+            (
+                format!("{}.podAffinityTerm", preferred),
+                vec![ChartSetValue {
+                    key: format!("{}.weight", preferred),
+                    value: weight.to_string(),
+                }],
+            )
+        }
+        None => (
+            "affinity.podAffinity.requiredDuringSchedulingIgnoredDuringExecution[0]".to_string(),
+            vec![],
+        ),
+    };
+
+    values.push(ChartSetValue {
+        key: format!("{}.topologyKey", affinity_term),
+        value: loki_affinity.topology_key.clone(),
+    });
+    values.extend(get_string_map_values(
+        &format!("{}.labelSelector.matchLabels", affinity_term),
+        &loki_affinity.match_labels,
+    ));
+
+    Ok(values)
+}
+
+/// Returns the loki service promtail pushes logs to.
+fn loki_write_service_name(mode: LokiMode, loki_service_name: &str) -> String {
+    match mode {
+        LokiMode::Monolithic => loki_service_name.to_string(),
+        LokiMode::SimpleScalable => format!("{}-write", loki_service_name),
+    }
+}
+
+/// Returns the loki service grafana and qovery-agent query logs from.
+fn loki_read_service_name(mode: LokiMode, loki_service_name: &str) -> String {
+    match mode {
+        LokiMode::Monolithic => loki_service_name.to_string(),
+        LokiMode::SimpleScalable => format!("{}-read", loki_service_name),
+    }
+}
+
+/// Returns the loki releases to deploy with their specific values.
+/// In simple scalable mode, read and write releases join the same memberlist ring through both headless services.
+fn loki_components(
+    mode: LokiMode,
+    loki_service_name: &str,
+    loki_namespace: HelmChartNamespaces,
+) -> Vec<(String, Vec<ChartSetValue>)> {
+    match mode {
+        LokiMode::Monolithic => vec![(loki_service_name.to_string(), vec![])],
+        LokiMode::SimpleScalable => {
+            let write_service_name = loki_write_service_name(mode, loki_service_name);
+            let read_service_name = loki_read_service_name(mode, loki_service_name);
+            let join_members = [&write_service_name, &read_service_name];
+
+            vec![
+                ("write", write_service_name.clone()),
+                ("read", read_service_name.clone()),
+            ]
+            .into_iter()
+            .map(|(target, name)| {
+                let mut values = vec![
+                    ChartSetValue {
+                        key: "extraArgs.target".to_string(),
+                        value: target.to_string(),
+                    },
+                    ChartSetValue {
+                        key: "replicas".to_string(),
+                        value: LOKI_SIMPLE_SCALABLE_REPLICAS.to_string(),
+                    },
+                    ChartSetValue {
+                        key: "image.tag".to_string(),
+                        value: LOKI_SIMPLE_SCALABLE_IMAGE_TAG.to_string(),
+                    },
+                ];
+                for (i, member) in join_members.iter().enumerate() {
+                    values.push(ChartSetValue {
+                        key: format!("config.memberlist.join_members[{}]", i),
+                        value: format!("{}-headless.{}.svc:{}", member, loki_namespace, LOKI_MEMBERLIST_PORT),
+                    });
+                }
+
+                (name, values)
+            })
+            .collect()
+        }
+    }
+}
+
+/// Returns the loki releases deployed by the other modes, which must not keep running along with the current mode ones.
+fn loki_stale_release_names(mode: LokiMode, loki_service_name: &str) -> Vec<String> {
+    let release_names = |mode: LokiMode| match mode {
+        LokiMode::Monolithic => vec![loki_service_name.to_string()],
+        LokiMode::SimpleScalable => vec![
+            loki_write_service_name(mode, loki_service_name),
+            loki_read_service_name(mode, loki_service_name),
+        ],
+    };
+    let current_release_names = release_names(mode);
+
+    [LokiMode::Monolithic, LokiMode::SimpleScalable]
+        .iter()
+        .filter(|x| **x != mode)
+        .flat_map(|x| release_names(*x))
+        .filter(|name| !current_release_names.contains(name))
+        .collect()
+}
+
+/// Returns qovery-agent liveness probe values, no probe is set when not configured.
+fn qovery_agent_liveness_probe_values(
+    probe: &Option<QoveryAgentLivenessProbe>,
+) -> Result<Vec<ChartSetValue>, CommandError> {
+    let probe = match probe {
+        Some(probe) => probe,
+        None => return Ok(vec![]),
+    };
+
+    if !probe.path.starts_with('/') {
+        return Err(CommandError::new_from_safe_message(format!(
+            "qovery-agent liveness probe path `{}` must start with `/`.",
+            probe.path
+        )));
+    }
+    if probe.port == 0 || probe.period_seconds == 0 || probe.timeout_seconds == 0 || probe.failure_threshold == 0 {
+        return Err(CommandError::new_from_safe_message(
+            "qovery-agent liveness probe port, period, timeout and failure threshold must be greater than 0."
+                .to_string(),
+        ));
+    }
+
+    Ok(vec![
+        ChartSetValue {
+            key: "livenessProbe.httpGet.path".to_string(),
+            value: probe.path.clone(),
+        },
+        ChartSetValue {
+            key: "livenessProbe.httpGet.port".to_string(),
+            value: probe.port.to_string(),
+        },
+        ChartSetValue {
+            key: "livenessProbe.initialDelaySeconds".to_string(),
+            value: probe.initial_delay_seconds.to_string(),
+        },
+        ChartSetValue {
+            key: "livenessProbe.periodSeconds".to_string(),
+            value: probe.period_seconds.to_string(),
+        },
+        ChartSetValue {
+            key: "livenessProbe.timeoutSeconds".to_string(),
+            value: probe.timeout_seconds.to_string(),
+        },
+        ChartSetValue {
+            key: "livenessProbe.failureThreshold".to_string(),
+            value: probe.failure_threshold.to_string(),
+        },
+    ])
+}
+
+/// Returns promtail values to push logs to loki.
+fn promtail_loki_values(loki_kube_dns_prefix: &str, loki_port: u16) -> Vec<ChartSetValue> {
+    vec![
+        ChartSetValue {
+            key: "loki.serviceName".to_string(),
+            value: loki_kube_dns_prefix.to_string(),
+        },
+        ChartSetValue {
+            key: "loki.servicePort".to_string(),
+            value: loki_port.to_string(),
+        },
+    ]
+}
+
+/// Returns promtail values dropping noisy pod labels, to keep loki indexes cardinality low.
+/// Promtail pipeline stages can't drop labels in the deployed version, so they are dropped when relabeling scraped pods.
+fn promtail_drop_labels_values(drop_labels: &[String]) -> Result<Vec<ChartSetValue>, CommandError> {
+    if drop_labels.is_empty() {
+        return Ok(vec![]);
+    }
+
+    for label in drop_labels {
+        let is_valid_label_name = label
+            .chars()
+            .enumerate()
+            .all(|(index, c)| c == '_' || c.is_ascii_alphabetic() || (index > 0 && c.is_ascii_digit()));
+        if label.is_empty() || !is_valid_label_name {
+            return Err(CommandError::new_from_safe_message(format!(
+                "Promtail drop label `{}` is not a valid label name.",
+                label
+            )));
+        }
+    }
+
+    Ok(vec![
+        ChartSetValue {
+            key: "extraRelabelConfigs[0].action".to_string(),
+            value: "labeldrop".to_string(),
+        },
+        ChartSetValue {
+            key: "extraRelabelConfigs[0].regex".to_string(),
+            value: drop_labels.join("|"),
+        },
+    ])
+}
+
+/// Returns loki URL qovery-agent reads logs from.
+fn qovery_agent_loki_url(loki_kube_dns_prefix: &str, loki_port: u16) -> String {
+    format!("http://{}.cluster.local:{}", loki_kube_dns_prefix, loki_port)
+}
+
+/// Returns a random alphanumeric grafana admin password.
+pub fn generate_grafana_admin_password() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(GRAFANA_ADMIN_PASSWORD_LENGTH)
+        .map(char::from)
+        .collect::<String>()
+}
+
+/// Returns grafana admin password and ingress values, grafana is only exposed when an ingress host is set.
+fn grafana_values(admin_password: &str, ingress_host: &Option<String>) -> Vec<ChartSetValue> {
+    let mut values = vec![];
+
+    if !admin_password.is_empty() {
+        values.push(ChartSetValue {
+            key: "adminPassword".to_string(),
+            value: admin_password.to_string(),
+        });
+    }
+
+    if let Some(host) = ingress_host {
+        let mut annotations = BTreeMap::new();
+        annotations.insert("kubernetes.io/ingress.class".to_string(), "nginx-qovery".to_string());
+        annotations.insert("cert-manager.io/cluster-issuer".to_string(), "letsencrypt-qovery".to_string());
+
+        values.extend(vec![
+            ChartSetValue {
+                key: "ingress.enabled".to_string(),
+                value: "true".to_string(),
+            },
+            ChartSetValue {
+                key: "ingress.hosts[0]".to_string(),
+                value: host.to_string(),
+            },
+            ChartSetValue {
+                key: "ingress.tls[0].secretName".to_string(),
+                value: "grafana-tls".to_string(),
+            },
+            ChartSetValue {
+                key: "ingress.tls[0].hosts[0]".to_string(),
+                value: host.to_string(),
+            },
+        ]);
+        values.extend(get_string_map_values("ingress.annotations", &annotations));
+    }
+
+    values
+}
+
+/// Returns grafana datasources, prometheus being the default one.
+fn grafana_datasources(prometheus_internal_url: &str, loki_kube_dns_prefix: &str, loki_port: u16) -> String {
+    format!(
+        "
+datasources:
+  datasources.yaml:
+    apiVersion: 1
+    datasources:
+      - name: Prometheus
+        type: prometheus
+        url: \"{}:9090\"
+        access: proxy
+        isDefault: true
+      - name: PromLoki
+        type: prometheus
+        url: \"http://{}:{}/loki\"
+        access: proxy
+        isDefault: false
+      - name: Loki
+        type: loki
+        url: \"http://{}:{}\"
+      ",
+        prometheus_internal_url, loki_kube_dns_prefix, loki_port, loki_kube_dns_prefix, loki_port,
+    )
+}
+
+/// Returns tolerations values of system daemonsets, which have to run on every node.
+fn system_chart_tolerations_values(chart_config_prerequisites: &ChartsConfigPrerequisites) -> Vec<ChartSetValue> {
+    get_tolerations_values(
+        "tolerations",
+        &chart_config_prerequisites
+            .infra_options
+            .monitoring
+            .system_chart_tolerations,
+    )
+}
+
+/// Returns qovery-engine build resources values, once validated.
+fn qovery_engine_build_resources_values(resources: &ResourceSpec) -> Result<Vec<ChartSetValue>, CommandError> {
+    resources.validate()?;
+    Ok(resources.to_chart_set_values("buildResources"))
+}
+
+/// Returns digital-mobius image tag value when overridden, the chart default tag is used otherwise.
+fn digital_mobius_image_tag_values(image_tag: &Option<String>) -> Vec<ChartSetValue> {
+    match image_tag {
+        Some(image_tag) => vec![ChartSetValue {
+            key: "image.digitalMobiusImageTag".to_string(),
+            value: image_tag.to_string(),
+        }],
+        None => vec![],
+    }
+}
+
+/// Returns qovery-engine image values, the engine image is expected to be named `engine` in the overriding registry.
+fn qovery_engine_image_values(registry: &Option<String>, pull_policy: ImagePullPolicy) -> Vec<ChartSetValue> {
+    let mut values = vec![ChartSetValue {
+        key: "image.pullPolicy".to_string(),
+        value: pull_policy.to_string(),
+    }];
+    if let Some(registry) = registry {
+        values.push(ChartSetValue {
+            key: "image.repository".to_string(),
+            value: format!("{}/engine", registry.trim_end_matches('/')),
+        });
+    }
+
+    values
+}
+
+/// Returns qovery-engine NATS TLS values, the CA certificate (PEM format) being mounted into the engine container.
+fn qovery_engine_nats_tls_values(enabled: bool, ca_cert: &Option<String>) -> Result<Vec<ChartSetValue>, CommandError> {
+    if !enabled {
+        return Ok(vec![ChartSetValue {
+            key: "nats.tls.enabled".to_string(),
+            value: "false".to_string(),
+        }]);
+    }
+
+    let ca_cert = match ca_cert {
+        Some(ca_cert)
+            if ca_cert.trim().starts_with("-----BEGIN CERTIFICATE-----")
+                && ca_cert.trim().ends_with("-----END CERTIFICATE-----") =>
+        {
+            ca_cert.trim()
+        }
+        _ => {
+            return Err(CommandError::new_from_safe_message(
+                "NATS TLS is enabled but its CA certificate is missing or isn't PEM encoded.".to_string(),
+            ))
+        }
+    };
+
+    Ok(vec![
+        ChartSetValue {
+            key: "nats.tls.enabled".to_string(),
+            value: "true".to_string(),
+        },
+        ChartSetValue {
+            key: "nats.tls.caCert".to_string(),
+            value: ca_cert.replace(',', "\\,"),
+        },
+        ChartSetValue {
+            key: "environmentVariables.QOVERY_NATS_TLS_ENABLED".to_string(),
+            value: "true".to_string(),
+        },
+        ChartSetValue {
+            key: "environmentVariables.QOVERY_NATS_CA_CERT_PATH".to_string(),
+            value: format!("{}/ca.crt", QOVERY_ENGINE_NATS_CA_MOUNT_PATH),
+        },
+    ])
+}
+
+/// Returns qovery-engine build cache values, the engine default cache is kept when not configured.
+fn qovery_engine_build_cache_values(
+    build_cache: &Option<BuildCacheConfig>,
+) -> Result<Vec<ChartSetValue>, CommandError> {
+    let backend_value = |backend: &str| ChartSetValue {
+        key: "environmentVariables.QOVERY_BUILD_CACHE_BACKEND".to_string(),
+        value: backend.to_string(),
+    };
+
+    match build_cache {
+        None => Ok(vec![]),
+        Some(BuildCacheConfig::Local) => Ok(vec![backend_value("local")]),
+        Some(BuildCacheConfig::Registry { reference }) => {
+            if reference.is_empty() || reference.contains("://") || reference.contains(char::is_whitespace) {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "Build cache registry reference `{}` must be an image reference without scheme (ex: `registry.acme.com/qovery/build-cache`).",
+                    reference
+                )));
+            }
+
+            Ok(vec![
+                backend_value("registry"),
+                ChartSetValue {
+                    key: "environmentVariables.QOVERY_BUILD_CACHE_REGISTRY_REF".to_string(),
+                    value: reference.to_string(),
+                },
+            ])
+        }
+    }
+}
+
+/// Returns qovery-engine build timeout value, bounding image builds independently of the chart deployment timeout.
+fn qovery_engine_build_timeout_values(timeout_seconds: u32) -> Result<Vec<ChartSetValue>, CommandError> {
+    if timeout_seconds == 0 {
+        return Err(CommandError::new_from_safe_message(
+            "qovery-engine build timeout must be greater than 0 seconds.".to_string(),
+        ));
+    }
+
+    Ok(vec![ChartSetValue {
+        key: format!("environmentVariables.{}", BUILD_DURATION_TIMEOUT_ENV_VAR),
+        value: timeout_seconds.to_string(),
+    }])
+}
+
+/// Returns qovery-engine extra environment variables values.
+/// Variables already managed by the engine chart take precedence, colliding extra variables are dropped.
+fn qovery_engine_extra_env_values(
+    extra_env: &BTreeMap<String, String>,
+    engine_values: &[ChartSetValue],
+) -> Vec<ChartSetValue> {
+    get_string_map_values("environmentVariables", extra_env)
+        .into_iter()
+        .filter(|extra_value| !engine_values.iter().any(|value| value.key == extra_value.key))
+        .collect()
+}
+
+/// Returns a warning for each qovery-engine extra environment variable overridden by the engine chart.
+pub fn qovery_engine_ignored_extra_env(
+    charts: &[Vec<Box<dyn HelmChart>>],
+    extra_env: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let engine_values = match charts
+        .iter()
+        .flatten()
+        .find(|chart| chart.get_chart_info().name == "qovery-engine")
+    {
+        Some(chart) => &chart.get_chart_info().values,
+        None => return vec![],
+    };
+
+    extra_env
+        .iter()
+        .filter(|(name, value)| {
+            let key = format!("environmentVariables.{}", name);
+            engine_values.iter().any(|v| v.key == key && &v.value != *value)
+        })
+        .map(|(name, _)| {
+            format!(
+                "qovery-engine extra environment variable `{}` is ignored as it is already managed by the engine chart.",
+                name
+            )
+        })
+        .collect()
+}
+
+/// Returns qovery-engine Docker Hub mirror value, so base images are pulled through a mirror instead of Docker Hub.
+fn qovery_engine_docker_hub_mirror_values(
+    docker_hub_mirror: &Option<String>,
+) -> Result<Vec<ChartSetValue>, CommandError> {
+    let docker_hub_mirror = match docker_hub_mirror {
+        Some(docker_hub_mirror) => docker_hub_mirror,
+        None => return Ok(vec![]),
+    };
+
+    match Url::parse(docker_hub_mirror) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(vec![ChartSetValue {
+            key: "environmentVariables.DOCKER_HUB_MIRROR".to_string(),
+            value: url.to_string(),
+        }]),
+        _ => Err(CommandError::new_from_safe_message(format!(
+            "Docker Hub mirror `{}` is not a valid http(s) URL.",
+            docker_hub_mirror
+        ))),
+    }
+}
+
+/// Returns qovery-engine node selector and tolerations values, to pin it on a dedicated node pool.
+fn qovery_engine_scheduling_values(chart_config_prerequisites: &ChartsConfigPrerequisites) -> Vec<ChartSetValue> {
+    let mut values =
+        get_string_map_values("nodeSelector", &chart_config_prerequisites.infra_options.engine.node_selector);
+    values.extend(get_tolerations_values(
+        "tolerations",
+        &chart_config_prerequisites.infra_options.engine.tolerations,
+    ));
+    values
+}
+
+/// Returns the engine environment variables locating the cluster (organization, cloud provider and region).
+fn qovery_engine_location_env_values(chart_config_prerequisites: &ChartsConfigPrerequisites) -> Vec<ChartSetValue> {
+    vec![
+        ChartSetValue {
+            key: "environmentVariables.ORGANIZATION".to_string(),
+            value: chart_config_prerequisites.organization_id.clone(),
+        },
+        ChartSetValue {
+            key: "environmentVariables.CLOUD_PROVIDER".to_string(),
+            value: chart_config_prerequisites.cloud_provider.short_name().to_string(),
+        },
+        ChartSetValue {
+            key: "environmentVariables.REGION".to_string(),
+            value: chart_config_prerequisites.region.clone(),
+        },
+    ]
+}
+
+/// Returns the ServiceMonitor toggle value of a chart, consistently enabled along with metrics history.
+fn service_monitor_values(chart_name: &str, enable_service_monitors: bool) -> Vec<ChartSetValue> {
+    SERVICE_MONITOR_VALUE_KEYS
+        .iter()
+        .filter(|(name, _)| *name == chart_name)
+        .map(|(name, key)| ChartSetValue {
+            key: key.to_string(),
+            // Due to cycle, prometheus need tls certificate from cert manager, and enabling this will require
+            // prometheus to be already installed
+            value: (enable_service_monitors && *name != "cert-manager").to_string(),
+        })
+        .collect()
+}
+
+/// Returns cert-manager and its configuration charts, unless TLS is terminated outside of the cluster or by a
+/// cert-manager already deployed by the user.
+fn cert_manager_charts(
+    chart_config_prerequisites: &ChartsConfigPrerequisites,
+    chart_prefix: &str,
+) -> Result<Option<(CommonChart, CommonChart)>, CommandError> {
+    if !chart_config_prerequisites.infra_options.dns.deploy_cert_manager {
+        return Ok(None);
+    }
+
+    let chart_path = |x: &str| -> String { format!("{}/{}", &chart_prefix, x) };
+
+    let mut cert_manager = CommonChart {
+        chart_info: ChartInfo {
+            name: "cert-manager".to_string(),
+            path: chart_path("common/charts/cert-manager"),
+            namespace: HelmChartNamespaces::CertManager,
+            values: vec![
+                ChartSetValue {
+                    key: "installCRDs".to_string(),
+                    value: "true".to_string(),
+                },
+                ChartSetValue {
+                    key: "replicaCount".to_string(),
+                    value: "1".to_string(),
+                },
+                // https://cert-manager.io/docs/configuration/acme/dns01/#setting-nameservers-for-dns01-self-check
+                cert_manager_dns01_extra_args_value(
+                    &chart_config_prerequisites.external_dns_provider,
+                    &chart_config_prerequisites
+                        .infra_options
+                        .dns
+                        .dns01_self_check_nameservers,
+                )?,
+                ChartSetValue {
+                    key: "prometheus.servicemonitor.prometheusInstance".to_string(),
+                    value: "qovery".to_string(),
+                },
+                // resources limits
+                ChartSetValue {
+                    key: "resources.limits.cpu".to_string(),
+                    value: "200m".to_string(),
+                },
+                ChartSetValue {
+                    key: "resources.requests.cpu".to_string(),
+                    value: "100m".to_string(),
+                },
+                ChartSetValue {
+                    key: "resources.limits.memory".to_string(),
+                    value: "1Gi".to_string(),
+                },
+                ChartSetValue {
+                    key: "resources.requests.memory".to_string(),
+                    value: "1Gi".to_string(),
+                },
+                // Webhooks resources limits
+                ChartSetValue {
+                    key: "webhook.resources.limits.cpu".to_string(),
+                    value: "200m".to_string(),
+                },
+                ChartSetValue {
+                    key: "webhook.resources.requests.cpu".to_string(),
+                    value: "50m".to_string(),
+                },
+                ChartSetValue {
+                    key: "webhook.resources.limits.memory".to_string(),
+                    value: "128Mi".to_string(),
+                },
+                ChartSetValue {
+                    key: "webhook.resources.requests.memory".to_string(),
+                    value: "128Mi".to_string(),
+                },
+                // Cainjector resources limits
+                ChartSetValue {
+                    key: "cainjector.resources.limits.cpu".to_string(),
+                    value: "500m".to_string(),
+                },
+                ChartSetValue {
+                    key: "cainjector.resources.requests.cpu".to_string(),
+                    value: "100m".to_string(),
+                },
+                ChartSetValue {
+                    key: "cainjector.resources.limits.memory".to_string(),
+                    value: "1Gi".to_string(),
+                },
+                ChartSetValue {
+                    key: "cainjector.resources.requests.memory".to_string(),
+                    value: "1Gi".to_string(),
+                },
+            ],
+            ..Default::default()
+        },
+    };
+
+    cert_manager.chart_info.values.extend(service_monitor_values(
+        "cert-manager",
+        chart_config_prerequisites.enable_service_monitors(),
+    ));
+
+    let mut cert_manager_config = CommonChart {
+        chart_info: ChartInfo {
+            name: "cert-manager-configs".to_string(),
+            path: chart_path("common/charts/cert-manager-configs"),
+            namespace: HelmChartNamespaces::CertManager,
+            values: vec![
+                ChartSetValue {
+                    key: "externalDnsProvider".to_string(),
+                    value: chart_config_prerequisites.external_dns_provider.clone(),
+                },
+                ChartSetValue {
+                    key: "acme.letsEncrypt.emailReport".to_string(),
+                    value: chart_config_prerequisites.dns_email_report.clone(),
+                },
+                ChartSetValue {
+                    key: "acme.letsEncrypt.acmeUrl".to_string(),
+                    value: chart_config_prerequisites.acme_url.clone(),
+                },
+                ChartSetValue {
+                    key: "managedDns".to_string(),
+                    value: chart_config_prerequisites.managed_dns_helm_format.clone(),
+                },
+            ],
+            ..Default::default()
+        },
+    };
+    if chart_config_prerequisites.external_dns_provider == "cloudflare" {
+        cert_manager_config.chart_info.values.push(ChartSetValue {
+            key: "provider.cloudflare.apiToken".to_string(),
+            value: chart_config_prerequisites.cloudflare_api_token.clone(),
+        });
+        cert_manager_config.chart_info.values.push(ChartSetValue {
+            key: "provider.cloudflare.email".to_string(),
+            value: chart_config_prerequisites.cloudflare_email.clone(),
+        })
+    }
+
+    Ok(Some((cert_manager, cert_manager_config)))
+}
+
+const EXTERNAL_DNS_PROVIDER_RELEASE_PREFIX: &str = "externaldns-";
+
+/// Uninstalls external-dns providers releases which are no longer configured, they would keep managing records
+/// otherwise.
+pub struct ExternalDnsProvidersCleanupChart {
+    pub chart_info: ChartInfo,
+    pub configured_release_names: Vec<String>,
+}
+
+impl HelmChart for ExternalDnsProvidersCleanupChart {
+    fn get_chart_info(&self) -> &ChartInfo {
+        &self.chart_info
+    }
+
+    fn get_chart_info_mut(&mut self) -> &mut ChartInfo {
+        &mut self.chart_info
+    }
+
+    fn exec(
+        &self,
+        kubernetes_config: &Path,
+        envs: &[(String, String)],
+        payload: Option<ChartPayload>,
+    ) -> Result<Option<ChartPayload>, CommandError> {
+        let environment_variables: Vec<(&str, &str)> = envs.iter().map(|x| (x.0.as_str(), x.1.as_str())).collect();
+        let helm = Helm::new(kubernetes_config, &environment_variables).map_err(to_command_error)?;
+        let namespace = self.chart_info.get_namespace_string();
+        let release_names = helm
+            .list_release(Some(namespace.as_str()), &[])
+            .map_err(to_command_error)?
+            .into_iter()
+            .map(|release| release.name)
+            .collect::<Vec<_>>();
+
+        for release_name in stale_external_dns_provider_releases(&release_names, &self.configured_release_names) {
+            let chart_info = ChartInfo::new_from_release_name(release_name, namespace.as_str());
+            helm.uninstall(&chart_info, &[]).map_err(to_command_error)?;
+        }
+
+        Ok(payload)
+    }
+}
+
+/// Returns external-dns providers releases which aren't part of the configured ones.
+fn stale_external_dns_provider_releases<'a>(
+    release_names: &'a [String],
+    configured_release_names: &[String],
+) -> Vec<&'a str> {
+    release_names
+        .iter()
+        .filter(|name| {
+            name.starts_with(EXTERNAL_DNS_PROVIDER_RELEASE_PREFIX) && !configured_release_names.contains(name)
+        })
+        .map(|name| name.as_str())
+        .collect()
+}
+
+/// Returns one external-dns chart per additional DNS provider, each one only managing its own domains.
+fn external_dns_providers_charts(
+    chart_config_prerequisites: &ChartsConfigPrerequisites,
+    chart_prefix: &str,
+) -> Result<Vec<CommonChart>, CommandError> {
+    let mut charts = Vec::with_capacity(
+        chart_config_prerequisites
+            .infra_options
+            .dns
+            .external_dns_providers
+            .len(),
+    );
+
+    for (index, provider) in chart_config_prerequisites
+        .infra_options
+        .dns
+        .external_dns_providers
+        .iter()
+        .enumerate()
+    {
+        let provider_name = provider.credentials.provider_name();
+        if provider.domain_filters.is_empty() {
+            return Err(CommandError::new_from_safe_message(format!(
+                "External DNS provider `{}` requires at least one domain.",
+                provider_name
+            )));
+        }
+
+        let mut values = vec![
+            ChartSetValue {
+                key: "provider".to_string(),
+                value: provider_name.to_string(),
+            },
+            external_dns_policy_value(chart_config_prerequisites.infra_options.dns.external_dns_policy),
+            // each provider owns its records, so it never deletes the ones created by the others
+            ChartSetValue {
+                key: "txtOwnerId".to_string(),
+                value: format!("{}-{}-{}", chart_config_prerequisites.do_cluster_id, provider_name, index + 1),
+            },
+            ChartSetValue {
+                key: "txtPrefix".to_string(),
+                value: format!("qvy-{}-", chart_config_prerequisites.do_cluster_id),
+            },
+        ];
+        values.extend(
+            provider
+                .domain_filters
+                .iter()
+                .enumerate()
+                .map(|(i, domain)| ChartSetValue {
+                    key: format!("domainFilters[{}]", i),
+                    value: domain.clone(),
+                }),
+        );
+
+        let credentials_values = match &provider.credentials {
+            ExternalDnsProviderCredentials::Cloudflare { api_token, email } => {
+                vec![("cloudflare.apiToken", api_token), ("cloudflare.email", email)]
+            }
+            ExternalDnsProviderCredentials::Route53 {
+                access_key_id,
+                secret_access_key,
+                region,
+            } => vec![
+                ("aws.credentials.accessKey", access_key_id),
+                ("aws.credentials.secretKey", secret_access_key),
+                ("aws.region", region),
+            ],
+        };
+        for (key, value) in credentials_values {
+            if value.trim().is_empty() {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "External DNS provider `{}` requires `{}` to be set.",
+                    provider_name, key
+                )));
+            }
+            values.push(ChartSetValue {
+                key: key.to_string(),
+                value: value.clone(),
+            });
+        }
+
+        charts.push(CommonChart {
+            chart_info: ChartInfo {
+                name: format!("{}{}-{}", EXTERNAL_DNS_PROVIDER_RELEASE_PREFIX, provider_name, index + 1),
+                path: format!("{}/{}", chart_prefix, "common/charts/external-dns"),
+                values,
+                ..Default::default()
+            },
+        });
+    }
+
+    Ok(charts)
+}
+
+/// Returns external-dns records management policy value.
+fn external_dns_policy_value(policy: ExternalDnsPolicy) -> ChartSetValue {
+    ChartSetValue {
+        key: "policy".to_string(),
+        value: policy.to_string(),
+    }
+}
+
+pub fn bootstrap_canary_hostname(chart_config_prerequisites: &ChartsConfigPrerequisites) -> String {
+    format!(
+        "{}-{}.{}",
+        BOOTSTRAP_CANARY_CHART_NAME, chart_config_prerequisites.cluster_id, chart_config_prerequisites.managed_dns_name
+    )
+}
+
+pub fn bootstrap_canary_chart_info(chart_prefix: &str) -> ChartInfo {
+    ChartInfo {
+        name: BOOTSTRAP_CANARY_CHART_NAME.to_string(),
+        path: format!("{}/{}", chart_prefix, "common/charts/bootstrap-canary"),
+        namespace: HelmChartNamespaces::Qovery,
+        ..Default::default()
+    }
+}
+
+fn bootstrap_canary_chart(
+    chart_config_prerequisites: &ChartsConfigPrerequisites,
+    chart_prefix: &str,
+) -> Option<CommonChart> {
+    if !chart_config_prerequisites.infra_options.ingress.deploy_bootstrap_canary {
+        return None;
+    }
+
+    let mut chart_info = bootstrap_canary_chart_info(chart_prefix);
+    chart_info.values = vec![ChartSetValue {
+        key: "hostname".to_string(),
+        value: bootstrap_canary_hostname(chart_config_prerequisites),
+    }];
+
+    Some(CommonChart { chart_info })
+}
+
+/// Probes the bootstrap canary through HTTPS until it answers, proving ingress, DNS and TLS work end to end.
+pub fn probe_bootstrap_canary(hostname: &str) -> Result<(), CommandError> {
+    let url = format!("https://{}/health", hostname);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(BOOTSTRAP_CANARY_PROBE_TIMEOUT_SECONDS))
+        .build()
+        .map_err(|e| {
+            CommandError::new(
+                "Cannot build bootstrap canary probe client.".to_string(),
+                Some(e.to_string()),
+                None,
+            )
+        })?;
+    let result = retry::retry(Fixed::from_millis(10000).take(60), || match client.get(&url).send() {
+        Ok(response) if response.status().is_success() => OperationResult::Ok(()),
+        Ok(response) => OperationResult::Retry(format!("status code {}", response.status())),
+        Err(e) => OperationResult::Retry(e.to_string()),
+    });
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(Operation { error, .. }) => Err(CommandError::new(
+            format!("Bootstrap canary is not reachable on {}", url),
+            Some(error),
+            None,
+        )),
+        Err(retry::Error::Internal(e)) => Err(CommandError::new(
+            format!("Bootstrap canary is not reachable on {}", url),
+            Some(e),
+            None,
+        )),
+    }
+}
+
+/// Returns nginx-ingress controller replicas values, with a preferred pod anti-affinity spreading replicas across
+/// nodes. As the controller is autoscaled, the minimum and maximum replicas counts are set as well.
+fn nginx_ingress_replicas_values(replicas: u32) -> Result<Vec<ChartSetValue>, CommandError> {
+    if replicas < 1 {
+        return Err(CommandError::new_from_safe_message(format!(
+            "Invalid nginx-ingress controller replicas count `{}`, at least 1 replica is required.",
+            replicas
+        )));
+    }
+
+    let anti_affinity = "controller.affinity.podAntiAffinity.preferredDuringSchedulingIgnoredDuringExecution[0]";
+    let match_expressions = format!("{}.podAffinityTerm.labelSelector.matchExpressions", anti_affinity);
+    let mut values = vec![
+        ChartSetValue {
+            key: "controller.replicaCount".to_string(),
+            value: replicas.to_string(),
+        },
+        ChartSetValue {
+            key: "controller.autoscaling.minReplicas".to_string(),
+            value: replicas.to_string(),
+        },
+        ChartSetValue {
+            key: "controller.autoscaling.maxReplicas".to_string(),
+            value: replicas.max(INGRESS_CONTROLLER_MAX_REPLICAS).to_string(),
+        },
+        ChartSetValue {
+            key: format!("{}.weight", anti_affinity),
+            value: "100".to_string(),
+        },
+        ChartSetValue {
+            key: format!("{}.podAffinityTerm.topologyKey", anti_affinity),
+            value: "kubernetes.io/hostname".to_string(),
+        },
+    ];
+
+    for (i, (label, label_value)) in [
+        ("app.kubernetes.io/name", "ingress-nginx"),
+        ("app.kubernetes.io/instance", "nginx-ingress"),
+        ("app.kubernetes.io/component", "controller"),
+    ]
+    .iter()
+    .enumerate()
+    {
+        values.extend(vec![
+            ChartSetValue {
+                key: format!("{}[{}].key", match_expressions, i),
+                value: label.to_string(),
+            },
+            ChartSetValue {
+                key: format!("{}[{}].operator", match_expressions, i),
+                value: "In".to_string(),
+            },
+            ChartSetValue {
+                key: format!("{}[{}].values[0]", match_expressions, i),
+                value: label_value.to_string(),
+            },
+        ]);
+    }
+
+    Ok(values)
+}
+
+/// Returns nginx-ingress values for DO load balancer health checks and controller graceful shutdown.
+/// https://github.com/digitalocean/digitalocean-cloud-controller-manager/blob/master/docs/controllers/services/annotations.md
+fn nginx_ingress_load_balancer_draining_values(draining: &DoLoadBalancerDraining) -> Vec<ChartSetValue> {
+    let mut annotations = BTreeMap::new();
+    annotations.insert(
+        "service.beta.kubernetes.io/do-loadbalancer-healthcheck-check-interval-seconds".to_string(),
+        draining.healthcheck_interval_seconds.to_string(),
+    );
+    annotations.insert(
+        "service.beta.kubernetes.io/do-loadbalancer-healthcheck-unhealthy-threshold".to_string(),
+        draining.healthcheck_unhealthy_threshold.to_string(),
+    );
+
+    let mut values = get_string_map_values("controller.service.annotations", &annotations);
+    values.push(ChartSetValue {
+        key: "controller.config.worker-shutdown-timeout".to_string(),
+        value: format!("{}s", draining.draining_timeout_seconds),
+    });
+    // load balancer has to detect the controller is down, then in flight connections have to be completed
+    values.push(ChartSetValue {
+        key: "controller.terminationGracePeriodSeconds".to_string(),
+        value: (draining.healthcheck_interval_seconds * draining.healthcheck_unhealthy_threshold
+            + draining.draining_timeout_seconds)
+            .to_string(),
+    });
+
+    values
+}
+
+/// Returns nginx-ingress TCP/UDP passthrough values, the chart renders them in the tcp-services/udp-services
+/// configmaps and exposes the ports on the load balancer service. The load balancer sends TCP connections with the
+/// proxy protocol, which is decoded by nginx for TCP services (`:PROXY`), UDP services don't support it.
+fn nginx_ingress_passthrough_values(
+    tcp_services: &BTreeMap<u16, ServiceRef>,
+    udp_services: &BTreeMap<u16, ServiceRef>,
+) -> Result<Vec<ChartSetValue>, CommandError> {
+    let mut values = vec![];
+
+    for (protocol, services) in &[("tcp", tcp_services), ("udp", udp_services)] {
+        for (port, service) in services.iter() {
+            // http and https ports are already used by the ingress itself
+            if *port == 0 || (*protocol == "tcp" && (*port == 80 || *port == 443)) {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "Port {} can't be used for {} passthrough.",
+                    port,
+                    protocol.to_uppercase()
+                )));
+            }
+            if service.namespace.is_empty() || service.name.is_empty() || service.port == 0 {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "{} passthrough service of port {} must have a namespace, a name and a port.",
+                    protocol.to_uppercase(),
+                    port
+                )));
+            }
+
+            let proxy_protocol = match *protocol {
+                "tcp" => ":PROXY",
+                _ => "",
+            };
+            values.push(ChartSetValue {
+                key: format!("{}.{}", protocol, port),
+                value: format!("{}/{}:{}{}", service.namespace, service.name, service.port, proxy_protocol),
+            });
+        }
+    }
+
+    Ok(values)
+}
+
+/// Returns nginx-ingress default certificate value, the wildcard certificate is issued by cert-manager so none is set
+/// when it is not deployed.
+fn nginx_ingress_default_certificate_values(deploy_cert_manager: bool) -> Vec<ChartSetValue> {
+    if !deploy_cert_manager {
+        return vec![];
+    }
+
+    vec![ChartSetValue {
+        key: "controller.extraArgs.default-ssl-certificate".to_string(),
+        value: "cert-manager/letsencrypt-acme-qovery-cert".to_string(),
+    }]
+}
+
+/// Returns nginx-ingress default backend values, resources and custom image (`repository:tag`, to serve branded
+/// error pages) are only set when the default backend is enabled.
+fn nginx_ingress_default_backend_values(
+    enabled: bool,
+    image: &Option<String>,
+) -> Result<Vec<ChartSetValue>, CommandError> {
+    let mut values = vec![ChartSetValue {
+        key: "defaultBackend.enabled".to_string(),
+        value: enabled.to_string(),
+    }];
+
+    if let (true, Some(image)) = (enabled, image) {
+        // registry may have a port, so the tag is only looked for in the last path segment
+        let (repository, tag) = match image.rsplit_once(':') {
+            Some((repository, tag)) if !repository.is_empty() && !tag.is_empty() && !tag.contains('/') => {
+                (repository, tag)
+            }
+            _ => {
+                return Err(CommandError::new_from_safe_message(format!(
+                    "Default backend image `{}` must be formatted as `repository:tag`.",
+                    image
+                )))
+            }
+        };
+        values.extend(vec![
+            ChartSetValue {
+                key: "defaultBackend.image.repository".to_string(),
+                value: repository.to_string(),
+            },
+            ChartSetValue {
+                key: "defaultBackend.image.tag".to_string(),
+                value: tag.to_string(),
+            },
+        ]);
+    }
+
+    if enabled {
+        values.extend(vec![
+            // Default backend resources limits
+            ChartSetValue {
+                key: "defaultBackend.resources.limits.cpu".to_string(),
+                value: "20m".to_string(),
+            },
+            ChartSetValue {
+                key: "defaultBackend.resources.requests.cpu".to_string(),
+                value: "10m".to_string(),
+            },
+            ChartSetValue {
+                key: "defaultBackend.resources.limits.memory".to_string(),
+                value: "32Mi".to_string(),
+            },
+            ChartSetValue {
+                key: "defaultBackend.resources.requests.memory".to_string(),
+                value: "32Mi".to_string(),
+            },
+        ]);
+    }
+
+    Ok(values)
+}
+
+/// Validates a standard 5 fields cron expression (minute, hour, day of month, month, day of week).
+fn validate_cron_expression(expression: &str) -> Result<(), CommandError> {
+    // (field name, min value, max value)
+    let fields_bounds = [
+        ("minute", 0, 59),
+        ("hour", 0, 23),
+        ("day of month", 1, 31),
+        ("month", 1, 12),
+        ("day of week", 0, 7),
+    ];
+    let invalid_cron = |reason: String| {
+        CommandError::new_from_safe_message(format!("Invalid cron expression `{}`: {}.", expression, reason))
+    };
+
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    if fields.len() != fields_bounds.len() {
+        return Err(invalid_cron(format!(
+            "expected {} fields, got {}",
+            fields_bounds.len(),
+            fields.len()
+        )));
+    }
+
+    let parse_value = |value: &str, name: &str, min: u32, max: u32| -> Result<u32, CommandError> {
+        match value.parse::<u32>() {
+            Ok(v) if v >= min && v <= max => Ok(v),
+            _ => Err(invalid_cron(format!(
+                "{} value `{}` should be between {} and {}",
+                name, value, min, max
+            ))),
+        }
+    };
+
+    for (field, (name, min, max)) in fields.iter().zip(fields_bounds.iter()) {
+        for item in field.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (item, None),
+            };
+
+            if let Some(step) = step {
+                match step.parse::<u32>() {
+                    Ok(s) if s > 0 => {}
+                    _ => return Err(invalid_cron(format!("{} step `{}` is not a positive number", name, step))),
+                }
+            }
+
+            if range == "*" {
+                continue;
+            }
+
+            match range.split_once('-') {
+                Some((start, end)) => {
+                    let start = parse_value(start, name, *min, *max)?;
+                    let end = parse_value(end, name, *min, *max)?;
+                    if start > end {
+                        return Err(invalid_cron(format!("{} range `{}` is reversed", name, range)));
+                    }
+                }
+                None => {
+                    parse_value(range, name, *min, *max)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::digitalocean::kubernetes::helm_charts::{
+        bootstrap_canary_chart, cert_manager_charts, cert_manager_dns01_extra_args_value, coredns_auto_reload_values,
+        coredns_config_chart, coredns_stub_domains_values, default_engine_build_resources,
+        default_prometheus_external_labels, digital_mobius_image_tag_values, external_dns_policy_value,
+        external_dns_providers_charts, generate_grafana_admin_password, grafana_datasources, grafana_values,
+        loki_affinity_values, loki_components, loki_read_service_name, loki_stale_release_names,
+        loki_write_service_name, nginx_ingress_default_backend_values, nginx_ingress_default_certificate_values,
+        nginx_ingress_load_balancer_draining_values, nginx_ingress_passthrough_values, nginx_ingress_replicas_values,
+        prometheus_adapter_replicas_values, prometheus_remote_write_values, promtail_drop_labels_values,
+        promtail_loki_values, qovery_agent_liveness_probe_values, qovery_agent_loki_url,
+        qovery_engine_build_cache_values, qovery_engine_build_resources_values, qovery_engine_build_timeout_values,
+        qovery_engine_docker_hub_mirror_values, qovery_engine_extra_env_values, qovery_engine_ignored_extra_env,
+        qovery_engine_image_values, qovery_engine_location_env_values, qovery_engine_nats_tls_values,
+        qovery_engine_scheduling_values, service_monitor_values, stale_external_dns_provider_releases,
+        system_chart_tolerations_values, validate_acme_url, validate_cron_expression, validate_feature_flags,
+        validate_managed_dns, BuildCacheConfig, ChartsConfigPrerequisites, DoLoadBalancerDraining, DoksDnsOptions,
+        DoksEngineOptions, DoksIngressOptions, DoksMaintenanceOptions, DoksMonitoringOptions,
+        ExternalDnsProviderConfig, ExternalDnsProviderCredentials, LokiAffinity, LokiMode, QoveryAgentLivenessProbe,
+        RemoteWriteBasicAuth, RemoteWriteConfig, ServiceRef, BOOTSTRAP_CANARY_CHART_NAME,
+        CHART_NAMESPACE_ISOLATION_GROUPS, DEFAULT_ENGINE_BUILD_TIMEOUT_SECONDS, DEFAULT_INGRESS_CONTROLLER_REPLICAS,
+        DEFAULT_LOKI_PORT, DEFAULT_LOKI_SERVICE_NAME, DEFAULT_PROMETHEUS_ADAPTER_REPLICAS,
+        GRAFANA_ADMIN_PASSWORD_LENGTH, LOKI_SIMPLE_SCALABLE_IMAGE_TAG, SERVICE_MONITOR_VALUE_KEYS,
+    };
+    use crate::cloud_provider::digitalocean::kubernetes::DoksOptions;
+    use crate::cloud_provider::digitalocean::network::vpc::VpcInitKind;
+    use crate::cloud_provider::helm::{
+        conflicting_chart_namespaces, get_string_map_values, ChartInfo, ChartSetValue, CommonChart, ExternalDnsPolicy,
+        HelmChart, HelmChartNamespaces, ImagePullPolicy, Toleration,
+    };
+    use crate::cloud_provider::qovery::EngineLocation;
+    use crate::cloud_provider::Kind as CloudProviderKind;
+    use crate::errors::CommandError;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    fn get_charts_config_prerequisites() -> ChartsConfigPrerequisites {
+        ChartsConfigPrerequisites {
+            organization_id: "z1234abc".to_string(),
+            organization_long_id: Uuid::new_v4(),
+            cluster_id: "z5678def".to_string(),
+            cluster_long_id: Uuid::new_v4(),
+            do_cluster_id: "do-cluster-id".to_string(),
+            region: "fra1".to_string(),
+            cluster_name: "qovery-z5678def".to_string(),
+            cloud_provider: CloudProviderKind::Do,
+            test_cluster: true,
+            do_token: "do-token".to_string(),
+            do_space_access_id: "do-space-access-id".to_string(),
+            do_space_secret_key: "do-space-secret-key".to_string(),
+            do_space_bucket_kubeconfig: "qovery-kubeconfigs-z5678def".to_string(),
+            do_space_kubeconfig_filename: "z5678def.yaml".to_string(),
+            qovery_engine_location: EngineLocation::ClientSide,
+            ff_log_history_enabled: true,
+            ff_metrics_history_enabled: true,
+            managed_dns_name: "qovery.example.com".to_string(),
+            managed_dns_helm_format: "{qovery.example.com}".to_string(),
+            managed_dns_resolvers_terraform_format: "{1.1.1.1}".to_string(),
+            external_dns_provider: "cloudflare".to_string(),
+            dns_email_report: "tls@example.com".to_string(),
+            acme_url: "https://acme-staging-v02.api.letsencrypt.org/directory".to_string(),
+            cloudflare_email: "dns@example.com".to_string(),
+            cloudflare_api_token: "cloudflare-token".to_string(),
+            disable_pleco: false,
+            infra_options: DoksOptions {
+                vpc_cidr_block: "10.0.0.0/16".to_string(),
+                vpc_name: "qovery-vpc".to_string(),
+                vpc_cidr_set: VpcInitKind::Autodetect,
+                qovery_api_url: "api.example.com".to_string(),
+                qovery_grpc_url: "grpc.example.com:443".to_string(),
+                qovery_cluster_secret_token: "cluster-secret-token".to_string(),
+                qovery_engine_location: EngineLocation::ClientSide,
+                engine_version_controller_token: "engine-token".to_string(),
+                agent_version_controller_token: "agent-token".to_string(),
+                grafana_admin_user: "admin".to_string(),
+                discord_api_key: "discord-api-key".to_string(),
+                qovery_nats_url: "nats.example.com".to_string(),
+                qovery_nats_user: "nats-user".to_string(),
+                qovery_nats_password: "nats-password".to_string(),
+                qovery_ssh_key: "ssh-key".to_string(),
+                tls_email_report: "tls@example.com".to_string(),
+                engine: DoksEngineOptions::default(),
+                monitoring: DoksMonitoringOptions {
+                    loki_deployment_mode: LokiMode::Monolithic,
+                    grafana_admin_password: "password".to_string(),
+                    qovery_agent_liveness_probe: Some(QoveryAgentLivenessProbe::default()),
+                    ..Default::default()
+                },
+                ingress: DoksIngressOptions::default(),
+                dns: DoksDnsOptions::default(),
+                maintenance: DoksMaintenanceOptions::default(),
+            },
+        }
+    }
+
+    fn get_value(values: &[ChartSetValue], key: &str) -> Option<String> {
+        values.iter().find(|v| v.key == key).map(|v| v.value.clone())
+    }
+
+    #[test]
+    fn test_prometheus_adapter_replicas_and_pdb_are_consistent() {
+        for replicas in 1..5 {
+            // execute:
+            let values = prometheus_adapter_replicas_values(replicas).expect("valid replicas count");
+
+            // verify:
+            assert_eq!(Some(replicas.to_string()), get_value(&values, "replicas"));
+            let pdb_enabled = get_value(&values, "podDisruptionBudget.enabled") == Some("true".to_string());
+            assert_eq!(replicas > 1, pdb_enabled);
+            match get_value(&values, "podDisruptionBudget.maxUnavailable") {
+                Some(max_unavailable) => {
+                    assert!(pdb_enabled);
+                    assert!(max_unavailable.parse::<u32>().unwrap() < replicas);
+                }
+                None => assert!(!pdb_enabled),
+            }
+        }
+    }
+
+    #[test]
+    fn test_prometheus_adapter_default_replicas_enables_pdb() {
+        // execute:
+        let values = prometheus_adapter_replicas_values(DEFAULT_PROMETHEUS_ADAPTER_REPLICAS).unwrap();
+
+        // verify:
+        assert_eq!(Some("true".to_string()), get_value(&values, "podDisruptionBudget.enabled"));
+    }
+
+    #[test]
+    fn test_prometheus_adapter_replicas_must_be_at_least_one() {
+        assert!(prometheus_adapter_replicas_values(0).is_err());
+    }
+
+    #[test]
+    fn test_validate_cron_expression() {
+        // valid expressions
+        for cron in &["0 12 * * */1", "*/15 * * * *", "0 0,12 1-15 * 1-5", "30 2 * 1,6,12 0"] {
+            assert!(validate_cron_expression(cron).is_ok(), "`{}` should be valid", cron);
+        }
+
+        // invalid expressions
+        for cron in &[
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "0 24 * * *",
+            "0 0 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a b c d e",
+        ] {
+            assert!(validate_cron_expression(cron).is_err(), "`{}` should be invalid", cron);
+        }
+    }
+
+    #[test]
+    fn test_nginx_ingress_default_backend_values() {
+        // enabled
+        let values = nginx_ingress_default_backend_values(true, &None).expect("values should be valid");
+        assert_eq!(Some("true".to_string()), get_value(&values, "defaultBackend.enabled"));
+        assert_eq!(
+            Some("20m".to_string()),
+            get_value(&values, "defaultBackend.resources.limits.cpu")
+        );
+
+        // disabled
+        let values = nginx_ingress_default_backend_values(false, &None).expect("values should be valid");
+        assert_eq!(Some("false".to_string()), get_value(&values, "defaultBackend.enabled"));
+        assert!(values.iter().all(|v| !v.key.starts_with("defaultBackend.resources")));
+    }
+
+    #[test]
+    fn test_nginx_ingress_default_certificate_values() {
+        // execute & verify: the default certificate is issued by cert-manager
+        assert_eq!(
+            Some("cert-manager/letsencrypt-acme-qovery-cert".to_string()),
+            get_value(
+                &nginx_ingress_default_certificate_values(true),
+                "controller.extraArgs.default-ssl-certificate"
+            )
+        );
+        assert!(nginx_ingress_default_certificate_values(false).is_empty());
+    }
+
+    #[test]
+    fn test_external_dns_policy_value() {
+        // default
+        let value = external_dns_policy_value(ExternalDnsPolicy::default());
+        assert_eq!("policy", value.key);
+        assert_eq!("upsert-only", value.value);
+
+        // sync
+        assert_eq!("sync", external_dns_policy_value(ExternalDnsPolicy::Sync).value);
+    }
+
+    #[test]
+    fn test_validate_feature_flags() {
+        // consistent configuration
+        assert!(validate_feature_flags(&get_charts_config_prerequisites()).is_ok());
+
+        // no observability at all doesn't require anything
+        let mut config = get_charts_config_prerequisites();
+        config.ff_log_history_enabled = false;
+        config.ff_metrics_history_enabled = false;
+        config.do_space_secret_key = "".to_string();
+        config.infra_options.monitoring.grafana_admin_password = "".to_string();
+        assert!(validate_feature_flags(&config).is_ok());
+
+        // logs history without spaces credentials
+        let mut config = get_charts_config_prerequisites();
+        config.do_space_access_id = "".to_string();
+        assert!(validate_feature_flags(&config).is_err());
+        let mut config = get_charts_config_prerequisites();
+        config.do_space_secret_key = "".to_string();
+        assert!(validate_feature_flags(&config).is_err());
+
+        // logs history only without grafana credentials
+        let mut config = get_charts_config_prerequisites();
+        config.ff_metrics_history_enabled = false;
+        config.infra_options.grafana_admin_user = "".to_string();
+        assert!(validate_feature_flags(&config).is_err());
+
+        // metrics history only without grafana credentials
+        let mut config = get_charts_config_prerequisites();
+        config.ff_log_history_enabled = false;
+        config.infra_options.grafana_admin_user = "".to_string();
+        assert!(validate_feature_flags(&config).is_err());
+
+        // logs or metrics history without grafana password
+        let mut config = get_charts_config_prerequisites();
+        config.infra_options.monitoring.grafana_admin_password = "".to_string();
+        assert!(validate_feature_flags(&config).is_err());
+    }
+
+    #[test]
+    fn test_qovery_engine_scheduling_values() {
+        // setup:
+        let mut config = get_charts_config_prerequisites();
+
+        // execute & verify: nothing is set by default
+        assert!(qovery_engine_scheduling_values(&config).is_empty());
+
+        // setup:
+        config
+            .infra_options
+            .engine
+            .node_selector
+            .insert("doks.digitalocean.com/node-pool".to_string(), "builds".to_string());
+        config.infra_options.engine.tolerations = vec![Toleration {
+            key: "dedicated".to_string(),
+            operator: "Equal".to_string(),
+            value: Some("builds".to_string()),
+            effect: Some("NoSchedule".to_string()),
+        }];
+
+        // execute:
+        let values = qovery_engine_scheduling_values(&config);
+
+        // verify:
+        assert_eq!(
+            Some("builds".to_string()),
+            get_value(&values, "nodeSelector.doks\\.digitalocean\\.com/node-pool")
+        );
+        assert_eq!(Some("dedicated".to_string()), get_value(&values, "tolerations[0].key"));
+        assert_eq!(Some("Equal".to_string()), get_value(&values, "tolerations[0].operator"));
+        assert_eq!(Some("builds".to_string()), get_value(&values, "tolerations[0].value"));
+        assert_eq!(Some("NoSchedule".to_string()), get_value(&values, "tolerations[0].effect"));
+    }
+
+    #[test]
+    fn test_prometheus_external_labels_values() {
+        // setup:
+        let mut labels = default_prometheus_external_labels("z1234abc", "fra1");
+        labels.insert("team".to_string(), "platform".to_string());
+
+        // execute:
+        let values = get_string_map_values("prometheus.prometheusSpec.externalLabels", &labels);
+
+        // verify:
+        assert_eq!(3, values.len());
+        assert_eq!(
+            Some("z1234abc".to_string()),
+            get_value(&values, "prometheus.prometheusSpec.externalLabels.cluster_id")
+        );
+        assert_eq!(
+            Some("fra1".to_string()),
+            get_value(&values, "prometheus.prometheusSpec.externalLabels.region")
+        );
+        assert_eq!(
+            Some("platform".to_string()),
+            get_value(&values, "prometheus.prometheusSpec.externalLabels.team")
+        );
+    }
+
+    #[test]
+    fn test_coredns_stub_domains_values() {
+        // setup:
+        let mut stub_domains = BTreeMap::new();
+        stub_domains.insert(
+            "corp.example.com".to_string(),
+            vec!["10.0.0.10:53".to_string(), "10.0.0.11:5353".to_string()],
+        );
+
+        // execute:
+        let values = coredns_stub_domains_values(&stub_domains).expect("stub domains should be valid");
+
+        // verify:
+        assert_eq!(1, values.len());
+        assert_eq!(
+            Some("{10.0.0.10:53,10.0.0.11:5353}".to_string()),
+            get_value(&values, "stub_domains.corp\\.example\\.com")
+        );
+
+        // invalid resolvers
+        for resolver in &["10.0.0.10", "corp-dns:53", "10.0.0.10:port", ""] {
+            let mut stub_domains = BTreeMap::new();
+            stub_domains.insert("corp.example.com".to_string(), vec![resolver.to_string()]);
+            assert!(coredns_stub_domains_values(&stub_domains).is_err(), "resolver `{}`", resolver);
+        }
+
+        // no resolvers
+        let mut stub_domains = BTreeMap::new();
+        stub_domains.insert("corp.example.com".to_string(), vec![]);
+        assert!(coredns_stub_domains_values(&stub_domains).is_err());
+    }
+
+    #[test]
+    fn test_qovery_engine_build_resources_values() {
+        // execute & verify: default values
+        let values = qovery_engine_build_resources_values(&default_engine_build_resources())
+            .expect("default build resources should be valid");
+        assert_eq!(Some("4Gi".to_string()), get_value(&values, "buildResources.limits.memory"));
+        assert_eq!(Some("500m".to_string()), get_value(&values, "buildResources.requests.cpu"));
+
+        // execute & verify: memory override
+        let mut resources = default_engine_build_resources();
+        resources.memory_request = "8Gi".to_string();
+        resources.memory_limit = "8Gi".to_string();
+        let values = qovery_engine_build_resources_values(&resources).expect("build resources should be valid");
+        assert_eq!(Some("8Gi".to_string()), get_value(&values, "buildResources.limits.memory"));
+        assert_eq!(Some("8Gi".to_string()), get_value(&values, "buildResources.requests.memory"));
+
+        // execute & verify: requests higher than limits are rejected
+        let mut resources = default_engine_build_resources();
+        resources.memory_request = "8Gi".to_string();
+        assert!(qovery_engine_build_resources_values(&resources).is_err());
+        let mut resources = default_engine_build_resources();
+        resources.cpu_request = "1500m".to_string();
+        assert!(qovery_engine_build_resources_values(&resources).is_err());
+
+        // execute & verify: invalid quantities are rejected
+        for invalid_cpu in vec!["", "abc", "-500m", "0", "1 cpu"] {
+            let mut resources = default_engine_build_resources();
+            resources.cpu_request = invalid_cpu.to_string();
+            assert!(qovery_engine_build_resources_values(&resources).is_err());
+        }
+        for invalid_memory in vec!["", "abc", "-1Gi", "0Mi", "4GB", "4"] {
+            let mut resources = default_engine_build_resources();
+            resources.memory_limit = invalid_memory.to_string();
+            assert!(qovery_engine_build_resources_values(&resources).is_err());
+        }
+    }
+
+    #[test]
+    fn test_qovery_engine_docker_hub_mirror_values() {
+        // setup:
+        let mut chart_config_prerequisites = get_charts_config_prerequisites();
+
+        // execute & verify: no mirror by default
+        assert!(qovery_engine_docker_hub_mirror_values(
+            &chart_config_prerequisites.infra_options.engine.docker_hub_mirror
+        )
+        .expect("no mirror should be valid")
+        .is_empty());
+
+        // execute & verify: mirror is set as engine env var
+        chart_config_prerequisites.infra_options.engine.docker_hub_mirror = Some("https://mirror.gcr.io".to_string());
+        let values =
+            qovery_engine_docker_hub_mirror_values(&chart_config_prerequisites.infra_options.engine.docker_hub_mirror)
+                .expect("mirror should be valid");
+        assert_eq!(
+            Some("https://mirror.gcr.io/".to_string()),
+            get_value(&values, "environmentVariables.DOCKER_HUB_MIRROR")
+        );
+
+        // execute & verify: invalid mirrors are rejected
+        for invalid_mirror in vec!["mirror.gcr.io", "localhost:5000", "ftp://mirror.gcr.io", ""] {
+            assert!(qovery_engine_docker_hub_mirror_values(&Some(invalid_mirror.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_promtail_system_chart_tolerations() {
+        // setup:
+        let mut chart_config_prerequisites = get_charts_config_prerequisites();
+
+        // execute & verify: every taint is tolerated by default
+        let values = system_chart_tolerations_values(&chart_config_prerequisites);
+        assert_eq!(Some("".to_string()), get_value(&values, "tolerations[0].key"));
+        assert_eq!(Some("Exists".to_string()), get_value(&values, "tolerations[0].operator"));
+        assert_eq!(None, get_value(&values, "tolerations[0].effect"));
+
+        // execute & verify: custom tolerations
+        chart_config_prerequisites
+            .infra_options
+            .monitoring
+            .system_chart_tolerations = vec![Toleration {
+            key: "nvidia.com/gpu".to_string(),
+            operator: "Equal".to_string(),
+            value: Some("true".to_string()),
+            effect: Some("NoSchedule".to_string()),
+        }];
+        let values = system_chart_tolerations_values(&chart_config_prerequisites);
+        assert_eq!(Some("nvidia.com/gpu".to_string()), get_value(&values, "tolerations[0].key"));
+        assert_eq!(Some("Equal".to_string()), get_value(&values, "tolerations[0].operator"));
+        assert_eq!(Some("true".to_string()), get_value(&values, "tolerations[0].value"));
+        assert_eq!(Some("NoSchedule".to_string()), get_value(&values, "tolerations[0].effect"));
+    }
+
+    #[test]
+    fn test_validate_managed_dns() {
+        // execute & verify: valid formats
+        let mut config = get_charts_config_prerequisites();
+        assert!(validate_managed_dns(&config).is_ok());
+        config.managed_dns_helm_format = "{qovery.example.com,sub-domain.example.com}".to_string();
+        config.managed_dns_resolvers_terraform_format = "{1.1.1.1,8.8.8.8}".to_string();
+        assert!(validate_managed_dns(&config).is_ok());
+
+        // execute & verify: malformed managed DNS
+        for malformed_dns in vec![
+            "",
+            "{}",
+            "qovery.example.com",
+            "{qovery.example.com",
+            "{qovery..com}",
+            "{-qovery.com}",
+        ] {
+            let mut config = get_charts_config_prerequisites();
+            config.managed_dns_helm_format = malformed_dns.to_string();
+            assert!(validate_managed_dns(&config).is_err(), "`{}` should be rejected", malformed_dns);
+        }
+
+        // execute & verify: malformed resolvers
+        for malformed_resolvers in vec!["", "{}", "1.1.1.1", "{1.1.1.1,}", "{1.1.1}", "{dns.google}"] {
+            let mut config = get_charts_config_prerequisites();
+            config.managed_dns_resolvers_terraform_format = malformed_resolvers.to_string();
+            assert!(
+                validate_managed_dns(&config).is_err(),
+                "`{}` should be rejected",
+                malformed_resolvers
+            );
+        }
+    }
+
+    #[test]
+    fn test_digital_mobius_image_tag_values() {
+        // setup:
+        let mut chart_config_prerequisites = get_charts_config_prerequisites();
+
+        // execute & verify: chart default tag is kept
+        assert!(digital_mobius_image_tag_values(
+            &chart_config_prerequisites
+                .infra_options
+                .maintenance
+                .digital_mobius_image_tag
+        )
+        .is_empty());
+
+        // execute & verify: tag override
+        chart_config_prerequisites
+            .infra_options
+            .maintenance
+            .digital_mobius_image_tag = Some("0.1.3".to_string());
+        let values = digital_mobius_image_tag_values(
+            &chart_config_prerequisites
+                .infra_options
+                .maintenance
+                .digital_mobius_image_tag,
+        );
+        assert_eq!(Some("0.1.3".to_string()), get_value(&values, "image.digitalMobiusImageTag"));
+    }
+
+    #[test]
+    fn test_validate_acme_url() {
+        // execute & verify: known Let's Encrypt directories
+        assert!(validate_acme_url("https://acme-v02.api.letsencrypt.org/directory").is_ok());
+        assert!(validate_acme_url("https://acme-staging-v02.api.letsencrypt.org/directory").is_ok());
+        assert!(validate_acme_url(&get_charts_config_prerequisites().acme_url).is_ok());
+
+        // execute & verify: other ACME directories
+        assert!(validate_acme_url("https://acme.zerossl.com/v2/DV90/directory").is_ok());
+
+        // execute & verify: malformed URLs
+        for acme_url in vec![
+            "",
+            "acme-v02.api.letsencrypt.org/directory",
+            "http://acme-v02.api.letsencrypt.org/directory",
+            "https://acme-v02.api.letsencrypt.org/directoy",
+            "https://acme-v02.api.letsencrypt.org",
+        ] {
+            assert!(validate_acme_url(acme_url).is_err(), "`{}` should be rejected", acme_url);
+        }
+    }
+
+    #[test]
+    fn test_loki_clients_endpoints() {
+        // setup:
+        let default_loki_kube_dns_prefix = format!("{}.logging.svc", DEFAULT_LOKI_SERVICE_NAME);
+
+        // execute & verify: default service name and port
+        let values = promtail_loki_values(&default_loki_kube_dns_prefix, DEFAULT_LOKI_PORT);
+        assert_eq!(Some("loki.logging.svc".to_string()), get_value(&values, "loki.serviceName"));
+        assert_eq!(Some("3100".to_string()), get_value(&values, "loki.servicePort"));
+        assert_eq!(
+            "http://loki.logging.svc.cluster.local:3100",
+            qovery_agent_loki_url(&default_loki_kube_dns_prefix, DEFAULT_LOKI_PORT)
+        );
+        let datasources = grafana_datasources("http://prometheus", &default_loki_kube_dns_prefix, DEFAULT_LOKI_PORT);
+        assert!(datasources.contains("url: \"http://loki.logging.svc:3100/loki\""));
+        assert!(datasources.contains("url: \"http://loki.logging.svc:3100\""));
+
+        // execute & verify: custom service name and port propagate to promtail, qovery-agent and grafana
+        let loki_kube_dns_prefix = "my-loki.logging.svc";
+        let values = promtail_loki_values(loki_kube_dns_prefix, 3200);
+        assert_eq!(Some("my-loki.logging.svc".to_string()), get_value(&values, "loki.serviceName"));
+        assert_eq!(Some("3200".to_string()), get_value(&values, "loki.servicePort"));
+        assert_eq!(
+            "http://my-loki.logging.svc.cluster.local:3200",
+            qovery_agent_loki_url(loki_kube_dns_prefix, 3200)
+        );
+        let datasources = grafana_datasources("http://prometheus", loki_kube_dns_prefix, 3200);
+        assert!(datasources.contains("url: \"http://my-loki.logging.svc:3200/loki\""));
+        assert!(datasources.contains("url: \"http://my-loki.logging.svc:3200\""));
+        assert!(datasources.contains("url: \"http://prometheus:9090\""));
+    }
+
+    #[test]
+    fn test_bootstrap_canary_chart_is_deployed_only_when_enabled() {
+        // setup:
+        let mut chart_config_prerequisites = get_charts_config_prerequisites();
+
+        // execute & verify:
+        assert!(bootstrap_canary_chart(&chart_config_prerequisites, "/tmp").is_none());
+
+        // setup:
+        chart_config_prerequisites.infra_options.ingress.deploy_bootstrap_canary = true;
+
+        // execute:
+        let chart =
+            bootstrap_canary_chart(&chart_config_prerequisites, "/tmp").expect("canary chart should be present");
+
+        // verify:
+        assert_eq!(BOOTSTRAP_CANARY_CHART_NAME, chart.chart_info.name);
+        assert_eq!("/tmp/common/charts/bootstrap-canary", chart.chart_info.path);
+        assert_eq!(
+            Some("bootstrap-canary-z5678def.qovery.example.com".to_string()),
+            get_value(&chart.chart_info.values, "hostname")
+        );
+    }
+
+    #[test]
+    fn test_qovery_engine_image_values() {
+        // execute:
+        let values = qovery_engine_image_values(&None, ImagePullPolicy::default());
+
+        // verify:
+        assert_eq!(Some("IfNotPresent".to_string()), get_value(&values, "image.pullPolicy"));
+        assert_eq!(None, get_value(&values, "image.repository"));
+
+        // execute:
+        let values =
+            qovery_engine_image_values(&Some("registry.example.com/qovery/".to_string()), ImagePullPolicy::Always);
+
+        // verify:
+        assert_eq!(Some("Always".to_string()), get_value(&values, "image.pullPolicy"));
+        assert_eq!(
+            Some("registry.example.com/qovery/engine".to_string()),
+            get_value(&values, "image.repository")
+        );
+    }
+
+    #[test]
+    fn test_nginx_ingress_load_balancer_draining_values() {
+        // execute:
+        let values = nginx_ingress_load_balancer_draining_values(&DoLoadBalancerDraining::default());
+
+        // verify:
+        assert_eq!(
+            Some("3".to_string()),
+            get_value(
+                &values,
+                "controller.service.annotations.service\\.beta\\.kubernetes\\.io/do-loadbalancer-healthcheck-check-interval-seconds"
+            )
+        );
+        assert_eq!(
+            Some("2".to_string()),
+            get_value(
+                &values,
+                "controller.service.annotations.service\\.beta\\.kubernetes\\.io/do-loadbalancer-healthcheck-unhealthy-threshold"
+            )
+        );
+        assert_eq!(
+            Some("30s".to_string()),
+            get_value(&values, "controller.config.worker-shutdown-timeout")
+        );
+        assert_eq!(
+            Some("36".to_string()),
+            get_value(&values, "controller.terminationGracePeriodSeconds")
+        );
+    }
+
+    #[test]
+    fn test_external_dns_multiple_providers() {
+        // setup:
+        let mut chart_config_prerequisites = get_charts_config_prerequisites();
+
+        // execute & verify: single provider
+        assert!(external_dns_providers_charts(&chart_config_prerequisites, "/tmp")
+            .expect("valid configuration")
+            .is_empty());
+
+        // setup:
+        chart_config_prerequisites.infra_options.dns.external_dns_providers = vec![
+            ExternalDnsProviderConfig {
+                domain_filters: vec!["example.com".to_string()],
+                credentials: ExternalDnsProviderCredentials::Cloudflare {
+                    api_token: "cloudflare-token".to_string(),
+                    email: "dns@example.com".to_string(),
+                },
+            },
+            ExternalDnsProviderConfig {
+                domain_filters: vec!["eu.example.com".to_string(), "us.example.com".to_string()],
+                credentials: ExternalDnsProviderCredentials::Route53 {
+                    access_key_id: "access-key".to_string(),
+                    secret_access_key: "secret-key".to_string(),
+                    region: "eu-west-3".to_string(),
+                },
+            },
+        ];
+
+        // execute:
+        let charts = external_dns_providers_charts(&chart_config_prerequisites, "/tmp").expect("valid configuration");
+
+        // verify:
+        assert_eq!(
+            vec!["externaldns-cloudflare-1", "externaldns-aws-2"],
+            charts.iter().map(|c| c.chart_info.name.as_str()).collect::<Vec<_>>()
+        );
+        let cloudflare = &charts[0].chart_info.values;
+        assert_eq!(Some("cloudflare".to_string()), get_value(cloudflare, "provider"));
+        assert_eq!(Some("example.com".to_string()), get_value(cloudflare, "domainFilters[0]"));
+        assert_eq!(
+            Some("cloudflare-token".to_string()),
+            get_value(cloudflare, "cloudflare.apiToken")
+        );
+        let route53 = &charts[1].chart_info.values;
+        assert_eq!(Some("aws".to_string()), get_value(route53, "provider"));
+        assert_eq!(Some("us.example.com".to_string()), get_value(route53, "domainFilters[1]"));
+        assert_eq!(Some("eu-west-3".to_string()), get_value(route53, "aws.region"));
+        assert_ne!(get_value(cloudflare, "txtOwnerId"), get_value(route53, "txtOwnerId"));
+
+        // execute & verify: releases of removed providers are uninstalled
+        let release_names = vec![
+            "externaldns".to_string(),
+            "externaldns-cloudflare-1".to_string(),
+            "externaldns-aws-2".to_string(),
+            "externaldns-aws-3".to_string(),
+            "nginx-ingress".to_string(),
+        ];
+        let configured_release_names = charts.iter().map(|c| c.chart_info.name.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            vec!["externaldns-aws-3"],
+            stale_external_dns_provider_releases(&release_names, &configured_release_names)
+        );
+
+        // execute & verify: credentials are required
+        chart_config_prerequisites.infra_options.dns.external_dns_providers[1].credentials =
+            ExternalDnsProviderCredentials::Route53 {
+                access_key_id: "access-key".to_string(),
+                secret_access_key: "".to_string(),
+                region: "eu-west-3".to_string(),
+            };
+        assert!(external_dns_providers_charts(&chart_config_prerequisites, "/tmp").is_err());
+    }
+
+    #[test]
+    fn test_nginx_ingress_replicas_and_anti_affinity_values() {
+        // execute:
+        let values = nginx_ingress_replicas_values(DEFAULT_INGRESS_CONTROLLER_REPLICAS).unwrap();
+
+        // verify:
+        let anti_affinity = "controller.affinity.podAntiAffinity.preferredDuringSchedulingIgnoredDuringExecution[0]";
+        assert_eq!(Some("2".to_string()), get_value(&values, "controller.replicaCount"));
+        assert_eq!(Some("2".to_string()), get_value(&values, "controller.autoscaling.minReplicas"));
+        assert_eq!(Some("4".to_string()), get_value(&values, "controller.autoscaling.maxReplicas"));
+        assert_eq!(
+            Some("kubernetes.io/hostname".to_string()),
+            get_value(&values, &format!("{}.podAffinityTerm.topologyKey", anti_affinity))
+        );
+        assert_eq!(
+            Some("app.kubernetes.io/instance".to_string()),
+            get_value(
+                &values,
+                &format!("{}.podAffinityTerm.labelSelector.matchExpressions[1].key", anti_affinity)
+            )
+        );
+        assert_eq!(
+            Some("nginx-ingress".to_string()),
+            get_value(
+                &values,
+                &format!("{}.podAffinityTerm.labelSelector.matchExpressions[1].values[0]", anti_affinity)
+            )
+        );
+        assert!(nginx_ingress_replicas_values(0).is_err());
+
+        // execute & verify: maximum is raised when the minimum exceeds it
+        let values = nginx_ingress_replicas_values(12).unwrap();
+        assert_eq!(Some("12".to_string()), get_value(&values, "controller.autoscaling.minReplicas"));
+        assert_eq!(Some("12".to_string()), get_value(&values, "controller.autoscaling.maxReplicas"));
+    }
+
+    #[test]
+    fn test_cert_manager_dns01_extra_args_value() {
+        // setup:
+        let mut nameservers = BTreeMap::new();
+        nameservers.insert(
+            "cloudflare".to_string(),
+            vec!["173.245.58.51:53".to_string(), "173.245.59.41:53".to_string()],
+        );
+
+        // execute & verify:
+        // provider specific nameservers
+        let value = cert_manager_dns01_extra_args_value("cloudflare", &nameservers).unwrap();
+        assert_eq!("extraArgs", value.key);
+        assert_eq!(
+            "{--dns01-recursive-nameservers-only,--dns01-recursive-nameservers=173.245.58.51:53\\,173.245.59.41:53}",
+            value.value
+        );
+
+        // default nameservers for other providers
+        let value = cert_manager_dns01_extra_args_value("route53", &nameservers).unwrap();
+        assert_eq!(
+            "{--dns01-recursive-nameservers-only,--dns01-recursive-nameservers=1.1.1.1:53\\,8.8.8.8:53}",
+            value.value
+        );
+
+        // invalid nameservers
+        nameservers.insert("route53".to_string(), vec!["ns-1.awsdns.com".to_string()]);
+        assert!(cert_manager_dns01_extra_args_value("cloudflare", &nameservers).is_err());
+        nameservers.insert("route53".to_string(), vec![]);
+        assert!(cert_manager_dns01_extra_args_value("cloudflare", &nameservers).is_err());
+    }
+
+    #[test]
+    fn test_grafana_values() {
+        // execute & verify: provided password and host
+        let values = grafana_values("password", &Some("grafana.qovery.io".to_string()));
+        assert_eq!(Some("password".to_string()), get_value(&values, "adminPassword"));
+        assert_eq!(Some("true".to_string()), get_value(&values, "ingress.enabled"));
+        assert_eq!(Some("grafana.qovery.io".to_string()), get_value(&values, "ingress.hosts[0]"));
+        assert_eq!(
+            Some("grafana.qovery.io".to_string()),
+            get_value(&values, "ingress.tls[0].hosts[0]")
+        );
+
+        // execute & verify: grafana is not exposed without host
+        let values = grafana_values("", &None);
+        assert_eq!(None, get_value(&values, "adminPassword"));
+        assert_eq!(None, get_value(&values, "ingress.enabled"));
+    }
+
+    #[test]
+    fn test_generate_grafana_admin_password() {
+        // execute:
+        let password = generate_grafana_admin_password();
+
+        // verify:
+        assert_eq!(GRAFANA_ADMIN_PASSWORD_LENGTH, password.len());
+        assert!(password.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(password, generate_grafana_admin_password());
+    }
+
+    #[test]
+    fn test_prometheus_remote_write_values() {
+        // setup:
+        let remote_writes = vec![
+            RemoteWriteConfig {
+                url: "https://cortex.qovery.io/api/v1/push".to_string(),
+                basic_auth: Some(RemoteWriteBasicAuth {
+                    secret_name: "cortex-credentials".to_string(),
+                    username_key: "username".to_string(),
+                    password_key: "password".to_string(),
+                }),
+            },
+            RemoteWriteConfig {
+                url: "http://thanos-receive.thanos.svc:19291/api/v1/receive".to_string(),
+                basic_auth: None,
+            },
+        ];
+
+        // execute:
+        let values = prometheus_remote_write_values(&remote_writes).unwrap();
+
+        // verify:
+        assert_eq!(
+            Some("https://cortex.qovery.io/api/v1/push".to_string()),
+            get_value(&values, "prometheus.prometheusSpec.remoteWrite[0].url")
+        );
+        assert_eq!(
+            Some("cortex-credentials".to_string()),
+            get_value(&values, "prometheus.prometheusSpec.remoteWrite[0].basicAuth.password.name")
+        );
+        assert_eq!(
+            Some("password".to_string()),
+            get_value(&values, "prometheus.prometheusSpec.remoteWrite[0].basicAuth.password.key")
+        );
+        assert_eq!(
+            Some("http://thanos-receive.thanos.svc:19291/api/v1/receive".to_string()),
+            get_value(&values, "prometheus.prometheusSpec.remoteWrite[1].url")
+        );
+        assert_eq!(
+            None,
+            get_value(&values, "prometheus.prometheusSpec.remoteWrite[1].basicAuth.username.name")
+        );
+
+        // invalid URL
+        let invalid = vec![RemoteWriteConfig {
+            url: "cortex.qovery.io/api/v1/push".to_string(),
+            basic_auth: None,
+        }];
+        assert!(prometheus_remote_write_values(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_qovery_engine_nats_tls_values() {
+        // setup:
+        let ca_cert = "-----BEGIN CERTIFICATE-----\nMIIBszCCAVmgAwIBAgIUQ7\n-----END CERTIFICATE-----\n".to_string();
+
+        // execute & verify: TLS disabled
+        let values = qovery_engine_nats_tls_values(false, &None).unwrap();
+        assert_eq!(Some("false".to_string()), get_value(&values, "nats.tls.enabled"));
+        assert_eq!(None, get_value(&values, "environmentVariables.QOVERY_NATS_TLS_ENABLED"));
+
+        // execute & verify: TLS enabled
+        let values = qovery_engine_nats_tls_values(true, &Some(ca_cert.clone())).unwrap();
+        assert_eq!(Some("true".to_string()), get_value(&values, "nats.tls.enabled"));
+        assert_eq!(Some(ca_cert.trim().to_string()), get_value(&values, "nats.tls.caCert"));
+        assert_eq!(
+            Some("true".to_string()),
+            get_value(&values, "environmentVariables.QOVERY_NATS_TLS_ENABLED")
+        );
+        assert_eq!(
+            Some("/etc/qovery/nats/ca.crt".to_string()),
+            get_value(&values, "environmentVariables.QOVERY_NATS_CA_CERT_PATH")
+        );
+
+        // execute & verify: TLS enabled without valid CA
+        assert!(qovery_engine_nats_tls_values(true, &None).is_err());
+        assert!(qovery_engine_nats_tls_values(true, &Some("not a certificate".to_string())).is_err());
+    }
+
+    fn new_charts_config_prerequisites(
+        p: ChartsConfigPrerequisites,
+        cluster_long_id: Uuid,
+        infra_options: DoksOptions,
+    ) -> Result<ChartsConfigPrerequisites, CommandError> {
+        ChartsConfigPrerequisites::new(
+            p.organization_id,
+            p.organization_long_id,
+            p.cluster_id,
+            cluster_long_id,
+            p.do_cluster_id,
+            p.region,
+            p.cluster_name,
+            p.cloud_provider,
+            p.test_cluster,
+            p.do_token,
+            p.do_space_access_id,
+            p.do_space_secret_key,
+            p.do_space_bucket_kubeconfig,
+            p.do_space_kubeconfig_filename,
+            p.ff_log_history_enabled,
+            p.ff_metrics_history_enabled,
+            p.managed_dns_name,
+            p.managed_dns_helm_format,
+            p.managed_dns_resolvers_terraform_format,
+            p.external_dns_provider,
+            p.acme_url,
+            p.cloudflare_email,
+            p.cloudflare_api_token,
+            p.disable_pleco,
+            infra_options,
+        )
+    }
+
+    #[test]
+    fn test_charts_config_prerequisites_rejects_nil_uuid() {
+        // setup:
+        let p = get_charts_config_prerequisites();
+        let infra_options = p.infra_options.clone();
+
+        // execute:
+        let result = new_charts_config_prerequisites(p, Uuid::nil(), infra_options);
+
+        // verify:
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_charts_config_prerequisites_reads_options() {
+        // setup:
+        let p = get_charts_config_prerequisites();
+        let cluster_long_id = p.cluster_long_id;
+        let mut options = serde_json::to_value(&p.infra_options).expect("options should be serializable");
+        options["ingress"] = serde_json::json!({ "deploy_bootstrap_canary": true });
+        options["monitoring"] = serde_json::json!({
+            "loki_deployment_mode": "simple_scalable",
+            "prometheus_external_labels": { "team": "infra", "region": "overridden" },
+            "grafana_admin_password": "password",
+        });
+        options["dns"] = serde_json::json!({ "coredns_auto_reload": false });
+        let infra_options: DoksOptions = serde_json::from_value(options).expect("options should be deserializable");
+
+        // execute:
+        let config = new_charts_config_prerequisites(p, cluster_long_id, infra_options)
+            .expect("charts config prerequisites should be valid");
+
+        // verify: set options are read, the other ones keep their defaults
+        assert!(config.infra_options.ingress.deploy_bootstrap_canary);
+        assert_eq!(
+            DEFAULT_INGRESS_CONTROLLER_REPLICAS,
+            config.infra_options.ingress.controller_replicas
+        );
+        assert_eq!(LokiMode::SimpleScalable, config.infra_options.monitoring.loki_deployment_mode);
+        assert_eq!(DEFAULT_LOKI_PORT, config.infra_options.monitoring.loki_port);
+        assert_eq!(Some(&"infra".to_string()), config.prometheus_external_labels().get("team"));
+        assert_eq!(Some(&"fra1".to_string()), config.prometheus_external_labels().get("region"));
+        assert!(!config.infra_options.dns.coredns_auto_reload);
+        assert_eq!(
+            DoksEngineOptions::default().build_resources,
+            config.infra_options.engine.build_resources
+        );
+        assert_eq!("password", config.infra_options.monitoring.grafana_admin_password);
+        assert!(config.infra_options.dns.deploy_cert_manager);
+        assert_eq!(DoksMaintenanceOptions::default(), config.infra_options.maintenance);
+    }
+
+    #[test]
+    fn test_loki_deployment_mode() {
+        // execute & verify: monolithic
+        let components = loki_components(LokiMode::Monolithic, DEFAULT_LOKI_SERVICE_NAME, HelmChartNamespaces::Logging);
+        assert_eq!(1, components.len());
+        assert_eq!("loki", components[0].0);
+        assert!(components[0].1.is_empty());
+        assert_eq!("loki", loki_write_service_name(LokiMode::Monolithic, "loki"));
+        assert_eq!("loki", loki_read_service_name(LokiMode::Monolithic, "loki"));
+
+        // execute & verify: simple scalable
+        let components = loki_components(
+            LokiMode::SimpleScalable,
+            DEFAULT_LOKI_SERVICE_NAME,
+            HelmChartNamespaces::Logging,
+        );
+        assert_eq!(
+            vec!["loki-write", "loki-read"],
+            components.iter().map(|(name, _)| name.as_str()).collect::<Vec<&str>>()
+        );
+        assert_eq!(Some("write".to_string()), get_value(&components[0].1, "extraArgs.target"));
+        assert_eq!(Some("read".to_string()), get_value(&components[1].1, "extraArgs.target"));
+        for (_, values) in &components {
+            assert_eq!(Some(LOKI_SIMPLE_SCALABLE_IMAGE_TAG.to_string()), get_value(values, "image.tag"));
+            assert_eq!(
+                Some("loki-write-headless.logging.svc:7946".to_string()),
+                get_value(values, "config.memberlist.join_members[0]")
+            );
+            assert_eq!(
+                Some("loki-read-headless.logging.svc:7946".to_string()),
+                get_value(values, "config.memberlist.join_members[1]")
+            );
+        }
+        assert_eq!("loki-write", loki_write_service_name(LokiMode::SimpleScalable, "loki"));
+        assert_eq!("loki-read", loki_read_service_name(LokiMode::SimpleScalable, "loki"));
+
+        // execute & verify: releases of the other mode are uninstalled
+        assert_eq!(
+            vec!["loki-write".to_string(), "loki-read".to_string()],
+            loki_stale_release_names(LokiMode::Monolithic, "loki")
+        );
+        assert_eq!(
+            vec!["loki".to_string()],
+            loki_stale_release_names(LokiMode::SimpleScalable, "loki")
+        );
+    }
+
+    #[test]
+    fn test_nginx_ingress_default_backend_image() {
+        // execute & verify: chart default image is kept
+        let values = nginx_ingress_default_backend_values(true, &None).expect("values should be valid");
+        assert_eq!(None, get_value(&values, "defaultBackend.image.repository"));
+
+        // execute & verify: custom image, registry with a port
+        let values = nginx_ingress_default_backend_values(
+            true,
+            &Some("registry.acme.com:5000/errors/default-backend:2.1".to_string()),
+        )
+        .expect("values should be valid");
+        assert_eq!(
+            Some("registry.acme.com:5000/errors/default-backend".to_string()),
+            get_value(&values, "defaultBackend.image.repository")
+        );
+        assert_eq!(Some("2.1".to_string()), get_value(&values, "defaultBackend.image.tag"));
+
+        // execute & verify: invalid images
+        assert!(nginx_ingress_default_backend_values(true, &Some("acme/default-backend".to_string())).is_err());
+        assert!(nginx_ingress_default_backend_values(
+            true,
+            &Some("registry.acme.com:5000/default-backend".to_string())
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_qovery_agent_liveness_probe_values() {
+        // execute & verify: no probe
+        assert!(qovery_agent_liveness_probe_values(&None)
+            .expect("values should be valid")
+            .is_empty());
+
+        // execute & verify: probe renders
+        let values = qovery_agent_liveness_probe_values(&Some(QoveryAgentLivenessProbe::default()))
+            .expect("values should be valid");
+        assert_eq!(Some("/health".to_string()), get_value(&values, "livenessProbe.httpGet.path"));
+        assert_eq!(Some("80".to_string()), get_value(&values, "livenessProbe.httpGet.port"));
+        assert_eq!(Some("3".to_string()), get_value(&values, "livenessProbe.failureThreshold"));
+
+        // execute & verify: invalid probes
+        let invalid_path = QoveryAgentLivenessProbe {
+            path: "health".to_string(),
+            ..Default::default()
+        };
+        assert!(qovery_agent_liveness_probe_values(&Some(invalid_path)).is_err());
+        let invalid_threshold = QoveryAgentLivenessProbe {
+            failure_threshold: 0,
+            ..Default::default()
+        };
+        assert!(qovery_agent_liveness_probe_values(&Some(invalid_threshold)).is_err());
+    }
+
+    #[test]
+    fn test_qovery_engine_build_cache_values() {
+        // execute & verify: engine default
+        assert!(qovery_engine_build_cache_values(&None)
+            .expect("values should be valid")
+            .is_empty());
+
+        // execute & verify: local cache
+        let values = qovery_engine_build_cache_values(&Some(BuildCacheConfig::Local)).expect("values should be valid");
+        assert_eq!(
+            Some("local".to_string()),
+            get_value(&values, "environmentVariables.QOVERY_BUILD_CACHE_BACKEND")
+        );
+        assert_eq!(None, get_value(&values, "environmentVariables.QOVERY_BUILD_CACHE_REGISTRY_REF"));
+
+        // execute & verify: registry cache
+        let values = qovery_engine_build_cache_values(&Some(BuildCacheConfig::Registry {
+            reference: "registry.acme.com/qovery/build-cache".to_string(),
+        }))
+        .expect("values should be valid");
+        assert_eq!(
+            Some("registry".to_string()),
+            get_value(&values, "environmentVariables.QOVERY_BUILD_CACHE_BACKEND")
+        );
+        assert_eq!(
+            Some("registry.acme.com/qovery/build-cache".to_string()),
+            get_value(&values, "environmentVariables.QOVERY_BUILD_CACHE_REGISTRY_REF")
+        );
+
+        // execute & verify: invalid registry references
+        for reference in &["", "https://registry.acme.com/cache", "registry.acme.com/build cache"] {
+            assert!(qovery_engine_build_cache_values(&Some(BuildCacheConfig::Registry {
+                reference: reference.to_string()
+            }))
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_conflicting_chart_namespaces() {
+        // setup:
+        let chart = |name: &str, namespace: HelmChartNamespaces| -> Box<dyn HelmChart> {
+            Box::new(CommonChart {
+                chart_info: ChartInfo {
+                    name: name.to_string(),
+                    namespace,
+                    ..Default::default()
+                },
+            })
+        };
+        let isolated_charts = vec![
+            vec![
+                chart("cert-manager", HelmChartNamespaces::CertManager),
+                chart("kube-prometheus-stack", HelmChartNamespaces::Prometheus),
+                chart("coredns", HelmChartNamespaces::KubeSystem),
+            ],
+            vec![
+                chart("cert-manager-configs", HelmChartNamespaces::CertManager),
+                chart("kube-state-metrics", HelmChartNamespaces::Prometheus),
+                chart("metrics-server", HelmChartNamespaces::KubeSystem),
+            ],
+        ];
+        let conflicting_charts = vec![
+            vec![
+                chart("cert-manager", HelmChartNamespaces::CertManager),
+                chart("kube-prometheus-stack", HelmChartNamespaces::Prometheus),
+            ],
+            vec![chart("kube-state-metrics", HelmChartNamespaces::CertManager)],
+        ];
+
+        // execute & verify:
+        assert!(conflicting_chart_namespaces(&isolated_charts, &CHART_NAMESPACE_ISOLATION_GROUPS).is_empty());
+        assert_eq!(
+            conflicting_chart_namespaces(&conflicting_charts, &CHART_NAMESPACE_ISOLATION_GROUPS),
+            vec![
+                "Charts `kube-state-metrics` and `cert-manager` share the `cert-manager` namespace while they should be isolated from each other."
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_qovery_engine_build_timeout_values() {
+        // execute:
+        let values =
+            qovery_engine_build_timeout_values(DEFAULT_ENGINE_BUILD_TIMEOUT_SECONDS).expect("values should be valid");
+
+        // verify:
+        assert_eq!(
+            get_value(&values, "environmentVariables.QOVERY_BUILD_TIMEOUT_SECONDS"),
+            Some("1800".to_string())
+        );
+        assert!(qovery_engine_build_timeout_values(0).is_err());
+    }
+
+    #[test]
+    fn test_cert_manager_charts_are_deployed_only_when_enabled() {
+        // setup:
+        let mut chart_config_prerequisites = get_charts_config_prerequisites();
+
+        // execute:
+        let (cert_manager, cert_manager_config) = cert_manager_charts(&chart_config_prerequisites, "/tmp")
+            .expect("charts should be valid")
+            .expect("cert-manager charts should be present");
+
+        // verify:
+        assert_eq!("cert-manager", cert_manager.chart_info.name);
+        assert_eq!("cert-manager-configs", cert_manager_config.chart_info.name);
+
+        // setup:
+        chart_config_prerequisites.infra_options.dns.deploy_cert_manager = false;
+
+        // execute & verify:
+        assert!(cert_manager_charts(&chart_config_prerequisites, "/tmp")
+            .expect("charts should be valid")
+            .is_none());
+    }
+
+    #[test]
+    fn test_qovery_engine_extra_env_values() {
+        // setup:
+        let engine_values = vec![ChartSetValue {
+            key: "environmentVariables.LOG_LEVEL".to_string(),
+            value: "info".to_string(),
+        }];
+        let mut extra_env = BTreeMap::new();
+        extra_env.insert("EXPERIMENTAL_FLAG".to_string(), "true".to_string());
+        extra_env.insert("LOG_LEVEL".to_string(), "debug".to_string());
+
+        // execute:
+        let values = qovery_engine_extra_env_values(&extra_env, &engine_values);
+
+        // verify:
+        assert_eq!(
+            get_value(&values, "environmentVariables.EXPERIMENTAL_FLAG"),
+            Some("true".to_string())
+        );
+        assert_eq!(get_value(&values, "environmentVariables.LOG_LEVEL"), None);
+        assert_eq!(1, values.len());
+
+        // execute & verify: a warning is returned for the ignored variable only
+        let mut chart_values = engine_values.clone();
+        chart_values.extend(values);
+        let charts: Vec<Vec<Box<dyn HelmChart>>> = vec![vec![Box::new(CommonChart {
+            chart_info: ChartInfo {
+                name: "qovery-engine".to_string(),
+                values: chart_values,
+                ..Default::default()
+            },
+        })]];
+        let warnings = qovery_engine_ignored_extra_env(&charts, &extra_env);
+        assert_eq!(1, warnings.len());
+        assert!(warnings[0].contains("`LOG_LEVEL`"));
+    }
+
+    #[test]
+    fn test_promtail_drop_labels_values() {
+        // execute:
+        let values =
+            promtail_drop_labels_values(&["pod_template_hash".to_string(), "controller_revision_hash".to_string()])
+                .expect("drop labels should be valid");
+
+        // verify:
+        assert_eq!(
+            get_value(&values, "extraRelabelConfigs[0].action"),
+            Some("labeldrop".to_string())
+        );
+        assert_eq!(
+            get_value(&values, "extraRelabelConfigs[0].regex"),
+            Some("pod_template_hash|controller_revision_hash".to_string())
+        );
+        assert!(promtail_drop_labels_values(&[])
+            .expect("no drop labels is valid")
+            .is_empty());
+        assert!(promtail_drop_labels_values(&["app.kubernetes.io/name".to_string()]).is_err());
+        assert!(promtail_drop_labels_values(&["".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_nginx_ingress_passthrough_values() {
+        // setup:
+        let service_ref = |name: &str, port: u16| ServiceRef {
+            namespace: "game".to_string(),
+            name: name.to_string(),
+            port,
+        };
+        let mut tcp_services = BTreeMap::new();
+        tcp_services.insert(25565, service_ref("minecraft", 25565));
+        let mut udp_services = BTreeMap::new();
+        udp_services.insert(27015, service_ref("steam", 27015));
+
+        // execute:
+        let values = nginx_ingress_passthrough_values(&tcp_services, &udp_services).expect("values should be valid");
+
+        // verify:
+        assert_eq!(get_value(&values, "tcp.25565"), Some("game/minecraft:25565:PROXY".to_string()));
+        assert_eq!(get_value(&values, "udp.27015"), Some("game/steam:27015".to_string()));
+
+        let mut invalid_tcp_services = BTreeMap::new();
+        invalid_tcp_services.insert(443, service_ref("minecraft", 25565));
+        assert!(nginx_ingress_passthrough_values(&invalid_tcp_services, &BTreeMap::new()).is_err());
+        let mut invalid_tcp_services = BTreeMap::new();
+        invalid_tcp_services.insert(25565, service_ref("", 25565));
+        assert!(nginx_ingress_passthrough_values(&invalid_tcp_services, &BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_loki_affinity_values() {
+        // setup:
+        let mut loki_affinity = LokiAffinity {
+            match_labels: BTreeMap::new(),
+            topology_key: "kubernetes.io/hostname".to_string(),
+            preferred_weight: Some(50),
+        };
+        loki_affinity
+            .match_labels
+            .insert("app".to_string(), "log-heavy".to_string());
+
+        // execute:
+        let values = loki_affinity_values(&loki_affinity).expect("values should be valid");
+
+        // verify:
+        let preferred = "affinity.podAffinity.preferredDuringSchedulingIgnoredDuringExecution[0]";
+        assert_eq!(get_value(&values, &format!("{}.weight", preferred)), Some("50".to_string()));
+        assert_eq!(
+            get_value(&values, &format!("{}.podAffinityTerm.topologyKey", preferred)),
+            Some("kubernetes.io/hostname".to_string())
+        );
+        assert_eq!(
+            get_value(&values, &format!("{}.podAffinityTerm.labelSelector.matchLabels.app", preferred)),
+            Some("log-heavy".to_string())
+        );
+
+        // execute & verify: required affinity and invalid weight
+        let required_values = loki_affinity_values(&LokiAffinity {
+            preferred_weight: None,
+            ..loki_affinity.clone()
+        })
+        .expect("values should be valid");
+        assert_eq!(
+            get_value(
+                &required_values,
+                "affinity.podAffinity.requiredDuringSchedulingIgnoredDuringExecution[0].topologyKey"
+            ),
+            Some("kubernetes.io/hostname".to_string())
+        );
+        assert!(loki_affinity_values(&LokiAffinity {
+            preferred_weight: Some(0),
+            ..loki_affinity
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_service_monitor_values() {
+        // setup:
+        let mut chart_config_prerequisites = get_charts_config_prerequisites();
+        chart_config_prerequisites.ff_metrics_history_enabled = false;
+
+        // execute & verify: no chart enables a servicemonitor without metrics history
+        for (chart_name, key) in SERVICE_MONITOR_VALUE_KEYS.iter() {
+            let values = service_monitor_values(chart_name, chart_config_prerequisites.enable_service_monitors());
+            assert_eq!(get_value(&values, key), Some("false".to_string()), "chart `{}`", chart_name);
+        }
+        let (cert_manager, _) = cert_manager_charts(&chart_config_prerequisites, "/tmp")
+            .expect("charts should be valid")
+            .expect("cert-manager charts should be present");
+        assert_eq!(
+            get_value(&cert_manager.chart_info.values, "prometheus.servicemonitor.enabled"),
+            Some("false".to_string())
+        );
+
+        // execute & verify: servicemonitors are enabled with metrics history, but cert-manager one
+        chart_config_prerequisites.ff_metrics_history_enabled = true;
+        for (chart_name, key) in SERVICE_MONITOR_VALUE_KEYS.iter() {
+            let values = service_monitor_values(chart_name, chart_config_prerequisites.enable_service_monitors());
+            assert_eq!(
+                get_value(&values, key),
+                Some((*chart_name != "cert-manager").to_string()),
+                "chart `{}`",
+                chart_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_qovery_engine_location_env_values() {
+        // setup:
+        let chart_config_prerequisites = get_charts_config_prerequisites();
+
+        // execute:
+        let values = qovery_engine_location_env_values(&chart_config_prerequisites);
+
+        // verify:
+        assert_eq!(
+            Some("do".to_string()),
+            get_value(&values, "environmentVariables.CLOUD_PROVIDER")
+        );
+        assert_eq!(
+            Some(chart_config_prerequisites.organization_id.clone()),
+            get_value(&values, "environmentVariables.ORGANIZATION")
+        );
+        assert_eq!(
+            Some(chart_config_prerequisites.region.clone()),
+            get_value(&values, "environmentVariables.REGION")
+        );
+    }
+
+    #[test]
+    fn test_coredns_auto_reload_values() {
+        // setup:
+        let p = get_charts_config_prerequisites();
+        let cluster_long_id = p.cluster_long_id;
+        let mut options = serde_json::to_value(&p.infra_options).expect("options should be serializable");
+        options["dns"] = serde_json::json!({ "coredns_auto_reload": false });
+        let infra_options: DoksOptions = serde_json::from_value(options).expect("options should be deserializable");
+
+        // execute:
+        let default_chart = coredns_config_chart(&get_charts_config_prerequisites(), "/tmp")
+            .expect("default coredns config chart should be valid");
+        let disabled_chart = coredns_config_chart(
+            &new_charts_config_prerequisites(p, cluster_long_id, infra_options)
+                .expect("charts config prerequisites should be valid"),
+            "/tmp",
+        )
+        .expect("coredns config chart without auto reload should be valid");
+
+        // verify: the option reaches the chart values
+        assert_eq!(
+            Some("true".to_string()),
+            get_value(&default_chart.chart_info.values, "auto_reload")
+        );
+        assert_eq!(
+            Some("false".to_string()),
+            get_value(&disabled_chart.chart_info.values, "auto_reload")
+        );
+        assert_eq!(
+            Some("false".to_string()),
+            get_value(&coredns_auto_reload_values(false), "auto_reload")
+        );
+    }
 }
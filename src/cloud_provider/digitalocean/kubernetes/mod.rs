@@ -10,7 +10,12 @@ use crate::cloud_provider::digitalocean::do_api_common::{do_get_from_api, DoApiT
 use crate::cloud_provider::digitalocean::kubernetes::doks_api::{
     get_do_kubeconfig_by_cluster_name, get_do_latest_doks_slug_from_api, get_doks_info_from_name,
 };
-use crate::cloud_provider::digitalocean::kubernetes::helm_charts::{do_helm_charts, ChartsConfigPrerequisites};
+use crate::cloud_provider::digitalocean::kubernetes::helm_charts::{
+    bootstrap_canary_chart_info, bootstrap_canary_hostname, do_helm_charts, generate_grafana_admin_password,
+    probe_bootstrap_canary, qovery_engine_ignored_extra_env, ChartsConfigPrerequisites, DoksDnsOptions,
+    DoksEngineOptions, DoksIngressOptions, DoksMaintenanceOptions, DoksMonitoringOptions,
+    CHART_NAMESPACE_ISOLATION_GROUPS,
+};
 use crate::cloud_provider::digitalocean::kubernetes::node::DoInstancesType;
 use crate::cloud_provider::digitalocean::models::doks::KubernetesCluster;
 use crate::cloud_provider::digitalocean::network::load_balancer::do_get_load_balancer_ip;
@@ -18,7 +23,10 @@ use crate::cloud_provider::digitalocean::network::vpc::{
     get_do_random_available_subnet_from_api, get_do_vpc_name_available_from_api, VpcInitKind,
 };
 use crate::cloud_provider::environment::Environment;
-use crate::cloud_provider::helm::{deploy_charts_levels, ChartInfo, ChartSetValue, HelmChartNamespaces};
+use crate::cloud_provider::helm::{
+    conflicting_chart_namespaces, deploy_charts_levels, get_charts_levels_estimated_duration, ChartInfo, ChartSetValue,
+    ChartsDeploymentOptions, HelmChartNamespaces,
+};
 use crate::cloud_provider::kubernetes::{
     is_kubernetes_upgrade_required, send_progress_on_long_task, uninstall_cert_manager, Kind, Kubernetes,
     KubernetesUpgradeStatus, ProviderOptions,
@@ -26,10 +34,11 @@ use crate::cloud_provider::kubernetes::{
 use crate::cloud_provider::models::NodeGroups;
 use crate::cloud_provider::qovery::EngineLocation;
 use crate::cloud_provider::utilities::print_action;
-use crate::cloud_provider::{kubernetes, CloudProvider};
+use crate::cloud_provider::{kubernetes, CloudProvider, Kind as CloudProviderKind};
 use crate::cmd::helm::{to_engine_error, Helm};
 use crate::cmd::kubectl::{
     do_kubectl_exec_get_loadbalancer_id, kubectl_exec_get_all_namespaces, kubectl_exec_get_events,
+    kubectl_exec_get_secret_value,
 };
 use crate::cmd::terraform::{terraform_exec, terraform_init_validate_plan_apply, terraform_init_validate_state_list};
 use crate::deletion_utilities::{get_firsts_namespaces_to_delete, get_qovery_managed_namespaces};
@@ -80,7 +89,6 @@ pub struct DoksOptions {
     pub engine_version_controller_token: String,
     pub agent_version_controller_token: String,
     pub grafana_admin_user: String,
-    pub grafana_admin_password: String,
     pub discord_api_key: String,
     pub qovery_nats_url: String,
     pub qovery_nats_user: String,
@@ -88,6 +96,17 @@ pub struct DoksOptions {
     pub qovery_ssh_key: String,
     // Others
     pub tls_email_report: String,
+    // Charts
+    #[serde(default)]
+    pub engine: DoksEngineOptions,
+    #[serde(default)]
+    pub monitoring: DoksMonitoringOptions,
+    #[serde(default)]
+    pub ingress: DoksIngressOptions,
+    #[serde(default)]
+    pub dns: DoksDnsOptions,
+    #[serde(default)]
+    pub maintenance: DoksMaintenanceOptions,
 }
 
 impl ProviderOptions for DoksOptions {}
@@ -308,7 +327,10 @@ impl DOKS {
         // grafana credentials
         context.insert("grafana_admin_user", self.options.grafana_admin_user.as_str());
 
-        context.insert("grafana_admin_password", self.options.grafana_admin_password.as_str());
+        context.insert(
+            "grafana_admin_password",
+            self.options.monitoring.grafana_admin_password.as_str(),
+        );
 
         // TLS
         context.insert("acme_server_url", &self.lets_encrypt_url());
@@ -615,35 +637,66 @@ impl DOKS {
             Err(e) => return Err(EngineError::new_cannot_get_cluster_error(event_details, e)),
         };
 
-        let charts_prerequisites = ChartsConfigPrerequisites {
-            organization_id: self.cloud_provider.organization_id().to_string(),
-            organization_long_id: self.cloud_provider.organization_long_id(),
-            infra_options: self.options.clone(),
-            cluster_id: self.id.clone(),
-            cluster_long_id: self.long_id,
-            do_cluster_id: doks_id,
-            region: self.region(),
-            cluster_name: self.cluster_name(),
-            cloud_provider: "digitalocean".to_string(),
-            test_cluster: self.context.is_test_cluster(),
-            do_token: self.cloud_provider.token().to_string(),
-            do_space_access_id: self.cloud_provider.access_key_id(),
-            do_space_secret_key: self.cloud_provider.secret_access_key(),
-            do_space_bucket_kubeconfig: self.kubeconfig_bucket_name(),
-            do_space_kubeconfig_filename: self.kubeconfig_file_name(),
-            qovery_engine_location: self.options.qovery_engine_location.clone(),
-            ff_log_history_enabled: self.context.is_feature_enabled(&Features::LogsHistory),
-            ff_metrics_history_enabled: self.context.is_feature_enabled(&Features::MetricsHistory),
-            managed_dns_name: self.dns_provider.domain().to_string(),
-            managed_dns_helm_format: self.dns_provider.domain().to_helm_format_string(),
-            managed_dns_resolvers_terraform_format: self.managed_dns_resolvers_terraform_format(),
-            external_dns_provider: self.dns_provider.provider_name().to_string(),
-            dns_email_report: self.options.tls_email_report.clone(),
-            acme_url: self.lets_encrypt_url(),
-            cloudflare_email: self.dns_provider.account().to_string(),
-            cloudflare_api_token: self.dns_provider.token().to_string(),
-            disable_pleco: self.context.disable_pleco(),
-        };
+        let mut charts_prerequisites = ChartsConfigPrerequisites::new(
+            self.cloud_provider.organization_id().to_string(),
+            self.cloud_provider.organization_long_id(),
+            self.id.clone(),
+            self.long_id,
+            doks_id,
+            self.region(),
+            self.cluster_name(),
+            CloudProviderKind::Do,
+            self.context.is_test_cluster(),
+            self.cloud_provider.token().to_string(),
+            self.cloud_provider.access_key_id(),
+            self.cloud_provider.secret_access_key(),
+            self.kubeconfig_bucket_name(),
+            self.kubeconfig_file_name(),
+            self.context.is_feature_enabled(&Features::LogsHistory),
+            self.context.is_feature_enabled(&Features::MetricsHistory),
+            self.dns_provider.domain().to_string(),
+            self.dns_provider.domain().to_helm_format_string(),
+            self.managed_dns_resolvers_terraform_format(),
+            self.dns_provider.provider_name().to_string(),
+            self.lets_encrypt_url(),
+            self.dns_provider.account().to_string(),
+            self.dns_provider.token().to_string(),
+            self.context.disable_pleco(),
+            self.options.clone(),
+        )
+        .map_err(|e| EngineError::new_helm_charts_setup_error(event_details.clone(), e))?;
+
+        if charts_prerequisites
+            .infra_options
+            .monitoring
+            .grafana_admin_password
+            .is_empty()
+        {
+            // keep the password of an already deployed grafana, it would be rotated at every deployment otherwise
+            let deployed_grafana_admin_password = kubectl_exec_get_secret_value(
+                kubeconfig_path,
+                HelmChartNamespaces::Prometheus.to_string().as_str(),
+                "grafana",
+                "admin-password",
+                self.cloud_provider.credentials_environment_variables(),
+            )
+            .map_err(|e| EngineError::new_helm_charts_setup_error(event_details.clone(), e))?;
+
+            charts_prerequisites.infra_options.monitoring.grafana_admin_password = match deployed_grafana_admin_password
+            {
+                Some(password) => password,
+                None => {
+                    self.logger().log(EngineEvent::Info(
+                        event_details.clone(),
+                        EventMessage::new_from_safe(
+                            "No Grafana admin password set, a random one has been generated and is available in `grafana` secret."
+                                .to_string(),
+                        ),
+                    ));
+                    generate_grafana_admin_password()
+                }
+            };
+        }
 
         let chart_prefix_path = &temp_dir;
 
@@ -658,11 +711,38 @@ impl DOKS {
         )
         .map_err(|e| EngineError::new_helm_charts_setup_error(event_details.clone(), e))?;
 
+        for conflict in conflicting_chart_namespaces(&helm_charts_to_deploy, &CHART_NAMESPACE_ISOLATION_GROUPS) {
+            self.logger().log(EngineEvent::Warning(
+                event_details.clone(),
+                EventMessage::new_from_safe(conflict),
+            ));
+        }
+        for ignored_env in qovery_engine_ignored_extra_env(
+            &helm_charts_to_deploy,
+            &charts_prerequisites.infra_options.engine.extra_env,
+        ) {
+            self.logger().log(EngineEvent::Warning(
+                event_details.clone(),
+                EventMessage::new_from_safe(ignored_env),
+            ));
+        }
+
+        let message = format!(
+            "Deploying charts, estimated duration is up to {} minutes",
+            get_charts_levels_estimated_duration(&helm_charts_to_deploy).as_secs() / 60
+        );
+        self.send_to_customer(&message, &listeners_helper);
+        self.logger()
+            .log(EngineEvent::Info(event_details.clone(), EventMessage::new_from_safe(message)));
+
         deploy_charts_levels(
             kubeconfig_path,
             &credentials_environment_variables,
             helm_charts_to_deploy,
-            self.context.is_dry_run_deploy(),
+            &ChartsDeploymentOptions {
+                dry_run: self.context.is_dry_run_deploy(),
+                ..Default::default()
+            },
         )
         .map_err(|e| EngineError::new_helm_charts_deploy_error(event_details.clone(), e))?;
 
@@ -728,7 +808,23 @@ impl DOKS {
         // This will ony print the diff on stdout
         let _ = helm.upgrade_diff(&load_balancer_dns_hostname, &[]);
         helm.upgrade(&load_balancer_dns_hostname, &[])
-            .map_err(|e| EngineError::new_helm_error(event_details.clone(), e))
+            .map_err(|e| EngineError::new_helm_error(event_details.clone(), e))?;
+
+        if charts_prerequisites.infra_options.ingress.deploy_bootstrap_canary && !self.context.is_dry_run_deploy() {
+            let hostname = bootstrap_canary_hostname(&charts_prerequisites);
+            self.send_to_customer(
+                format!("Checking cluster is reachable through {}", hostname).as_str(),
+                &listeners_helper,
+            );
+            let probe_result = probe_bootstrap_canary(&hostname);
+
+            // canary is only useful for the check, it's removed whatever the result
+            helm.uninstall(&bootstrap_canary_chart_info(chart_prefix_path), &[])
+                .map_err(|e| EngineError::new_helm_error(event_details.clone(), e))?;
+            probe_result.map_err(|e| EngineError::new_k8s_service_issue(event_details.clone(), e))?;
+        }
+
+        Ok(())
     }
 
     fn create_error(&self) -> Result<(), EngineError> {
@@ -1130,6 +1226,10 @@ impl Kubernetes for DOKS {
         None
     }
 
+    fn is_cert_manager_deployed(&self) -> bool {
+        self.options.dns.deploy_cert_manager
+    }
+
     fn cloud_provider(&self) -> &dyn CloudProvider {
         self.cloud_provider.as_ref().borrow()
     }
@@ -1,5 +1,9 @@
-use crate::cloud_provider::service::{Action, DatabaseService, RouterService, StatefulService, StatelessService};
+use crate::cloud_provider::service::{
+    Action, DatabaseService, RouterService, Service, StatefulService, StatelessService,
+};
+use crate::io_models::{DnsCheckConfig, EnvironmentError, ResourceQuota};
 use crate::models::application::ApplicationService;
+use crate::unit_conversion::cpu_string_to_float;
 
 pub struct Environment {
     namespace: String,
@@ -11,6 +15,8 @@ pub struct Environment {
     pub applications: Vec<Box<dyn ApplicationService>>,
     pub routers: Vec<Box<dyn RouterService>>,
     pub databases: Vec<Box<dyn DatabaseService>>,
+    pub resource_quota: Option<ResourceQuota>,
+    pub dns_check: Option<DnsCheckConfig>,
 }
 
 impl Environment {
@@ -23,6 +29,8 @@ impl Environment {
         applications: Vec<Box<dyn ApplicationService>>,
         routers: Vec<Box<dyn RouterService>>,
         databases: Vec<Box<dyn DatabaseService>>,
+        resource_quota: Option<ResourceQuota>,
+        dns_check: Option<DnsCheckConfig>,
     ) -> Self {
         Environment {
             namespace: format!("{}-{}", project_id, id),
@@ -34,6 +42,8 @@ impl Environment {
             applications,
             routers,
             databases,
+            resource_quota,
+            dns_check,
         }
     }
 
@@ -68,4 +78,506 @@ impl Environment {
     pub fn namespace(&self) -> &str {
         self.namespace.as_str()
     }
+
+    /// Sorts databases and applications so each of them comes after the services it depends on.
+    /// Databases being deployed first, an application dependency on a database is always satisfied.
+    pub fn sort_services_by_dependencies(&mut self) -> Result<(), EnvironmentError> {
+        let services = self
+            .databases
+            .iter()
+            .map(|x| (x.long_id().to_string(), x.depends_on()))
+            .chain(
+                self.applications
+                    .iter()
+                    .map(|x| (x.long_id().to_string(), x.depends_on())),
+            )
+            .collect::<Vec<_>>();
+        let order = sort_by_dependencies(&services)?;
+
+        let databases_count = self.databases.len();
+        let mut databases = self.databases.drain(..).map(Some).collect::<Vec<_>>();
+        let mut applications = self.applications.drain(..).map(Some).collect::<Vec<_>>();
+        for index in order {
+            if index < databases_count {
+                self.databases.extend(databases[index].take());
+            } else {
+                self.applications.extend(applications[index - databases_count].take());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns selectors shared by several services of the environment, pods of those services would be mixed up.
+    pub fn duplicated_selectors(&self) -> Vec<String> {
+        let selectors = self
+            .stateful_services()
+            .iter()
+            .filter_map(|x| x.selector())
+            .chain(self.stateless_services().iter().filter_map(|x| x.selector()))
+            .collect::<Vec<_>>();
+
+        find_duplicates(&selectors)
+    }
+
+    /// Returns resources requested in the environment namespace by applications and containerized databases, at their
+    /// maximum scale and during rolling updates. Routers are only ingresses and managed databases don't run on the cluster.
+    pub fn resource_requests(&self) -> Vec<ServiceResourceRequests> {
+        let applications = self
+            .applications
+            .iter()
+            .map(|x| ServiceResourceRequests::new_application(x.as_ref()));
+        let databases = self
+            .databases
+            .iter()
+            .filter(|x| !x.is_managed_service())
+            .map(|x| ServiceResourceRequests::new(x.as_ref()));
+
+        applications.chain(databases).collect()
+    }
+
+    /// Returns changes by service to go from this environment (the deployed one) to the other one.
+    pub fn diff(&self, other: &Environment) -> EnvironmentDiff {
+        diff_services(&self.services_states(), &other.services_states())
+    }
+
+    fn services_states(&self) -> Vec<ServiceState> {
+        let applications = self
+            .applications
+            .iter()
+            .map(|x| ServiceState::new(x.as_ref(), Some(x.get_build().image.full_image_name_with_tag())));
+        let routers = self.routers.iter().map(|x| ServiceState::new(x.as_ref(), None));
+        let databases = self.databases.iter().map(|x| ServiceState::new(x.as_ref(), None));
+
+        applications.chain(routers).chain(databases).collect()
+    }
+}
+
+/// Changes by service between two environments.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EnvironmentDiff {
+    pub changes: Vec<ServiceChange>,
+}
+
+impl EnvironmentDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceChange {
+    pub long_id: String,
+    pub name: String,
+    pub service_type: String,
+    pub kind: ServiceChangeKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServiceChangeKind {
+    Added,
+    Removed,
+    Modified(Vec<FieldChange>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Service properties compared when diffing environments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceState {
+    pub long_id: String,
+    pub name: String,
+    pub service_type: String,
+    pub image: Option<String>,
+    pub version: String,
+    pub total_cpus: String,
+    pub cpu_burst: String,
+    pub total_ram_in_mib: u32,
+    pub min_instances: u32,
+    pub max_instances: u32,
+}
+
+impl ServiceState {
+    fn new<S: Service + ?Sized>(service: &S, image: Option<String>) -> Self {
+        ServiceState {
+            long_id: service.long_id().to_string(),
+            name: service.name().to_string(),
+            service_type: service.service_type().name(),
+            image,
+            version: service.version(),
+            total_cpus: service.total_cpus(),
+            cpu_burst: service.cpu_burst(),
+            total_ram_in_mib: service.total_ram_in_mib(),
+            min_instances: service.min_instances(),
+            max_instances: service.max_instances(),
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.clone()),
+            ("image", self.image.clone().unwrap_or_default()),
+            ("version", self.version.clone()),
+            ("total_cpus", self.total_cpus.clone()),
+            ("cpu_burst", self.cpu_burst.clone()),
+            ("total_ram_in_mib", self.total_ram_in_mib.to_string()),
+            ("min_instances", self.min_instances.to_string()),
+            ("max_instances", self.max_instances.to_string()),
+        ]
+    }
+}
+
+/// Resources requested by a service, all its instances included.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceResourceRequests {
+    pub cpu: f32,
+    pub memory_in_mib: u32,
+    pub pods: u32,
+}
+
+impl ServiceResourceRequests {
+    fn new<S: Service + ?Sized>(service: &S) -> Self {
+        let instances = service.max_instances();
+        ServiceResourceRequests {
+            cpu: cpu_string_to_float(service.total_cpus()) * instances as f32,
+            memory_in_mib: service.total_ram_in_mib() * instances,
+            pods: instances,
+        }
+    }
+
+    /// Applications pods request their `resources` requests, not their total cpus and ram.
+    fn new_application(application: &dyn ApplicationService) -> Self {
+        let resources = application.resources();
+        let instances = application.max_instances()
+            + rolling_update_surge_pods(application.is_stateful(), application.max_instances());
+        ServiceResourceRequests {
+            cpu: cpu_string_to_float(resources.cpu_request.as_str()) * instances as f32,
+            memory_in_mib: resources.memory_request_mib * instances,
+            pods: instances,
+        }
+    }
+}
+
+/// Returns pods created on top of `max_instances` during a rolling update. Deployments surge by 25% rounded up
+/// (kubernetes default, 1 pod for a single instance as set in the q-application chart) while statefulsets pods are
+/// replaced one at a time.
+pub fn rolling_update_surge_pods(is_stateful: bool, max_instances: u32) -> u32 {
+    if is_stateful {
+        0
+    } else {
+        (max_instances + 3) / 4
+    }
+}
+
+/// Returns a description of each quota resource exceeded by the sum of the services requests.
+pub fn resource_quota_violations(quota: &ResourceQuota, requests: &[ServiceResourceRequests]) -> Vec<String> {
+    let cpu = requests.iter().map(|x| x.cpu).sum::<f32>();
+    let memory_in_mib = requests.iter().map(|x| x.memory_in_mib).sum::<u32>();
+    let pods = requests.iter().map(|x| x.pods).sum::<u32>();
+    let mut violations = vec![];
+
+    if cpu > cpu_string_to_float(quota.cpu.as_str()) {
+        violations.push(format!("cpu: {} requested, {} allowed", cpu, quota.cpu));
+    }
+    if memory_in_mib > quota.memory_in_mib {
+        violations.push(format!(
+            "memory: {}MiB requested, {}MiB allowed",
+            memory_in_mib, quota.memory_in_mib
+        ));
+    }
+    if pods > quota.pods {
+        violations.push(format!("pods: {} requested, {} allowed", pods, quota.pods));
+    }
+
+    violations
+}
+
+/// Returns changes between services states, services being matched on their long id.
+/// Removed services come first, then modified and added ones in the order of `after`.
+pub fn diff_services(before: &[ServiceState], after: &[ServiceState]) -> EnvironmentDiff {
+    let find = |services: &[ServiceState], long_id: &str| services.iter().position(|x| x.long_id == long_id);
+    let change = |service: &ServiceState, kind: ServiceChangeKind| ServiceChange {
+        long_id: service.long_id.clone(),
+        name: service.name.clone(),
+        service_type: service.service_type.clone(),
+        kind,
+    };
+
+    let mut changes = before
+        .iter()
+        .filter(|service| find(after, &service.long_id).is_none())
+        .map(|service| change(service, ServiceChangeKind::Removed))
+        .collect::<Vec<_>>();
+
+    for service in after {
+        match find(before, &service.long_id) {
+            None => changes.push(change(service, ServiceChangeKind::Added)),
+            Some(index) => {
+                let field_changes = before[index]
+                    .fields()
+                    .into_iter()
+                    .zip(service.fields())
+                    .filter(|((_, before), (_, after))| before != after)
+                    .map(|((field, before), (_, after))| FieldChange {
+                        field: field.to_string(),
+                        before,
+                        after,
+                    })
+                    .collect::<Vec<_>>();
+                if !field_changes.is_empty() {
+                    changes.push(change(service, ServiceChangeKind::Modified(field_changes)));
+                }
+            }
+        }
+    }
+
+    EnvironmentDiff { changes }
+}
+
+/// Returns values appearing more than once, in order of first appearance.
+pub fn find_duplicates(values: &[String]) -> Vec<String> {
+    let mut duplicates: Vec<String> = vec![];
+
+    for (index, value) in values.iter().enumerate() {
+        if values[..index].contains(value) && !duplicates.contains(value) {
+            duplicates.push(value.clone());
+        }
+    }
+
+    duplicates
+}
+
+/// Returns services (long id, dependencies long ids) indexes, ordered so every service comes after its dependencies.
+/// Original order is kept when possible.
+/// Fails when a dependency is not part of the list, or with the id of a service being part of a dependency cycle.
+pub fn sort_by_dependencies(services: &[(String, Vec<String>)]) -> Result<Vec<usize>, EnvironmentError> {
+    let index_of = |id: &String| services.iter().position(|(service_id, _)| service_id == id);
+    for (service_id, dependencies) in services {
+        if let Some(dependency_id) = dependencies.iter().find(|id| index_of(id).is_none()) {
+            return Err(EnvironmentError::UnknownServiceDependency(
+                service_id.clone(),
+                dependency_id.clone(),
+            ));
+        }
+    }
+
+    let mut sorted: Vec<usize> = Vec::with_capacity(services.len());
+    while sorted.len() < services.len() {
+        let is_sorted = |id: &String| sorted.iter().any(|&index| &services[index].0 == id);
+        let next =
+            (0..services.len()).find(|index| !sorted.contains(index) && services[*index].1.iter().all(&is_sorted));
+
+        match next {
+            Some(index) => sorted.push(index),
+            None => {
+                // every remaining service waits for another remaining one: following them leads into a cycle
+                let mut visited: Vec<usize> = vec![];
+                let mut index = (0..services.len())
+                    .find(|index| !sorted.contains(index))
+                    .unwrap_or_default();
+                while !visited.contains(&index) {
+                    visited.push(index);
+                    index = services[index]
+                        .1
+                        .iter()
+                        .filter_map(&index_of)
+                        .find(|dependency| !sorted.contains(dependency))
+                        .unwrap_or(index);
+                }
+                return Err(EnvironmentError::ServiceDependencyCycle(services[index].0.clone()));
+            }
+        }
+    }
+
+    Ok(sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::environment::{
+        diff_services, find_duplicates, resource_quota_violations, rolling_update_surge_pods, sort_by_dependencies,
+        FieldChange, ServiceChangeKind, ServiceResourceRequests, ServiceState,
+    };
+    use crate::errors::{EngineError, Tag};
+    use crate::events::{EnvironmentStep, EventDetails, Stage, Transmitter};
+    use crate::io_models::{EnvironmentError, QoveryIdentifier, ResourceQuota};
+
+    #[test]
+    fn test_sort_by_dependencies() {
+        // setup:
+        let app = ("app-id".to_string(), vec!["db-id".to_string()]);
+        let db = ("db-id".to_string(), vec![]);
+        let other_app = ("other-app-id".to_string(), vec![]);
+
+        // execute & verify: db is deployed first
+        assert_eq!(
+            Ok(vec![1, 0, 2]),
+            sort_by_dependencies(&[app.clone(), db.clone(), other_app.clone()])
+        );
+        assert_eq!(Ok(vec![0, 1]), sort_by_dependencies(&[db.clone(), app.clone()]));
+        assert_eq!(Ok(vec![]), sort_by_dependencies(&[]));
+
+        // execute & verify: unknown dependencies are rejected
+        let unknown_dependency_app = ("unknown-dependency-app-id".to_string(), vec!["unknown-id".to_string()]);
+        assert_eq!(
+            Err(EnvironmentError::UnknownServiceDependency(
+                "unknown-dependency-app-id".to_string(),
+                "unknown-id".to_string()
+            )),
+            sort_by_dependencies(&[db.clone(), unknown_dependency_app])
+        );
+
+        // execute & verify: cycles are rejected with one of their members
+        let a = ("a-id".to_string(), vec!["b-id".to_string()]);
+        let b = ("b-id".to_string(), vec!["a-id".to_string()]);
+        assert_eq!(
+            Err(EnvironmentError::ServiceDependencyCycle("a-id".to_string())),
+            sort_by_dependencies(&[a.clone(), b.clone(), other_app])
+        );
+        let c = ("c-id".to_string(), vec!["c-id".to_string()]);
+        assert_eq!(
+            Err(EnvironmentError::ServiceDependencyCycle("c-id".to_string())),
+            sort_by_dependencies(&[c])
+        );
+
+        // execute & verify: a service waiting for a cycle isn't reported as part of it
+        let waiting_app = ("waiting-app-id".to_string(), vec!["a-id".to_string()]);
+        assert_eq!(
+            Err(EnvironmentError::ServiceDependencyCycle("a-id".to_string())),
+            sort_by_dependencies(&[waiting_app, db, a, b])
+        );
+    }
+
+    #[test]
+    fn test_duplicated_selectors() {
+        // setup:
+        let selectors = vec![
+            "appId=app-1".to_string(),
+            "appId=app-2".to_string(),
+            "routerId=router-1".to_string(),
+            "appId=app-1".to_string(),
+            "appId=app-2".to_string(),
+            "appId=app-1".to_string(),
+        ];
+        let event_details = EventDetails::new(
+            None,
+            QoveryIdentifier::new_from_long_id("organization_id".to_string()),
+            QoveryIdentifier::new_from_long_id("cluster_id".to_string()),
+            QoveryIdentifier::new_from_long_id("execution_id".to_string()),
+            None,
+            Stage::Environment(EnvironmentStep::Deploy),
+            Transmitter::Environment("environment_id".to_string(), "environment".to_string()),
+        );
+
+        // execute:
+        let duplicated_selectors = find_duplicates(&selectors);
+        let error = EngineError::new_duplicated_service_selectors(event_details, duplicated_selectors.clone());
+
+        // verify:
+        assert_eq!(vec!["appId=app-1".to_string(), "appId=app-2".to_string()], duplicated_selectors);
+        assert_eq!(&Tag::DuplicatedServiceSelectors, error.tag());
+        assert!(error.user_log_message().contains("appId=app-1, appId=app-2"));
+        assert!(find_duplicates(&selectors[..3]).is_empty());
+    }
+
+    fn service_state(long_id: &str, image: &str) -> ServiceState {
+        ServiceState {
+            long_id: long_id.to_string(),
+            name: format!("app-{}", long_id),
+            service_type: "Application".to_string(),
+            image: Some(image.to_string()),
+            version: "c3dda8a".to_string(),
+            total_cpus: "500m".to_string(),
+            cpu_burst: "1".to_string(),
+            total_ram_in_mib: 256,
+            min_instances: 1,
+            max_instances: 2,
+        }
+    }
+
+    #[test]
+    fn test_diff_services() {
+        // setup:
+        let kept = service_state("kept-id", "registry/kept:v1");
+        let removed = service_state("removed-id", "registry/removed:v1");
+        let added = service_state("added-id", "registry/added:v1");
+        let updated_before = service_state("updated-id", "registry/updated:v1");
+        let updated_after = service_state("updated-id", "registry/updated:v2");
+
+        // execute:
+        let diff = diff_services(&[kept.clone(), removed, updated_before], &[kept.clone(), updated_after, added]);
+
+        // verify:
+        let changes = diff
+            .changes
+            .iter()
+            .map(|x| (x.long_id.as_str(), x.kind.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("removed-id", ServiceChangeKind::Removed),
+                (
+                    "updated-id",
+                    ServiceChangeKind::Modified(vec![FieldChange {
+                        field: "image".to_string(),
+                        before: "registry/updated:v1".to_string(),
+                        after: "registry/updated:v2".to_string(),
+                    }])
+                ),
+                ("added-id", ServiceChangeKind::Added),
+            ],
+            changes
+        );
+        assert!(diff_services(&[kept.clone()], &[kept]).is_empty());
+    }
+
+    #[test]
+    fn test_resource_quota_violations() {
+        // setup:
+        let quota = ResourceQuota {
+            cpu: "2".to_string(),
+            memory_in_mib: 2048,
+            pods: 4,
+        };
+        let app = ServiceResourceRequests {
+            cpu: 1.0,
+            memory_in_mib: 512,
+            pods: 2,
+        };
+        let database = ServiceResourceRequests {
+            cpu: 0.5,
+            memory_in_mib: 1024,
+            pods: 1,
+        };
+
+        // execute & verify: environment fits its quota
+        assert!(resource_quota_violations(&quota, &[app.clone(), database.clone()]).is_empty());
+
+        // execute & verify: environment exceeding its quota is rejected
+        let violations = resource_quota_violations(&quota, &[app.clone(), database, app]);
+        assert_eq!(
+            vec![
+                "cpu: 2.5 requested, 2 allowed".to_string(),
+                "pods: 5 requested, 4 allowed".to_string(),
+            ],
+            violations
+        );
+    }
+
+    #[test]
+    fn test_rolling_update_surge_pods() {
+        // execute & verify: deployments surge by 25% rounded up
+        assert_eq!(1, rolling_update_surge_pods(false, 1));
+        assert_eq!(1, rolling_update_surge_pods(false, 4));
+        assert_eq!(2, rolling_update_surge_pods(false, 5));
+        assert_eq!(0, rolling_update_surge_pods(false, 0));
+
+        // execute & verify: statefulsets don't surge
+        assert_eq!(0, rolling_update_surge_pods(true, 5));
+    }
 }
@@ -1,6 +1,9 @@
 use crate::cloud_provider::helm::HelmAction::Deploy;
 use crate::cloud_provider::helm::HelmChartNamespaces::KubeSystem;
-use crate::cloud_provider::qovery::{get_qovery_app_version, EngineLocation, QoveryAppName, QoveryShellAgent};
+use crate::cloud_provider::kubernetes::convert_k8s_cpu_value_to_f32;
+use crate::cloud_provider::qovery::{
+    get_qovery_app_version, EngineLocation, QoveryAppName, QoveryShellAgent, TokenSource,
+};
 use crate::cmd::helm::{to_command_error, Helm};
 use crate::cmd::kubectl::{
     kubectl_delete_crash_looping_pods, kubectl_exec_delete_crd, kubectl_exec_get_configmap, kubectl_exec_get_events,
@@ -10,9 +13,11 @@ use crate::cmd::structs::HelmHistoryRow;
 use crate::errors::{CommandError, ErrorMessageVerbosity};
 use crate::utilities::calculate_hash;
 use semver::Version;
-use std::collections::HashMap;
-use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Debug, Display, Formatter};
 use std::path::Path;
+use std::time::Duration;
 use std::{fs, thread};
 use thread::spawn;
 use tracing::{span, Level};
@@ -52,22 +57,238 @@ impl Display for HelmChartNamespaces {
     }
 }
 
+/// External DNS records management policy.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalDnsPolicy {
+    /// UpsertOnly: records are created and updated, never deleted.
+    UpsertOnly,
+    /// Sync: records are fully synchronized, including deletions.
+    Sync,
+}
+
+impl Default for ExternalDnsPolicy {
+    fn default() -> Self {
+        ExternalDnsPolicy::UpsertOnly
+    }
+}
+
+impl Display for ExternalDnsPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ExternalDnsPolicy::UpsertOnly => "upsert-only",
+            ExternalDnsPolicy::Sync => "sync",
+        };
+
+        f.write_str(str)
+    }
+}
+
+/// Kubernetes container image pull policy.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ImagePullPolicy {
+    Always,
+    IfNotPresent,
+    Never,
+}
+
+impl Default for ImagePullPolicy {
+    fn default() -> Self {
+        ImagePullPolicy::IfNotPresent
+    }
+}
+
+impl Display for ImagePullPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ImagePullPolicy::Always => "Always",
+            ImagePullPolicy::IfNotPresent => "IfNotPresent",
+            ImagePullPolicy::Never => "Never",
+        };
+
+        f.write_str(str)
+    }
+}
+
 #[derive(Clone)]
 pub struct ChartSetValue {
     pub key: String,
     pub value: String,
 }
 
+const MASKED_SECRET: &str = "********";
+
+/// Replaces every occurrence of the given secrets in the message, empty secrets are ignored.
+fn mask_secrets(message: &str, secrets: &[&str]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(message.to_string(), |message, secret| message.replace(secret, MASKED_SECRET))
+}
+
+/// Kubernetes container resources, cpu and memory being expressed in kubernetes format (ex: `500m`, `4Gi`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResourceSpec {
+    pub cpu_request: String,
+    pub cpu_limit: String,
+    pub memory_request: String,
+    pub memory_limit: String,
+}
+
+impl ResourceSpec {
+    /// Checks values are valid kubernetes quantities and requests don't exceed limits.
+    pub fn validate(&self) -> Result<(), CommandError> {
+        if parse_cpu(self.cpu_request.as_str())? > parse_cpu(self.cpu_limit.as_str())? {
+            return Err(CommandError::new_from_safe_message(format!(
+                "CPU request `{}` is higher than CPU limit `{}`.",
+                self.cpu_request, self.cpu_limit
+            )));
+        }
+
+        if parse_memory_in_mib(self.memory_request.as_str())? > parse_memory_in_mib(self.memory_limit.as_str())? {
+            return Err(CommandError::new_from_safe_message(format!(
+                "Memory request `{}` is higher than memory limit `{}`.",
+                self.memory_request, self.memory_limit
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns chart values for the resources, under the given prefix.
+    pub fn to_chart_set_values(&self, prefix: &str) -> Vec<ChartSetValue> {
+        vec![
+            ChartSetValue {
+                key: format!("{}.limits.cpu", prefix),
+                value: self.cpu_limit.clone(),
+            },
+            ChartSetValue {
+                key: format!("{}.requests.cpu", prefix),
+                value: self.cpu_request.clone(),
+            },
+            ChartSetValue {
+                key: format!("{}.limits.memory", prefix),
+                value: self.memory_limit.clone(),
+            },
+            ChartSetValue {
+                key: format!("{}.requests.memory", prefix),
+                value: self.memory_request.clone(),
+            },
+        ]
+    }
+}
+
+/// Parses a positive cpu quantity (ex: `500m`, `2`), unlike `cpu_string_to_float` invalid values are rejected.
+fn parse_cpu(value: &str) -> Result<f32, CommandError> {
+    match convert_k8s_cpu_value_to_f32(value.to_string()) {
+        Ok(cpu) if cpu.is_finite() && cpu > 0.0 => Ok(cpu),
+        _ => Err(CommandError::new_from_safe_message(format!(
+            "CPU `{}` is not a valid positive quantity (ex: `500m`, `2`).",
+            value
+        ))),
+    }
+}
+
+/// Parses a positive memory quantity in `Ki`, `Mi` or `Gi` (ex: `512Mi`), unlike `any_to_mi` invalid values are
+/// rejected.
+fn parse_memory_in_mib(value: &str) -> Result<f32, CommandError> {
+    let memory = [("Ki", 1.0 / 1024.0), ("Mi", 1.0), ("Gi", 1024.0)]
+        .iter()
+        .find_map(|(unit, mib)| {
+            value
+                .strip_suffix(unit)
+                .and_then(|number| number.parse::<f32>().ok())
+                .map(|number| number * mib)
+        });
+
+    match memory {
+        Some(memory) if memory.is_finite() && memory > 0.0 => Ok(memory),
+        _ => Err(CommandError::new_from_safe_message(format!(
+            "Memory `{}` is not a valid positive quantity (ex: `512Mi`, `4Gi`).",
+            value
+        ))),
+    }
+}
+
+/// Kubernetes pod toleration, allowing chart pods to be scheduled on tainted nodes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Toleration {
+    pub key: String,
+    pub operator: String,
+    pub value: Option<String>,
+    pub effect: Option<String>,
+}
+
+/// Escapes characters having a special meaning in helm `--set` keys and values.
+pub fn escape_chart_set_string(s: &str) -> String {
+    s.replace('.', "\\.").replace(',', "\\,")
+}
+
+/// Returns chart values for a string map (node selector, labels...), keys being escaped as they usually contain dots.
+pub fn get_string_map_values(prefix: &str, map: &BTreeMap<String, String>) -> Vec<ChartSetValue> {
+    map.iter()
+        .map(|(key, value)| ChartSetValue {
+            key: format!("{}.{}", prefix, escape_chart_set_string(key)),
+            value: value.replace(',', "\\,"),
+        })
+        .collect()
+}
+
+/// Returns chart values for a list of tolerations.
+pub fn get_tolerations_values(prefix: &str, tolerations: &[Toleration]) -> Vec<ChartSetValue> {
+    let mut values = vec![];
+
+    for (index, toleration) in tolerations.iter().enumerate() {
+        values.push(ChartSetValue {
+            key: format!("{}[{}].key", prefix, index),
+            value: toleration.key.clone(),
+        });
+        values.push(ChartSetValue {
+            key: format!("{}[{}].operator", prefix, index),
+            value: toleration.operator.clone(),
+        });
+        if let Some(value) = &toleration.value {
+            values.push(ChartSetValue {
+                key: format!("{}[{}].value", prefix, index),
+                value: value.clone(),
+            });
+        }
+        if let Some(effect) = &toleration.effect {
+            values.push(ChartSetValue {
+                key: format!("{}[{}].effect", prefix, index),
+                value: effect.clone(),
+            });
+        }
+    }
+
+    values
+}
+
 #[derive(Clone)]
 pub struct ChartValuesGenerated {
     pub filename: String,
     pub yaml_content: String,
 }
 
+/// Where a chart is read from, charts mirrored in an OCI registry (`url` being the chart reference, ex:
+/// `oci://registry.acme.com/charts/loki`) are pulled before being deployed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChartSource {
+    LocalPath,
+    OciRegistry { url: String, version: String },
+}
+
+impl Default for ChartSource {
+    fn default() -> Self {
+        ChartSource::LocalPath
+    }
+}
+
 #[derive(Clone)]
 pub struct ChartInfo {
     pub name: String,
     pub path: String,
+    pub source: ChartSource,
     pub namespace: HelmChartNamespaces,
     pub custom_namespace: Option<String>,
     pub action: HelmAction,
@@ -82,6 +303,8 @@ pub struct ChartInfo {
     pub yaml_files_content: Vec<ChartValuesGenerated>,
     pub parse_stderr_for_error: bool,
     pub k8s_selector: Option<String>,
+    /// helm_history_max: maximum number of release revisions kept by helm (default one is used when not set).
+    pub helm_history_max: Option<u32>,
 }
 
 impl ChartInfo {
@@ -132,6 +355,7 @@ impl Default for ChartInfo {
         ChartInfo {
             name: "undefined".to_string(),
             path: "undefined".to_string(),
+            source: ChartSource::default(),
             namespace: KubeSystem,
             custom_namespace: None,
             action: Deploy,
@@ -146,6 +370,7 @@ impl Default for ChartInfo {
             yaml_files_content: vec![],
             parse_stderr_for_error: true,
             k8s_selector: None,
+            helm_history_max: None,
         }
     }
 }
@@ -172,10 +397,21 @@ pub trait HelmChart: Send {
 
     fn get_chart_info(&self) -> &ChartInfo;
 
+    fn get_chart_info_mut(&mut self) -> &mut ChartInfo;
+
     fn namespace(&self) -> String {
         self.get_chart_info().get_namespace_string()
     }
 
+    /// Worst case duration of the chart deployment, used to give users an ETA.
+    fn estimated_duration(&self) -> Duration {
+        let chart_info = self.get_chart_info();
+        match chart_info.action {
+            HelmAction::Skip => Duration::from_secs(0),
+            _ => Duration::from_secs(chart_info.timeout_in_seconds.max(0) as u64),
+        }
+    }
+
     fn pre_exec(
         &self,
         kubernetes_config: &Path,
@@ -321,12 +557,88 @@ fn deploy_parallel_charts(
     }
 }
 
+/// Options applied to all charts levels deployment.
+#[derive(Clone, Default)]
+pub struct ChartsDeploymentOptions {
+    pub dry_run: bool,
+    /// force_upgrade_charts: names of the charts to upgrade with `--force`, even if they didn't change.
+    pub force_upgrade_charts: HashSet<String>,
+}
+
+/// Forces upgrade of charts whose name is part of the given ones.
+pub fn set_force_upgrade_charts(charts: &mut [Vec<Box<dyn HelmChart>>], force_upgrade_charts: &HashSet<String>) {
+    for chart in charts.iter_mut().flatten() {
+        let chart_info = chart.get_chart_info_mut();
+        if force_upgrade_charts.contains(&chart_info.name) {
+            chart_info.force_upgrade = true;
+        }
+    }
+}
+
+/// Returns charts levels estimated duration: levels are deployed one after the other,
+/// while charts of a same level are deployed in parallel.
+pub fn get_charts_levels_estimated_duration(charts: &[Vec<Box<dyn HelmChart>>]) -> Duration {
+    charts
+        .iter()
+        .map(|level| {
+            level
+                .iter()
+                .map(|chart| chart.estimated_duration())
+                .max()
+                .unwrap_or_default()
+        })
+        .sum()
+}
+
+/// Returns a message for every namespace where charts of different isolation groups are deployed, charts of a group
+/// being expected to be alone in their namespace (ex: `kube-state-metrics` deployed next to `cert-manager`).
+/// Charts not belonging to any group are ignored.
+pub fn conflicting_chart_namespaces(charts: &[Vec<Box<dyn HelmChart>>], isolation_groups: &[&[&str]]) -> Vec<String> {
+    let mut groups_by_namespace: BTreeMap<String, BTreeMap<usize, Vec<String>>> = BTreeMap::new();
+    for chart in charts.iter().flatten() {
+        let chart_info = chart.get_chart_info();
+        if chart_info.action != HelmAction::Deploy {
+            continue;
+        }
+
+        if let Some(group) = isolation_groups
+            .iter()
+            .position(|group| group.contains(&chart_info.name.as_str()))
+        {
+            groups_by_namespace
+                .entry(chart.namespace())
+                .or_default()
+                .entry(group)
+                .or_default()
+                .push(chart_info.name.clone());
+        }
+    }
+
+    groups_by_namespace
+        .into_iter()
+        .filter(|(_, groups)| groups.len() > 1)
+        .map(|(namespace, groups)| {
+            format!(
+                "Charts {} share the `{}` namespace while they should be isolated from each other.",
+                groups
+                    .values()
+                    .map(|charts| format!("`{}`", charts.join(", ")))
+                    .collect::<Vec<_>>()
+                    .join(" and "),
+                namespace
+            )
+        })
+        .collect()
+}
+
 pub fn deploy_charts_levels(
     kubernetes_config: &Path,
     envs: &[(String, String)],
-    charts: Vec<Vec<Box<dyn HelmChart>>>,
-    dry_run: bool,
+    mut charts: Vec<Vec<Box<dyn HelmChart>>>,
+    options: &ChartsDeploymentOptions,
 ) -> Result<(), CommandError> {
+    set_force_upgrade_charts(&mut charts, &options.force_upgrade_charts);
+
     // first show diff
     let envs_ref: Vec<(&str, &str)> = envs.iter().map(|(x, y)| (x.as_str(), y.as_str())).collect();
     let helm = Helm::new(&kubernetes_config, &envs_ref).map_err(to_command_error)?;
@@ -342,7 +654,7 @@ pub fn deploy_charts_levels(
         }
 
         // Skip actual deployment if dry run
-        if dry_run {
+        if options.dry_run {
             continue;
         }
 
@@ -375,6 +687,10 @@ impl HelmChart for CommonChart {
     fn get_chart_info(&self) -> &ChartInfo {
         &self.chart_info
     }
+
+    fn get_chart_info_mut(&mut self) -> &mut ChartInfo {
+        &mut self.chart_info
+    }
 }
 
 // CoreDNS config
@@ -389,6 +705,10 @@ impl HelmChart for CoreDNSConfigChart {
         &self.chart_info
     }
 
+    fn get_chart_info_mut(&mut self) -> &mut ChartInfo {
+        &mut self.chart_info
+    }
+
     fn pre_exec(
         &self,
         kubernetes_config: &Path,
@@ -584,6 +904,10 @@ impl HelmChart for PrometheusOperatorConfigChart {
         &self.chart_info
     }
 
+    fn get_chart_info_mut(&mut self) -> &mut ChartInfo {
+        &mut self.chart_info
+    }
+
     fn exec(
         &self,
         kubernetes_config: &Path,
@@ -662,16 +986,43 @@ pub struct ShellAgentContext<'a> {
     pub grpc_url: &'a str,
 }
 
+impl<'a> Debug for ShellAgentContext<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShellAgentContext")
+            .field("api_url", &self.api_url)
+            .field("api_token", &MASKED_SECRET)
+            .field("organization_long_id", &self.organization_long_id)
+            .field("cluster_id", &self.cluster_id)
+            .field("cluster_long_id", &self.cluster_long_id)
+            .field("cluster_token", &MASKED_SECRET)
+            .field("grpc_url", &self.grpc_url)
+            .finish()
+    }
+}
+
+impl<'a> ShellAgentContext<'a> {
+    /// Returns the error with context tokens masked, so it can be safely logged.
+    fn mask_secrets_in_error(&self, error: CommandError) -> CommandError {
+        let secrets = [self.api_token, self.cluster_token];
+        CommandError::new(
+            mask_secrets(&error.message_safe(), &secrets),
+            error.message_raw().map(|message| mask_secrets(&message, &secrets)),
+            error.env_vars(),
+        )
+    }
+}
+
 pub fn get_chart_for_shell_agent(
     context: ShellAgentContext,
     chart_path: impl Fn(&str) -> String,
 ) -> Result<CommonChart, CommandError> {
     let shell_agent_version: QoveryShellAgent = get_qovery_app_version(
         QoveryAppName::ShellAgent,
-        context.api_token,
+        &TokenSource::Literal(context.api_token.to_string()),
         context.api_url,
         context.cluster_id,
-    )?;
+    )
+    .map_err(|e| context.mask_secrets_in_error(e))?;
     let shell_agent = CommonChart {
         chart_info: ChartInfo {
             name: "shell-agent".to_string(),
@@ -752,7 +1103,7 @@ pub fn get_chart_for_cluster_agent(
 ) -> Result<CommonChart, CommandError> {
     let shell_agent_version: QoveryShellAgent = get_qovery_app_version(
         QoveryAppName::ClusterAgent,
-        context.api_token,
+        &TokenSource::Literal(context.api_token.to_string()),
         context.api_url,
         context.cluster_id,
     )?;
@@ -821,8 +1172,14 @@ pub fn get_chart_for_cluster_agent(
 
 #[cfg(test)]
 mod tests {
-    use crate::cloud_provider::helm::get_latest_successful_deployment;
+    use crate::cloud_provider::helm::{
+        get_charts_levels_estimated_duration, get_latest_successful_deployment, mask_secrets, ChartInfo, CommonChart,
+        HelmAction, HelmChart, ShellAgentContext,
+    };
     use crate::cmd::structs::HelmHistoryRow;
+    use crate::errors::CommandError;
+    use std::time::Duration;
+    use uuid::Uuid;
 
     #[test]
     fn test_last_succeeded_deployment() {
@@ -859,4 +1216,75 @@ mod tests {
         let final_succeed = get_latest_successful_deployment(&results).unwrap();
         assert_eq!(results[1].updated, final_succeed.updated);
     }
+
+    #[test]
+    fn test_shell_agent_context_debug_masks_tokens() {
+        // setup:
+        let organization_long_id = Uuid::new_v4();
+        let cluster_long_id = Uuid::new_v4();
+        let context = ShellAgentContext {
+            api_url: "api.example.com",
+            api_token: "my-api-token",
+            organization_long_id: &organization_long_id,
+            cluster_id: "z1234abc",
+            cluster_long_id: &cluster_long_id,
+            cluster_token: "my-cluster-token",
+            grpc_url: "grpc.example.com:443",
+        };
+
+        // execute:
+        let debug = format!("{:?}", context);
+        let error = context.mask_secrets_in_error(CommandError::new(
+            "Error with my-api-token.".to_string(),
+            Some("Error with my-api-token and my-cluster-token.".to_string()),
+            None,
+        ));
+
+        // verify:
+        assert!(debug.contains("z1234abc"));
+        assert!(!debug.contains("my-api-token"));
+        assert!(!debug.contains("my-cluster-token"));
+        assert_eq!("Error with ********.", error.message_safe());
+        assert_eq!(Some("Error with ******** and ********.".to_string()), error.message_raw());
+    }
+
+    #[test]
+    fn test_mask_secrets() {
+        assert_eq!("token=********", mask_secrets("token=secret", &["secret"]));
+        assert_eq!("token=secret", mask_secrets("token=secret", &[""]));
+    }
+
+    #[test]
+    fn test_charts_levels_estimated_duration() {
+        // setup:
+        let chart = |name: &str, timeout_in_seconds: i64, action: HelmAction| -> Box<dyn HelmChart> {
+            Box::new(CommonChart {
+                chart_info: ChartInfo {
+                    name: name.to_string(),
+                    timeout_in_seconds,
+                    action,
+                    ..Default::default()
+                },
+            })
+        };
+        let charts = vec![
+            vec![chart("q-storageclass", 300, HelmAction::Deploy)],
+            vec![
+                chart("cert-manager", 600, HelmAction::Deploy),
+                chart("kube-prometheus-stack", 480, HelmAction::Deploy),
+                chart("loki", 900, HelmAction::Skip),
+            ],
+            vec![chart("qovery-engine", 900, HelmAction::Deploy)],
+            vec![],
+        ];
+
+        // execute & verify:
+        assert_eq!(Duration::from_secs(600), charts[1][0].estimated_duration());
+        assert_eq!(Duration::from_secs(0), charts[1][2].estimated_duration());
+        assert_eq!(
+            Duration::from_secs(300 + 600 + 900),
+            get_charts_levels_estimated_duration(&charts)
+        );
+        assert_eq!(Duration::from_secs(0), get_charts_levels_estimated_duration(&[]));
+    }
 }
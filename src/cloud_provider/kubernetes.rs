@@ -15,25 +15,30 @@ use retry::OperationResult;
 use serde::{Deserialize, Serialize};
 
 use crate::cloud_provider::aws::regions::AwsZones;
-use crate::cloud_provider::environment::Environment;
+use crate::cloud_provider::environment::{resource_quota_violations, Environment};
 use crate::cloud_provider::models::{CpuLimits, NodeGroups};
-use crate::cloud_provider::service::CheckAction;
+use crate::cloud_provider::service::{CheckAction, ServiceType};
+use crate::cloud_provider::utilities::DnsCheckCoordinator;
 use crate::cloud_provider::{service, CloudProvider, DeploymentTarget};
 use crate::cmd::kubectl;
 use crate::cmd::kubectl::{
-    kubectl_delete_objects_in_all_namespaces, kubectl_exec_count_all_objects, kubectl_exec_delete_pod,
-    kubectl_exec_get_node, kubectl_exec_is_namespace_present, kubectl_exec_version, kubectl_get_crash_looping_pods,
-    kubernetes_get_all_pdbs,
+    kubectl_delete_objects_in_all_namespaces, kubectl_exec_apply_resource_quota, kubectl_exec_count_all_objects,
+    kubectl_exec_create_namespace, kubectl_exec_delete_pod, kubectl_exec_delete_pvcs,
+    kubectl_exec_delete_resource_quota, kubectl_exec_get_namespace_remaining_resources, kubectl_exec_get_node,
+    kubectl_exec_is_namespace_present, kubectl_exec_version, kubectl_get_crash_looping_pods, kubernetes_get_all_pdbs,
 };
 use crate::cmd::structs::KubernetesNodeCondition;
 use crate::dns_provider::DnsProvider;
 use crate::errors::{CommandError, EngineError, ErrorMessageVerbosity};
 use crate::events::Stage::Infrastructure;
-use crate::events::{EngineEvent, EventDetails, EventMessage, GeneralStep, InfrastructureStep, Stage, Transmitter};
+use crate::events::{
+    EngineEvent, EnvironmentStep, EventDetails, EventMessage, GeneralStep, InfrastructureStep, Stage, Transmitter,
+};
 use crate::fs::workspace_directory;
 use crate::io_models::ProgressLevel::Info;
 use crate::io_models::{
-    Action, Context, Listen, ListenersHelper, ProgressInfo, ProgressLevel, ProgressScope, QoveryIdentifier, StringPath,
+    Action, Context, DnsCheckConfig, Listen, ListenersHelper, ProgressInfo, ProgressLevel, ProgressScope,
+    QoveryIdentifier, ResourceQuota, StringPath,
 };
 use crate::logger::Logger;
 use crate::models::types::VersionsNumber;
@@ -66,6 +71,11 @@ pub trait Kubernetes: Listen {
     fn config_file_store(&self) -> &dyn ObjectStorage;
     fn is_valid(&self) -> Result<(), EngineError>;
 
+    /// Routers request their custom domains certificates to cert-manager, unless it is not deployed on the cluster.
+    fn is_cert_manager_deployed(&self) -> bool {
+        true
+    }
+
     fn get_event_details(&self, stage: Stage) -> EventDetails {
         let context = self.context();
         EventDetails::new(
@@ -399,6 +409,22 @@ pub fn deploy_environment(
 ) -> Result<(), EngineError> {
     let listeners_helper = ListenersHelper::new(kubernetes.listeners());
 
+    // services sharing a selector would have their pods mixed up
+    let duplicated_selectors = environment.duplicated_selectors();
+    if !duplicated_selectors.is_empty() {
+        return Err(EngineError::new_duplicated_service_selectors(
+            event_details,
+            duplicated_selectors,
+        ));
+    }
+
+    match &environment.resource_quota {
+        Some(resource_quota) => {
+            apply_environment_resource_quota(kubernetes, environment, resource_quota, event_details.clone())?
+        }
+        None => delete_environment_resource_quota(kubernetes, environment, event_details.clone())?,
+    }
+
     let stateful_deployment_target = match kubernetes.kind() {
         Kind::Eks => DeploymentTarget {
             kubernetes,
@@ -487,7 +513,23 @@ pub fn deploy_environment(
     // Quick fix: adding 100 ms delay to avoid race condition on service status update
     thread::sleep(std::time::Duration::from_millis(100));
 
+    let routers_dns_checked = match &environment.dns_check {
+        Some(dns_check) => {
+            check_routers_dns(environment, dns_check, event_details.clone(), logger)?;
+            true
+        }
+        None => false,
+    };
+
     for service in environment.stateless_services() {
+        // routers check action only consists in checking their domains, already done for the whole environment
+        if routers_dns_checked
+            && service.service_type() == ServiceType::Router
+            && *service.action() == service::Action::Create
+        {
+            continue;
+        }
+
         let _ = service::check_kubernetes_service_error(
             service.exec_check_action(),
             kubernetes,
@@ -504,6 +546,102 @@ pub fn deploy_environment(
     Ok(())
 }
 
+/// Checks the domains of all the environment routers being created at once, within the DNS check limits.
+fn check_routers_dns(
+    environment: &Environment,
+    dns_check: &DnsCheckConfig,
+    event_details: EventDetails,
+    logger: &dyn Logger,
+) -> Result<(), EngineError> {
+    let dns_checks = environment
+        .routers
+        .iter()
+        .filter(|router| *router.action() == service::Action::Create)
+        .flat_map(|router| router.dns_checks(router.get_event_details(Stage::Environment(EnvironmentStep::Deploy))))
+        .collect::<Vec<_>>();
+    let nb_dns_checks = dns_checks.len();
+
+    let coordinator =
+        DnsCheckCoordinator::new(dns_check.max_concurrency, Duration::from_secs(dns_check.budget_seconds));
+    let results = coordinator.run(dns_checks, &|dns_check| dns_check.run(logger));
+
+    let mut nb_skipped_checks = 0;
+    for result in results {
+        match result {
+            Some(result) => result?,
+            None => nb_skipped_checks += 1,
+        }
+    }
+
+    if nb_skipped_checks > 0 {
+        logger.log(EngineEvent::Warning(
+            event_details,
+            EventMessage::new_from_safe(format!(
+                "DNS checks budget of {} seconds spent, {} out of {} domain checks have been skipped. Note: this is not critical.",
+                dns_check.budget_seconds, nb_skipped_checks, nb_dns_checks
+            )),
+        ));
+    }
+
+    Ok(())
+}
+
+const ENVIRONMENT_RESOURCE_QUOTA_NAME: &str = "qovery-environment-quota";
+
+/// Checks the environment services fit in the quota, then creates or updates it on the environment namespace.
+fn apply_environment_resource_quota(
+    kubernetes: &dyn Kubernetes,
+    environment: &Environment,
+    resource_quota: &ResourceQuota,
+    event_details: EventDetails,
+) -> Result<(), EngineError> {
+    let violations = resource_quota_violations(resource_quota, &environment.resource_requests());
+    if !violations.is_empty() {
+        return Err(EngineError::new_environment_resource_quota_exceeded(event_details, violations));
+    }
+
+    let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+    let namespace = environment.namespace();
+    kubectl_exec_create_namespace(
+        kubernetes_config_file_path.as_str(),
+        namespace,
+        None,
+        kubernetes.cloud_provider().credentials_environment_variables(),
+    )
+    .map_err(|e| EngineError::new_k8s_create_namespace(event_details.clone(), namespace.to_string(), e))?;
+
+    kubectl_exec_apply_resource_quota(
+        kubernetes_config_file_path.as_str(),
+        namespace,
+        ENVIRONMENT_RESOURCE_QUOTA_NAME,
+        &[
+            ("requests.cpu", resource_quota.cpu.clone()),
+            ("requests.memory", format!("{}Mi", resource_quota.memory_in_mib)),
+            ("pods", resource_quota.pods.to_string()),
+        ],
+        kubernetes.cloud_provider().credentials_environment_variables(),
+    )
+    .map_err(|e| EngineError::new_k8s_cannot_apply_resource_quota(event_details, namespace.to_string(), e))
+}
+
+/// Removes the quota a previous deployment may have set on the environment namespace.
+fn delete_environment_resource_quota(
+    kubernetes: &dyn Kubernetes,
+    environment: &Environment,
+    event_details: EventDetails,
+) -> Result<(), EngineError> {
+    let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+    let namespace = environment.namespace();
+
+    kubectl_exec_delete_resource_quota(
+        kubernetes_config_file_path.as_str(),
+        namespace,
+        ENVIRONMENT_RESOURCE_QUOTA_NAME,
+        kubernetes.cloud_provider().credentials_environment_variables(),
+    )
+    .map_err(|e| EngineError::new_k8s_cannot_apply_resource_quota(event_details, namespace.to_string(), e))
+}
+
 /// common function to react to an error when a environment deployment goes wrong
 pub fn deploy_environment_error(
     kubernetes: &dyn Kubernetes,
@@ -762,15 +900,77 @@ pub fn delete_environment(
     }
 
     // do not catch potential error - to confirm
-    let _ = kubectl::kubectl_exec_delete_namespace(
-        kubeconfig,
-        environment.namespace(),
-        kubernetes.cloud_provider().credentials_environment_variables(),
-    );
+    let namespace = environment.namespace();
+    match delete_namespace_if_empty(
+        || {
+            kubectl_exec_delete_pvcs(
+                &kubeconfig,
+                namespace,
+                &format!("envId={}", environment.id),
+                kubernetes.cloud_provider().credentials_environment_variables(),
+            )
+        },
+        || {
+            kubectl_exec_get_namespace_remaining_resources(
+                &kubeconfig,
+                namespace,
+                &format!("envId!={}", environment.id),
+                kubernetes.cloud_provider().credentials_environment_variables(),
+            )
+        },
+        || {
+            kubectl::kubectl_exec_delete_namespace(
+                &kubeconfig,
+                namespace,
+                kubernetes.cloud_provider().credentials_environment_variables(),
+            )
+        },
+    ) {
+        Ok(true) => {}
+        Ok(false) => logger.log(EngineEvent::Info(
+            event_details,
+            EventMessage::new_from_safe(format!(
+                "Namespace `{}` still contains resources, it will not be deleted.",
+                namespace
+            )),
+        )),
+        Err(e) => logger.log(EngineEvent::Warning(
+            event_details,
+            EventMessage::new(
+                format!("Error while deleting namespace `{}`.", namespace),
+                Some(e.message(ErrorMessageVerbosity::FullDetailsWithoutEnvVars)),
+            ),
+        )),
+    }
 
     Ok(())
 }
 
+/// Deletes an environment namespace only when no resource remains in it, as it may be shared. Only the namespace
+/// itself is deleted, cluster scoped resources (ClusterIssuer, CRDs...) are never touched.
+/// Volumes of the environment are deleted first, and its resources are not counted as remaining ones since their
+/// deletion is asynchronous: they may still be terminating, namespace deletion will then finish the job.
+/// Returns whether the namespace has been deleted.
+fn delete_namespace_if_empty<V, G, D>(
+    delete_environment_volumes: V,
+    get_remaining_foreign_resources: G,
+    delete_namespace: D,
+) -> Result<bool, CommandError>
+where
+    V: FnOnce() -> Result<(), CommandError>,
+    G: FnOnce() -> Result<Vec<String>, CommandError>,
+    D: FnOnce() -> Result<(), CommandError>,
+{
+    delete_environment_volumes()?;
+
+    if !get_remaining_foreign_resources()?.is_empty() {
+        return Ok(false);
+    }
+
+    delete_namespace()?;
+    Ok(true)
+}
+
 pub fn uninstall_cert_manager<P>(
     kubernetes_config: P,
     envs: Vec<(&str, &str)>,
@@ -1403,6 +1603,25 @@ pub fn validate_k8s_required_cpu_and_burstable(
     })
 }
 
+pub fn validate_k8s_required_memory(
+    service_type: &ServiceType,
+    total_ram_in_mib: u32,
+    event_details: EventDetails,
+) -> Result<(), EngineError> {
+    let minimum_ram_in_mib = service_type.minimum_ram_in_mib();
+
+    if total_ram_in_mib < minimum_ram_in_mib {
+        return Err(EngineError::new_k8s_validate_required_memory_error(
+            event_details,
+            service_type.name(),
+            total_ram_in_mib,
+            minimum_ram_in_mib,
+        ));
+    }
+
+    Ok(())
+}
+
 pub fn convert_k8s_cpu_value_to_f32(value: String) -> Result<f32, CommandError> {
     if value.ends_with('m') {
         let mut value_number_string = value;
@@ -1429,16 +1648,189 @@ pub fn convert_k8s_cpu_value_to_f32(value: String) -> Result<f32, CommandError>
     }
 }
 
+#[cfg(test)]
+pub mod test_utils {
+    use crate::cloud_provider::aws::regions::AwsZones;
+    use crate::cloud_provider::environment::Environment;
+    use crate::cloud_provider::kubernetes::{Kind, Kubernetes, KubernetesUpgradeStatus};
+    use crate::cloud_provider::CloudProvider;
+    use crate::dns_provider::DnsProvider;
+    use crate::errors::EngineError;
+    use crate::io_models::{Context, Listen, Listener, Listeners};
+    use crate::logger::{Logger, StdIoLogger};
+    use crate::object_storage::ObjectStorage;
+
+    /// Cluster only answering what services and transactions need in unit tests, anything requiring a real cluster
+    /// panics.
+    pub struct TestKubernetes {
+        context: Context,
+        kind: Kind,
+        listeners: Listeners,
+        logger: StdIoLogger,
+    }
+
+    impl TestKubernetes {
+        pub fn new(context: Context, kind: Kind) -> Self {
+            TestKubernetes {
+                context,
+                kind,
+                listeners: vec![],
+                logger: StdIoLogger::new(),
+            }
+        }
+    }
+
+    impl Listen for TestKubernetes {
+        fn listeners(&self) -> &Listeners {
+            &self.listeners
+        }
+
+        fn add_listener(&mut self, listener: Listener) {
+            self.listeners.push(listener);
+        }
+    }
+
+    impl Kubernetes for TestKubernetes {
+        fn context(&self) -> &Context {
+            &self.context
+        }
+
+        fn kind(&self) -> Kind {
+            self.kind.clone()
+        }
+
+        fn id(&self) -> &str {
+            "cluster-id"
+        }
+
+        fn name(&self) -> &str {
+            "test-cluster"
+        }
+
+        fn version(&self) -> &str {
+            "1.22"
+        }
+
+        fn region(&self) -> String {
+            match &self.kind {
+                Kind::Eks => "us-east-2",
+                Kind::Doks => "fra1",
+                Kind::ScwKapsule => "fr-par",
+            }
+            .to_string()
+        }
+
+        fn zone(&self) -> &str {
+            ""
+        }
+
+        fn aws_zones(&self) -> Option<Vec<AwsZones>> {
+            None
+        }
+
+        fn cloud_provider(&self) -> &dyn CloudProvider {
+            unimplemented!()
+        }
+
+        fn dns_provider(&self) -> &dyn DnsProvider {
+            unimplemented!()
+        }
+
+        fn logger(&self) -> &dyn Logger {
+            &self.logger
+        }
+
+        fn config_file_store(&self) -> &dyn ObjectStorage {
+            unimplemented!()
+        }
+
+        fn is_valid(&self) -> Result<(), EngineError> {
+            Ok(())
+        }
+
+        fn on_create(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_create_error(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn upgrade_with_status(&self, _kubernetes_upgrade_status: KubernetesUpgradeStatus) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_upgrade(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_upgrade_error(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_downgrade(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_downgrade_error(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_pause(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_pause_error(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_delete(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn on_delete_error(&self) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn deploy_environment(&self, _environment: &Environment) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn deploy_environment_error(&self, _environment: &Environment) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn pause_environment(&self, _environment: &Environment) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn pause_environment_error(&self, _environment: &Environment) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn delete_environment(&self, _environment: &Environment) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+
+        fn delete_environment_error(&self, _environment: &Environment) -> Result<(), EngineError> {
+            unimplemented!()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::cloud_provider::Kind::Aws;
 
     use crate::cloud_provider::kubernetes::{
         check_kubernetes_upgrade_status, compare_kubernetes_cluster_versions_for_upgrade, convert_k8s_cpu_value_to_f32,
-        validate_k8s_required_cpu_and_burstable, KubernetesNodesType,
+        delete_namespace_if_empty, validate_k8s_required_cpu_and_burstable, validate_k8s_required_memory,
+        KubernetesNodesType,
     };
     use crate::cloud_provider::models::CpuLimits;
+    use crate::cloud_provider::service::{DatabaseType, ServiceType};
     use crate::cmd::structs::{KubernetesList, KubernetesNode, KubernetesVersion};
+    use crate::errors::CommandError;
     use crate::events::{EventDetails, InfrastructureStep, Stage, Transmitter};
     use crate::io_models::{ListenersHelper, QoveryIdentifier};
     use crate::logger::StdIoLogger;
@@ -2078,4 +2470,87 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    pub fn test_required_memory() {
+        let event_details = EventDetails::new(
+            Some(Aws),
+            QoveryIdentifier::new_from_long_id("organization_id".to_string()),
+            QoveryIdentifier::new_from_long_id("cluster_id".to_string()),
+            QoveryIdentifier::new_from_long_id("execution_id".to_string()),
+            Some("region_fake".to_string()),
+            Stage::Infrastructure(InfrastructureStep::LoadConfiguration),
+            Transmitter::Kubernetes("cluster_id".to_string(), "cluster_id-name".to_string()),
+        );
+        let postgresql = ServiceType::Database(DatabaseType::PostgreSQL);
+
+        // under minimum
+        assert!(validate_k8s_required_memory(&postgresql, 1, event_details.clone()).is_err());
+        assert!(validate_k8s_required_memory(&postgresql, 255, event_details.clone()).is_err());
+        assert!(validate_k8s_required_memory(&ServiceType::Application, 8, event_details.clone()).is_err());
+
+        // valid
+        assert!(validate_k8s_required_memory(&postgresql, 256, event_details.clone()).is_ok());
+        assert!(validate_k8s_required_memory(&postgresql, 512, event_details.clone()).is_ok());
+        assert!(validate_k8s_required_memory(&ServiceType::Application, 256, event_details.clone()).is_ok());
+        // router memory is hardcoded to 1MiB
+        assert!(validate_k8s_required_memory(&ServiceType::Router, 1, event_details).is_ok());
+    }
+
+    #[test]
+    fn test_namespace_is_deleted_only_when_empty() {
+        // execute & verify: resources of something else than the environment remain, namespace is kept
+        let mut delete_calls = 0;
+        let mut delete_volumes_calls = 0;
+        let result = delete_namespace_if_empty(
+            || {
+                delete_volumes_calls += 1;
+                Ok(())
+            },
+            || Ok(vec!["statefulset.apps/postgresql-z1234abc".to_string()]),
+            || {
+                delete_calls += 1;
+                Ok(())
+            },
+        );
+        assert_eq!(Ok(false), result);
+        assert_eq!(0, delete_calls);
+        assert_eq!(1, delete_volumes_calls);
+
+        // execute & verify: empty namespace is deleted
+        let result = delete_namespace_if_empty(
+            || Ok(()),
+            || Ok(vec![]),
+            || {
+                delete_calls += 1;
+                Ok(())
+            },
+        );
+        assert_eq!(Ok(true), result);
+        assert_eq!(1, delete_calls);
+
+        // execute & verify: namespace is kept when its content can't be listed
+        let result = delete_namespace_if_empty(
+            || Ok(()),
+            || Err(CommandError::new_from_safe_message("cannot list resources".to_string())),
+            || {
+                delete_calls += 1;
+                Ok(())
+            },
+        );
+        assert!(result.is_err());
+        assert_eq!(1, delete_calls);
+
+        // execute & verify: namespace is kept when volumes can't be deleted
+        let result = delete_namespace_if_empty(
+            || Err(CommandError::new_from_safe_message("cannot delete volumes".to_string())),
+            || Ok(vec![]),
+            || {
+                delete_calls += 1;
+                Ok(())
+            },
+        );
+        assert!(result.is_err());
+        assert_eq!(1, delete_calls);
+    }
 }
@@ -54,6 +54,17 @@ pub enum Kind {
     Scw,
 }
 
+impl Kind {
+    /// Short name of the provider, as expected by Qovery components (engine, agents...).
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Kind::Aws => "aws",
+            Kind::Do => "do",
+            Kind::Scw => "scw",
+        }
+    }
+}
+
 impl Display for Kind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
@@ -1,3 +1,4 @@
+use crate::io_models::ReclaimPolicy;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Eq, PartialEq, Hash)]
@@ -20,6 +21,7 @@ pub struct Storage<T> {
     pub size_in_gib: u16,
     pub mount_point: String,
     pub snapshot_retention_in_days: u16,
+    pub reclaim_policy: ReclaimPolicy,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -30,6 +32,7 @@ pub struct StorageDataTemplate {
     pub size_in_gib: u16,
     pub mount_point: String,
     pub snapshot_retention_in_days: u16,
+    pub reclaim_policy: ReclaimPolicy,
 }
 
 pub struct CustomDomain {
@@ -49,7 +52,7 @@ pub struct Route {
     pub application_name: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct RouteDataTemplate {
     pub path: String,
     pub application_name: String,
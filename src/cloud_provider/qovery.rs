@@ -1,7 +1,18 @@
 use crate::errors::CommandError;
 use reqwest::header;
+use reqwest::StatusCode;
+use retry::delay::{jitter, Exponential};
+use retry::{Error as RetryError, OperationResult};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const QOVERY_API_MAX_ATTEMPTS: usize = 5;
+const QOVERY_API_INITIAL_BACKOFF_IN_MILLIS: u64 = 1000;
+const QOVERY_API_BACKOFF_FACTOR: f64 = 2.0;
+const QOVERY_API_TIMEOUT_IN_SECONDS: u64 = 30;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QoveryAgent {
@@ -37,15 +48,67 @@ pub enum QoveryAppName {
     ClusterAgent,
 }
 
+/// Where to read the token used to authenticate against Qovery API.
+/// Resolution is lazy, so the token is only read when the API is actually called.
+#[derive(Clone, PartialEq)]
+pub enum TokenSource {
+    Literal(String),
+    EnvVar(String),
+    FilePath(PathBuf),
+}
+
+// Literal token is a secret, it must never end up in logs
+impl fmt::Debug for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Literal(_) => f.debug_tuple("Literal").field(&"********").finish(),
+            TokenSource::EnvVar(name) => f.debug_tuple("EnvVar").field(name).finish(),
+            TokenSource::FilePath(path) => f.debug_tuple("FilePath").field(path).finish(),
+        }
+    }
+}
+
+impl TokenSource {
+    pub fn resolve(&self) -> Result<String, CommandError> {
+        match self {
+            TokenSource::Literal(token) => Ok(token.to_string()),
+            TokenSource::EnvVar(name) => std::env::var(name).map_err(|e| {
+                CommandError::new(
+                    format!("Cannot read Qovery API token from environment variable `{}`.", name),
+                    Some(e.to_string()),
+                    None,
+                )
+            }),
+            TokenSource::FilePath(path) => std::fs::read_to_string(path)
+                .map(|token| token.trim().to_string())
+                .map_err(|e| {
+                    CommandError::new(
+                        format!("Cannot read Qovery API token from file `{}`.", path.display()),
+                        Some(e.to_string()),
+                        None,
+                    )
+                }),
+        }
+    }
+}
+
 pub fn get_qovery_app_version<T: DeserializeOwned>(
     qovery_app_type: QoveryAppName,
-    token: &str,
+    token: &TokenSource,
     api_fqdn: &str,
     cluster_id: &str,
 ) -> Result<T, CommandError> {
+    let token = token.resolve()?;
     let mut headers = header::HeaderMap::new();
     headers.insert("Content-Type", "application/json".parse().unwrap());
-    headers.insert("X-Qovery-Signature", token.parse().unwrap());
+    headers.insert(
+        "X-Qovery-Signature",
+        token.parse().map_err(|_| {
+            CommandError::new_from_safe_message(
+                "Qovery API token contains characters which are not allowed in an HTTP header.".to_string(),
+            )
+        })?,
+    );
 
     let app_type = match qovery_app_type {
         QoveryAppName::Agent => "agent",
@@ -60,12 +123,214 @@ pub fn get_qovery_app_version<T: DeserializeOwned>(
     );
 
     let message_safe = format!("Error while trying to get `{}` version.", app_type);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(QOVERY_API_TIMEOUT_IN_SECONDS))
+        .build()
+        .map_err(|e| CommandError::new(message_safe.clone(), Some(e.to_string()), None))?;
+
+    call_qovery_api_with_retry(qovery_api_retry_delays(), message_safe, || {
+        let response =
+            client
+                .get(&url)
+                .headers(headers.clone())
+                .send()
+                .map_err(|e| match e.is_timeout() || e.is_connect() {
+                    true => QoveryApiCallError::Retryable(e.to_string()),
+                    false => QoveryApiCallError::Fatal(e.to_string()),
+                })?;
+
+        let status = response.status();
+        if status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT {
+            return Err(QoveryApiCallError::Retryable(format!(
+                "Qovery API answered with status {}",
+                status
+            )));
+        }
+        if !status.is_success() {
+            return Err(QoveryApiCallError::Fatal(format!("Qovery API answered with status {}", status)));
+        }
+
+        response
+            .json::<T>()
+            .map_err(|e| QoveryApiCallError::Fatal(e.to_string()))
+    })
+}
+
+/// Outcome of a failed call to Qovery API, telling whether the call is worth retrying.
+#[derive(Debug, Clone, PartialEq)]
+enum QoveryApiCallError {
+    /// Retryable: transient failure (5xx, timeout, connection error).
+    Retryable(String),
+    /// Fatal: failure which won't be fixed by retrying (4xx, invalid payload, etc.).
+    Fatal(String),
+}
+
+/// Jittered exponential backoff used between two Qovery API calls (up to 1s, 2s, 4s then 8s).
+fn qovery_api_retry_delays() -> impl Iterator<Item = Duration> {
+    Exponential::from_millis_with_factor(QOVERY_API_INITIAL_BACKOFF_IN_MILLIS, QOVERY_API_BACKOFF_FACTOR)
+        .map(jitter)
+        .take(QOVERY_API_MAX_ATTEMPTS - 1)
+}
+
+/// Calls `operation` until it succeeds, a fatal error occurs or `delays` are exhausted.
+fn call_qovery_api_with_retry<T, D, F>(delays: D, message_safe: String, mut operation: F) -> Result<T, CommandError>
+where
+    D: IntoIterator<Item = Duration>,
+    F: FnMut() -> Result<T, QoveryApiCallError>,
+{
+    let mut attempts = 0;
+    let result = retry::retry(delays, || {
+        attempts += 1;
+        match operation() {
+            Ok(x) => OperationResult::Ok(x),
+            Err(QoveryApiCallError::Retryable(e)) => OperationResult::Retry(e),
+            Err(QoveryApiCallError::Fatal(e)) => OperationResult::Err(e),
+        }
+    });
+
+    match result {
+        Ok(x) => Ok(x),
+        Err(RetryError::Operation { error, .. }) => Err(CommandError::new(
+            format!("{} Gave up after {} attempt(s).", message_safe, attempts),
+            Some(error),
+            None,
+        )),
+        Err(RetryError::Internal(e)) => Err(CommandError::new(message_safe, Some(e), None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::qovery::{
+        call_qovery_api_with_retry, qovery_api_retry_delays, QoveryApiCallError, TokenSource,
+    };
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use tempfile::NamedTempFile;
+
+    fn no_delays(count: usize) -> Vec<Duration> {
+        vec![Duration::from_millis(0); count]
+    }
+
+    #[test]
+    fn test_qovery_api_call_succeeds_after_transient_failures() {
+        // setup:
+        let mut calls = 0;
+
+        // execute:
+        let result = call_qovery_api_with_retry(no_delays(4), "error".to_string(), || {
+            calls += 1;
+            match calls {
+                1 | 2 => Err(QoveryApiCallError::Retryable("503 Service Unavailable".to_string())),
+                _ => Ok("1.0.0".to_string()),
+            }
+        });
+
+        // verify:
+        assert_eq!(Ok("1.0.0".to_string()), result);
+        assert_eq!(3, calls);
+    }
+
+    #[test]
+    fn test_qovery_api_call_does_not_retry_on_fatal_error() {
+        // setup:
+        let mut calls = 0;
+
+        // execute:
+        let result: Result<String, _> = call_qovery_api_with_retry(no_delays(4), "error".to_string(), || {
+            calls += 1;
+            Err(QoveryApiCallError::Fatal("404 Not Found".to_string()))
+        });
+
+        // verify:
+        assert!(result.is_err());
+        assert_eq!(1, calls);
+    }
+
+    #[test]
+    fn test_qovery_api_call_gives_up_when_attempts_are_exhausted() {
+        // setup:
+        let mut calls = 0;
+
+        // execute:
+        let result: Result<String, _> = call_qovery_api_with_retry(no_delays(2), "error".to_string(), || {
+            calls += 1;
+            Err(QoveryApiCallError::Retryable("502 Bad Gateway".to_string()))
+        });
+
+        // verify:
+        assert_eq!(3, calls);
+        let err = result.unwrap_err();
+        assert_eq!("error Gave up after 3 attempt(s).", err.message_safe());
+        assert_eq!(Some("502 Bad Gateway".to_string()), err.message_raw());
+    }
+
+    #[test]
+    fn test_qovery_api_retry_delays_are_bounded() {
+        // execute:
+        let delays = qovery_api_retry_delays().collect::<Vec<_>>();
+
+        // verify: jitter only shortens the exponential delays
+        assert_eq!(4, delays.len());
+        for (delay, max_delay_in_millis) in delays.iter().zip(&[1000, 2000, 4000, 8000]) {
+            assert!(*delay <= Duration::from_millis(*max_delay_in_millis), "{:?} is too long", delay);
+        }
+    }
+
+    #[test]
+    fn test_token_source_literal_resolves() {
+        // setup:
+        let source = TokenSource::Literal("my-token".to_string());
+
+        // execute & verify:
+        assert_eq!(Ok("my-token".to_string()), source.resolve());
+        assert_eq!("Literal(\"********\")", format!("{:?}", source));
+        assert_eq!(
+            "EnvVar(\"QOVERY_API_TOKEN\")",
+            format!("{:?}", TokenSource::EnvVar("QOVERY_API_TOKEN".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_token_source_env_var_resolves() {
+        // setup:
+        let var_name = "QOVERY_ENGINE_TEST_TOKEN_SOURCE_ENV_VAR";
+        std::env::set_var(var_name, "my-env-token");
+        let source = TokenSource::EnvVar(var_name.to_string());
+
+        // execute:
+        let result = source.resolve();
+        std::env::remove_var(var_name);
+
+        // verify:
+        assert_eq!(Ok("my-env-token".to_string()), result);
+    }
+
+    #[test]
+    fn test_token_source_file_path_resolves() {
+        // setup:
+        let mut file = NamedTempFile::new().expect("cannot create temp file");
+        writeln!(file, "my-file-token").expect("cannot write temp file");
+        let source = TokenSource::FilePath(file.path().to_path_buf());
+
+        // execute & verify:
+        assert_eq!(Ok("my-file-token".to_string()), source.resolve());
+    }
+
+    #[test]
+    fn test_token_source_missing_file_fails() {
+        // setup:
+        let source = TokenSource::FilePath(PathBuf::from("/this/path/does/not/exist/token"));
+
+        // execute:
+        let result = source.resolve();
 
-    match reqwest::blocking::Client::new().get(&url).headers(headers).send() {
-        Ok(x) => match x.json::<T>() {
-            Ok(qa) => Ok(qa),
-            Err(e) => Err(CommandError::new(message_safe, Some(e.to_string()), None)),
-        },
-        Err(e) => Err(CommandError::new(message_safe, Some(e.to_string()), None)),
+        // verify:
+        let err = result.unwrap_err();
+        assert_eq!(
+            "Cannot read Qovery API token from file `/this/path/does/not/exist/token`.",
+            err.message_safe()
+        );
     }
 }
@@ -3,8 +3,11 @@ use crate::cloud_provider::helm::{
     ChartSetValue, ChartValuesGenerated, ClusterAgentContext, CommonChart, CoreDNSConfigChart, HelmChart,
     HelmChartNamespaces, PrometheusOperatorConfigChart, ShellAgentContext,
 };
-use crate::cloud_provider::qovery::{get_qovery_app_version, EngineLocation, QoveryAgent, QoveryAppName, QoveryEngine};
+use crate::cloud_provider::qovery::{
+    get_qovery_app_version, EngineLocation, QoveryAgent, QoveryAppName, QoveryEngine, TokenSource,
+};
 use crate::cloud_provider::scaleway::kubernetes::KapsuleOptions;
+use crate::cloud_provider::utilities::validate_non_nil_uuid;
 use crate::errors::CommandError;
 use crate::models::scaleway::{ScwRegion, ScwZone};
 use semver::Version;
@@ -73,8 +76,11 @@ impl ChartsConfigPrerequisites {
         cloudflare_api_token: String,
         disable_pleco: bool,
         infra_options: KapsuleOptions,
-    ) -> Self {
-        ChartsConfigPrerequisites {
+    ) -> Result<Self, CommandError> {
+        validate_non_nil_uuid("organization_long_id", &organization_long_id)?;
+        validate_non_nil_uuid("cluster_long_id", &cluster_long_id)?;
+
+        Ok(ChartsConfigPrerequisites {
             organization_id,
             organization_long_id,
             cluster_id,
@@ -100,7 +106,7 @@ impl ChartsConfigPrerequisites {
             cloudflare_api_token,
             disable_pleco,
             infra_options,
-        }
+        })
     }
 }
 
@@ -691,7 +697,12 @@ datasources:
 
     let qovery_agent_version: QoveryAgent = get_qovery_app_version(
         QoveryAppName::Agent,
-        &chart_config_prerequisites.infra_options.agent_version_controller_token,
+        &TokenSource::Literal(
+            chart_config_prerequisites
+                .infra_options
+                .agent_version_controller_token
+                .clone(),
+        ),
         &chart_config_prerequisites.infra_options.qovery_api_url,
         &chart_config_prerequisites.cluster_id,
     )?;
@@ -763,7 +774,12 @@ datasources:
 
     let qovery_engine_version: QoveryEngine = get_qovery_app_version(
         QoveryAppName::Engine,
-        &chart_config_prerequisites.infra_options.engine_version_controller_token,
+        &TokenSource::Literal(
+            chart_config_prerequisites
+                .infra_options
+                .engine_version_controller_token
+                .clone(),
+        ),
         &chart_config_prerequisites.infra_options.qovery_api_url,
         &chart_config_prerequisites.cluster_id,
     )?;
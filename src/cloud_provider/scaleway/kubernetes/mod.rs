@@ -3,7 +3,9 @@ pub mod node;
 
 use crate::cloud_provider::aws::regions::AwsZones;
 use crate::cloud_provider::environment::Environment;
-use crate::cloud_provider::helm::{deploy_charts_levels, ChartInfo};
+use crate::cloud_provider::helm::{
+    deploy_charts_levels, get_charts_levels_estimated_duration, ChartInfo, ChartsDeploymentOptions,
+};
 use crate::cloud_provider::kubernetes::{
     is_kubernetes_upgrade_required, send_progress_on_long_task, uninstall_cert_manager, Kind, Kubernetes,
     KubernetesUpgradeStatus, ProviderOptions,
@@ -963,7 +965,8 @@ impl Kapsule {
             self.dns_provider.token().to_string(),
             self.context.disable_pleco(),
             self.options.clone(),
-        );
+        )
+        .map_err(|e| EngineError::new_helm_charts_setup_error(event_details.clone(), e))?;
 
         self.logger().log(EngineEvent::Info(
             event_details.clone(),
@@ -978,11 +981,22 @@ impl Kapsule {
         )
         .map_err(|e| EngineError::new_helm_charts_setup_error(event_details.clone(), e))?;
 
+        let message = format!(
+            "Deploying charts, estimated duration is up to {} minutes",
+            get_charts_levels_estimated_duration(&helm_charts_to_deploy).as_secs() / 60
+        );
+        self.send_to_customer(&message, &listeners_helper);
+        self.logger()
+            .log(EngineEvent::Info(event_details.clone(), EventMessage::new_from_safe(message)));
+
         deploy_charts_levels(
             kubeconfig_path,
             &credentials_environment_variables,
             helm_charts_to_deploy,
-            self.context.is_dry_run_deploy(),
+            &ChartsDeploymentOptions {
+                dry_run: self.context.is_dry_run_deploy(),
+                ..Default::default()
+            },
         )
         .map_err(|e| EngineError::new_helm_charts_deploy_error(event_details.clone(), e))
     }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::net::TcpStream;
 use std::str::FromStr;
 use std::sync::mpsc;
@@ -5,19 +6,23 @@ use std::sync::mpsc::TryRecvError;
 use std::thread;
 use std::time::Duration;
 
+use serde::Serialize;
 use tera::Context as TeraContext;
 use uuid::Uuid;
 
 use crate::cloud_provider::environment::Environment;
 use crate::cloud_provider::helm::ChartInfo;
 use crate::cloud_provider::kubernetes::Kubernetes;
-use crate::cloud_provider::utilities::check_domain_for;
+use crate::cloud_provider::utilities::{check_domain_for, validate_cidr, DnsCheck, DnsCheckKind};
 use crate::cloud_provider::DeploymentTarget;
 use crate::cmd;
 use crate::cmd::helm;
 use crate::cmd::kubectl::ScalingKind::Statefulset;
-use crate::cmd::kubectl::{kubectl_exec_delete_secret, kubectl_exec_scale_replicas_by_selector, ScalingKind};
-use crate::cmd::structs::LabelsContent;
+use crate::cmd::kubectl::{
+    kubectl_exec_delete_secret, kubectl_exec_get_pods, kubectl_exec_logs, kubectl_exec_scale_replicas_by_selector,
+    ScalingKind,
+};
+use crate::cmd::structs::{KubernetesList, KubernetesPod, KubernetesPodStatusReason, LabelsContent};
 use crate::errors::{CommandError, EngineError};
 use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage, ToTransmitter};
 use crate::io_models::ProgressLevel::Info;
@@ -31,6 +36,10 @@ use crate::models::types::VersionsNumber;
 pub trait Service: ToTransmitter {
     fn context(&self) -> &Context;
     fn service_type(&self) -> ServiceType;
+    /// Canonical kind of the service, to be used as label in logs, events and metrics.
+    fn kind_label(&self) -> &'static str {
+        self.service_type().kind_label()
+    }
     fn id(&self) -> &str;
     fn long_id(&self) -> &Uuid;
     fn name(&self) -> &str;
@@ -48,10 +57,12 @@ pub trait Service: ToTransmitter {
             ServiceType::Router => "routers",
         };
 
-        crate::fs::workspace_directory(
+        service_workspace_directory(
             self.context().workspace_root_dir(),
             self.context().execution_id(),
-            format!("{}/{}", dir_root, self.name()),
+            dir_root,
+            self.id(),
+            self.name(),
         )
         .unwrap()
     }
@@ -86,6 +97,23 @@ pub trait Service: ToTransmitter {
         }
     }
     fn tera_context(&self, target: &DeploymentTarget) -> Result<TeraContext, EngineError>;
+    /// Dumps the tera context, secrets redacted, into the workspace directory when enabled by
+    /// `QOVERY_ENGINE_DUMP_TERA_CONTEXT`. It's a debugging helper, so a failure never fails the deployment.
+    fn dump_tera_context(&self, context: &TeraContext, event_details: EventDetails) {
+        if !crate::template::is_tera_context_dump_enabled() {
+            return;
+        }
+
+        match crate::template::dump_tera_context(context, self.workspace_directory()) {
+            Ok(dest) => self.logger().log(EngineEvent::Debug(
+                event_details,
+                EventMessage::new_from_safe(format!("Tera context dumped into `{}`", dest)),
+            )),
+            Err(e) => self
+                .logger()
+                .log(EngineEvent::Warning(event_details, EventMessage::from(e))),
+        }
+    }
     // used to retrieve logs by using Kubernetes labels (selector)
     fn logger(&self) -> &dyn Logger;
     fn selector(&self) -> Option<String>;
@@ -106,6 +134,10 @@ pub trait Service: ToTransmitter {
         TcpStream::connect(format!("{}:{}", ip, private_port)).is_ok()
     }
 
+    /// Long ids of the services which have to be deployed before this one.
+    fn depends_on(&self) -> Vec<String> {
+        vec![]
+    }
     fn progress_scope(&self) -> ProgressScope {
         let id = self.id().to_string();
 
@@ -170,11 +202,30 @@ pub trait RouterService: StatelessService + Listen + Helm {
             self.domains(),
             self.id(),
             self.context().execution_id(),
+            &self.context().dns_check_tld_budgets(),
             event_details,
             logger,
         )?;
         Ok(())
     }
+
+    /// Domain checks of the router, run by its check action or by the environment DNS check coordinator.
+    fn dns_checks(&self, event_details: EventDetails) -> Vec<DnsCheck> {
+        self.domains()
+            .into_iter()
+            .map(|domain| DnsCheck {
+                kind: DnsCheckKind::Resolution {
+                    domain: domain.to_string(),
+                },
+                scope: self.progress_scope(),
+                listeners: self.listeners(),
+                service_id: self.id().to_string(),
+                execution_id: self.context().execution_id().to_string(),
+                tld_budgets: self.context().dns_check_tld_budgets(),
+                event_details: event_details.clone(),
+            })
+            .collect()
+    }
 }
 
 pub trait DatabaseService: StatefulService {
@@ -191,6 +242,7 @@ pub trait DatabaseService: StatefulService {
                 domains,
                 self.id(),
                 self.context().execution_id(),
+                &self.context().dns_check_tld_budgets(),
                 event_details,
                 logger,
             )?;
@@ -238,7 +290,7 @@ pub enum Action {
     Nothing,
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Eq, PartialEq, Serialize)]
 pub struct DatabaseOptions {
     pub login: String,
     pub password: String,
@@ -251,6 +303,54 @@ pub struct DatabaseOptions {
     pub activate_high_availability: bool,
     pub activate_backups: bool,
     pub publicly_accessible: bool,
+    /// allowed_cidrs: CIDR blocks allowed to reach a publicly accessible database.
+    pub allowed_cidrs: Vec<String>,
+    /// storage_class_name: storage class of self-hosted database volumes, cluster default one if not set.
+    pub storage_class_name: Option<String>,
+    /// disk_iops: provisioned disk IOPS, only for disk types supporting it.
+    pub disk_iops: Option<u32>,
+    /// deletion_protection: refuse to delete the database, unless the deletion is forced.
+    pub deletion_protection: bool,
+    /// region and zone: location of a managed database, the cluster one if not set.
+    pub region: Option<String>,
+    pub zone: Option<String>,
+}
+
+impl DatabaseOptions {
+    /// Validates allowed CIDRs syntax, a publicly accessible database requires at least one allowed CIDR.
+    pub fn validate_allowed_cidrs(&self) -> Result<(), CommandError> {
+        if self.publicly_accessible && self.allowed_cidrs.is_empty() {
+            return Err(CommandError::new_from_safe_message(
+                "A publicly accessible database requires at least one allowed CIDR.".to_string(),
+            ));
+        }
+
+        for cidr in &self.allowed_cidrs {
+            validate_cidr(cidr)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns CIDRs allowed to reach the database, none (deny all) if the database isn't publicly accessible.
+    pub fn allowed_cidrs(&self) -> Vec<String> {
+        match self.publicly_accessible {
+            true => self.allowed_cidrs.clone(),
+            false => vec![],
+        }
+    }
+
+    /// Returns effective options as JSON, credentials being redacted so it can be shared safely.
+    pub fn to_sanitized_json(&self) -> String {
+        let mut options = serde_json::to_value(self).unwrap_or_default();
+        for secret_field in &["login", "password"] {
+            if let Some(value) = options.get_mut(*secret_field) {
+                *value = serde_json::Value::from("********");
+            }
+        }
+
+        options.to_string()
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -272,6 +372,9 @@ impl ToString for DatabaseType {
     }
 }
 
+/// All service kind labels, see `ServiceType::kind_label`.
+pub const SERVICE_KIND_LABELS: [&str; 3] = ["application", "router", "database"];
+
 #[derive(Eq, PartialEq)]
 pub enum ServiceType {
     Application,
@@ -287,6 +390,28 @@ impl ServiceType {
             ServiceType::Router => "Router".to_string(),
         }
     }
+
+    /// Returns the service kind label, shared by all services of the same kind whatever their sub type.
+    pub fn kind_label(&self) -> &'static str {
+        match self {
+            ServiceType::Application => "application",
+            ServiceType::Database(_) => "database",
+            ServiceType::Router => "router",
+        }
+    }
+
+    /// Returns the minimum memory a service needs to be scheduled and start properly, in MiB.
+    pub fn minimum_ram_in_mib(&self) -> u32 {
+        match self {
+            ServiceType::Application => 16,
+            ServiceType::Database(DatabaseType::PostgreSQL) => 256,
+            ServiceType::Database(DatabaseType::MongoDB) => 256,
+            ServiceType::Database(DatabaseType::MySQL) => 256,
+            ServiceType::Database(DatabaseType::Redis) => 64,
+            // router doesn't run its own pods
+            ServiceType::Router => 0,
+        }
+    }
 }
 
 impl<'a> ToString for ServiceType {
@@ -352,10 +477,40 @@ pub fn default_tera_context(
     }
 
     context.insert("version", &service.version());
+    insert_common_labels(&mut context, environment.organization_id.as_str(), kubernetes.id());
 
     context
 }
 
+/// Returns service's workspace directory, which is unique per execution and per service:
+/// names aren't unique (several services can share a name or a short id prefix), so the service id is part of the path.
+pub fn service_workspace_directory(
+    workspace_root_dir: &str,
+    execution_id: &str,
+    dir_root: &str,
+    service_id: &str,
+    service_name: &str,
+) -> Result<String, std::io::Error> {
+    crate::fs::workspace_directory(
+        workspace_root_dir,
+        execution_id,
+        format!("{}/{}-{}", dir_root, service_name, service_id),
+    )
+}
+
+/// Returns labels set on every Kubernetes resource created by the engine for a service (used for cost allocation).
+pub fn common_labels(organization_id: &str, cluster_id: &str) -> BTreeMap<String, String> {
+    let mut labels = BTreeMap::new();
+    labels.insert("qovery.com/organization".to_string(), organization_id.to_string());
+    labels.insert("qovery.com/cluster".to_string(), cluster_id.to_string());
+    labels
+}
+
+/// Inserts `common_labels` into the given tera context, so charts can add them to their resources.
+pub fn insert_common_labels(context: &mut TeraContext, organization_id: &str, cluster_id: &str) {
+    context.insert("common_labels", &common_labels(organization_id, cluster_id));
+}
+
 /// deploy a stateless service created by the user (E.g: App or External Service)
 /// the difference with `deploy_service(..)` is that this function provides the thrown error in case of failure
 pub fn deploy_user_stateless_service<T>(target: &DeploymentTarget, service: &T) -> Result<(), EngineError>
@@ -375,6 +530,7 @@ where
     let workspace_dir = service.workspace_directory();
     let tera_context = service.tera_context(target)?;
     let event_details = service.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
+    service.dump_tera_context(&tera_context, event_details.clone());
 
     if let Err(e) = crate::template::generate_and_copy_all_files_into_dir(
         service.helm_chart_dir(),
@@ -389,7 +545,6 @@ where
         ));
     }
 
-    let helm_release_name = service.helm_release_name();
     let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
 
     // define labels to add to namespace
@@ -419,18 +574,7 @@ where
         &kubernetes.cloud_provider().credentials_environment_variables(),
     )
     .map_err(|e| helm::to_engine_error(&event_details, e))?;
-    let chart = ChartInfo::new_from_custom_namespace(
-        helm_release_name,
-        workspace_dir.clone(),
-        environment.namespace().to_string(),
-        600_i64,
-        match service.service_type() {
-            ServiceType::Database(_) => vec![format!("{}/q-values.yaml", &workspace_dir)],
-            _ => vec![],
-        },
-        false,
-        service.selector(),
-    );
+    let chart = stateless_service_chart_info(service, environment, &workspace_dir);
 
     helm.upgrade(&chart, &[])
         .map_err(|e| helm::to_engine_error(&event_details, e))?;
@@ -454,15 +598,173 @@ where
 }
 
 /// do specific operations on a stateless service deployment error
-pub fn deploy_stateless_service_error<T>(_target: &DeploymentTarget, _service: &T) -> Result<(), EngineError>
+/// Returns the chart of a stateless service, once its files have been generated in its workspace directory.
+fn stateless_service_chart_info<T>(service: &T, environment: &Environment, workspace_dir: &str) -> ChartInfo
+where
+    T: Service + Helm,
+{
+    ChartInfo::new_from_custom_namespace(
+        service.helm_release_name(),
+        workspace_dir.to_string(),
+        environment.namespace().to_string(),
+        600_i64,
+        match service.service_type() {
+            ServiceType::Database(_) => vec![format!("{}/q-values.yaml", workspace_dir)],
+            _ => vec![],
+        },
+        false,
+        service.selector(),
+    )
+}
+
+/// Kubernetes manifest of a service, as it would be applied on the cluster.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenderedManifest {
+    /// Chart template the manifest comes from (ex: `q-application/templates/deployment.yaml`)
+    pub source: Option<String>,
+    pub content: String,
+}
+
+/// Returns the manifests a stateless service deployment would apply, rendered with `helm template` so the cluster
+/// is left untouched, to let users review them.
+pub fn render_manifests<T>(target: &DeploymentTarget, service: &T) -> Result<Vec<RenderedManifest>, EngineError>
 where
     T: Service + Helm,
 {
-    // Nothing to do as we sait --atomic on chart release that we do
-    // So helm rollback for us if a deployment fails
+    let kubernetes = target.kubernetes;
+    let workspace_dir = service.workspace_directory();
+    let tera_context = service.tera_context(target)?;
+    let event_details = service.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
+
+    if let Err(e) = crate::template::generate_and_copy_all_files_into_dir(
+        service.helm_chart_dir(),
+        workspace_dir.as_str(),
+        tera_context,
+    ) {
+        return Err(EngineError::new_cannot_copy_files_from_one_directory_to_another(
+            event_details,
+            service.helm_chart_dir(),
+            workspace_dir,
+            e,
+        ));
+    }
+
+    let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+    let helm = helm::Helm::new(
+        &kubernetes_config_file_path,
+        &kubernetes.cloud_provider().credentials_environment_variables(),
+    )
+    .map_err(|e| helm::to_engine_error(&event_details, e))?;
+    let chart = stateless_service_chart_info(service, target.environment, &workspace_dir);
+
+    template_manifests(&chart, |chart| helm.template(chart, &[])).map_err(|e| helm::to_engine_error(&event_details, e))
+}
+
+fn template_manifests<F>(chart: &ChartInfo, helm_template: F) -> Result<Vec<RenderedManifest>, helm::HelmError>
+where
+    F: FnOnce(&ChartInfo) -> Result<String, helm::HelmError>,
+{
+    let output = helm_template(chart)?;
+
+    let manifests = output
+        .split("\n---")
+        .map(|document| document.trim_start_matches("---").trim())
+        .filter(|document| {
+            document
+                .lines()
+                .any(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        })
+        .map(|document| RenderedManifest {
+            source: document
+                .lines()
+                .find_map(|line| line.strip_prefix("# Source: "))
+                .map(|source| source.trim().to_string()),
+            content: format!("{}\n", document),
+        })
+        .collect();
+
+    Ok(manifests)
+}
+
+pub fn deploy_stateless_service_error<T>(target: &DeploymentTarget, service: &T) -> Result<(), EngineError>
+where
+    T: Service + Helm,
+{
+    // Nothing to revert as we sait --atomic on chart release that we do
+    // So helm rollback for us if a deployment fails, but users have to know why their containers exited
+    let selector = match service.selector() {
+        Some(selector) => selector,
+        None => return Ok(()),
+    };
+    let kubernetes = target.kubernetes;
+    let namespace = target.environment.namespace();
+    let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+    let envs = kubernetes.cloud_provider().credentials_environment_variables();
+
+    let termination_details = get_container_termination_details(
+        || kubectl_exec_get_pods(&kubernetes_config_file_path, Some(namespace), Some(&selector), envs.clone()),
+        || kubectl_exec_logs(&kubernetes_config_file_path, namespace, &selector, envs.clone()),
+    );
+
+    // only reported, failing here would abort the rollback of the other services
+    if let Some(details) = termination_details {
+        service.logger().log(EngineEvent::Error(
+            EngineError::new_k8s_container_terminated(
+                service.get_event_details(Stage::Environment(EnvironmentStep::Deploy)),
+                selector,
+                namespace.to_string(),
+                details,
+            ),
+            None,
+        ));
+    }
+
     Ok(())
 }
 
+/// Number of container log lines given to users when a container has been terminated.
+const TERMINATED_CONTAINER_LOGS_LINES: usize = 20;
+
+/// Returns the termination message of the last terminated container and its recent logs, if any container has been
+/// terminated. Pods and logs are retrieved lazily, logs being only fetched when a terminated container is found.
+pub fn get_container_termination_details<P, L>(get_pods: P, get_logs: L) -> Option<String>
+where
+    P: Fn() -> Result<KubernetesList<KubernetesPod>, CommandError>,
+    L: Fn() -> Result<Vec<String>, CommandError>,
+{
+    let pods = get_pods().ok()?;
+    let terminated = pods
+        .items
+        .iter()
+        .flat_map(|pod| pod.status.container_statuses.clone().unwrap_or_default())
+        .filter_map(|status| {
+            status
+                .state
+                .terminated
+                .clone()
+                .or_else(|| status.last_state.and_then(|last_state| last_state.terminated))
+        })
+        .last()?;
+
+    let mut details = format!("exit code {}", terminated.exit_code);
+    match &terminated.reason {
+        KubernetesPodStatusReason::Unknown(None) => {}
+        KubernetesPodStatusReason::Unknown(Some(reason)) => details = format!("{} ({})", details, reason),
+        reason => details = format!("{} ({:?})", details, reason),
+    }
+    if let Some(message) = terminated.message.as_ref().filter(|message| !message.trim().is_empty()) {
+        details = format!("{}, termination message: {}", details, message.trim());
+    }
+    if let Ok(logs) = get_logs() {
+        let first_line = logs.len().saturating_sub(TERMINATED_CONTAINER_LOGS_LINES);
+        if first_line < logs.len() {
+            details = format!("{}\nLast logs:\n{}", details, logs[first_line..].join("\n"));
+        }
+    }
+
+    Some(details)
+}
+
 pub fn scale_down_database(
     target: &DeploymentTarget,
     service: &impl DatabaseService,
@@ -570,6 +872,7 @@ where
         ));
 
         let context = service.tera_context(target)?;
+        service.dump_tera_context(&context, event_details.clone());
 
         if let Err(e) = crate::template::generate_and_copy_all_files_into_dir(
             service.terraform_common_resource_dir_path(),
@@ -628,6 +931,7 @@ where
         ));
 
         let context = service.tera_context(target)?;
+        service.dump_tera_context(&context, event_details.clone());
         let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
 
         // default chart
@@ -1340,3 +1644,239 @@ pub fn get_tfstate_suffix(service: &dyn Service) -> String {
 pub fn get_tfstate_name(service: &dyn Service) -> String {
     format!("tfstate-default-{}", service.id())
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use crate::cloud_provider::helm::ChartInfo;
+    use crate::cloud_provider::service::{
+        get_container_termination_details, insert_common_labels, service_workspace_directory, template_manifests,
+        DatabaseOptions, DatabaseType, ServiceType, SERVICE_KIND_LABELS,
+    };
+    use crate::cmd::structs::{KubernetesList, KubernetesPod};
+    use crate::errors::{CommandError, EngineError, Tag};
+    use crate::events::{EnvironmentStep, EventDetails, Stage, Transmitter};
+    use crate::io_models::{DatabaseMode, QoveryIdentifier};
+    use tempdir::TempDir;
+    use tera::Context as TeraContext;
+
+    fn database_options(publicly_accessible: bool, allowed_cidrs: Vec<&str>) -> DatabaseOptions {
+        DatabaseOptions {
+            login: "login".to_string(),
+            password: "password".to_string(),
+            host: "host".to_string(),
+            port: 5432,
+            mode: DatabaseMode::MANAGED,
+            disk_size_in_gib: 10,
+            database_disk_type: "bssd".to_string(),
+            encrypt_disk: false,
+            activate_high_availability: false,
+            activate_backups: false,
+            publicly_accessible,
+            allowed_cidrs: allowed_cidrs.into_iter().map(|c| c.to_string()).collect(),
+            storage_class_name: None,
+            disk_iops: None,
+            deletion_protection: false,
+            region: None,
+            zone: None,
+        }
+    }
+
+    #[test]
+    fn test_database_options_allowed_cidrs_validation() {
+        // private database, deny all by default
+        let options = database_options(false, vec![]);
+        assert!(options.validate_allowed_cidrs().is_ok());
+        assert!(options.allowed_cidrs().is_empty());
+
+        // private database, CIDRs are ignored
+        let options = database_options(false, vec!["10.0.0.0/16"]);
+        assert!(options.validate_allowed_cidrs().is_ok());
+        assert!(options.allowed_cidrs().is_empty());
+
+        // public database requires at least one CIDR
+        assert!(database_options(true, vec![]).validate_allowed_cidrs().is_err());
+
+        // public database with valid CIDRs
+        let options = database_options(true, vec!["10.0.0.0/16", "1.2.3.4/32"]);
+        assert!(options.validate_allowed_cidrs().is_ok());
+        assert_eq!(
+            vec!["10.0.0.0/16".to_string(), "1.2.3.4/32".to_string()],
+            options.allowed_cidrs()
+        );
+
+        // invalid CIDR
+        assert!(database_options(true, vec!["10.0.0.0/16", "10.0.0.0/42"])
+            .validate_allowed_cidrs()
+            .is_err());
+    }
+
+    #[test]
+    fn test_service_workspace_directories_are_unique() {
+        // setup:
+        let tmp_dir = TempDir::new("workspace_directory").expect("error creating temporary dir");
+        let root_dir = tmp_dir.path().to_str().unwrap();
+
+        // execute:
+        let app_1 = service_workspace_directory(root_dir, "execution-1", "applications", "z1234abc", "my-app").unwrap();
+        let app_2 = service_workspace_directory(root_dir, "execution-1", "applications", "z1234abd", "my-app").unwrap();
+        let app_3 =
+            service_workspace_directory(root_dir, "execution-1", "applications", "z1234abc", "my-app-2").unwrap();
+        let app_1_other_execution =
+            service_workspace_directory(root_dir, "execution-2", "applications", "z1234abc", "my-app").unwrap();
+
+        // verify:
+        assert_ne!(app_1, app_2);
+        assert_ne!(app_1, app_3);
+        assert_ne!(app_2, app_3);
+        assert_ne!(app_1, app_1_other_execution);
+        assert!(app_1.contains("z1234abc"));
+        assert!(app_1.contains("execution-1"));
+    }
+
+    #[test]
+    fn test_common_labels_are_inserted_in_tera_context() {
+        // setup:
+        let mut context = TeraContext::new();
+
+        // execute:
+        insert_common_labels(&mut context, "organization-id", "cluster-id");
+
+        // verify:
+        let labels = context.get("common_labels").expect("common_labels should be set");
+        assert_eq!(
+            Some("organization-id"),
+            labels.get("qovery.com/organization").and_then(|v| v.as_str())
+        );
+        assert_eq!(Some("cluster-id"), labels.get("qovery.com/cluster").and_then(|v| v.as_str()));
+    }
+
+    #[test]
+    fn test_database_options_to_sanitized_json() {
+        // setup:
+        let options = database_options(true, vec!["10.0.0.0/16"]);
+
+        // execute:
+        let json = options.to_sanitized_json();
+
+        // verify:
+        let sanitized: serde_json::Value = serde_json::from_str(&json).expect("sanitized options should be valid JSON");
+        assert!(!json.contains("\"password\":\"password\""));
+        assert_eq!(sanitized["password"], "********");
+        assert_eq!(sanitized["login"], "********");
+        assert_eq!(sanitized["host"], "host");
+        assert_eq!(sanitized["port"], 5432);
+        assert_eq!(sanitized["mode"], "MANAGED");
+        assert_eq!(sanitized["disk_size_in_gib"], 10);
+        assert_eq!(sanitized["publicly_accessible"], true);
+        assert_eq!(sanitized["allowed_cidrs"][0], "10.0.0.0/16");
+    }
+
+    #[test]
+    fn test_container_termination_details() {
+        // setup: mocked kubectl outputs
+        let pods_json = r#"{
+            "items": [{
+                "metadata": {"name": "app-z1234abc-0", "namespace": "project-environment"},
+                "status": {
+                    "phase": "Running",
+                    "conditions": [],
+                    "containerStatuses": [{
+                        "ready": false,
+                        "restartCount": 3,
+                        "state": {"waiting": {"reason": "CrashLoopBackOff"}},
+                        "lastState": {"terminated": {"exitCode": 1, "reason": "Error", "message": "missing DATABASE_URL\n"}}
+                    }]
+                }
+            }]
+        }"#;
+        let get_pods = || -> Result<KubernetesList<KubernetesPod>, CommandError> {
+            Ok(serde_json::from_str(pods_json).expect("valid pods json"))
+        };
+        let get_logs = || -> Result<Vec<String>, CommandError> {
+            Ok(vec!["starting app".to_string(), "panic: DATABASE_URL is not set".to_string()])
+        };
+        let event_details = EventDetails::new(
+            None,
+            QoveryIdentifier::new_from_long_id("organization_id".to_string()),
+            QoveryIdentifier::new_from_long_id("cluster_id".to_string()),
+            QoveryIdentifier::new_from_long_id("execution_id".to_string()),
+            None,
+            Stage::Environment(EnvironmentStep::Deploy),
+            Transmitter::Environment("environment_id".to_string(), "environment".to_string()),
+        );
+
+        // execute:
+        let details = get_container_termination_details(get_pods, get_logs).expect("container has been terminated");
+        let error = EngineError::new_k8s_container_terminated(
+            event_details,
+            "appId=z1234abc".to_string(),
+            "project-environment".to_string(),
+            details,
+        );
+
+        // verify:
+        assert_eq!(&Tag::K8sContainerTerminated, error.tag());
+        assert!(error.user_log_message().contains("exit code 1 (Error)"));
+        assert!(error
+            .user_log_message()
+            .contains("termination message: missing DATABASE_URL"));
+        assert!(error.user_log_message().contains("panic: DATABASE_URL is not set"));
+
+        // execute & verify: no terminated container
+        let running_pods =
+            || -> Result<KubernetesList<KubernetesPod>, CommandError> { Ok(KubernetesList { items: vec![] }) };
+        assert!(get_container_termination_details(running_pods, get_logs).is_none());
+    }
+
+    #[test]
+    fn test_service_type_kind_label() {
+        // setup:
+        let service_types = vec![
+            (ServiceType::Application, "application"),
+            (ServiceType::Router, "router"),
+            (ServiceType::Database(DatabaseType::PostgreSQL), "database"),
+            (ServiceType::Database(DatabaseType::MongoDB), "database"),
+            (ServiceType::Database(DatabaseType::MySQL), "database"),
+            (ServiceType::Database(DatabaseType::Redis), "database"),
+        ];
+
+        for (service_type, expected) in service_types {
+            // execute:
+            let kind_label = service_type.kind_label();
+
+            // verify:
+            assert_eq!(expected, kind_label);
+            assert!(SERVICE_KIND_LABELS.contains(&kind_label));
+        }
+    }
+
+    #[test]
+    fn test_template_manifests() {
+        // setup:
+        let chart = ChartInfo::new_from_custom_namespace(
+            "app-z1234abc".to_string(),
+            "/tmp/app-z1234abc".to_string(),
+            "z1234abc-z5678def".to_string(),
+            600,
+            vec![],
+            false,
+            None,
+        );
+        let helm_template = |chart: &ChartInfo| {
+            assert_eq!("app-z1234abc", chart.name);
+            Ok("---\n# Source: q-application/templates/secret.yaml\n---\n# Source: q-application/templates/service.yaml\napiVersion: v1\nkind: Service\nmetadata:\n  name: app-z1234abc\n---\n# Source: q-application/templates/deployment.yaml\napiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: app-z1234abc\n".to_string())
+        };
+
+        // execute:
+        let manifests = template_manifests(&chart, helm_template).expect("manifests should be rendered");
+
+        // verify: templates rendering nothing are skipped
+        assert_eq!(2, manifests.len());
+        assert_eq!(Some("q-application/templates/service.yaml".to_string()), manifests[0].source);
+        assert!(manifests[0].content.contains("kind: Service\n"));
+        assert_eq!(Some("q-application/templates/deployment.yaml".to_string()), manifests[1].source);
+        assert!(manifests[1].content.ends_with("  name: app-z1234abc\n"));
+    }
+}
@@ -1,8 +1,8 @@
 #![allow(clippy::field_reassign_with_default)]
 
-use crate::errors::EngineError;
+use crate::errors::{CommandError, EngineError};
 use crate::events::{EngineEvent, EventDetails, EventMessage};
-use crate::io_models::{Listeners, ListenersHelper, ProgressInfo, ProgressLevel, ProgressScope};
+use crate::io_models::{DnsCheckBudget, Listeners, ListenersHelper, ProgressInfo, ProgressLevel, ProgressScope};
 use crate::logger::Logger;
 use chrono::Duration;
 use core::option::Option::{None, Some};
@@ -10,6 +10,11 @@ use core::result::Result;
 use core::result::Result::{Err, Ok};
 use retry::delay::Fixed;
 use retry::OperationResult;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Instant;
 use trust_dns_resolver::config::*;
 use trust_dns_resolver::proto::rr::{RData, RecordType};
 use trust_dns_resolver::Resolver;
@@ -125,11 +130,23 @@ pub fn check_cname_for(
     Ok(cname_to_check.to_string())
 }
 
+/// Returns the resolution check budget of a domain, the one of its TLD if overridden or the default one otherwise.
+pub fn dns_check_budget_for(domain: &str, tld_budgets: &BTreeMap<String, DnsCheckBudget>) -> DnsCheckBudget {
+    let tld = domain.trim_end_matches('.').rsplit('.').next().unwrap_or_default();
+
+    tld_budgets
+        .iter()
+        .find(|(configured_tld, _)| configured_tld.trim_start_matches('.').eq_ignore_ascii_case(tld))
+        .map(|(_, budget)| *budget)
+        .unwrap_or_default()
+}
+
 pub fn check_domain_for(
     listener_helper: ListenersHelper,
     domains_to_check: Vec<&str>,
     execution_id: &str,
     context_id: &str,
+    tld_budgets: &BTreeMap<String, DnsCheckBudget>,
     event_details: EventDetails,
     logger: &dyn Logger,
 ) -> Result<(), EngineError> {
@@ -162,7 +179,8 @@ pub fn check_domain_for(
             EventMessage::new_from_safe(message.to_string()),
         ));
 
-        let fixed_iterable = Fixed::from_millis(3000).take(100);
+        let budget = dns_check_budget_for(domain, tld_budgets);
+        let fixed_iterable = Fixed::from_millis(budget.backoff_millis).take(budget.retries);
         let check_result = retry::retry(fixed_iterable, || match next_resolver().lookup_ip(domain) {
             Ok(lookup_ip) => OperationResult::Ok(lookup_ip),
             Err(err) => {
@@ -231,6 +249,132 @@ pub fn check_domain_for(
     Ok(())
 }
 
+/// Checks a custom domain is a CNAME targeting `target_domain`, only warning otherwise as it is fine when using a CDN.
+pub fn check_custom_domain_cname(
+    scope: ProgressScope,
+    listeners: &Listeners,
+    domain: &str,
+    target_domain: &str,
+    execution_id: &str,
+    event_details: EventDetails,
+    logger: &dyn Logger,
+) {
+    match check_cname_for(scope, listeners, domain, execution_id) {
+        Ok(cname) if cname.trim_end_matches('.') == target_domain.trim_end_matches('.') => {}
+        Ok(err) | Err(err) => {
+            // TODO(benjaminch): Handle better this one via a proper error eventually
+            logger.log(EngineEvent::Warning(
+                event_details,
+                EventMessage::new(
+                    format!("Invalid CNAME for {}. Might not be an issue if user is using a CDN.", domain,),
+                    Some(err),
+                ),
+            ));
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DnsCheckKind {
+    /// Domain has to resolve.
+    Resolution { domain: String },
+    /// Custom domain has to be a CNAME targeting `target_domain`.
+    Cname { domain: String, target_domain: String },
+}
+
+/// A single domain check of a service, carrying what is needed to run it outside of the service.
+pub struct DnsCheck<'a> {
+    pub kind: DnsCheckKind,
+    pub scope: ProgressScope,
+    pub listeners: &'a Listeners,
+    pub service_id: String,
+    pub execution_id: String,
+    pub tld_budgets: BTreeMap<String, DnsCheckBudget>,
+    pub event_details: EventDetails,
+}
+
+impl DnsCheck<'_> {
+    pub fn run(self, logger: &dyn Logger) -> Result<(), EngineError> {
+        match &self.kind {
+            DnsCheckKind::Resolution { domain } => check_domain_for(
+                ListenersHelper::new(self.listeners),
+                vec![domain.as_str()],
+                self.service_id.as_str(),
+                self.execution_id.as_str(),
+                &self.tld_budgets,
+                self.event_details,
+                logger,
+            ),
+            DnsCheckKind::Cname { domain, target_domain } => {
+                check_custom_domain_cname(
+                    self.scope,
+                    self.listeners,
+                    domain,
+                    target_domain,
+                    self.execution_id.as_str(),
+                    self.event_details,
+                    logger,
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Runs the DNS checks of a whole environment concurrently, instead of router after router, so that
+/// many custom domains don't blow the deployment time: checks share a global concurrency cap and a time budget.
+pub struct DnsCheckCoordinator {
+    max_concurrency: usize,
+    budget: std::time::Duration,
+}
+
+impl DnsCheckCoordinator {
+    pub fn new(max_concurrency: usize, budget: std::time::Duration) -> Self {
+        DnsCheckCoordinator {
+            max_concurrency: max_concurrency.max(1),
+            budget,
+        }
+    }
+
+    /// Apply `check` to every item, with at most `max_concurrency` checks running at the same time.
+    /// Checks not started when the budget is spent are skipped, their result being `None`, while running ones are
+    /// not interrupted. Results are returned in the same order as the items.
+    pub fn run<T, R, F>(&self, items: Vec<T>, check: &F) -> Vec<Option<R>>
+    where
+        T: Send,
+        R: Send,
+        F: Fn(T) -> R + Sync,
+    {
+        let deadline = Instant::now() + self.budget;
+        let nb_items = items.len();
+        let nb_workers = self.max_concurrency.min(nb_items);
+        let pending_items = Mutex::new(items.into_iter().enumerate());
+        let results: Mutex<Vec<Option<R>>> = Mutex::new((0..nb_items).map(|_| None).collect());
+
+        crossbeam_utils::thread::scope(|scope| {
+            for _ in 0..nb_workers {
+                scope.spawn(|_| loop {
+                    if Instant::now() >= deadline {
+                        break;
+                    }
+
+                    let next_item = pending_items.lock().unwrap().next();
+                    match next_item {
+                        Some((idx, item)) => {
+                            let result = check(item);
+                            results.lock().unwrap()[idx] = Some(result);
+                        }
+                        None => break,
+                    }
+                });
+            }
+        })
+        .expect("a DNS check thread panicked");
+
+        results.into_inner().unwrap()
+    }
+}
+
 pub fn sanitize_name(prefix: &str, name: &str) -> String {
     format!("{}-{}", prefix, name).replace('_', "-")
 }
@@ -244,6 +388,75 @@ pub fn managed_db_name_sanitizer(max_size: usize, prefix: &str, name: &str) -> S
     new_name
 }
 
+/// Kubernetes label values are limited to 63 characters.
+pub const K8S_LABEL_VALUE_MAX_LENGTH: usize = 63;
+
+fn is_k8s_label_value_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'
+}
+
+/// Turns any string into a valid Kubernetes label value: invalid characters are replaced by `-`, value is truncated
+/// to 63 characters and trimmed so it starts and ends with an alphanumeric character.
+pub fn sanitize_k8s_label_value(value: &str) -> String {
+    let sanitized = value
+        .chars()
+        .map(|c| if is_k8s_label_value_char(c) { c } else { '-' })
+        .take(K8S_LABEL_VALUE_MAX_LENGTH)
+        .collect::<String>();
+
+    sanitized.trim_matches(|c: char| !c.is_ascii_alphanumeric()).to_string()
+}
+
+/// Validates a Kubernetes label value: empty, or up to 63 characters `[a-zA-Z0-9-_.]` starting and ending with an
+/// alphanumeric character.
+pub fn validate_k8s_label_value(value: &str) -> Result<(), CommandError> {
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    let is_valid = value.len() <= K8S_LABEL_VALUE_MAX_LENGTH
+        && value.chars().all(is_k8s_label_value_char)
+        && value.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && value.ends_with(|c: char| c.is_ascii_alphanumeric());
+
+    match is_valid {
+        true => Ok(()),
+        false => Err(CommandError::new_from_safe_message(format!(
+            "`{}` is not a valid Kubernetes label value, it must be at most {} characters `[a-zA-Z0-9-_.]` starting and ending with an alphanumeric character",
+            value, K8S_LABEL_VALUE_MAX_LENGTH
+        ))),
+    }
+}
+
+/// Validates a CIDR block such as `10.0.0.0/16` or `2001:db8::/32`.
+pub fn validate_cidr(cidr: &str) -> Result<(), CommandError> {
+    let invalid_cidr = || CommandError::new_from_safe_message(format!("`{}` is not a valid CIDR block", cidr));
+
+    let (ip, prefix_length) = cidr.split_once('/').ok_or_else(invalid_cidr)?;
+    let ip = IpAddr::from_str(ip).map_err(|_| invalid_cidr())?;
+    let max_prefix_length = match ip {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+
+    match prefix_length.parse::<u8>() {
+        Ok(length) if length <= max_prefix_length => Ok(()),
+        _ => Err(invalid_cidr()),
+    }
+}
+
+/// Validates an identifier is not the nil UUID (all zeros), which would be a wrong identity for what it identifies.
+pub fn validate_non_nil_uuid(name: &str, id: &uuid::Uuid) -> Result<(), CommandError> {
+    if id.is_nil() {
+        return Err(CommandError::new_from_safe_message(format!(
+            "`{}` can't be the nil UUID `{}`",
+            name, id
+        )));
+    }
+
+    Ok(())
+}
+
 pub fn print_action(
     cloud_provider_name: &str,
     struct_name: &str,
@@ -261,10 +474,18 @@ pub fn print_action(
 
 #[cfg(test)]
 mod tests {
-    use crate::cloud_provider::utilities::{dns_resolvers, get_cname_record_value};
+    use crate::cloud_provider::utilities::{
+        dns_check_budget_for, dns_resolvers, get_cname_record_value, sanitize_k8s_label_value, validate_cidr,
+        validate_k8s_label_value, validate_non_nil_uuid, DnsCheckCoordinator, K8S_LABEL_VALUE_MAX_LENGTH,
+    };
     use crate::errors::CommandError;
+    use crate::io_models::DnsCheckBudget;
     use crate::models::types::VersionsNumber;
+    use std::collections::BTreeMap;
     use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
 
     #[test]
     pub fn test_cname_resolution() {
@@ -274,6 +495,28 @@ mod tests {
         assert_eq!(cname, Some(String::from("qovery.io.")));
     }
 
+    #[test]
+    pub fn test_validate_cidr() {
+        // valid CIDRs
+        for cidr in &["0.0.0.0/0", "10.0.0.0/16", "192.168.1.1/32", "2001:db8::/32", "::/0"] {
+            assert!(validate_cidr(cidr).is_ok(), "`{}` should be valid", cidr);
+        }
+
+        // invalid CIDRs
+        for cidr in &[
+            "",
+            "10.0.0.0",
+            "10.0.0.0/33",
+            "10.0.0/16",
+            "300.0.0.0/8",
+            "10.0.0.0/-1",
+            "2001:db8::/129",
+            "abc/8",
+        ] {
+            assert!(validate_cidr(cidr).is_err(), "`{}` should be invalid", cidr);
+        }
+    }
+
     #[test]
     pub fn test_versions_number() {
         // setup:
@@ -300,20 +543,34 @@ mod tests {
                 description: "version as a space str",
             },
             TestCase {
-                input: "-", // TODO(benjaminch): better handle this case, should trigger an error
-                expected_output: Ok(VersionsNumber::new("-".to_string(), None, None, None)),
+                input: "-",
+                expected_output: Err(CommandError::new_from_safe_message(
+                    "please check the version you've sent (-), major version `-` is not a number".to_string(),
+                )),
                 description: "suffix separator only",
             },
             TestCase {
                 input: "test",
-                expected_output: Ok(VersionsNumber::new("test".to_string(), None, None, None)),
+                expected_output: Err(CommandError::new_from_safe_message(
+                    "please check the version you've sent (test), major version `test` is not a number".to_string(),
+                )),
                 description: "bad string",
             },
             TestCase {
-                input: "1,2,3,4", // TODO(benjaminch): better handle this case, should trigger an error
-                expected_output: Ok(VersionsNumber::new("1,2,3,4".to_string(), None, None, None)),
+                input: "1,2,3,4",
+                expected_output: Err(CommandError::new_from_safe_message(
+                    "please check the version you've sent (1,2,3,4), major version `1,2,3,4` is not a number"
+                        .to_string(),
+                )),
                 description: "bad versions separator",
             },
+            TestCase {
+                input: ".1",
+                expected_output: Err(CommandError::new_from_safe_message(
+                    "please check the version you've sent (.1), major version `` is not a number".to_string(),
+                )),
+                description: "empty major",
+            },
             TestCase {
                 input: "1",
                 expected_output: Ok(VersionsNumber::new("1".to_string(), None, None, None)),
@@ -354,4 +611,119 @@ mod tests {
             assert_eq!(tc.expected_output, result, "case {} : '{}'", tc.description, tc.input);
         }
     }
+
+    #[test]
+    pub fn test_k8s_label_value() {
+        // valid values
+        let max_length_value = "a".repeat(K8S_LABEL_VALUE_MAX_LENGTH);
+        for value in &["", "app", "my-app_1.0", "z1234abc", max_length_value.as_str()] {
+            assert!(validate_k8s_label_value(value).is_ok(), "`{}` should be valid", value);
+            assert_eq!(value.to_string(), sanitize_k8s_label_value(value));
+        }
+
+        // invalid chars
+        for (value, sanitized) in &[
+            ("my app", "my-app"),
+            ("app.example.com/path", "app.example.com-path"),
+            ("-app-", "app"),
+            ("été", "t"),
+        ] {
+            assert!(validate_k8s_label_value(value).is_err(), "`{}` should be invalid", value);
+            assert_eq!(sanitized.to_string(), sanitize_k8s_label_value(value));
+            assert!(validate_k8s_label_value(&sanitize_k8s_label_value(value)).is_ok());
+        }
+
+        // over length
+        let too_long = format!("{}-b", "a".repeat(K8S_LABEL_VALUE_MAX_LENGTH - 1));
+        assert!(validate_k8s_label_value(&too_long).is_err());
+        assert_eq!("a".repeat(K8S_LABEL_VALUE_MAX_LENGTH - 1), sanitize_k8s_label_value(&too_long));
+    }
+
+    #[test]
+    fn test_validate_non_nil_uuid() {
+        // execute & verify:
+        assert!(validate_non_nil_uuid("cluster_long_id", &uuid::Uuid::new_v4()).is_ok());
+        let result = validate_non_nil_uuid("cluster_long_id", &uuid::Uuid::nil());
+        assert_eq!(
+            "`cluster_long_id` can't be the nil UUID `00000000-0000-0000-0000-000000000000`",
+            result.unwrap_err().message_safe()
+        );
+    }
+
+    #[test]
+    fn test_dns_check_coordinator_respects_global_concurrency_cap() {
+        // setup:
+        let routers_domains: Vec<(&str, Vec<&str>)> = vec![
+            ("router-1", vec!["a.example.com", "b.example.com", "c.example.com"]),
+            ("router-2", vec!["d.example.com", "e.example.com"]),
+            (
+                "router-3",
+                vec!["f.example.com", "g.example.com", "h.example.com", "i.example.com"],
+            ),
+        ];
+        let checks: Vec<(&str, &str)> = routers_domains
+            .iter()
+            .flat_map(|(router, domains)| domains.iter().map(move |domain| (*router, *domain)))
+            .collect();
+        let max_concurrency = 3;
+        let coordinator = DnsCheckCoordinator::new(max_concurrency, Duration::from_secs(60));
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        // execute:
+        let results = coordinator.run(checks.clone(), &|(router, domain)| {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(current, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            format!("{}/{}", router, domain)
+        });
+
+        // verify:
+        assert!(max_in_flight.load(Ordering::SeqCst) <= max_concurrency);
+        assert_eq!(
+            results,
+            checks
+                .iter()
+                .map(|(router, domain)| Some(format!("{}/{}", router, domain)))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_dns_check_coordinator_skips_checks_once_budget_is_spent() {
+        // setup:
+        let coordinator = DnsCheckCoordinator::new(1, Duration::from_millis(50));
+
+        // execute:
+        let results = coordinator.run(vec![1, 2, 3], &|check| {
+            thread::sleep(Duration::from_millis(100));
+            check
+        });
+
+        // verify:
+        assert_eq!(results, vec![Some(1), None, None]);
+    }
+
+    #[test]
+    pub fn test_dns_check_budget_for() {
+        // setup:
+        let extended_budget = DnsCheckBudget {
+            retries: 300,
+            backoff_millis: 5000,
+        };
+        let tld_budgets = vec![("dev".to_string(), extended_budget)]
+            .into_iter()
+            .collect::<BTreeMap<String, DnsCheckBudget>>();
+
+        // execute & verify:
+        assert_eq!(dns_check_budget_for("app.qovery.dev", &tld_budgets), extended_budget);
+        assert_eq!(dns_check_budget_for("app.qovery.DEV.", &tld_budgets), extended_budget);
+        assert_eq!(dns_check_budget_for("app.qovery.io", &tld_budgets), DnsCheckBudget::default());
+        assert_eq!(dns_check_budget_for("dev.qovery.io", &tld_budgets), DnsCheckBudget::default());
+        assert_eq!(
+            dns_check_budget_for("app.qovery.dev", &BTreeMap::new()),
+            DnsCheckBudget::default()
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use crate::cmd::command::{CommandError, CommandKiller, QoveryCommand};
 use lazy_static::lazy_static;
 use std::fmt::{Display, Formatter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::ExitStatus;
 use std::sync::Mutex;
 use url::Url;
@@ -172,6 +172,38 @@ impl ContainerImage {
     }
 }
 
+/// Where buildkit exports the build cache to and imports it from. If not set, the cached image is imported only.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildCacheBackend {
+    /// Local: cache is kept in a directory on the engine disk.
+    Local(PathBuf),
+    /// Registry: cache is pushed to and pulled from an image reference (ex: `registry.acme.com/qovery/build-cache:app`).
+    Registry(String),
+}
+
+fn buildkit_cache_args(cache: &ContainerImage, build_cache_backend: Option<&BuildCacheBackend>) -> Vec<String> {
+    match build_cache_backend {
+        // Exporting to the cached image is disabled, because private ECR does not support it ...
+        // https://github.com/aws/containers-roadmap/issues/876
+        None => vec![
+            "--cache-from".to_string(),
+            format!("type=registry,ref={}", cache.image_name()),
+        ],
+        Some(BuildCacheBackend::Local(directory)) => vec![
+            "--cache-from".to_string(),
+            format!("type=local,src={}", directory.to_string_lossy()),
+            "--cache-to".to_string(),
+            format!("type=local,dest={},mode=max", directory.to_string_lossy()),
+        ],
+        Some(BuildCacheBackend::Registry(reference)) => vec![
+            "--cache-from".to_string(),
+            format!("type=registry,ref={}", reference),
+            "--cache-to".to_string(),
+            format!("type=registry,ref={},mode=max", reference),
+        ],
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Docker {
     use_buildkit: bool,
@@ -342,6 +374,7 @@ impl Docker {
         image_to_build: &ContainerImage,
         build_args: &[(&str, &str)],
         cache: &ContainerImage,
+        build_cache_backend: Option<&BuildCacheBackend>,
         push_after_build: bool,
         stdout_output: &mut Stdout,
         stderr_output: &mut Stderr,
@@ -381,12 +414,14 @@ impl Docker {
                 image_to_build,
                 build_args,
                 cache,
+                build_cache_backend,
                 push_after_build,
                 stdout_output,
                 stderr_output,
                 should_abort,
             )
         } else {
+            // without buildkit, the cache can't be exported and docker local layers cache is used
             self.build_with_docker(
                 dockerfile,
                 context,
@@ -478,6 +513,7 @@ impl Docker {
         image_to_build: &ContainerImage,
         build_args: &[(&str, &str)],
         cache: &ContainerImage,
+        build_cache_backend: Option<&BuildCacheBackend>,
         push_after_build: bool,
         stdout_output: &mut Stdout,
         stderr_output: &mut Stderr,
@@ -504,15 +540,10 @@ impl Docker {
             } else {
                 "--output=type=docker".to_string() // tell buildkit to load the image into docker after build
             },
-            "--cache-from".to_string(),
-            format!("type=registry,ref={}", cache.image_name()),
-            // Disabled for now, because private ECR does not support it ...
-            // https://github.com/aws/containers-roadmap/issues/876
-            // "--cache-to".to_string(),
-            // format!("type=registry,ref={}", cache.image_name()),
-            "-f".to_string(),
-            dockerfile.to_str().unwrap_or_default().to_string(),
         ];
+        args_string.extend(buildkit_cache_args(cache, build_cache_backend));
+        args_string.push("-f".to_string());
+        args_string.push(dockerfile.to_str().unwrap_or_default().to_string());
 
         for image_name in image_to_build.image_names() {
             args_string.push("--tag".to_string());
@@ -623,8 +654,8 @@ where
 #[cfg(test)]
 mod tests {
     use crate::cmd::command::CommandKiller;
-    use crate::cmd::docker::{ContainerImage, Docker, DockerError};
-    use std::path::Path;
+    use crate::cmd::docker::{buildkit_cache_args, BuildCacheBackend, ContainerImage, Docker, DockerError};
+    use std::path::{Path, PathBuf};
     use std::time::Duration;
     use url::Url;
 
@@ -744,6 +775,7 @@ mod tests {
             &image_to_build,
             &[],
             &image_cache,
+            None,
             false,
             &mut |msg| println!("{}", msg),
             &mut |msg| eprintln!("{}", msg),
@@ -758,6 +790,7 @@ mod tests {
             &image_to_build,
             &[],
             &image_cache,
+            None,
             false,
             &mut |msg| println!("{}", msg),
             &mut |msg| eprintln!("{}", msg),
@@ -790,6 +823,7 @@ mod tests {
             &image_to_build,
             &[],
             &image_cache,
+            None,
             false,
             &mut |msg| println!("{}", msg),
             &mut |msg| eprintln!("{}", msg),
@@ -819,4 +853,45 @@ mod tests {
         );
         assert!(matches!(ret, Ok(_)));
     }
+
+    #[test]
+    fn test_buildkit_cache_args() {
+        let image_cache = ContainerImage {
+            registry: private_registry_url(),
+            name: "erebe/alpine".to_string(),
+            tags: vec!["cache".to_string()],
+        };
+
+        // only the cached image is imported by default
+        assert_eq!(
+            buildkit_cache_args(&image_cache, None),
+            vec!["--cache-from", "type=registry,ref=localhost:5000/erebe/alpine:cache"]
+        );
+
+        // cache is exported to the backend and imported from it
+        assert_eq!(
+            buildkit_cache_args(
+                &image_cache,
+                Some(&BuildCacheBackend::Local(PathBuf::from("/workspace/build-cache/alpine")))
+            ),
+            vec![
+                "--cache-from",
+                "type=local,src=/workspace/build-cache/alpine",
+                "--cache-to",
+                "type=local,dest=/workspace/build-cache/alpine,mode=max"
+            ]
+        );
+        assert_eq!(
+            buildkit_cache_args(
+                &image_cache,
+                Some(&BuildCacheBackend::Registry("localhost:5000/build-cache:alpine".to_string()))
+            ),
+            vec![
+                "--cache-from",
+                "type=registry,ref=localhost:5000/build-cache:alpine",
+                "--cache-to",
+                "type=registry,ref=localhost:5000/build-cache:alpine,mode=max"
+            ]
+        );
+    }
 }
@@ -3,7 +3,7 @@ use std::path::{Path, PathBuf};
 
 use tracing::{error, info};
 
-use crate::cloud_provider::helm::ChartInfo;
+use crate::cloud_provider::helm::{ChartInfo, ChartSource};
 use crate::cmd::command::QoveryCommand;
 use crate::cmd::helm::HelmCommand::{LIST, ROLLBACK, STATUS, UNINSTALL, UPGRADE};
 use crate::cmd::helm::HelmError::{CannotRollback, CmdError, InvalidKubeConfig, ReleaseDoesNotExist};
@@ -14,9 +14,10 @@ use semver::Version;
 use serde_derive::Deserialize;
 use std::fs::File;
 use std::str::FromStr;
+use uuid::Uuid;
 
 const HELM_DEFAULT_TIMEOUT_IN_SECONDS: u32 = 600;
-const HELM_MAX_HISTORY: &str = "50";
+const HELM_DEFAULT_HISTORY_MAX: u32 = 10;
 
 pub enum Timeout<T> {
     Default,
@@ -56,6 +57,54 @@ pub enum HelmError {
     CmdError(String, HelmCommand, CommandError),
 }
 
+impl HelmError {
+    /// Returns the kind of failure, telling callers whether it's worth retrying.
+    pub fn kind(&self) -> HelmErrorKind {
+        match self {
+            HelmError::ReleaseLocked(_) => HelmErrorKind::ReleaseLocked,
+            HelmError::Timeout(_, _, _) => HelmErrorKind::Timeout,
+            HelmError::CmdError(_, _, cmd_error) => match cmd_error.message_raw() {
+                Some(stderr) => HelmErrorKind::from_stderr(&stderr),
+                None => HelmErrorKind::Unknown,
+            },
+            _ => HelmErrorKind::Unknown,
+        }
+    }
+}
+
+/// Classification of an helm failure, parsed from helm stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelmErrorKind {
+    /// Release resources were not ready in time, retrying may succeed.
+    Timeout,
+    /// Chart templates or values are invalid, retrying won't help.
+    ValidationError,
+    /// Another helm operation is in progress on the release.
+    ReleaseLocked,
+    Unknown,
+}
+
+impl HelmErrorKind {
+    pub fn from_stderr(stderr: &str) -> HelmErrorKind {
+        let stderr = stderr.to_lowercase();
+
+        if stderr.contains("another operation (install/upgrade/rollback) is in progress") {
+            HelmErrorKind::ReleaseLocked
+        } else if stderr.contains("timed out waiting") || stderr.contains("context deadline exceeded") {
+            HelmErrorKind::Timeout
+        } else if stderr.contains("parse error")
+            || stderr.contains("error validating")
+            || stderr.contains("unable to build kubernetes objects")
+            || stderr.contains("don't meet the specifications of the schema")
+            || stderr.contains("execution error at")
+        {
+            HelmErrorKind::ValidationError
+        } else {
+            HelmErrorKind::Unknown
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Helm {
     kubernetes_config: PathBuf,
@@ -70,6 +119,8 @@ pub enum HelmCommand {
     UNINSTALL,
     LIST,
     DIFF,
+    TEMPLATE,
+    PULL,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -159,6 +210,7 @@ impl Helm {
 
         let timeout = format!("{}s", &chart.timeout_in_seconds);
         let namespace = chart.get_namespace_string();
+        let history_max = get_history_max(chart).to_string();
         let args = vec![
             "rollback",
             &chart.name,
@@ -169,7 +221,7 @@ impl Helm {
             "--timeout",
             &timeout,
             "--history-max",
-            HELM_MAX_HISTORY,
+            &history_max,
             "--cleanup-on-fail",
             "--force",
             "--wait",
@@ -406,30 +458,184 @@ impl Helm {
         }
     }
 
-    pub fn upgrade(&self, chart: &ChartInfo, envs: &[(&str, &str)]) -> Result<(), HelmError> {
-        // Due to crash or error it is possible that the release is under an helm lock
-        // Try to un-stuck the situation first if needed
-        // We don't care if the rollback failed, as it is a best effort to remove the lock
-        // and to re-launch an upgrade just after
-        let unlock_ret = self.unlock_release(chart, envs);
-        info!("Helm lock status: {:?}", unlock_ret);
+    /// Returns the manifests helm would apply for the chart, rendered locally without touching the cluster.
+    pub fn template(&self, chart: &ChartInfo, envs: &[(&str, &str)]) -> Result<String, HelmError> {
+        let mut args_string: Vec<String> = vec![
+            "template".to_string(),
+            "--kubeconfig".to_string(),
+            self.kubernetes_config.to_str().unwrap_or_default().to_string(),
+            "--namespace".to_string(),
+            chart.get_namespace_string(),
+        ];
 
-        let debug = false;
-        let timeout_string = format!("{}s", &chart.timeout_in_seconds);
+        for value in &chart.values {
+            args_string.push("--set".to_string());
+            args_string.push(format!("{}={}", value.key, value.value));
+        }
 
-        let mut args_string: Vec<String> = vec![
+        for value_file in &chart.values_files {
+            args_string.push("-f".to_string());
+            args_string.push(value_file.clone());
+        }
+
+        for value_file in &chart.yaml_files_content {
+            let file_path = format!("{}/{}", chart.path, &value_file.filename);
+            let file_create = || -> Result<(), Error> {
+                let mut file = File::create(&file_path)?;
+                file.write_all(value_file.yaml_content.as_bytes())?;
+                Ok(())
+            };
+
+            // no need to validate yaml as it will be done by helm
+            if let Err(e) = file_create() {
+                let cmd_err = CommandError::new(
+                    format!("Error while writing yaml content to file `{}`", &file_path),
+                    Some(format!("Content\n{}\nError: {}", value_file.yaml_content, e)),
+                    Some(
+                        envs.iter()
+                            .map(|(k, v)| (k.to_string(), v.to_string()))
+                            .collect::<Vec<(String, String)>>(),
+                    ),
+                );
+                return Err(HelmError::CmdError(chart.name.clone(), HelmCommand::TEMPLATE, cmd_err));
+            };
+
+            args_string.push("-f".to_string());
+            args_string.push(file_path);
+        }
+
+        // add last elements
+        args_string.push(chart.name.clone());
+        args_string.push(chart.path.clone());
+
+        let mut manifests = String::new();
+        let mut stderr_msg = String::new();
+        let helm_ret = helm_exec_with_output(
+            &args_string.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
+            &self.get_all_envs(envs),
+            &mut |line| {
+                manifests.push_str(&line);
+                manifests.push('\n');
+            },
+            &mut |line| {
+                stderr_msg.push_str(&line);
+                warn!("chart {}: {}", chart.name, line);
+            },
+        );
+
+        match helm_ret {
+            Ok(_) => Ok(manifests),
+            Err(err) => {
+                error!("Helm error: {:?}", err);
+                Err(CmdError(
+                    chart.name.clone(),
+                    HelmCommand::TEMPLATE,
+                    CommandError::new(
+                        "Helm error".to_string(),
+                        Some(stderr_msg),
+                        Some(envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// Returns the path of the chart to deploy, charts from an OCI registry are pulled first in their own directory,
+    /// removed when the returned `PulledChart` is dropped.
+    fn pull_chart_if_needed(
+        &self,
+        chart: &ChartInfo,
+        envs: &[(&str, &str)],
+    ) -> Result<(String, Option<PulledChart>), HelmError> {
+        let (url, version) = match &chart.source {
+            ChartSource::LocalPath => return Ok((chart.path.clone(), None)),
+            ChartSource::OciRegistry { url, version } => (url, version),
+        };
+
+        if !url.starts_with("oci://") {
+            return Err(CmdError(
+                chart.name.clone(),
+                HelmCommand::PULL,
+                CommandError::new_from_safe_message(format!("Chart reference `{}` must start with `oci://`", url)),
+            ));
+        }
+
+        // several deployments can pull the same chart at once, each one gets its own directory
+        let pulled_chart = PulledChart {
+            directory: std::env::temp_dir().join(format!("helm-{}-{}-{}", chart.name, version, Uuid::new_v4())),
+        };
+        let args_string = get_pull_args(url, version, &pulled_chart.directory);
+        // OCI support is still experimental in helm 3
+        let mut all_envs = self.get_all_envs(envs);
+        all_envs.push(("HELM_EXPERIMENTAL_OCI", "1"));
+
+        let mut stderr_msg = String::new();
+        let helm_ret = helm_exec_with_output(
+            &args_string.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
+            &all_envs,
+            &mut |line| {
+                info!("{}", line);
+            },
+            &mut |line| {
+                stderr_msg.push_str(&line);
+                warn!("chart {}: {}", chart.name, line);
+            },
+        );
+
+        match helm_ret {
+            // chart is untarred in a directory named after it
+            Ok(_) => Ok((
+                pulled_chart
+                    .directory
+                    .join(url.trim_end_matches('/').rsplit('/').next().unwrap_or_default())
+                    .to_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                Some(pulled_chart),
+            )),
+            Err(err) => {
+                error!("Helm error: {:?}", err);
+                Err(CmdError(
+                    chart.name.clone(),
+                    HelmCommand::PULL,
+                    CommandError::new(
+                        "Helm error".to_string(),
+                        Some(stderr_msg),
+                        Some(envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
+                    ),
+                ))
+            }
+        }
+    }
+
+    fn get_upgrade_base_args(&self, chart: &ChartInfo) -> Vec<String> {
+        vec![
             "upgrade".to_string(),
             "--kubeconfig".to_string(),
             self.kubernetes_config.to_str().unwrap_or_default().to_string(),
             "--create-namespace".to_string(),
             "--install".to_string(),
             "--timeout".to_string(),
-            timeout_string.as_str().to_string(),
+            format!("{}s", &chart.timeout_in_seconds),
             "--history-max".to_string(),
-            HELM_MAX_HISTORY.to_string(),
+            get_history_max(chart).to_string(),
             "--namespace".to_string(),
             chart.get_namespace_string(),
-        ];
+        ]
+    }
+
+    pub fn upgrade(&self, chart: &ChartInfo, envs: &[(&str, &str)]) -> Result<(), HelmError> {
+        // Due to crash or error it is possible that the release is under an helm lock
+        // Try to un-stuck the situation first if needed
+        // We don't care if the rollback failed, as it is a best effort to remove the lock
+        // and to re-launch an upgrade just after
+        let unlock_ret = self.unlock_release(chart, envs);
+        info!("Helm lock status: {:?}", unlock_ret);
+
+        let (chart_path, _pulled_chart) = self.pull_chart_if_needed(chart, envs)?;
+
+        let debug = false;
+        let mut args_string = self.get_upgrade_base_args(chart);
 
         if debug {
             args_string.push("-o".to_string());
@@ -437,18 +643,7 @@ impl Helm {
         }
 
         // warn: don't add debug or json output won't work
-        if chart.atomic {
-            args_string.push("--atomic".to_string())
-        }
-        if chart.force_upgrade {
-            args_string.push("--force".to_string())
-        }
-        if chart.dry_run {
-            args_string.push("--dry-run".to_string())
-        }
-        if chart.wait {
-            args_string.push("--wait".to_string())
-        }
+        args_string.extend(get_upgrade_flags(chart));
 
         // overrides and files overrides
         for value in &chart.values {
@@ -488,7 +683,7 @@ impl Helm {
 
         // add last elements
         args_string.push(chart.name.clone());
-        args_string.push(chart.path.clone());
+        args_string.push(chart_path);
 
         let mut error_message: Vec<String> = vec![];
 
@@ -517,11 +712,12 @@ impl Helm {
                     stderr_msg,
                     err.message(ErrorMessageVerbosity::FullDetailsWithoutEnvVars)
                 );
-                let error = if stderr_msg.contains("another operation (install/upgrade/rollback) is in progress") {
+                let error_kind = HelmErrorKind::from_stderr(&stderr_msg);
+                let error = if error_kind == HelmErrorKind::ReleaseLocked {
                     HelmError::ReleaseLocked(chart.name.clone())
                 } else if stderr_msg.contains("has been rolled back") {
                     HelmError::Rollbacked(chart.name.clone(), UPGRADE)
-                } else if stderr_msg.contains("timed out waiting") {
+                } else if error_kind == HelmErrorKind::Timeout {
                     HelmError::Timeout(chart.name.clone(), UPGRADE, stderr_msg)
                 } else {
                     CmdError(
@@ -563,6 +759,10 @@ impl Helm {
     }
 }
 
+fn get_history_max(chart: &ChartInfo) -> u32 {
+    chart.helm_history_max.unwrap_or(HELM_DEFAULT_HISTORY_MAX)
+}
+
 fn helm_exec_with_output<STDOUT, STDERR>(
     args: &[&str],
     envs: &[(&str, &str)],
@@ -595,10 +795,261 @@ pub fn to_command_error(error: HelmError) -> CommandError {
     CommandError::new_from_safe_message(error.to_string())
 }
 
+/// Chart pulled from a registry, its directory is removed once the chart is deployed.
+struct PulledChart {
+    directory: PathBuf,
+}
+
+impl Drop for PulledChart {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.directory);
+    }
+}
+
+fn get_pull_args(url: &str, version: &str, destination: &Path) -> Vec<String> {
+    vec![
+        "pull".to_string(),
+        url.to_string(),
+        "--version".to_string(),
+        version.to_string(),
+        "--untar".to_string(),
+        "--untardir".to_string(),
+        destination.to_str().unwrap_or_default().to_string(),
+    ]
+}
+
+fn get_upgrade_flags(chart: &ChartInfo) -> Vec<String> {
+    let mut flags = vec![];
+
+    if chart.atomic {
+        flags.push("--atomic".to_string())
+    }
+    if chart.force_upgrade {
+        flags.push("--force".to_string())
+    }
+    if chart.dry_run {
+        flags.push("--dry-run".to_string())
+    }
+    if chart.wait {
+        flags.push("--wait".to_string())
+    }
+
+    flags
+}
+
 pub fn to_engine_error(event_details: &EventDetails, error: HelmError) -> EngineError {
     EngineError::new_helm_error(event_details.clone(), error)
 }
 
+#[cfg(test)]
+mod tests_upgrade_args {
+    use crate::cloud_provider::helm::{set_force_upgrade_charts, ChartInfo, ChartSource, CommonChart, HelmChart};
+    use crate::cmd::helm::{get_pull_args, get_upgrade_flags, Helm, HELM_DEFAULT_HISTORY_MAX};
+    use std::collections::HashSet;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use tempdir::TempDir;
+    use tempfile::NamedTempFile;
+
+    fn get_history_max_arg(args: &[String]) -> Option<String> {
+        args.iter()
+            .position(|arg| arg == "--history-max")
+            .and_then(|pos| args.get(pos + 1).cloned())
+    }
+
+    #[test]
+    fn test_upgrade_history_max_arg() {
+        // setup:
+        let kube_config = NamedTempFile::new().expect("cannot create kube config file");
+        let helm = Helm::new(kube_config.path(), &[]).expect("cannot create helm client");
+        let mut chart = ChartInfo::new_from_release_name("my-release", "my-namespace");
+
+        // execute & verify: default value
+        let args = helm.get_upgrade_base_args(&chart);
+        assert_eq!(Some(HELM_DEFAULT_HISTORY_MAX.to_string()), get_history_max_arg(&args));
+
+        // execute & verify: configured value
+        chart.helm_history_max = Some(3);
+        let args = helm.get_upgrade_base_args(&chart);
+        assert_eq!(Some("3".to_string()), get_history_max_arg(&args));
+    }
+
+    #[test]
+    fn test_upgrade_force_flag_only_for_named_charts() {
+        // setup:
+        let new_chart = |name: &str| -> Box<dyn HelmChart> {
+            Box::new(CommonChart {
+                chart_info: ChartInfo::new_from_release_name(name, "my-namespace"),
+            })
+        };
+        let mut charts = vec![
+            vec![new_chart("cert-manager"), new_chart("loki")],
+            vec![new_chart("nginx-ingress")],
+        ];
+        let force_upgrade_charts: HashSet<String> = vec!["cert-manager".to_string()].into_iter().collect();
+
+        // execute:
+        set_force_upgrade_charts(&mut charts, &force_upgrade_charts);
+
+        // verify:
+        for chart in charts.iter().flatten() {
+            let chart_info = chart.get_chart_info();
+            let is_forced = get_upgrade_flags(chart_info).contains(&"--force".to_string());
+            assert_eq!(chart_info.name == "cert-manager", is_forced, "chart `{}`", chart_info.name);
+        }
+    }
+
+    #[test]
+    fn test_pull_args_for_oci_source() {
+        // execute:
+        let args = get_pull_args(
+            "oci://registry.acme.com/charts/loki",
+            "2.4.2",
+            Path::new("/tmp/helm-loki-2.4.2"),
+        );
+
+        // verify:
+        assert_eq!(
+            vec![
+                "pull",
+                "oci://registry.acme.com/charts/loki",
+                "--version",
+                "2.4.2",
+                "--untar",
+                "--untardir",
+                "/tmp/helm-loki-2.4.2"
+            ],
+            args
+        );
+    }
+
+    #[test]
+    fn test_upgrade_pulls_oci_chart_in_its_own_directory() {
+        // setup: fake helm binary recording its calls, pulling creates the untarred chart directory
+        let bin_dir = TempDir::new("fake_helm").expect("cannot create fake helm directory");
+        let calls_file = bin_dir.path().join("calls");
+        let helm_binary = bin_dir.path().join("helm");
+        fs::write(
+            &helm_binary,
+            format!(
+                "#!/bin/sh\necho \"$@\" >> {}\nif [ \"$1\" = \"pull\" ]; then /bin/mkdir -p \"$7/loki\"; fi\n",
+                calls_file.to_str().unwrap()
+            ),
+        )
+        .expect("cannot write fake helm binary");
+        fs::set_permissions(&helm_binary, fs::Permissions::from_mode(0o755)).expect("cannot make fake helm executable");
+        let kube_config = NamedTempFile::new().expect("cannot create kube config file");
+        let helm = Helm::new(kube_config.path(), &[("PATH", bin_dir.path().to_str().unwrap())])
+            .expect("cannot create helm client");
+        let mut chart = ChartInfo::new_from_release_name("loki", "logging");
+        chart.source = ChartSource::OciRegistry {
+            url: "oci://registry.acme.com/charts/loki".to_string(),
+            version: "2.4.2".to_string(),
+        };
+
+        // execute:
+        helm.upgrade(&chart, &[]).expect("first upgrade should succeed");
+        helm.upgrade(&chart, &[]).expect("second upgrade should succeed");
+
+        // verify: each upgrade deploys the chart it pulled, in its own directory removed afterwards
+        let calls = fs::read_to_string(&calls_file).expect("cannot read fake helm calls");
+        let pull_directories = calls
+            .lines()
+            .filter(|call| call.starts_with("pull "))
+            .map(|call| call.rsplit(' ').next().unwrap().to_string())
+            .collect::<Vec<_>>();
+        let upgraded_charts = calls
+            .lines()
+            .filter(|call| call.starts_with("upgrade "))
+            .map(|call| call.rsplit(' ').next().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(pull_directories.len(), 2);
+        assert_ne!(pull_directories[0], pull_directories[1]);
+        for (pull_directory, upgraded_chart) in pull_directories.iter().zip(upgraded_charts.iter()) {
+            assert_eq!(&format!("{}/loki", pull_directory), upgraded_chart);
+            assert!(!Path::new(pull_directory).exists());
+        }
+        assert_eq!(upgraded_charts.len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod tests_error_kind {
+    use crate::cmd::helm::{HelmCommand, HelmError, HelmErrorKind};
+    use crate::errors::CommandError;
+
+    #[test]
+    fn test_helm_error_kind_from_stderr() {
+        // setup:
+        let samples = vec![
+            (
+                "Error: UPGRADE FAILED: timed out waiting for the condition",
+                HelmErrorKind::Timeout,
+            ),
+            (
+                "Error: UPGRADE FAILED: context deadline exceeded",
+                HelmErrorKind::Timeout,
+            ),
+            (
+                "Error: UPGRADE FAILED: another operation (install/upgrade/rollback) is in progress",
+                HelmErrorKind::ReleaseLocked,
+            ),
+            (
+                "Error: UPGRADE FAILED: YAML parse error on nginx/templates/deployment.yaml: error converting YAML to JSON",
+                HelmErrorKind::ValidationError,
+            ),
+            (
+                "Error: UPGRADE FAILED: unable to build kubernetes objects from release manifest: error validating \"\": error validating data: ValidationError(Deployment.spec.replicas)",
+                HelmErrorKind::ValidationError,
+            ),
+            (
+                "Error: values don't meet the specifications of the schema(s) in the following chart(s)",
+                HelmErrorKind::ValidationError,
+            ),
+            (
+                "Error: UPGRADE FAILED: Kubernetes cluster unreachable",
+                HelmErrorKind::Unknown,
+            ),
+        ];
+
+        for (stderr, expected) in samples {
+            // execute & verify:
+            assert_eq!(expected, HelmErrorKind::from_stderr(stderr), "stderr `{}`", stderr);
+        }
+    }
+
+    #[test]
+    fn test_helm_error_kind() {
+        // execute & verify:
+        assert_eq!(
+            HelmErrorKind::ReleaseLocked,
+            HelmError::ReleaseLocked("my-release".to_string()).kind()
+        );
+        assert_eq!(
+            HelmErrorKind::Timeout,
+            HelmError::Timeout("my-release".to_string(), HelmCommand::UPGRADE, "".to_string()).kind()
+        );
+        assert_eq!(
+            HelmErrorKind::ValidationError,
+            HelmError::CmdError(
+                "my-release".to_string(),
+                HelmCommand::UPGRADE,
+                CommandError::new(
+                    "Helm error".to_string(),
+                    Some("Error: UPGRADE FAILED: parse error at (nginx/templates/_helpers.tpl:12)".to_string()),
+                    None,
+                ),
+            )
+            .kind()
+        );
+        assert_eq!(
+            HelmErrorKind::Unknown,
+            HelmError::ReleaseDoesNotExist("my-release".to_string()).kind()
+        );
+    }
+}
+
 #[cfg(feature = "test-with-kube")]
 #[cfg(test)]
 mod tests {
@@ -1,4 +1,5 @@
 use std::path::Path;
+use std::time::Duration;
 
 use retry::delay::Fibonacci;
 use retry::OperationResult;
@@ -6,7 +7,8 @@ use serde::de::DeserializeOwned;
 
 use crate::cloud_provider::digitalocean::models::svc::DoLoadBalancer;
 use crate::cloud_provider::metrics::KubernetesApiMetrics;
-use crate::cmd::command::QoveryCommand;
+use crate::cmd::command;
+use crate::cmd::command::{CommandKiller, QoveryCommand};
 use crate::cmd::structs::{
     Configmap, Daemonset, Item, KubernetesEvent, KubernetesJob, KubernetesKind, KubernetesList, KubernetesNode,
     KubernetesPod, KubernetesPodStatusPhase, KubernetesPodStatusReason, KubernetesService, KubernetesVersion,
@@ -16,6 +18,13 @@ use crate::constants::KUBECONFIG;
 use crate::error::{SimpleError, SimpleErrorKind};
 use crate::errors::{CommandError, ErrorMessageVerbosity};
 
+/// Error of a kubectl command bounded by a timeout, timeouts being distinguished from command failures.
+#[derive(Debug)]
+pub enum KubectlExecError {
+    Timeout(Duration),
+    Command(CommandError),
+}
+
 pub enum ScalingKind {
     Deployment,
     Statefulset,
@@ -173,14 +182,16 @@ pub fn kubectl_exec_get_external_ingress_hostname<P>(
     namespace: &str,
     name: &str,
     envs: Vec<(&str, &str)>,
-) -> Result<Option<String>, CommandError>
+    timeout: Duration,
+) -> Result<Option<String>, KubectlExecError>
 where
     P: AsRef<Path>,
 {
-    let result = kubectl_exec::<P, KubernetesService>(
+    let result = kubectl_exec_with_timeout::<P, KubernetesService>(
         vec!["get", "-n", namespace, "svc", name, "-o", "json"],
         kubernetes_config,
         envs,
+        timeout,
     )?;
 
     if result.status.load_balancer.ingress.is_empty() {
@@ -402,6 +413,96 @@ where
     Ok(())
 }
 
+/// Creates or updates a ResourceQuota in the namespace, `hard` being the resource name and its limit
+/// (ex: `("requests.cpu", "2")`).
+pub fn kubectl_exec_apply_resource_quota<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    name: &str,
+    hard: &[(&str, String)],
+    envs: Vec<(&str, &str)>,
+) -> Result<(), CommandError>
+where
+    P: AsRef<Path>,
+{
+    let mut _envs = Vec::with_capacity(envs.len() + 1);
+    _envs.push((KUBECONFIG, kubernetes_config.as_ref().to_str().unwrap()));
+    _envs.extend(envs);
+
+    let is_present = kubectl_exec_with_output(
+        vec!["-n", namespace, "get", "resourcequota", name],
+        _envs.clone(),
+        &mut |line| info!("{}", line),
+        &mut |line| warn!("{}", line),
+    )
+    .is_ok();
+
+    if is_present {
+        let patch = serde_json::json!({
+            "spec": {
+                "hard": hard.iter().cloned().collect::<std::collections::BTreeMap<&str, String>>()
+            }
+        })
+        .to_string();
+        kubectl_exec_with_output(
+            vec![
+                "-n",
+                namespace,
+                "patch",
+                "resourcequota",
+                name,
+                "--type",
+                "merge",
+                "-p",
+                patch.as_str(),
+            ],
+            _envs,
+            &mut |line| info!("{}", line),
+            &mut |line| error!("{}", line),
+        )?;
+    } else {
+        let hard = format!(
+            "--hard={}",
+            hard.iter()
+                .map(|(resource, limit)| format!("{}={}", resource, limit))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        kubectl_exec_with_output(
+            vec!["-n", namespace, "create", "quota", name, hard.as_str()],
+            _envs,
+            &mut |line| info!("{}", line),
+            &mut |line| error!("{}", line),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Deletes a ResourceQuota from the namespace, nothing being done if it doesn't exist.
+pub fn kubectl_exec_delete_resource_quota<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    name: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<(), CommandError>
+where
+    P: AsRef<Path>,
+{
+    let mut _envs = Vec::with_capacity(envs.len() + 1);
+    _envs.push((KUBECONFIG, kubernetes_config.as_ref().to_str().unwrap()));
+    _envs.extend(envs);
+
+    kubectl_exec_with_output(
+        vec!["-n", namespace, "delete", "resourcequota", name, "--ignore-not-found"],
+        _envs,
+        &mut |line| info!("{}", line),
+        &mut |line| error!("{}", line),
+    )?;
+
+    Ok(())
+}
+
 pub fn kubectl_add_labels_to_namespace<P>(
     kubernetes_config: P,
     namespace: &str,
@@ -536,6 +637,116 @@ where
     Ok(())
 }
 
+/// Namespaced resources types a service may leave behind, a namespace is not considered as empty while any remains.
+pub const NAMESPACE_WORKLOAD_RESOURCES: &str =
+    "deployments,statefulsets,daemonsets,jobs,cronjobs,services,persistentvolumeclaims";
+
+/// Requests the deletion of the persistent volume claims matching the label selector, without waiting for them to be
+/// released by their pods.
+pub fn kubectl_exec_delete_pvcs<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    label_selector: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<(), CommandError>
+where
+    P: AsRef<Path>,
+{
+    let mut _envs = Vec::with_capacity(envs.len() + 1);
+    _envs.push((KUBECONFIG, kubernetes_config.as_ref().to_str().unwrap()));
+    _envs.extend(envs);
+
+    let _ = kubectl_exec_with_output(
+        vec![
+            "delete",
+            "pvc",
+            "-n",
+            namespace,
+            "-l",
+            label_selector,
+            "--wait=false",
+            "--ignore-not-found",
+        ],
+        _envs,
+        &mut |line| info!("{}", line),
+        &mut |line| error!("{}", line),
+    )?;
+
+    Ok(())
+}
+
+/// Returns the names (`kind/name` format) of the workload resources matching the label selector remaining in a
+/// namespace.
+pub fn kubectl_exec_get_namespace_remaining_resources<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    label_selector: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<Vec<String>, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let mut _envs = Vec::with_capacity(envs.len() + 1);
+    _envs.push((KUBECONFIG, kubernetes_config.as_ref().to_str().unwrap()));
+    _envs.extend(envs);
+
+    let mut resources: Vec<String> = vec![];
+    let _ = kubectl_exec_with_output(
+        vec![
+            "get",
+            NAMESPACE_WORKLOAD_RESOURCES,
+            "-n",
+            namespace,
+            "-l",
+            label_selector,
+            "-o",
+            "name",
+        ],
+        _envs,
+        &mut |line| {
+            if !line.trim().is_empty() {
+                resources.push(line.trim().to_string())
+            }
+        },
+        &mut |line| error!("{}", line),
+    )?;
+
+    Ok(resources)
+}
+
+/// Returns whether the cert-manager Certificate has its `Ready` condition set to `True`.
+pub fn kubectl_exec_is_certificate_ready<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    name: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<bool, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let mut _envs = Vec::with_capacity(envs.len() + 1);
+    _envs.push((KUBECONFIG, kubernetes_config.as_ref().to_str().unwrap()));
+    _envs.extend(envs);
+
+    let mut ready_status = String::new();
+    let _ = kubectl_exec_with_output(
+        vec![
+            "-n",
+            namespace,
+            "get",
+            "certificate",
+            name,
+            "-o",
+            "jsonpath={.status.conditions[?(@.type==\"Ready\")].status}",
+        ],
+        _envs,
+        &mut |line| ready_status.push_str(line.trim()),
+        &mut |line| error!("{}", line),
+    )?;
+
+    Ok(ready_status == "True")
+}
+
 pub fn kubectl_exec_delete_crd<P>(
     kubernetes_config: P,
     crd_name: &str,
@@ -558,6 +769,53 @@ where
     Ok(())
 }
 
+/// Returns the decoded value of a secret key, None if the secret or the key doesn't exist.
+pub fn kubectl_exec_get_secret_value<P>(
+    kubernetes_config: P,
+    namespace: &str,
+    secret: &str,
+    key: &str,
+    envs: Vec<(&str, &str)>,
+) -> Result<Option<String>, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let mut _envs = Vec::with_capacity(envs.len() + 1);
+    _envs.push((KUBECONFIG, kubernetes_config.as_ref().to_str().unwrap()));
+    _envs.extend(envs);
+
+    let jsonpath = format!("jsonpath={{.data.{}}}", key);
+    let mut encoded_value = String::new();
+    let _ = kubectl_exec_with_output(
+        vec![
+            "-n",
+            namespace,
+            "get",
+            "secret",
+            secret,
+            "--ignore-not-found",
+            "-o",
+            jsonpath.as_str(),
+        ],
+        _envs,
+        &mut |line| encoded_value.push_str(line.trim()),
+        &mut |line| error!("{}", line),
+    )?;
+
+    if encoded_value.is_empty() {
+        return Ok(None);
+    }
+
+    let value = base64::decode(&encoded_value)
+        .ok()
+        .and_then(|value| String::from_utf8(value).ok())
+        .ok_or_else(|| {
+            CommandError::new_from_safe_message(format!("Secret `{}` key `{}` is not valid base64 UTF-8.", secret, key))
+        })?;
+
+    Ok(Some(value))
+}
+
 pub fn kubectl_exec_delete_secret<P>(
     kubernetes_config: P,
     namespace: &str,
@@ -1162,6 +1420,54 @@ where
     }
 }
 
+/// Same as `kubectl_exec` but the command is killed once the timeout is reached.
+fn kubectl_exec_with_timeout<P, T>(
+    args: Vec<&str>,
+    kubernetes_config: P,
+    envs: Vec<(&str, &str)>,
+    timeout: Duration,
+) -> Result<T, KubectlExecError>
+where
+    P: AsRef<Path>,
+    T: DeserializeOwned,
+{
+    let mut extended_envs = Vec::with_capacity(envs.len() + 1);
+    extended_envs.push((KUBECONFIG, kubernetes_config.as_ref().to_str().unwrap()));
+    extended_envs.extend(envs);
+
+    let mut output_vec: Vec<String> = Vec::with_capacity(50);
+    let mut cmd = QoveryCommand::new("kubectl", &args, &extended_envs);
+    match cmd.exec_with_abort(
+        &mut |line| output_vec.push(line),
+        &mut |line| error!("{}", line),
+        &CommandKiller::from_timeout(timeout),
+    ) {
+        Ok(_) => {}
+        Err(command::CommandError::TimeoutError(_)) => return Err(KubectlExecError::Timeout(timeout)),
+        Err(err) => {
+            return Err(KubectlExecError::Command(CommandError::new_from_command_line(
+                format!("Error while executing a kubectl command: {:?}.", err),
+                "kubectl".to_string(),
+                args.into_iter().map(|a| a.to_string()).collect(),
+                extended_envs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+                None,
+                None,
+            )))
+        }
+    }
+
+    serde_json::from_str::<T>(output_vec.join("").as_str()).map_err(|err| {
+        KubectlExecError::Command(CommandError::new(
+            "JSON parsing error on kubectl command.".to_string(),
+            Some(err.to_string()),
+            None,
+        ))
+    })
+}
+
 fn kubectl_exec<P, T>(args: Vec<&str>, kubernetes_config: P, envs: Vec<(&str, &str)>) -> Result<T, CommandError>
 where
     P: AsRef<Path>,
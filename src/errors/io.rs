@@ -53,6 +53,7 @@ pub enum Tag {
     K8sHistory,
     K8sCannotCreateNamespace,
     K8sPodIsNotReady,
+    K8sContainerTerminated,
     K8sNodeIsNotReadyWithTheRequestedVersion,
     K8sNodeIsNotReady,
     UnsupportedRegion,
@@ -62,6 +63,8 @@ pub enum Tag {
     CannotGetOrCreateIamRole,
     CannotCopyFilesFromDirectoryToDirectory,
     CannotPauseClusterTasksAreRunning,
+    CannotPauseManagedDatabase,
+    CannotDeleteProtectedDatabase,
     TerraformCannotRemoveEntryOut,
     TerraformNoStateFileExists,
     TerraformErrorWhileExecutingPipeline,
@@ -82,6 +85,10 @@ pub enum Tag {
     OnlyOneClusterExpected,
     CloudProviderApiMissingInfo,
     K8sValidateRequiredCPUandBurstableError,
+    K8sValidateRequiredMemoryError,
+    DuplicatedServiceSelectors,
+    EnvironmentResourceQuotaExceeded,
+    K8sCannotApplyResourceQuota,
     TerraformContextUnsupportedParameterValue,
     ClientServiceFailedToStart,
     ClientServiceFailedToDeployBeforeStart,
@@ -153,11 +160,14 @@ impl From<errors::Tag> for Tag {
             errors::Tag::K8sHistory => Tag::K8sHistory,
             errors::Tag::K8sCannotCreateNamespace => Tag::K8sCannotCreateNamespace,
             errors::Tag::K8sPodIsNotReady => Tag::K8sPodIsNotReady,
+            errors::Tag::K8sContainerTerminated => Tag::K8sContainerTerminated,
             errors::Tag::CannotFindRequiredBinary => Tag::CannotFindRequiredBinary,
             errors::Tag::SubnetsCountShouldBeEven => Tag::SubnetsCountShouldBeEven,
             errors::Tag::CannotGetOrCreateIamRole => Tag::CannotGetOrCreateIamRole,
             errors::Tag::CannotCopyFilesFromDirectoryToDirectory => Tag::CannotCopyFilesFromDirectoryToDirectory,
             errors::Tag::CannotPauseClusterTasksAreRunning => Tag::CannotPauseClusterTasksAreRunning,
+            errors::Tag::CannotPauseManagedDatabase => Tag::CannotPauseManagedDatabase,
+            errors::Tag::CannotDeleteProtectedDatabase => Tag::CannotDeleteProtectedDatabase,
             errors::Tag::TerraformCannotRemoveEntryOut => Tag::TerraformCannotRemoveEntryOut,
             errors::Tag::TerraformNoStateFileExists => Tag::TerraformNoStateFileExists,
             errors::Tag::TerraformErrorWhileExecutingPipeline => Tag::TerraformErrorWhileExecutingPipeline,
@@ -183,6 +193,10 @@ impl From<errors::Tag> for Tag {
             errors::Tag::OnlyOneClusterExpected => Tag::OnlyOneClusterExpected,
             errors::Tag::CloudProviderApiMissingInfo => Tag::CloudProviderApiMissingInfo,
             errors::Tag::K8sValidateRequiredCPUandBurstableError => Tag::K8sValidateRequiredCPUandBurstableError,
+            errors::Tag::K8sValidateRequiredMemoryError => Tag::K8sValidateRequiredMemoryError,
+            errors::Tag::DuplicatedServiceSelectors => Tag::DuplicatedServiceSelectors,
+            errors::Tag::EnvironmentResourceQuotaExceeded => Tag::EnvironmentResourceQuotaExceeded,
+            errors::Tag::K8sCannotApplyResourceQuota => Tag::K8sCannotApplyResourceQuota,
             errors::Tag::TerraformContextUnsupportedParameterValue => Tag::TerraformContextUnsupportedParameterValue,
             errors::Tag::ClientServiceFailedToStart => Tag::ClientServiceFailedToStart,
             errors::Tag::ClientServiceFailedToDeployBeforeStart => Tag::ClientServiceFailedToDeployBeforeStart,
@@ -231,12 +231,22 @@ pub enum Tag {
     K8sCannotCreateNamespace,
     /// K8sPodIsNotReady: represents an error where the given pod is not ready.
     K8sPodIsNotReady,
+    /// K8sContainerTerminated: represents an error where a container of the given pods has been terminated.
+    K8sContainerTerminated,
     /// K8sNodeIsNotReadyInTheGivenVersion: represents an error where the given node is not ready in the given version.
     K8sNodeIsNotReadyWithTheRequestedVersion,
     /// K8sNodeIsNotReady: represents an error where the given node is not ready.
     K8sNodeIsNotReady,
     /// K8sValidateRequiredCPUandBurstableError: represents an error validating required CPU and burstable.
     K8sValidateRequiredCPUandBurstableError,
+    /// K8sValidateRequiredMemoryError: represents an error where the requested memory is lower than the service minimum.
+    K8sValidateRequiredMemoryError,
+    /// DuplicatedServiceSelectors: represents an error where several services of an environment share the same selector.
+    DuplicatedServiceSelectors,
+    /// EnvironmentResourceQuotaExceeded: represents an error where environment services request more resources than its quota.
+    EnvironmentResourceQuotaExceeded,
+    /// K8sCannotApplyResourceQuota: represents an error while trying to create or update a k8s resource quota.
+    K8sCannotApplyResourceQuota,
     /// CannotFindRequiredBinary: represents an error where a required binary is not found on the system.
     CannotFindRequiredBinary,
     /// SubnetsCountShouldBeEven: represents an error where subnets count should be even to have as many public than private subnets.
@@ -247,6 +257,10 @@ pub enum Tag {
     CannotCopyFilesFromDirectoryToDirectory,
     /// CannotPauseClusterTasksAreRunning: represents an error where we cannot pause the cluster because some tasks are still running in the engine.
     CannotPauseClusterTasksAreRunning,
+    /// CannotPauseManagedDatabase: represents an error where a managed database is requested to be paused, which is not supported.
+    CannotPauseManagedDatabase,
+    /// CannotDeleteProtectedDatabase: represents an error where a deletion protected database is requested to be deleted without forcing it.
+    CannotDeleteProtectedDatabase,
     /// TerraformCannotRemoveEntryOut: represents an error where we cannot remove an entry out of Terraform.
     TerraformCannotRemoveEntryOut,
     /// TerraformNoStateFileExists: represents an error where there is no Terraform state file.
@@ -353,6 +367,16 @@ pub enum Tag {
     ObjectStorageCannotActivateBucketVersioning,
 }
 
+impl Tag {
+    /// Returns whether the error is caused by an invalid user input rather than by an engine internal issue.
+    pub fn is_user_error(&self) -> bool {
+        matches!(
+            self,
+            Tag::VersionNumberParsingError | Tag::CannotPauseManagedDatabase | Tag::CannotDeleteProtectedDatabase
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 /// EngineError: represents an engine error. Engine will always returns such errors carrying context infos easing monitoring and debugging.
 pub struct EngineError {
@@ -484,8 +508,19 @@ impl EngineError {
 
     /// Converts to legacy engine error easing migration.
     pub fn to_legacy_engine_error(self) -> LegacyEngineError {
+        let cause = match self.tag {
+            Tag::VersionNumberParsingError => EngineErrorCause::User(
+                "The requested version cannot be parsed, please check it's a valid version number.",
+            ),
+            Tag::CannotPauseManagedDatabase => EngineErrorCause::User("Managed databases cannot be paused."),
+            Tag::CannotDeleteProtectedDatabase => {
+                EngineErrorCause::User("Deletion protected databases cannot be deleted unless the deletion is forced.")
+            }
+            _ => EngineErrorCause::Internal,
+        };
+
         LegacyEngineError::new(
-            EngineErrorCause::Internal,
+            cause,
             EngineErrorScope::from(self.event_details.transmitter()),
             self.event_details.execution_id().to_string(),
             // Note: Since legacy EngineError is read directly as is in the Core, not all details are exposed
@@ -1378,6 +1413,36 @@ impl EngineError {
         )
     }
 
+    /// Creates new error for kubernetes container being terminated, giving the reason it exited.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `selector`: Selector to get pods.
+    /// * `namespace`: Pods namespace.
+    /// * `termination_details`: Container termination message and recent logs.
+    pub fn new_k8s_container_terminated(
+        event_details: EventDetails,
+        selector: String,
+        namespace: String,
+        termination_details: String,
+    ) -> EngineError {
+        let message = format!(
+            "Error, container of pod with selector `{}` in namespace `{}` has been terminated:\n{}",
+            selector, namespace, termination_details
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::K8sContainerTerminated,
+            message.to_string(),
+            message,
+            None,
+            None,
+            Some("Check your application logs and configuration, it exits while starting.".to_string()),
+        )
+    }
+
     /// Creates new error for kubernetes node not being ready with the requested version.
     ///
     /// Arguments:
@@ -1453,6 +1518,113 @@ impl EngineError {
         )
     }
 
+    /// Creates new error for kubernetes validate required memory.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `service_type_name`: Service type name.
+    /// * `total_ram_in_mib`: Requested memory in MiB.
+    /// * `minimum_ram_in_mib`: Minimum memory in MiB for this service type.
+    pub fn new_k8s_validate_required_memory_error(
+        event_details: EventDetails,
+        service_type_name: String,
+        total_ram_in_mib: u32,
+        minimum_ram_in_mib: u32,
+    ) -> EngineError {
+        let message = format!(
+            "Requested memory ({}MiB) is lower than the minimum required by {} ({}MiB).",
+            total_ram_in_mib, service_type_name, minimum_ram_in_mib
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::K8sValidateRequiredMemoryError,
+            message.to_string(),
+            message,
+            None,
+            None,
+            Some(format!("Please increase the memory to at least {}MiB.", minimum_ram_in_mib)),
+        )
+    }
+
+    /// Creates new error for environment services sharing the same selector.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `duplicated_selectors`: Selectors used by more than one service.
+    pub fn new_duplicated_service_selectors(
+        event_details: EventDetails,
+        duplicated_selectors: Vec<String>,
+    ) -> EngineError {
+        let message = format!(
+            "Several services of the environment share the same selector, their pods would be mixed up: {}.",
+            duplicated_selectors.join(", ")
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::DuplicatedServiceSelectors,
+            message.to_string(),
+            message,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Creates new error for environment services requesting more resources than the environment quota.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `violations`: Exceeded quota resources.
+    pub fn new_environment_resource_quota_exceeded(
+        event_details: EventDetails,
+        violations: Vec<String>,
+    ) -> EngineError {
+        let message = format!(
+            "Environment services request more resources than its quota allows: {}.",
+            violations.join(", ")
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::EnvironmentResourceQuotaExceeded,
+            message.to_string(),
+            message,
+            None,
+            None,
+            Some("Please reduce services resources or instances, or increase the environment quota.".to_string()),
+        )
+    }
+
+    /// Creates new error for kubernetes not being able to create or update a resource quota.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `namespace`: Resource quota namespace.
+    /// * `raw_error`: Raw error message.
+    pub fn new_k8s_cannot_apply_resource_quota(
+        event_details: EventDetails,
+        namespace: String,
+        raw_error: CommandError,
+    ) -> EngineError {
+        let message = format!("Error, unable to apply resource quota on namespace `{}`.", namespace);
+
+        EngineError::new(
+            event_details,
+            Tag::K8sCannotApplyResourceQuota,
+            message.to_string(),
+            message,
+            Some(raw_error),
+            None,
+            None,
+        )
+    }
+
     /// Creates new error for kubernetes not being able to get crash looping pods.
     ///
     /// Arguments:
@@ -1575,6 +1747,54 @@ impl EngineError {
         )
     }
 
+    /// Creates new error for managed database which cannot be paused.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `database_type`: Database type name.
+    pub fn new_cannot_pause_managed_database(event_details: EventDetails, database_type: String) -> EngineError {
+        let message = format!(
+            "Managed {} databases cannot be paused, there is no instance to scale down.",
+            database_type
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::CannotPauseManagedDatabase,
+            message.to_string(),
+            message,
+            None,
+            None,
+            Some(
+                "Please delete the database instead, or use a container database if it needs to be paused.".to_string(),
+            ),
+        )
+    }
+
+    /// Creates new error for deletion protected database which cannot be deleted.
+    ///
+    /// Arguments:
+    ///
+    /// * `event_details`: Error linked event details.
+    /// * `database_name`: Database name.
+    pub fn new_cannot_delete_protected_database(event_details: EventDetails, database_name: String) -> EngineError {
+        let message = format!(
+            "Database `{}` has deletion protection enabled, it cannot be deleted.",
+            database_name
+        );
+
+        EngineError::new(
+            event_details,
+            Tag::CannotDeleteProtectedDatabase,
+            message.to_string(),
+            message,
+            None,
+            None,
+            Some("Please disable the database deletion protection, or force its deletion.".to_string()),
+        )
+    }
+
     /// Creates new error for removing an element out of terraform.
     ///
     /// Arguments:
@@ -2107,7 +2327,7 @@ impl EngineError {
             message,
             Some(raw_error),
             None,
-            None,
+            Some("Version should be a valid version number such as `13`, `13.4` or `13.4.1`.".to_string()),
         )
     }
 
@@ -2875,10 +3095,13 @@ impl Display for EngineError {
 #[cfg(test)]
 mod tests {
     use crate::cloud_provider::Kind;
-    use crate::errors::{CommandError, EngineError, ErrorMessageVerbosity};
-    use crate::events::{EventDetails, InfrastructureStep, Stage, Transmitter};
+    use crate::error::EngineErrorCause;
+    use crate::errors::{CommandError, EngineError, ErrorMessageVerbosity, Tag};
+    use crate::events::{EnvironmentStep, EventDetails, InfrastructureStep, Stage, Transmitter};
     use crate::io_models::QoveryIdentifier;
     use crate::models::scaleway::ScwRegion;
+    use crate::models::types::VersionsNumber;
+    use std::str::FromStr;
 
     #[test]
     fn test_command_error_test_hidding_env_vars_in_message_safe_only() {
@@ -3032,4 +3255,41 @@ mod tests {
         assert!(!res.contains("my_secret"));
         assert!(!res.contains("my_secret_value"));
     }
+
+    #[test]
+    fn test_version_number_parsing_error_is_a_user_error() {
+        // setup:
+        let raw_version = "not-a-version";
+        let parsing_error = VersionsNumber::from_str(raw_version).expect_err("version should not be parsable");
+        let database_id = QoveryIdentifier::new_random();
+
+        // execute:
+        let engine_err = EngineError::new_version_number_parsing_error(
+            EventDetails::new(
+                Some(Kind::Scw),
+                QoveryIdentifier::new_random(),
+                QoveryIdentifier::new_random(),
+                QoveryIdentifier::new_random(),
+                Some(ScwRegion::Paris.as_str().to_string()),
+                Stage::Environment(EnvironmentStep::Deploy),
+                Transmitter::Database(database_id.to_string(), "postgresql".to_string(), database_id.to_string()),
+            ),
+            raw_version.to_string(),
+            parsing_error.clone(),
+        );
+
+        // verify:
+        assert!(engine_err.tag().is_user_error());
+        assert_eq!(Some(parsing_error), engine_err.underlying_error());
+        let legacy_err = engine_err.to_legacy_engine_error();
+        assert!(matches!(legacy_err.cause, EngineErrorCause::User(_)));
+        assert_ne!(EngineErrorCause::Internal, legacy_err.cause);
+    }
+
+    #[test]
+    fn test_tag_is_user_error() {
+        assert!(Tag::VersionNumberParsingError.is_user_error());
+        assert!(!Tag::Unknown.is_user_error());
+        assert!(!Tag::HelmChartsDeployError.is_user_error());
+    }
 }
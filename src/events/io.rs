@@ -3,6 +3,7 @@
 use crate::cloud_provider::io::Kind;
 use crate::errors::io::EngineError;
 use crate::events;
+use crate::io_models::AuditContext;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
@@ -25,6 +26,11 @@ pub enum EngineEvent {
         error: EngineError,
         message: Option<EventMessage>,
     },
+    Audit {
+        details: EventDetails,
+        audit_context: AuditContext,
+        outcome: AuditOutcome,
+    },
 }
 
 impl From<events::EngineEvent> for EngineEvent {
@@ -46,6 +52,33 @@ impl From<events::EngineEvent> for EngineEvent {
                 error: EngineError::from(e),
                 message: m.map(EventMessage::from),
             },
+            events::EngineEvent::Audit(d, a, o) => EngineEvent::Audit {
+                details: EventDetails::from(d),
+                audit_context: a,
+                outcome: AuditOutcome::from(o),
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Started,
+    Succeeded,
+    Canceled,
+    RolledBack,
+    Failed,
+}
+
+impl From<events::AuditOutcome> for AuditOutcome {
+    fn from(outcome: events::AuditOutcome) -> Self {
+        match outcome {
+            events::AuditOutcome::Started => AuditOutcome::Started,
+            events::AuditOutcome::Succeeded => AuditOutcome::Succeeded,
+            events::AuditOutcome::Canceled => AuditOutcome::Canceled,
+            events::AuditOutcome::RolledBack => AuditOutcome::RolledBack,
+            events::AuditOutcome::Failed => AuditOutcome::Failed,
         }
     }
 }
@@ -91,6 +124,7 @@ pub enum GeneralStep {
     RetrieveClusterResources,
     ValidateSystemRequirements,
     UnderMigration,
+    Audit,
 }
 
 impl From<events::GeneralStep> for GeneralStep {
@@ -100,6 +134,7 @@ impl From<events::GeneralStep> for GeneralStep {
             events::GeneralStep::RetrieveClusterResources => GeneralStep::RetrieveClusterResources,
             events::GeneralStep::ValidateSystemRequirements => GeneralStep::ValidateSystemRequirements,
             events::GeneralStep::UnderMigration => GeneralStep::UnderMigration,
+            events::GeneralStep::Audit => GeneralStep::Audit,
         }
     }
 }
@@ -9,7 +9,7 @@ extern crate url;
 
 use crate::cloud_provider::Kind;
 use crate::errors::{CommandError, EngineError, ErrorMessageVerbosity};
-use crate::io_models::QoveryIdentifier;
+use crate::io_models::{AuditContext, QoveryIdentifier};
 use derivative::Derivative;
 use std::fmt::{Display, Formatter};
 
@@ -24,6 +24,8 @@ pub enum EngineEvent {
     Warning(EventDetails, EventMessage),
     /// Error: represents an error event.
     Error(EngineError, Option<EventMessage>),
+    /// Audit: represents a transaction audit event, who or what triggered it and its outcome.
+    Audit(EventDetails, AuditContext, AuditOutcome),
 }
 
 impl EngineEvent {
@@ -34,6 +36,7 @@ impl EngineEvent {
             EngineEvent::Info(details, _message) => details,
             EngineEvent::Warning(details, _message) => details,
             EngineEvent::Error(engine_error, _message) => engine_error.event_details(),
+            EngineEvent::Audit(details, _audit_context, _outcome) => details,
         }
     }
 
@@ -44,10 +47,36 @@ impl EngineEvent {
             EngineEvent::Info(_details, message) => message.message(message_verbosity),
             EngineEvent::Warning(_details, message) => message.message(message_verbosity),
             EngineEvent::Error(engine_error, _message) => engine_error.message(message_verbosity.into()),
+            EngineEvent::Audit(_details, audit_context, outcome) => format!(
+                "Transaction {}: actor=`{}`, trigger=`{}`, request_id=`{}`",
+                outcome, audit_context.actor, audit_context.trigger, audit_context.request_id
+            ),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// AuditOutcome: represents where an audited transaction stands.
+pub enum AuditOutcome {
+    Started,
+    Succeeded,
+    Canceled,
+    RolledBack,
+    Failed,
+}
+
+impl Display for AuditOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AuditOutcome::Started => "started",
+            AuditOutcome::Succeeded => "succeeded",
+            AuditOutcome::Canceled => "canceled",
+            AuditOutcome::RolledBack => "rolled back",
+            AuditOutcome::Failed => "failed",
+        })
+    }
+}
+
 /// EventMessageVerbosity: represents event message's verbosity from minimal to full verbosity.
 pub enum EventMessageVerbosity {
     SafeOnly,
@@ -219,6 +248,8 @@ pub enum GeneralStep {
     RetrieveClusterResources,
     /// UnderMigration: error migration hasn't been completed yet.
     UnderMigration,
+    /// Audit: tracing who or what triggered an engine operation.
+    Audit,
 }
 
 impl Display for GeneralStep {
@@ -231,6 +262,7 @@ impl Display for GeneralStep {
                 GeneralStep::RetrieveClusterResources => "retrieve-cluster-resources",
                 GeneralStep::ValidateSystemRequirements => "validate-system-requirements",
                 GeneralStep::UnderMigration => "under-migration",
+                GeneralStep::Audit => "audit",
             }
         )
     }
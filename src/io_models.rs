@@ -23,7 +23,7 @@ use crate::cmd::docker::Docker;
 use crate::container_registry::ContainerRegistryInfo;
 use crate::logger::Logger;
 use crate::models;
-use crate::models::application::{ApplicationError, ApplicationService};
+use crate::models::application::{ApplicationError, ApplicationResources, ApplicationService};
 use crate::models::aws::{AwsAppExtraSettings, AwsRouterExtraSettings, AwsStorageType};
 use crate::models::database::{Container, DatabaseError, Managed, MongoDB, MySQL, PostgresSQL, Redis};
 use crate::models::digital_ocean::{DoAppExtraSettings, DoRouterExtraSettings, DoStorageType};
@@ -91,6 +91,42 @@ pub struct EnvironmentRequest {
     pub routers: Vec<Router>,
     pub databases: Vec<Database>,
     pub clone_from_environment_id: Option<String>,
+    #[serde(default)]
+    pub resource_quota: Option<ResourceQuota>,
+    #[serde(default)]
+    pub dns_check: Option<DnsCheckConfig>,
+}
+
+/// Maximum resources the services of an environment namespace can request, cpu being in Kubernetes format (ex: `500m`).
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ResourceQuota {
+    pub cpu: String,
+    pub memory_in_mib: u32,
+    pub pods: u32,
+}
+
+/// Routers domains are checked all at once, with at most `max_concurrency` checks at the same time,
+/// checks not started after `budget_seconds` being skipped.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DnsCheckConfig {
+    pub max_concurrency: usize,
+    pub budget_seconds: u64,
+}
+
+/// A single domain resolution is checked up to `retries` times, waiting `backoff_millis` between attempts.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct DnsCheckBudget {
+    pub retries: usize,
+    pub backoff_millis: u64,
+}
+
+impl Default for DnsCheckBudget {
+    fn default() -> Self {
+        DnsCheckBudget {
+            retries: 100,
+            backoff_millis: 3000,
+        }
+    }
 }
 
 impl EnvironmentRequest {
@@ -142,6 +178,8 @@ impl EnvironmentRequest {
             applications,
             routers,
             databases,
+            self.resource_quota.clone(),
+            self.dns_check.clone(),
         ))
     }
 }
@@ -188,6 +226,102 @@ pub struct Port {
     pub protocol: Protocol,
 }
 
+/// Network isolation of an application, no other ingress than the allowed ones being accepted.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct NetworkPolicyConfig {
+    /// Namespaces allowed to reach the application
+    #[serde(default)]
+    pub ingress_from_namespaces: Vec<String>,
+    /// Labels of the pods allowed to reach the application
+    #[serde(default)]
+    pub ingress_from_labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub deny_all_egress: bool,
+}
+
+/// Graceful shutdown of an application, letting it drain connections before being stopped.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LifecycleConfig {
+    /// Command executed in the container before it receives the termination signal
+    #[serde(default)]
+    pub pre_stop_exec: Option<Vec<String>>,
+    /// Time given to the application (pre stop hook included) to stop before being killed
+    pub grace_period_seconds: u32,
+}
+
+/// Security context of an application pods, the chart default (no constraint) is used when not set.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PodSecurityContext {
+    #[serde(default)]
+    pub run_as_non_root: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_as_user: Option<u32>,
+    /// Mount the container root filesystem as read only
+    #[serde(default)]
+    pub read_only_root_fs: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs_group: Option<u32>,
+}
+
+/// Source of a volume mounted into an application, in addition to its persistent storage.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MountSource {
+    ConfigMap { name: String },
+    Secret { name: String },
+    EmptyDir,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ConfigMount {
+    pub source: MountSource,
+    pub mount_path: String,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// Entry added to the pod `/etc/hosts`, to resolve hostnames unknown to the cluster DNS.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HostAlias {
+    pub ip: String,
+    pub hostnames: Vec<String>,
+}
+
+/// Spreads the application pods across the domains of `topology_key` node label (ex: `topology.kubernetes.io/zone`),
+/// `when_unsatisfiable` being either `DoNotSchedule` or `ScheduleAnyway`.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TopologySpreadConstraint {
+    pub max_skew: u32,
+    pub topology_key: String,
+    pub when_unsatisfiable: String,
+}
+
+/// Startup probe of an application, giving slow starting applications time to warm up before being killed by the
+/// liveness probe. It is derived from the deployment start delay when not set.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ProbeConfig {
+    #[serde(default)]
+    pub initial_delay_seconds: u32,
+    pub period_seconds: u32,
+    pub timeout_seconds: u32,
+    pub failure_threshold: u32,
+}
+
+/// Prometheus metrics exposed by an application, scraped through pod annotations and a ServiceMonitor when metrics
+/// history is enabled.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct MetricsConfig {
+    #[serde(default = "default_metrics_path_value")]
+    pub path: String,
+    pub port: u16,
+    #[serde(default)]
+    pub scrape: bool,
+}
+
+fn default_metrics_path_value() -> String {
+    "/metrics".to_string()
+}
+
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
 pub struct ApplicationAdvanceSettings {
     pub deployment_delay_start_time_sec: u32,
@@ -226,6 +360,34 @@ pub struct Application {
     pub environment_vars: BTreeMap<String, String>,
     #[serde(default)]
     pub advance_settings: ApplicationAdvanceSettings,
+    /// Long ids of the services which have to be deployed before this application
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub network_policy: Option<NetworkPolicyConfig>,
+    #[serde(default)]
+    pub lifecycle: Option<LifecycleConfig>,
+    #[serde(default)]
+    pub security_context: Option<PodSecurityContext>,
+    #[serde(default)]
+    pub config_mounts: Vec<ConfigMount>,
+    #[serde(default)]
+    pub host_aliases: Vec<HostAlias>,
+    #[serde(default)]
+    pub topology_spread: Vec<TopologySpreadConstraint>,
+    #[serde(default)]
+    pub startup_probe: Option<ProbeConfig>,
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
+    /// Explicit resources requests and limits, `total_cpus`, `cpu_burst` and `total_ram_in_mib` are used when not set
+    #[serde(default)]
+    pub cpu_request: Option<String>,
+    #[serde(default)]
+    pub cpu_limit: Option<String>,
+    #[serde(default)]
+    pub memory_request_mib: Option<u32>,
+    #[serde(default)]
+    pub memory_limit_mib: Option<u32>,
 }
 
 impl Application {
@@ -255,6 +417,16 @@ impl Application {
                 self.storage.iter().map(|s| s.to_aws_storage()).collect::<Vec<_>>(),
                 environment_variables,
                 self.advance_settings.clone(),
+                self.depends_on.clone(),
+                self.network_policy.clone(),
+                self.lifecycle.clone(),
+                self.security_context.clone(),
+                self.config_mounts.clone(),
+                self.host_aliases.clone(),
+                self.topology_spread.clone(),
+                self.startup_probe.clone(),
+                self.metrics.clone(),
+                self.to_application_resources(),
                 AwsAppExtraSettings {},
                 listeners,
                 logger.clone(),
@@ -274,6 +446,16 @@ impl Application {
                 self.storage.iter().map(|s| s.to_do_storage()).collect::<Vec<_>>(),
                 environment_variables,
                 self.advance_settings.clone(),
+                self.depends_on.clone(),
+                self.network_policy.clone(),
+                self.lifecycle.clone(),
+                self.security_context.clone(),
+                self.config_mounts.clone(),
+                self.host_aliases.clone(),
+                self.topology_spread.clone(),
+                self.startup_probe.clone(),
+                self.metrics.clone(),
+                self.to_application_resources(),
                 DoAppExtraSettings {},
                 listeners,
                 logger.clone(),
@@ -293,6 +475,16 @@ impl Application {
                 self.storage.iter().map(|s| s.to_scw_storage()).collect::<Vec<_>>(),
                 environment_variables,
                 self.advance_settings.clone(),
+                self.depends_on.clone(),
+                self.network_policy.clone(),
+                self.lifecycle.clone(),
+                self.security_context.clone(),
+                self.config_mounts.clone(),
+                self.host_aliases.clone(),
+                self.topology_spread.clone(),
+                self.startup_probe.clone(),
+                self.metrics.clone(),
+                self.to_application_resources(),
                 ScwAppExtraSettings {},
                 listeners,
                 logger.clone(),
@@ -300,6 +492,15 @@ impl Application {
         }
     }
 
+    fn to_application_resources(&self) -> ApplicationResources {
+        ApplicationResources {
+            cpu_request: self.cpu_request.clone().unwrap_or_else(|| self.total_cpus.clone()),
+            cpu_limit: self.cpu_limit.clone().unwrap_or_else(|| self.cpu_burst.clone()),
+            memory_request_mib: self.memory_request_mib.unwrap_or(self.total_ram_in_mib),
+            memory_limit_mib: self.memory_limit_mib.unwrap_or(self.total_ram_in_mib),
+        }
+    }
+
     fn to_image(&self, cr_info: &ContainerRegistryInfo) -> Image {
         Image {
             application_id: to_short_id(&self.long_id),
@@ -447,6 +648,8 @@ pub struct Storage {
     pub size_in_gib: u16,
     pub mount_point: String,
     pub snapshot_retention_in_days: u16,
+    #[serde(default)]
+    pub reclaim_policy: ReclaimPolicy,
 }
 
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
@@ -458,6 +661,19 @@ pub enum StorageType {
     FastSsd,
 }
 
+/// What happens to a storage volume once it's not claimed anymore (e.g. application deleted).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ReclaimPolicy {
+    Retain,
+    Delete,
+}
+
+impl Default for ReclaimPolicy {
+    fn default() -> Self {
+        ReclaimPolicy::Delete
+    }
+}
+
 impl Storage {
     pub fn to_aws_storage(&self) -> crate::cloud_provider::models::Storage<AwsStorageType> {
         crate::cloud_provider::models::Storage {
@@ -472,6 +688,7 @@ impl Storage {
             size_in_gib: self.size_in_gib,
             mount_point: self.mount_point.clone(),
             snapshot_retention_in_days: self.snapshot_retention_in_days,
+            reclaim_policy: self.reclaim_policy,
         }
     }
 
@@ -483,6 +700,7 @@ impl Storage {
             size_in_gib: self.size_in_gib,
             mount_point: self.mount_point.clone(),
             snapshot_retention_in_days: self.snapshot_retention_in_days,
+            reclaim_policy: self.reclaim_policy,
         }
     }
 
@@ -494,6 +712,7 @@ impl Storage {
             size_in_gib: self.size_in_gib,
             mount_point: self.mount_point.clone(),
             snapshot_retention_in_days: self.snapshot_retention_in_days,
+            reclaim_policy: self.reclaim_policy,
         }
     }
 }
@@ -511,6 +730,133 @@ pub struct Router {
     pub sticky_sessions_enabled: bool,
     pub custom_domains: Vec<CustomDomain>,
     pub routes: Vec<Route>,
+    /// proxy_read_timeout_seconds: nginx proxy read timeout, nginx default (60s) if not set
+    #[serde(default)]
+    pub proxy_read_timeout_seconds: Option<u32>,
+    /// proxy_body_size_mb: maximum request body size, nginx default (1m) if not set
+    #[serde(default)]
+    pub proxy_body_size_mb: Option<u32>,
+    /// certificate_ready_timeout_seconds: time to wait for custom domains certificate issuance, not waited if not set
+    #[serde(default)]
+    pub certificate_ready_timeout_seconds: Option<u32>,
+    /// whitelist_source_ranges: CIDR blocks allowed to reach the router, any source if empty
+    #[serde(default)]
+    pub whitelist_source_ranges: Vec<String>,
+    /// min_tls_version: oldest TLS version accepted by the router, enforced by the ingress controller (TLS 1.2 by default)
+    #[serde(default)]
+    pub min_tls_version: TlsVersion,
+    /// hsts: Strict-Transport-Security header sent by the router, not sent if not set
+    #[serde(default)]
+    pub hsts: Option<HstsConfig>,
+    /// blue_green: routes of both blue/green applications are sent to the live one, no blue/green if not set
+    #[serde(default)]
+    pub blue_green: Option<BlueGreenStrategy>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TlsVersion {
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+/// HTTP Strict Transport Security: browsers only reach the router over HTTPS for `max_age_seconds`.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HstsConfig {
+    pub max_age_seconds: u64,
+    #[serde(default)]
+    pub include_subdomains: bool,
+    #[serde(default)]
+    pub preload: bool,
+}
+
+impl HstsConfig {
+    /// Returns the `Strict-Transport-Security` header value.
+    pub fn header_value(&self) -> String {
+        let mut header_value = format!("max-age={}", self.max_age_seconds);
+        if self.include_subdomains {
+            header_value.push_str("; includeSubDomains");
+        }
+        if self.preload {
+            header_value.push_str("; preload");
+        }
+
+        header_value
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum BlueGreenSlot {
+    Blue,
+    Green,
+}
+
+impl BlueGreenSlot {
+    pub fn other(&self) -> BlueGreenSlot {
+        match self {
+            BlueGreenSlot::Blue => BlueGreenSlot::Green,
+            BlueGreenSlot::Green => BlueGreenSlot::Blue,
+        }
+    }
+}
+
+/// Blue/green deployment: both applications run side by side, the router only sending traffic to the live one.
+/// Flipping `active`, or setting `rollback`, switches the ingress backend of every route in a single update.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BlueGreenStrategy {
+    pub blue_application_name: String,
+    pub green_application_name: String,
+    pub active: BlueGreenSlot,
+    /// rollback: sends traffic back to the slot which was live before `active` has been flipped
+    #[serde(default)]
+    pub rollback: bool,
+}
+
+impl BlueGreenStrategy {
+    /// Returns the slot receiving traffic.
+    pub fn live_slot(&self) -> BlueGreenSlot {
+        match self.rollback {
+            true => self.active.other(),
+            false => self.active,
+        }
+    }
+
+    pub fn application_name(&self, slot: BlueGreenSlot) -> &str {
+        match slot {
+            BlueGreenSlot::Blue => self.blue_application_name.as_str(),
+            BlueGreenSlot::Green => self.green_application_name.as_str(),
+        }
+    }
+
+    pub fn live_application_name(&self) -> &str {
+        self.application_name(self.live_slot())
+    }
+}
+
+impl Default for TlsVersion {
+    /// Oldest TLS version accepted by the ingress controllers, set by their `ssl-protocols` config.
+    fn default() -> Self {
+        TlsVersion::Tls12
+    }
+}
+
+impl TlsVersion {
+    pub fn version(&self) -> &'static str {
+        match self {
+            TlsVersion::Tls12 => "1.2",
+            TlsVersion::Tls13 => "1.3",
+        }
+    }
+
+    /// Returns nginx `ssl_protocols`, every protocol from this version onward.
+    pub fn ssl_protocols(&self) -> &'static str {
+        match self {
+            TlsVersion::Tls12 => "TLSv1.2 TLSv1.3",
+            TlsVersion::Tls13 => "TLSv1.3",
+        }
+    }
 }
 
 impl Router {
@@ -551,6 +897,13 @@ impl Router {
                     custom_domains,
                     routes,
                     self.sticky_sessions_enabled,
+                    self.proxy_read_timeout_seconds,
+                    self.proxy_body_size_mb,
+                    self.certificate_ready_timeout_seconds,
+                    self.whitelist_source_ranges.clone(),
+                    self.min_tls_version,
+                    self.hsts.clone(),
+                    self.blue_green.clone(),
                     AwsRouterExtraSettings {},
                     listeners,
                     logger,
@@ -567,6 +920,13 @@ impl Router {
                     custom_domains,
                     routes,
                     self.sticky_sessions_enabled,
+                    self.proxy_read_timeout_seconds,
+                    self.proxy_body_size_mb,
+                    self.certificate_ready_timeout_seconds,
+                    self.whitelist_source_ranges.clone(),
+                    self.min_tls_version,
+                    self.hsts.clone(),
+                    self.blue_green.clone(),
                     DoRouterExtraSettings {},
                     listeners,
                     logger,
@@ -583,6 +943,13 @@ impl Router {
                     custom_domains,
                     routes,
                     self.sticky_sessions_enabled,
+                    self.proxy_read_timeout_seconds,
+                    self.proxy_body_size_mb,
+                    self.certificate_ready_timeout_seconds,
+                    self.whitelist_source_ranges.clone(),
+                    self.min_tls_version,
+                    self.hsts.clone(),
+                    self.blue_green.clone(),
                     ScwRouterExtraSettings {},
                     listeners,
                     logger,
@@ -635,6 +1002,106 @@ pub struct Database {
     pub activate_backups: bool,
     pub publicly_accessible: bool,
     pub mode: DatabaseMode,
+    #[serde(default)] // => None if not present in input, requested mode is used
+    pub managed: Option<bool>,
+    #[serde(default)] // => empty if not present in input, meaning deny all
+    pub allowed_cidrs: Vec<String>,
+    #[serde(default)] // => None if not present in input, default storage class is used
+    pub storage_class_name: Option<String>,
+    #[serde(default)] // => None if not present in input, disk type default performance is used
+    pub disk_iops: Option<u32>,
+    #[serde(default)] // => false if not present in input
+    pub deletion_protection: bool,
+    #[serde(default)] // => None if not present in input, cluster region is used
+    pub region: Option<String>,
+    #[serde(default)] // => None if not present in input, cluster zone is used
+    pub zone: Option<String>,
+}
+
+impl DatabaseMode {
+    /// Returns the mode to use for the database, an explicit `managed` value takes precedence over the requested mode.
+    pub fn with_managed_override(&self, managed: Option<bool>) -> DatabaseMode {
+        match managed {
+            Some(true) => DatabaseMode::MANAGED,
+            Some(false) => DatabaseMode::CONTAINER,
+            None => self.clone(),
+        }
+    }
+}
+
+/// Checks provisioned disk IOPS are only requested where terraform provisions them: AWS managed PostgreSQL and MySQL
+/// `io1` disks.
+fn validate_provisioned_disk_iops(
+    cloud_provider: CPKind,
+    kind: &DatabaseKind,
+    mode: &DatabaseMode,
+    database_options: &DatabaseOptions,
+) -> Result<(), DatabaseError> {
+    let disk_iops = match database_options.disk_iops {
+        Some(disk_iops) => disk_iops,
+        None => return Ok(()),
+    };
+
+    let is_supported = cloud_provider == CPKind::Aws
+        && *mode == DatabaseMode::MANAGED
+        && matches!(kind, DatabaseKind::Postgresql | DatabaseKind::Mysql)
+        && database_options.database_disk_type == "io1";
+    if !is_supported {
+        return Err(DatabaseError::InvalidConfig(format!(
+            "Provisioned disk IOPS are only supported by AWS managed PostgreSQL and MySQL `io1` disks, not by {} {} `{}` ones",
+            cloud_provider,
+            kind.name(),
+            database_options.database_disk_type
+        )));
+    }
+    if disk_iops == 0 {
+        return Err(DatabaseError::InvalidConfig(
+            "Provisioned disk IOPS must be greater than 0".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks allowed CIDRs are only set where terraform enforces them: AWS and Scaleway managed PostgreSQL and MySQL
+/// public endpoints, a publicly accessible one of those requiring at least one valid CIDR.
+fn validate_allowed_cidrs(
+    cloud_provider: CPKind,
+    kind: &DatabaseKind,
+    mode: &DatabaseMode,
+    database_options: &DatabaseOptions,
+) -> Result<(), DatabaseError> {
+    let is_supported = matches!(cloud_provider, CPKind::Aws | CPKind::Scw)
+        && *mode == DatabaseMode::MANAGED
+        && matches!(kind, DatabaseKind::Postgresql | DatabaseKind::Mysql);
+    if !is_supported {
+        if !database_options.allowed_cidrs.is_empty() {
+            return Err(DatabaseError::InvalidConfig(format!(
+                "Allowed CIDRs are only supported by AWS and Scaleway managed PostgreSQL and MySQL, not by {} {} {} ones",
+                cloud_provider,
+                match mode {
+                    DatabaseMode::MANAGED => "managed",
+                    DatabaseMode::CONTAINER => "container",
+                },
+                kind.name()
+            )));
+        }
+        return Ok(());
+    }
+
+    database_options
+        .validate_allowed_cidrs()
+        .map_err(|e| DatabaseError::InvalidConfig(e.message_safe()))?;
+    if cloud_provider == CPKind::Scw {
+        if let Some(cidr) = database_options.allowed_cidrs.iter().find(|cidr| cidr.contains(':')) {
+            return Err(DatabaseError::InvalidConfig(format!(
+                "Scaleway managed database ACLs only support IPv4 CIDRs, not `{}`",
+                cidr
+            )));
+        }
+    }
+
+    Ok(())
 }
 
 impl Database {
@@ -644,8 +1111,9 @@ impl Database {
         cloud_provider: &dyn CloudProvider,
         logger: Box<dyn Logger>,
     ) -> Result<Box<dyn crate::cloud_provider::service::DatabaseService>, DatabaseError> {
+        let mode = self.mode.with_managed_override(self.managed);
         let database_options = DatabaseOptions {
-            mode: self.mode.clone(),
+            mode: mode.clone(),
             login: self.username.clone(),
             password: self.password.clone(),
             host: self.fqdn.clone(),
@@ -656,13 +1124,21 @@ impl Database {
             activate_high_availability: self.activate_high_availability,
             activate_backups: self.activate_backups,
             publicly_accessible: self.publicly_accessible,
+            allowed_cidrs: self.allowed_cidrs.clone(),
+            storage_class_name: self.storage_class_name.clone(),
+            disk_iops: self.disk_iops,
+            deletion_protection: self.deletion_protection,
+            region: self.region.clone(),
+            zone: self.zone.clone(),
         };
+        validate_allowed_cidrs(cloud_provider.kind(), &self.kind, &mode, &database_options)?;
+        validate_provisioned_disk_iops(cloud_provider.kind(), &self.kind, &mode, &database_options)?;
 
         let listeners = cloud_provider.listeners().clone();
         let version = VersionsNumber::from_str(self.version.as_str())
             .map_err(|_| DatabaseError::InvalidConfig(format!("Bad version number: {}", self.version)))?;
 
-        match (cloud_provider.kind(), &self.kind, &self.mode) {
+        match (cloud_provider.kind(), &self.kind, &mode) {
             (CPKind::Aws, DatabaseKind::Postgresql, DatabaseMode::MANAGED) => {
                 let db = models::database::Database::<AWS, Managed, PostgresSQL>::new(
                     context.clone(),
@@ -676,6 +1152,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -697,6 +1174,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -719,6 +1197,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -740,6 +1219,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -761,6 +1241,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -782,6 +1263,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -803,6 +1285,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -824,6 +1307,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -846,6 +1330,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -867,6 +1352,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -888,6 +1374,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -909,6 +1396,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -946,6 +1434,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -967,6 +1456,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -988,6 +1478,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -1009,6 +1500,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -1030,6 +1522,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -1051,6 +1544,7 @@ impl Database {
                     self.total_ram_in_mib,
                     self.database_instance_type.as_str(),
                     database_options.publicly_accessible,
+                    database_options.deletion_protection,
                     database_options.port,
                     database_options,
                     listeners,
@@ -1093,7 +1587,12 @@ impl DatabaseKind {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum EnvironmentError {}
+pub enum EnvironmentError {
+    /// Id of a service being part of a dependency cycle.
+    ServiceDependencyCycle(String),
+    /// Ids of a service and of the dependency it declares, which is not part of the environment.
+    UnknownServiceDependency(String, String),
+}
 
 #[derive(Clone)]
 pub struct ProgressInfo {
@@ -1352,6 +1851,24 @@ impl Context {
         }
     }
 
+    pub fn requires_forced_deletion(&self) -> bool {
+        match &self.metadata {
+            Some(meta) => matches!(meta.forced_deletion, Some(true)),
+            _ => false,
+        }
+    }
+
+    pub fn dns_check_tld_budgets(&self) -> BTreeMap<String, DnsCheckBudget> {
+        self.metadata
+            .as_ref()
+            .and_then(|meta| meta.dns_check_tld_budgets.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn audit_context(&self) -> Option<&AuditContext> {
+        self.metadata.as_ref().and_then(|meta| meta.audit_context.as_ref())
+    }
+
     pub fn is_test_cluster(&self) -> bool {
         self.test_cluster
     }
@@ -1382,6 +1899,12 @@ pub struct Metadata {
     pub resource_expiration_in_seconds: Option<u32>,
     pub forced_upgrade: Option<bool>,
     pub disable_pleco: Option<bool>,
+    /// Deletes deletion protected databases anyway
+    pub forced_deletion: Option<bool>,
+    /// Domain resolution check budgets by TLD (e.g. `dev`), for TLDs slower to propagate than others
+    pub dns_check_tld_budgets: Option<BTreeMap<String, DnsCheckBudget>>,
+    /// Who or what triggered the request, transactions start and end are audited with it
+    pub audit_context: Option<AuditContext>,
 }
 
 impl Metadata {
@@ -1390,16 +1913,33 @@ impl Metadata {
         resource_expiration_in_seconds: Option<u32>,
         forced_upgrade: Option<bool>,
         disable_pleco: Option<bool>,
+        forced_deletion: Option<bool>,
+        dns_check_tld_budgets: Option<BTreeMap<String, DnsCheckBudget>>,
+        audit_context: Option<AuditContext>,
     ) -> Self {
         Metadata {
             dry_run_deploy,
             resource_expiration_in_seconds,
             forced_upgrade,
             disable_pleco,
+            forced_deletion,
+            dns_check_tld_budgets,
+            audit_context,
         }
     }
 }
 
+/// Who or what triggered a request, for audit purposes.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Eq, PartialEq, Hash)]
+pub struct AuditContext {
+    /// actor: user or service account requesting the operation.
+    pub actor: String,
+    /// trigger: what led to the operation (e.g. `manual`, `git-push`, `api`).
+    pub trigger: String,
+    /// request_id: id of the originating request, to correlate engine logs with it.
+    pub request_id: String,
+}
+
 /// Represent a String path instead of passing a PathBuf struct
 pub type StringPath = String;
 
@@ -1491,7 +2031,208 @@ impl ToTerraformString for Ipv4Addr {
 
 #[cfg(test)]
 mod tests {
-    use crate::io_models::{Domain, QoveryIdentifier};
+    use crate::cloud_provider::service::DatabaseOptions;
+    use crate::cloud_provider::Kind as CPKind;
+    use crate::io_models::{
+        validate_allowed_cidrs, validate_provisioned_disk_iops, DatabaseKind, DatabaseMode, Domain, QoveryIdentifier,
+    };
+
+    #[test]
+    fn test_database_mode_managed_override() {
+        // no override, requested mode is kept
+        assert!(DatabaseMode::MANAGED.with_managed_override(None) == DatabaseMode::MANAGED);
+        assert!(DatabaseMode::CONTAINER.with_managed_override(None) == DatabaseMode::CONTAINER);
+
+        // override to self-hosted
+        assert!(DatabaseMode::MANAGED.with_managed_override(Some(false)) == DatabaseMode::CONTAINER);
+        assert!(DatabaseMode::CONTAINER.with_managed_override(Some(false)) == DatabaseMode::CONTAINER);
+
+        // override to managed
+        assert!(DatabaseMode::CONTAINER.with_managed_override(Some(true)) == DatabaseMode::MANAGED);
+        assert!(DatabaseMode::MANAGED.with_managed_override(Some(true)) == DatabaseMode::MANAGED);
+    }
+
+    #[test]
+    fn test_validate_provisioned_disk_iops() {
+        let database_options = |database_disk_type: &str, disk_iops: Option<u32>| DatabaseOptions {
+            login: "login".to_string(),
+            password: "password".to_string(),
+            host: "host".to_string(),
+            port: 5432,
+            mode: DatabaseMode::MANAGED,
+            disk_size_in_gib: 100,
+            database_disk_type: database_disk_type.to_string(),
+            encrypt_disk: true,
+            activate_high_availability: false,
+            activate_backups: false,
+            publicly_accessible: false,
+            allowed_cidrs: vec![],
+            storage_class_name: None,
+            disk_iops,
+            deletion_protection: false,
+            region: None,
+            zone: None,
+        };
+
+        // compatible disk type
+        assert!(validate_provisioned_disk_iops(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options("io1", Some(3000))
+        )
+        .is_ok());
+        assert!(validate_provisioned_disk_iops(
+            CPKind::Aws,
+            &DatabaseKind::Mysql,
+            &DatabaseMode::MANAGED,
+            &database_options("io1", Some(0))
+        )
+        .is_err());
+
+        // incompatible disk types, databases and providers
+        assert!(validate_provisioned_disk_iops(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options("gp2", None)
+        )
+        .is_ok());
+        assert!(validate_provisioned_disk_iops(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options("gp2", Some(3000))
+        )
+        .is_err());
+        assert!(validate_provisioned_disk_iops(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::CONTAINER,
+            &database_options("io1", Some(3000))
+        )
+        .is_err());
+        assert!(validate_provisioned_disk_iops(
+            CPKind::Aws,
+            &DatabaseKind::Redis,
+            &DatabaseMode::MANAGED,
+            &database_options("io1", Some(3000))
+        )
+        .is_err());
+        assert!(validate_provisioned_disk_iops(
+            CPKind::Scw,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options("bssd", Some(3000))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_allowed_cidrs() {
+        let database_options = |publicly_accessible: bool, allowed_cidrs: Vec<&str>| DatabaseOptions {
+            login: "login".to_string(),
+            password: "password".to_string(),
+            host: "host".to_string(),
+            port: 5432,
+            mode: DatabaseMode::MANAGED,
+            disk_size_in_gib: 100,
+            database_disk_type: "gp2".to_string(),
+            encrypt_disk: true,
+            activate_high_availability: false,
+            activate_backups: false,
+            publicly_accessible,
+            allowed_cidrs: allowed_cidrs.into_iter().map(|c| c.to_string()).collect(),
+            storage_class_name: None,
+            disk_iops: None,
+            deletion_protection: false,
+            region: None,
+            zone: None,
+        };
+
+        // enforced by managed PostgreSQL and MySQL terraform
+        assert!(validate_allowed_cidrs(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options(true, vec!["10.0.0.0/16"])
+        )
+        .is_ok());
+        assert!(validate_allowed_cidrs(
+            CPKind::Scw,
+            &DatabaseKind::Mysql,
+            &DatabaseMode::MANAGED,
+            &database_options(true, vec!["1.2.3.4/32"])
+        )
+        .is_ok());
+        assert!(validate_allowed_cidrs(
+            CPKind::Aws,
+            &DatabaseKind::Mysql,
+            &DatabaseMode::MANAGED,
+            &database_options(false, vec![])
+        )
+        .is_ok());
+
+        // publicly accessible requires valid CIDRs
+        assert!(validate_allowed_cidrs(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options(true, vec![])
+        )
+        .is_err());
+        assert!(validate_allowed_cidrs(
+            CPKind::Scw,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options(true, vec!["10.0.0.0/42"])
+        )
+        .is_err());
+        assert!(validate_allowed_cidrs(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options(true, vec!["2001:db8::/32"])
+        )
+        .is_ok());
+        assert!(validate_allowed_cidrs(
+            CPKind::Scw,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::MANAGED,
+            &database_options(true, vec!["2001:db8::/32"])
+        )
+        .is_err());
+
+        // not enforced, rejected when set
+        assert!(validate_allowed_cidrs(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::CONTAINER,
+            &database_options(true, vec![])
+        )
+        .is_ok());
+        assert!(validate_allowed_cidrs(
+            CPKind::Aws,
+            &DatabaseKind::Postgresql,
+            &DatabaseMode::CONTAINER,
+            &database_options(true, vec!["10.0.0.0/16"])
+        )
+        .is_err());
+        assert!(validate_allowed_cidrs(
+            CPKind::Aws,
+            &DatabaseKind::Redis,
+            &DatabaseMode::MANAGED,
+            &database_options(true, vec!["10.0.0.0/16"])
+        )
+        .is_err());
+        assert!(validate_allowed_cidrs(
+            CPKind::Do,
+            &DatabaseKind::Mysql,
+            &DatabaseMode::CONTAINER,
+            &database_options(true, vec!["10.0.0.0/16"])
+        )
+        .is_err());
+    }
 
     #[test]
     fn test_domain_new() {
@@ -60,6 +60,7 @@ impl Logger for StdIoLogger {
                 EngineEvent::Info(_, _) => info!("{}", event.message(EventMessageVerbosity::FullDetails)),
                 EngineEvent::Warning(_, _) => warn!("{}", event.message(EventMessageVerbosity::FullDetails)),
                 EngineEvent::Error(_, _) => error!("{}", event.message(EventMessageVerbosity::FullDetails)),
+                EngineEvent::Audit(_, _, _) => info!("{}", event.message(EventMessageVerbosity::FullDetails)),
             };
         });
     }
@@ -192,6 +193,7 @@ mod tests {
                     EngineEvent::Info(_, _) => "INFO",
                     EngineEvent::Warning(_, _) => "WARN",
                     EngineEvent::Error(_, _) => "ERROR",
+                    EngineEvent::Audit(_, _, _) => "INFO",
                 }),
                 "{}",
                 tc.description
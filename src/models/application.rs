@@ -1,23 +1,28 @@
 use crate::build_platform::Build;
 use crate::cloud_provider::environment::Environment;
-use crate::cloud_provider::kubernetes::Kubernetes;
+use crate::cloud_provider::kubernetes::{convert_k8s_cpu_value_to_f32, Kubernetes};
 use crate::cloud_provider::models::{EnvironmentVariable, EnvironmentVariableDataTemplate, Storage};
-use crate::cloud_provider::service::{delete_stateless_service, scale_down_application};
+use crate::cloud_provider::service::{delete_stateless_service, insert_common_labels, scale_down_application};
 use crate::cloud_provider::service::{
     deploy_stateless_service_error, deploy_user_stateless_service, send_progress_on_long_task, Action, Create, Delete,
     Helm, Pause, Service, ServiceType, StatelessService,
 };
-use crate::cloud_provider::utilities::{print_action, sanitize_name};
+use crate::cloud_provider::utilities::{print_action, sanitize_name, validate_k8s_label_value};
 use crate::cloud_provider::DeploymentTarget;
 use crate::cmd::kubectl::ScalingKind::{Deployment, Statefulset};
 use crate::errors::EngineError;
 use crate::events::{EnvironmentStep, EventDetails, Stage, ToTransmitter, Transmitter};
-use crate::io_models::{ApplicationAdvanceSettings, Context, Listen, Listener, Listeners, Port, QoveryIdentifier};
+use crate::io_models::{
+    ApplicationAdvanceSettings, ConfigMount, Context, Features, HostAlias, LifecycleConfig, Listen, Listener,
+    Listeners, MetricsConfig, MountSource, NetworkPolicyConfig, PodSecurityContext, Port, ProbeConfig,
+    QoveryIdentifier, TopologySpreadConstraint,
+};
 use crate::logger::Logger;
-use crate::models::types::{CloudProvider, ToTeraContext};
+use crate::models::types::{CloudProvider, StorageType, ToTeraContext};
 use crate::utilities::to_short_id;
 use function_name::named;
 use std::marker::PhantomData;
+use std::net::IpAddr;
 use tera::Context as TeraContext;
 use uuid::Uuid;
 
@@ -27,6 +32,34 @@ pub enum ApplicationError {
     InvalidConfig(String),
 }
 
+/// Container resources requests and limits, cpu being expressed in kubernetes format (ex: `500m`, `2`).
+/// Requests being equal to limits gives the Guaranteed QoS class, otherwise pods are Burstable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApplicationResources {
+    pub cpu_request: String,
+    pub cpu_limit: String,
+    pub memory_request_mib: u32,
+    pub memory_limit_mib: u32,
+}
+
+impl ApplicationResources {
+    pub fn is_guaranteed(&self) -> bool {
+        let same_cpu = match (
+            convert_k8s_cpu_value_to_f32(self.cpu_request.clone()),
+            convert_k8s_cpu_value_to_f32(self.cpu_limit.clone()),
+        ) {
+            (Ok(request), Ok(limit)) => (request - limit).abs() < f32::EPSILON,
+            _ => false,
+        };
+
+        same_cpu && self.memory_request_mib == self.memory_limit_mib
+    }
+}
+
+/// Longest graceful shutdown accepted for an application, beyond it deployments would hang for too long.
+pub const MAX_GRACE_PERIOD_SECONDS: u32 = 3600;
+const STARTUP_PROBE_PERIOD_SECONDS: u32 = 10;
+
 pub struct Application<T: CloudProvider> {
     _marker: PhantomData<T>,
     pub(super) context: Context,
@@ -46,6 +79,16 @@ pub struct Application<T: CloudProvider> {
     pub(super) listeners: Listeners,
     pub(super) logger: Box<dyn Logger>,
     pub(super) advance_settings: ApplicationAdvanceSettings,
+    pub(super) depends_on: Vec<String>,
+    pub(super) network_policy: Option<NetworkPolicyConfig>,
+    pub(super) lifecycle: Option<LifecycleConfig>,
+    pub(super) security_context: Option<PodSecurityContext>,
+    pub(super) config_mounts: Vec<ConfigMount>,
+    pub(super) host_aliases: Vec<HostAlias>,
+    pub(super) topology_spread: Vec<TopologySpreadConstraint>,
+    pub(super) startup_probe: Option<ProbeConfig>,
+    pub(super) metrics: Option<MetricsConfig>,
+    pub(super) resources: ApplicationResources,
     pub(super) _extra_settings: T::AppExtraSettings,
 }
 
@@ -66,11 +109,43 @@ impl<T: CloudProvider> Application<T> {
         storage: Vec<Storage<T::StorageTypes>>,
         environment_variables: Vec<EnvironmentVariable>,
         advance_settings: ApplicationAdvanceSettings,
+        depends_on: Vec<String>,
+        network_policy: Option<NetworkPolicyConfig>,
+        lifecycle: Option<LifecycleConfig>,
+        security_context: Option<PodSecurityContext>,
+        config_mounts: Vec<ConfigMount>,
+        host_aliases: Vec<HostAlias>,
+        topology_spread: Vec<TopologySpreadConstraint>,
+        startup_probe: Option<ProbeConfig>,
+        metrics: Option<MetricsConfig>,
+        resources: ApplicationResources,
         extra_settings: T::AppExtraSettings,
         listeners: Listeners,
         logger: Box<dyn Logger>,
     ) -> Result<Self, ApplicationError> {
         // TODO: Check that the information provided are coherent
+        validate_resources(&resources)?;
+        validate_storage_snapshots(&storage)?;
+        validate_config_mounts(&config_mounts, &storage)?;
+        validate_host_aliases(&host_aliases)?;
+        validate_topology_spread(&topology_spread)?;
+        if let Some(startup_probe) = &startup_probe {
+            validate_startup_probe(startup_probe)?;
+        }
+        if let Some(metrics) = &metrics {
+            validate_metrics(metrics)?;
+        }
+        if let Some(lifecycle) = &lifecycle {
+            validate_lifecycle(lifecycle)?;
+        }
+        if let Some(security_context) = &security_context {
+            validate_security_context(security_context)?;
+        }
+        if let Some(network_policy) = &network_policy {
+            for value in network_policy.ingress_from_labels.values() {
+                validate_k8s_label_value(value).map_err(|e| ApplicationError::InvalidConfig(e.message_safe()))?;
+            }
+        }
 
         Ok(Self {
             _marker: PhantomData,
@@ -91,6 +166,16 @@ impl<T: CloudProvider> Application<T> {
             listeners,
             logger,
             advance_settings,
+            depends_on,
+            network_policy,
+            lifecycle,
+            security_context,
+            config_mounts,
+            host_aliases,
+            topology_spread,
+            startup_probe,
+            metrics,
+            resources,
             _extra_settings: extra_settings,
         })
     }
@@ -113,6 +198,10 @@ impl<T: CloudProvider> Application<T> {
         context.insert("total_ram_in_mib", &self.total_ram_in_mib());
         context.insert("min_instances", &self.min_instances());
         context.insert("max_instances", &self.max_instances());
+        context.insert("cpu_request", &self.resources.cpu_request);
+        context.insert("cpu_limit", &self.resources.cpu_limit);
+        context.insert("memory_request_mib", &self.resources.memory_request_mib);
+        context.insert("memory_limit_mib", &self.resources.memory_limit_mib);
 
         if let Some(private_port) = self.public_port() {
             context.insert("is_private_port", &true);
@@ -122,6 +211,7 @@ impl<T: CloudProvider> Application<T> {
         }
 
         context.insert("version", &self.commit_id());
+        insert_common_labels(&mut context, environment.organization_id.as_str(), kubernetes.id());
 
         let commit_id = self.build.image.commit_id.as_str();
         context.insert("helm_app_version", &commit_id[..7]);
@@ -142,6 +232,31 @@ impl<T: CloudProvider> Application<T> {
 
         context.insert("environment_variables", &environment_variables);
         context.insert("ports", &self.ports);
+        if let Some(network_policy) = &self.network_policy {
+            context.insert("network_policy", network_policy);
+        }
+        if let Some(lifecycle) = &self.lifecycle {
+            context.insert("lifecycle", lifecycle);
+            context.insert("termination_grace_period_seconds", &lifecycle.grace_period_seconds);
+        }
+        if let Some(security_context) = &self.security_context {
+            context.insert("security_context", security_context);
+        }
+        context.insert("config_mounts", &self.config_mounts);
+        context.insert("host_aliases", &self.host_aliases);
+        context.insert("topology_spread", &self.topology_spread);
+        context.insert("startup_probe", &self.startup_probe());
+        if let Some(metrics) = &self.metrics {
+            context.insert("metrics", metrics);
+            context.insert(
+                "service_monitor_enabled",
+                &is_service_monitor_enabled(
+                    metrics,
+                    &self.ports,
+                    self.context.is_feature_enabled(&Features::MetricsHistory),
+                ),
+            );
+        }
         context.insert("is_registry_secret", &true);
         context.insert("registry_secret", self.build().image.registry_host());
 
@@ -156,6 +271,10 @@ impl<T: CloudProvider> Application<T> {
         !self.storage.is_empty()
     }
 
+    pub fn resources(&self) -> &ApplicationResources {
+        &self.resources
+    }
+
     pub fn context(&self) -> &Context {
         &self.context
     }
@@ -211,6 +330,14 @@ impl<T: CloudProvider> Application<T> {
         self.public_port().is_some()
     }
 
+    /// Returns the startup probe, derived from the deployment start delay when not explicitly set.
+    pub fn startup_probe(&self) -> ProbeConfig {
+        match &self.startup_probe {
+            Some(startup_probe) => startup_probe.clone(),
+            None => derived_startup_probe(self.advance_settings.deployment_delay_start_time_sec),
+        }
+    }
+
     pub fn logger(&self) -> &dyn Logger {
         &*self.logger
     }
@@ -341,6 +468,10 @@ where
     fn long_id(&self) -> &Uuid {
         &self.long_id
     }
+
+    fn depends_on(&self) -> Vec<String> {
+        self.depends_on.clone()
+    }
 }
 
 impl<T: CloudProvider> Helm for Application<T> {
@@ -378,7 +509,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
         print_action(
             T::short_name(),
-            "application",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -396,7 +527,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
         print_action(
             T::short_name(),
-            "application",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -416,7 +547,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Pause));
         print_action(
             T::short_name(),
-            "application",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -437,7 +568,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Pause));
         print_action(
             T::short_name(),
-            "application",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -457,7 +588,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Delete));
         print_action(
             T::short_name(),
-            "application",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
@@ -478,7 +609,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Delete));
         print_action(
             T::short_name(),
-            "application",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
@@ -503,6 +634,8 @@ where
 pub trait ApplicationService: StatelessService {
     fn get_build(&self) -> &Build;
     fn get_build_mut(&mut self) -> &mut Build;
+    fn resources(&self) -> &ApplicationResources;
+    fn is_stateful(&self) -> bool;
 }
 
 impl<T: CloudProvider> ApplicationService for Application<T>
@@ -516,4 +649,870 @@ where
     fn get_build_mut(&mut self) -> &mut Build {
         self.build_mut()
     }
+
+    fn resources(&self) -> &ApplicationResources {
+        self.resources()
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.is_stateful()
+    }
+}
+
+fn validate_storage_snapshots<S: StorageType>(storage: &[Storage<S>]) -> Result<(), ApplicationError> {
+    match storage
+        .iter()
+        .find(|s| s.snapshot_retention_in_days > 0 && !s.storage_type.supports_snapshots())
+    {
+        Some(s) => Err(ApplicationError::InvalidConfig(format!(
+            "storage `{}` type doesn't support snapshots, its snapshot retention must be 0 days",
+            s.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn validate_config_mounts<S>(config_mounts: &[ConfigMount], storage: &[Storage<S>]) -> Result<(), ApplicationError> {
+    let mut mount_paths: Vec<&str> = storage.iter().map(|s| s.mount_point.as_str()).collect();
+
+    for config_mount in config_mounts {
+        let mount_path = config_mount.mount_path.as_str();
+        if !mount_path.starts_with('/') || mount_path.split('/').any(|segment| segment == "..") {
+            return Err(ApplicationError::InvalidConfig(format!(
+                "mount path `{}` must be an absolute path without `..`",
+                mount_path
+            )));
+        }
+        if mount_paths.contains(&mount_path) {
+            return Err(ApplicationError::InvalidConfig(format!(
+                "mount path `{}` is used by several volumes",
+                mount_path
+            )));
+        }
+        mount_paths.push(mount_path);
+
+        match &config_mount.source {
+            MountSource::ConfigMap { name } | MountSource::Secret { name } if name.is_empty() => {
+                return Err(ApplicationError::InvalidConfig(format!(
+                    "mount path `{}` source name can't be empty",
+                    mount_path
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_host_aliases(host_aliases: &[HostAlias]) -> Result<(), ApplicationError> {
+    for host_alias in host_aliases {
+        if host_alias.ip.parse::<IpAddr>().is_err() {
+            return Err(ApplicationError::InvalidConfig(format!(
+                "host alias ip `{}` is not a valid IP address",
+                host_alias.ip
+            )));
+        }
+        if host_alias.hostnames.is_empty() || host_alias.hostnames.iter().any(|hostname| hostname.is_empty()) {
+            return Err(ApplicationError::InvalidConfig(format!(
+                "host alias `{}` requires at least one non empty hostname",
+                host_alias.ip
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+const TOPOLOGY_SPREAD_WHEN_UNSATISFIABLE_VALUES: [&str; 2] = ["DoNotSchedule", "ScheduleAnyway"];
+
+fn validate_topology_spread(topology_spread: &[TopologySpreadConstraint]) -> Result<(), ApplicationError> {
+    for constraint in topology_spread {
+        if constraint.max_skew == 0 {
+            return Err(ApplicationError::InvalidConfig(format!(
+                "topology spread constraint on `{}` requires a max skew greater than 0",
+                constraint.topology_key
+            )));
+        }
+        if constraint.topology_key.is_empty() {
+            return Err(ApplicationError::InvalidConfig(
+                "topology spread constraint requires a topology key".to_string(),
+            ));
+        }
+        if !TOPOLOGY_SPREAD_WHEN_UNSATISFIABLE_VALUES.contains(&constraint.when_unsatisfiable.as_str()) {
+            return Err(ApplicationError::InvalidConfig(format!(
+                "topology spread constraint `whenUnsatisfiable` must be one of {:?}, got `{}`",
+                TOPOLOGY_SPREAD_WHEN_UNSATISFIABLE_VALUES, constraint.when_unsatisfiable
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_resources(resources: &ApplicationResources) -> Result<(), ApplicationError> {
+    let to_cpu = |cpu: &str| {
+        convert_k8s_cpu_value_to_f32(cpu.to_string())
+            .map_err(|_| ApplicationError::InvalidConfig(format!("`{}` is not a valid cpu value", cpu)))
+    };
+
+    if to_cpu(&resources.cpu_request)? > to_cpu(&resources.cpu_limit)? {
+        return Err(ApplicationError::InvalidConfig(format!(
+            "cpu request `{}` is greater than cpu limit `{}`",
+            resources.cpu_request, resources.cpu_limit
+        )));
+    }
+    if resources.memory_request_mib > resources.memory_limit_mib {
+        return Err(ApplicationError::InvalidConfig(format!(
+            "memory request {}Mi is greater than memory limit {}Mi",
+            resources.memory_request_mib, resources.memory_limit_mib
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_lifecycle(lifecycle: &LifecycleConfig) -> Result<(), ApplicationError> {
+    if lifecycle.grace_period_seconds > MAX_GRACE_PERIOD_SECONDS {
+        return Err(ApplicationError::InvalidConfig(format!(
+            "grace period of {} seconds is greater than the maximum allowed of {} seconds",
+            lifecycle.grace_period_seconds, MAX_GRACE_PERIOD_SECONDS
+        )));
+    }
+
+    if let Some(pre_stop_exec) = &lifecycle.pre_stop_exec {
+        if pre_stop_exec.is_empty() {
+            return Err(ApplicationError::InvalidConfig(
+                "pre stop hook command can't be empty".to_string(),
+            ));
+        }
+        if lifecycle.grace_period_seconds == 0 {
+            return Err(ApplicationError::InvalidConfig(
+                "grace period can't be 0 seconds when a pre stop hook is set".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Startup probe failing for as long as the start timeout, liveness and readiness probes being disabled until it succeeds.
+fn derived_startup_probe(start_timeout_in_seconds: u32) -> ProbeConfig {
+    let failure_threshold =
+        (start_timeout_in_seconds + STARTUP_PROBE_PERIOD_SECONDS - 1) / STARTUP_PROBE_PERIOD_SECONDS;
+
+    ProbeConfig {
+        initial_delay_seconds: 0,
+        period_seconds: STARTUP_PROBE_PERIOD_SECONDS,
+        timeout_seconds: 1,
+        failure_threshold: failure_threshold.max(1),
+    }
+}
+
+fn validate_startup_probe(startup_probe: &ProbeConfig) -> Result<(), ApplicationError> {
+    if startup_probe.period_seconds == 0 || startup_probe.timeout_seconds == 0 || startup_probe.failure_threshold == 0 {
+        return Err(ApplicationError::InvalidConfig(
+            "startup probe period, timeout and failure threshold must be greater than 0".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_metrics(metrics: &MetricsConfig) -> Result<(), ApplicationError> {
+    if metrics.port == 0 {
+        return Err(ApplicationError::InvalidConfig("metrics port can't be 0".to_string()));
+    }
+    if !metrics.path.starts_with('/') {
+        return Err(ApplicationError::InvalidConfig(format!(
+            "metrics path `{}` must start with `/`",
+            metrics.path
+        )));
+    }
+
+    Ok(())
+}
+
+/// A ServiceMonitor can only target a port exposed by the application service, and needs prometheus to be set up to
+/// keep metrics history.
+fn is_service_monitor_enabled(metrics: &MetricsConfig, ports: &[Port], metrics_history_enabled: bool) -> bool {
+    metrics.scrape && metrics_history_enabled && ports.iter().any(|port| port.port == metrics.port)
+}
+
+fn validate_security_context(security_context: &PodSecurityContext) -> Result<(), ApplicationError> {
+    if security_context.run_as_non_root && security_context.run_as_user == Some(0) {
+        return Err(ApplicationError::InvalidConfig(
+            "can't run as user 0 (root) when running as non root is required".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build_platform::{Build, GitRepository, Image};
+    use crate::cloud_provider::environment::Environment;
+    use crate::cloud_provider::kubernetes::test_utils::TestKubernetes;
+    use crate::cloud_provider::kubernetes::{deploy_environment, Kind as KubernetesKind};
+    use crate::cloud_provider::models::Storage;
+    use crate::cloud_provider::service::{insert_common_labels, Action};
+    use crate::cloud_provider::DeploymentTarget;
+    use crate::cmd::docker::Docker;
+    use crate::errors::{EngineError, Tag};
+    use crate::events::{EnvironmentStep, Stage};
+    use crate::io_models::{
+        ApplicationAdvanceSettings, ConfigMount, Context, HostAlias, LifecycleConfig, MetricsConfig, MountSource,
+        NetworkPolicyConfig, PodSecurityContext, ProbeConfig, ReclaimPolicy, TopologySpreadConstraint,
+    };
+    use crate::logger::StdIoLogger;
+    use crate::models::application::{
+        derived_startup_probe, is_service_monitor_enabled, validate_config_mounts, validate_host_aliases,
+        validate_lifecycle, validate_metrics, validate_resources, validate_security_context, validate_startup_probe,
+        validate_storage_snapshots, validate_topology_spread, Application, ApplicationResources,
+        MAX_GRACE_PERIOD_SECONDS,
+    };
+    use crate::models::aws::AwsAppExtraSettings;
+    use crate::models::digital_ocean::{DoAppExtraSettings, DoStorageType};
+    use crate::models::scaleway::ScwStorageType;
+    use crate::models::types::{CloudProvider, ToTeraContext, AWS, DO};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+    use tera::{Context as TeraContext, Tera};
+    use url::Url;
+    use uuid::Uuid;
+
+    fn test_context() -> Context {
+        Context::new(
+            "organization-id".to_string(),
+            "cluster-id".to_string(),
+            "execution-id".to_string(),
+            "/tmp".to_string(),
+            "lib".to_string(),
+            true,
+            None,
+            vec![],
+            None,
+            Docker::new_with_options(false, None).expect("docker without buildkit should be created"),
+        )
+    }
+
+    /// Smallest valid application, tests set the fields they are about before rendering it.
+    fn test_application<T: CloudProvider>(extra_settings: T::AppExtraSettings) -> Application<T> {
+        let commit_id = "fc575a2f3be0b9100492c8a463bf18134a8698a5".to_string();
+        let registry_url = Url::parse("https://registry.qovery.io").expect("registry url should be valid");
+
+        Application::new(
+            test_context(),
+            Uuid::from_str("1b8f8c2e-1c8a-4b64-9b4e-7d4b8d4c3e21").expect("uuid should be valid"),
+            Action::Create,
+            "app",
+            vec![],
+            "1".to_string(),
+            "1".to_string(),
+            256,
+            1,
+            1,
+            Build {
+                git_repository: GitRepository {
+                    url: Url::parse("https://github.com/Qovery/engine-testing.git").expect("git url should be valid"),
+                    credentials: None,
+                    ssh_keys: vec![],
+                    commit_id: commit_id.clone(),
+                    dockerfile_path: None,
+                    root_path: PathBuf::from("."),
+                    buildpack_language: None,
+                },
+                image: Image {
+                    application_id: "app-id".to_string(),
+                    name: "app".to_string(),
+                    tag: "tag".to_string(),
+                    commit_id,
+                    registry_name: "registry".to_string(),
+                    registry_docker_json_config: None,
+                    registry_url,
+                    repository_name: "app".to_string(),
+                },
+                environment_variables: BTreeMap::new(),
+                disable_cache: false,
+            },
+            vec![],
+            vec![],
+            ApplicationAdvanceSettings::default(),
+            vec![],
+            None,
+            None,
+            None,
+            vec![],
+            vec![],
+            vec![],
+            None,
+            None,
+            ApplicationResources {
+                cpu_request: "500m".to_string(),
+                cpu_limit: "1".to_string(),
+                memory_request_mib: 256,
+                memory_limit_mib: 256,
+            },
+            extra_settings,
+            vec![],
+            Box::new(StdIoLogger::new()),
+        )
+        .expect("test application should be valid")
+    }
+
+    /// Builds the same templates context as a real deployment of the application.
+    fn application_tera_context<T: CloudProvider>(application: &Application<T>) -> Result<TeraContext, EngineError>
+    where
+        Application<T>: ToTeraContext,
+    {
+        let kubernetes = TestKubernetes::new(test_context(), KubernetesKind::Eks);
+        let environment = Environment::new(
+            "environment-id",
+            "project-id",
+            "owner-id",
+            "organization-id",
+            Action::Create,
+            vec![],
+            vec![],
+            vec![],
+            None,
+            None,
+        );
+        application.to_tera_context(&DeploymentTarget {
+            kubernetes: &kubernetes,
+            environment: &environment,
+        })
+    }
+
+    /// Renders a chart template with the same context as a real deployment of the application.
+    fn render_application<T: CloudProvider>(application: &Application<T>, template: &str) -> String
+    where
+        Application<T>: ToTeraContext,
+    {
+        let context = application_tera_context(application).expect("application context should be built");
+
+        Tera::one_off(template, &context, false).expect("application template should render")
+    }
+
+    fn render_custom_network_policy(network_policy: Option<NetworkPolicyConfig>) -> String {
+        let mut context = TeraContext::new();
+        context.insert("id", "z1234abc");
+        context.insert("long_id", "1b8f8c2e-1c8a-4b64-9b4e-7d4b8d4c3e21");
+        context.insert("owner_id", "owner-id");
+        context.insert("environment_id", "environment-id");
+        context.insert("sanitized_name", "app-z1234abc");
+        context.insert("namespace", "project-id-environment-id");
+        insert_common_labels(&mut context, "organization-id", "cluster-id");
+        if let Some(network_policy) = &network_policy {
+            context.insert("network_policy", network_policy);
+        }
+
+        Tera::one_off(
+            include_str!("../../lib/aws/charts/q-application/templates/custom_networkpolicy.j2.yaml"),
+            &context,
+            false,
+        )
+        .expect("network policy template should render")
+    }
+
+    #[test]
+    fn test_duplicated_selectors() {
+        // setup: applications sharing the same id
+        let application = test_application::<DO>(DoAppExtraSettings {});
+        let expected_selector = application.selector().expect("application should have a selector");
+        let event_details = application.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
+        let environment = Environment::new(
+            "environment-id",
+            "project-id",
+            "owner-id",
+            "organization-id",
+            Action::Create,
+            vec![
+                Box::new(application),
+                Box::new(test_application::<DO>(DoAppExtraSettings {})),
+            ],
+            vec![],
+            vec![],
+            None,
+            None,
+        );
+        let kubernetes = TestKubernetes::new(test_context(), KubernetesKind::Eks);
+
+        // execute:
+        let duplicated_selectors = environment.duplicated_selectors();
+        let result = deploy_environment(&kubernetes, &environment, event_details, &StdIoLogger::new());
+
+        // verify:
+        assert_eq!(vec![expected_selector.clone()], duplicated_selectors);
+        let error = result.expect_err("environment with duplicated selectors should be rejected");
+        assert_eq!(&Tag::DuplicatedServiceSelectors, error.tag());
+        assert!(error.user_log_message().contains(&expected_selector));
+    }
+
+    #[test]
+    fn test_custom_network_policy_rendering() {
+        // execute & verify: no policy by default
+        assert!(render_custom_network_policy(None).trim().is_empty());
+
+        // execute:
+        let rendered = render_custom_network_policy(Some(NetworkPolicyConfig {
+            ingress_from_namespaces: vec!["monitoring".to_string()],
+            ingress_from_labels: Default::default(),
+            deny_all_egress: true,
+        }));
+
+        // verify:
+        assert!(rendered.contains("kind: NetworkPolicy"));
+        assert!(rendered.contains("name: app-z1234abc-custom"));
+        assert!(rendered.contains("  - Egress\n"));
+        assert!(rendered.contains("  egress: []"));
+        assert!(rendered.contains("kubernetes.io/metadata.name: monitoring"));
+        assert!(!rendered.contains("podSelector:\n        matchLabels"));
+    }
+
+    #[test]
+    fn test_storage_reclaim_policy_rendering() {
+        // setup:
+        let mut application = test_application::<DO>(DoAppExtraSettings {});
+        let template = include_str!("../../lib/digitalocean/charts/q-application/templates/statefulset.j2.yaml");
+        let storage_classes =
+            include_str!("../../lib/digitalocean/bootstrap/charts/q-storageclass/templates/storageclass.yaml");
+
+        // execute & verify: released volumes are deleted by default
+        application.storage = vec![storage(DoStorageType::Standard, 0)];
+        let rendered = render_application(&application, template);
+        assert_eq!(ReclaimPolicy::Delete, ReclaimPolicy::default());
+        assert!(rendered.contains("      storageClassName: do-block-storage\n"));
+
+        // execute & verify: retained volumes are provisioned by a storage class keeping them
+        application.storage = vec![Storage {
+            reclaim_policy: ReclaimPolicy::Retain,
+            ..storage(DoStorageType::Standard, 0)
+        }];
+        let rendered = render_application(&application, template);
+        assert!(rendered.contains("      storageClassName: do-block-storage-retain\n"));
+        assert!(!rendered.contains("reclaimPolicy"));
+        assert!(storage_classes.contains("  name: do-block-storage-retain\n"));
+        assert!(storage_classes.ends_with("reclaimPolicy: Retain\n"));
+    }
+
+    #[test]
+    fn test_common_labels_rendering() {
+        // setup:
+        let mut application = test_application::<DO>(DoAppExtraSettings {});
+        application.max_instances = 2;
+        let templates = vec![
+            include_str!("../../lib/digitalocean/charts/q-application/templates/deployment.j2.yaml"),
+            include_str!("../../lib/digitalocean/charts/q-application/templates/secret.j2.yaml"),
+            include_str!("../../lib/digitalocean/charts/q-application/templates/horizontal_autoscaler.j2.yaml"),
+        ];
+
+        for template in templates {
+            // execute:
+            let rendered = render_application(&application, template);
+
+            // verify: every resource carries the organization and cluster labels
+            let resources = rendered.matches("\nkind: ").count();
+            assert!(resources > 0);
+            assert!(
+                rendered
+                    .matches("    qovery.com/organization: \"organization-id\"\n")
+                    .count()
+                    >= resources
+            );
+            assert!(rendered.matches("    qovery.com/cluster: \"cluster-id\"\n").count() >= resources);
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_pre_stop_hook_rendering() {
+        // setup:
+        let lifecycle = LifecycleConfig {
+            pre_stop_exec: Some(vec!["/bin/sh".to_string(), "-c".to_string(), "sleep 15".to_string()]),
+            grace_period_seconds: 90,
+        };
+        let mut application = test_application::<AWS>(AwsAppExtraSettings {});
+        application.lifecycle = Some(lifecycle.clone());
+
+        // execute:
+        let rendered = render_application(
+            &application,
+            include_str!("../../lib/aws/charts/q-application/templates/deployment.j2.yaml"),
+        );
+
+        // verify:
+        assert!(validate_lifecycle(&lifecycle).is_ok());
+        assert!(rendered.contains("terminationGracePeriodSeconds: 90"));
+        assert!(rendered.contains(
+            "          lifecycle:\n            preStop:\n              exec:\n                command:\n                  - \"/bin/sh\"\n                  - \"-c\"\n                  - \"sleep 15\"\n"
+        ));
+    }
+
+    #[test]
+    fn test_lifecycle_validation() {
+        // execute & verify:
+        assert!(validate_lifecycle(&LifecycleConfig {
+            pre_stop_exec: None,
+            grace_period_seconds: 0,
+        })
+        .is_ok());
+        assert!(validate_lifecycle(&LifecycleConfig {
+            pre_stop_exec: None,
+            grace_period_seconds: MAX_GRACE_PERIOD_SECONDS + 1,
+        })
+        .is_err());
+        assert!(validate_lifecycle(&LifecycleConfig {
+            pre_stop_exec: Some(vec![]),
+            grace_period_seconds: 30,
+        })
+        .is_err());
+        assert!(validate_lifecycle(&LifecycleConfig {
+            pre_stop_exec: Some(vec!["sleep".to_string(), "5".to_string()]),
+            grace_period_seconds: 0,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_guaranteed_and_burstable_resources() {
+        // setup:
+        let guaranteed = ApplicationResources {
+            cpu_request: "1".to_string(),
+            cpu_limit: "1000m".to_string(),
+            memory_request_mib: 512,
+            memory_limit_mib: 512,
+        };
+        let burstable = ApplicationResources {
+            cpu_request: "250m".to_string(),
+            cpu_limit: "1".to_string(),
+            memory_request_mib: 256,
+            memory_limit_mib: 512,
+        };
+
+        // execute & verify:
+        assert!(validate_resources(&guaranteed).is_ok());
+        assert!(guaranteed.is_guaranteed());
+        assert!(validate_resources(&burstable).is_ok());
+        assert!(!burstable.is_guaranteed());
+
+        // execute & verify: requests can't be greater than limits
+        assert!(validate_resources(&ApplicationResources {
+            cpu_request: "2".to_string(),
+            ..burstable.clone()
+        })
+        .is_err());
+        assert!(validate_resources(&ApplicationResources {
+            memory_request_mib: 1024,
+            ..burstable.clone()
+        })
+        .is_err());
+        assert!(validate_resources(&ApplicationResources {
+            cpu_limit: "one".to_string(),
+            ..burstable
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_memory_request_validation() {
+        // setup: total ram being enough, only the memory request is too low
+        let mut application = test_application::<AWS>(AwsAppExtraSettings {});
+        application.resources.memory_request_mib = 8;
+        application.resources.memory_limit_mib = 256;
+
+        // execute:
+        let error = application_tera_context(&application).expect_err("memory request should be too low");
+
+        // verify:
+        assert_eq!(256, application.total_ram_in_mib());
+        assert_eq!(&Tag::K8sValidateRequiredMemoryError, error.tag());
+
+        // execute & verify: memory request above the minimum is accepted
+        application.resources.memory_request_mib = 16;
+        assert!(application_tera_context(&application).is_ok());
+    }
+
+    fn storage<T>(storage_type: T, snapshot_retention_in_days: u16) -> Storage<T> {
+        Storage {
+            id: "z5678def".to_string(),
+            name: "photos".to_string(),
+            storage_type,
+            size_in_gib: 10,
+            mount_point: "/mnt/photos".to_string(),
+            snapshot_retention_in_days,
+            reclaim_policy: ReclaimPolicy::Delete,
+        }
+    }
+
+    #[test]
+    fn test_storage_snapshot_retention_validation() {
+        // execute & verify: supported with retention
+        assert!(validate_storage_snapshots(&[storage(DoStorageType::Standard, 7)]).is_ok());
+        assert!(validate_storage_snapshots(&[storage(ScwStorageType::BlockSsd, 7)]).is_ok());
+
+        // execute & verify: unsupported with retention
+        assert!(validate_storage_snapshots(&[storage(ScwStorageType::LocalSsd, 0)]).is_ok());
+        assert!(validate_storage_snapshots(&[
+            storage(ScwStorageType::BlockSsd, 7),
+            storage(ScwStorageType::LocalSsd, 7)
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_non_root_security_context_rendering() {
+        // setup:
+        let security_context = PodSecurityContext {
+            run_as_non_root: true,
+            run_as_user: Some(1000),
+            read_only_root_fs: true,
+            fs_group: Some(2000),
+        };
+        let mut application = test_application::<AWS>(AwsAppExtraSettings {});
+        let template = include_str!("../../lib/aws/charts/q-application/templates/deployment.j2.yaml");
+
+        // execute & verify: chart default without security context
+        let rendered = render_application(&application, template);
+        assert!(rendered.contains("      securityContext: {}\n"));
+        assert!(!rendered.contains("readOnlyRootFilesystem"));
+
+        // execute & verify: non root security context
+        application.security_context = Some(security_context.clone());
+        let rendered = render_application(&application, template);
+        assert!(validate_security_context(&security_context).is_ok());
+        assert!(rendered.contains(
+            "      securityContext:\n        runAsNonRoot: true\n        runAsUser: 1000\n        fsGroup: 2000\n"
+        ));
+        assert!(rendered.contains("          securityContext:\n            readOnlyRootFilesystem: true\n"));
+
+        // execute & verify: root user and group are explicit values, unset ones are left out
+        application.security_context = Some(PodSecurityContext {
+            run_as_non_root: false,
+            run_as_user: Some(0),
+            read_only_root_fs: false,
+            fs_group: Some(0),
+        });
+        let rendered = render_application(&application, template);
+        assert!(rendered.contains(
+            "      securityContext:\n        runAsNonRoot: false\n        runAsUser: 0\n        fsGroup: 0\n"
+        ));
+        application.security_context = Some(PodSecurityContext {
+            run_as_user: None,
+            fs_group: None,
+            ..security_context.clone()
+        });
+        let rendered = render_application(&application, template);
+        assert!(!rendered.contains("runAsUser"));
+        assert!(!rendered.contains("fsGroup"));
+
+        // root user can't be used when running as non root
+        assert!(validate_security_context(&PodSecurityContext {
+            run_as_user: Some(0),
+            ..security_context
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_config_map_mount_rendering() {
+        // setup:
+        let config_mounts = vec![
+            ConfigMount {
+                source: MountSource::ConfigMap {
+                    name: "nginx-config".to_string(),
+                },
+                mount_path: "/etc/nginx/conf.d".to_string(),
+                read_only: true,
+            },
+            ConfigMount {
+                source: MountSource::EmptyDir,
+                mount_path: "/tmp/cache".to_string(),
+                read_only: false,
+            },
+        ];
+        let mut application = test_application::<AWS>(AwsAppExtraSettings {});
+        application.config_mounts = config_mounts.clone();
+
+        // execute:
+        let rendered = render_application(
+            &application,
+            include_str!("../../lib/aws/charts/q-application/templates/deployment.j2.yaml"),
+        );
+
+        // verify:
+        assert!(validate_config_mounts(&config_mounts, &[storage(DoStorageType::Standard, 0)]).is_ok());
+        assert!(rendered.contains(
+            "          volumeMounts:\n            - name: config-mount-0\n              mountPath: /etc/nginx/conf.d\n              readOnly: true\n"
+        ));
+        assert!(rendered.contains(
+            "      volumes:\n        - name: config-mount-0\n          configMap:\n            name: nginx-config\n        - name: config-mount-1\n          emptyDir: {}\n"
+        ));
+    }
+
+    #[test]
+    fn test_config_mounts_validation() {
+        // setup:
+        let config_mount = |mount_path: &str, name: &str| ConfigMount {
+            source: MountSource::Secret { name: name.to_string() },
+            mount_path: mount_path.to_string(),
+            read_only: true,
+        };
+        let storage = vec![storage(DoStorageType::Standard, 0)];
+
+        // execute & verify:
+        assert!(validate_config_mounts(&[config_mount("/etc/secrets", "secret")], &storage).is_ok());
+        assert!(validate_config_mounts(&[config_mount("etc/secrets", "secret")], &storage).is_err());
+        assert!(validate_config_mounts(&[config_mount("/etc/../secrets", "secret")], &storage).is_err());
+        assert!(validate_config_mounts(&[config_mount("/etc/secrets", "")], &storage).is_err());
+        // conflicts with persistent storage or another mount
+        assert!(validate_config_mounts(&[config_mount("/mnt/photos", "secret")], &storage).is_err());
+        assert!(validate_config_mounts(
+            &[
+                config_mount("/etc/secrets", "secret"),
+                config_mount("/etc/secrets", "other")
+            ],
+            &storage
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_host_aliases_rendering() {
+        // setup:
+        let host_aliases = vec![HostAlias {
+            ip: "10.0.0.12".to_string(),
+            hostnames: vec!["db.internal".to_string(), "cache.internal".to_string()],
+        }];
+        let mut application = test_application::<AWS>(AwsAppExtraSettings {});
+        application.host_aliases = host_aliases.clone();
+
+        // execute:
+        let rendered = render_application(
+            &application,
+            include_str!("../../lib/aws/charts/q-application/templates/deployment.j2.yaml"),
+        );
+
+        // verify:
+        assert!(validate_host_aliases(&host_aliases).is_ok());
+        assert!(rendered.contains(
+            "      hostAliases:\n        - ip: \"10.0.0.12\"\n          hostnames:\n            - \"db.internal\"\n            - \"cache.internal\"\n"
+        ));
+    }
+
+    #[test]
+    fn test_host_aliases_validation() {
+        // setup:
+        let host_alias = |ip: &str, hostnames: Vec<&str>| HostAlias {
+            ip: ip.to_string(),
+            hostnames: hostnames.into_iter().map(|x| x.to_string()).collect(),
+        };
+
+        // execute & verify:
+        assert!(validate_host_aliases(&[host_alias("10.0.0.12", vec!["db.internal"])]).is_ok());
+        assert!(validate_host_aliases(&[host_alias("fd00::12", vec!["db.internal"])]).is_ok());
+        assert!(validate_host_aliases(&[host_alias("10.0.0.256", vec!["db.internal"])]).is_err());
+        assert!(validate_host_aliases(&[host_alias("db.internal", vec!["db.internal"])]).is_err());
+        assert!(validate_host_aliases(&[host_alias("10.0.0.12", vec![])]).is_err());
+        assert!(validate_host_aliases(&[host_alias("10.0.0.12", vec![""])]).is_err());
+    }
+
+    #[test]
+    fn test_topology_spread_rendering() {
+        // setup:
+        let topology_spread = vec![TopologySpreadConstraint {
+            max_skew: 1,
+            topology_key: "topology.kubernetes.io/zone".to_string(),
+            when_unsatisfiable: "DoNotSchedule".to_string(),
+        }];
+        let mut application = test_application::<AWS>(AwsAppExtraSettings {});
+        application.topology_spread = topology_spread.clone();
+
+        // execute:
+        let rendered = render_application(
+            &application,
+            include_str!("../../lib/aws/charts/q-application/templates/deployment.j2.yaml"),
+        );
+
+        // verify:
+        assert!(validate_topology_spread(&topology_spread).is_ok());
+        assert!(rendered.contains(&format!(
+            "      topologySpreadConstraints:\n        - maxSkew: 1\n          topologyKey: \"topology.kubernetes.io/zone\"\n          whenUnsatisfiable: DoNotSchedule\n          labelSelector:\n            matchLabels:\n              appId: {}\n",
+            application.id()
+        )));
+    }
+
+    #[test]
+    fn test_topology_spread_validation() {
+        // setup:
+        let constraint = |max_skew: u32, topology_key: &str, when_unsatisfiable: &str| TopologySpreadConstraint {
+            max_skew,
+            topology_key: topology_key.to_string(),
+            when_unsatisfiable: when_unsatisfiable.to_string(),
+        };
+
+        // execute & verify:
+        assert!(validate_topology_spread(&[constraint(1, "topology.kubernetes.io/zone", "DoNotSchedule")]).is_ok());
+        assert!(validate_topology_spread(&[constraint(2, "kubernetes.io/hostname", "ScheduleAnyway")]).is_ok());
+        assert!(validate_topology_spread(&[constraint(0, "topology.kubernetes.io/zone", "DoNotSchedule")]).is_err());
+        assert!(validate_topology_spread(&[constraint(1, "", "DoNotSchedule")]).is_err());
+        assert!(validate_topology_spread(&[constraint(1, "topology.kubernetes.io/zone", "Whatever")]).is_err());
+    }
+
+    #[test]
+    fn test_derived_startup_probe() {
+        // execute:
+        let startup_probe = derived_startup_probe(300);
+
+        // verify:
+        assert_eq!(30, startup_probe.failure_threshold);
+        assert_eq!(300, startup_probe.failure_threshold * startup_probe.period_seconds);
+        assert!(validate_startup_probe(&startup_probe).is_ok());
+        // partial periods are rounded up, and the probe is never given less than one try
+        assert_eq!(4, derived_startup_probe(35).failure_threshold);
+        assert_eq!(1, derived_startup_probe(0).failure_threshold);
+        assert!(validate_startup_probe(&ProbeConfig {
+            failure_threshold: 0,
+            ..startup_probe
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_metrics_scrape_annotations_rendering() {
+        // setup:
+        let metrics = MetricsConfig {
+            path: "/metrics".to_string(),
+            port: 9100,
+            scrape: true,
+        };
+        let mut application = test_application::<DO>(DoAppExtraSettings {});
+        application.metrics = Some(metrics.clone());
+        let template = include_str!("../../lib/digitalocean/charts/q-application/templates/deployment.j2.yaml");
+
+        // execute:
+        let rendered = render_application(&application, template);
+
+        // verify:
+        assert!(validate_metrics(&metrics).is_ok());
+        assert!(rendered.contains(
+            "        prometheus.io/scrape: \"true\"\n        prometheus.io/path: \"/metrics\"\n        prometheus.io/port: \"9100\"\n"
+        ));
+        // service monitor needs metrics history and the metrics port to be exposed by the service
+        assert!(!is_service_monitor_enabled(&metrics, &[], true));
+
+        // execute & verify: no annotation when scraping is disabled
+        application.metrics = Some(MetricsConfig {
+            scrape: false,
+            ..metrics.clone()
+        });
+        let rendered = render_application(&application, template);
+        assert!(!rendered.contains("prometheus.io/scrape"));
+        assert!(validate_metrics(&MetricsConfig {
+            path: "metrics".to_string(),
+            ..metrics
+        })
+        .is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use crate::cloud_provider::kubernetes::validate_k8s_required_cpu_and_burstable;
+use crate::cloud_provider::kubernetes::{validate_k8s_required_cpu_and_burstable, validate_k8s_required_memory};
 use crate::cloud_provider::models::StorageDataTemplate;
 use crate::cloud_provider::DeploymentTarget;
 use crate::errors::EngineError;
@@ -14,12 +14,14 @@ impl ToTeraContext for Application<AWS> {
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::LoadConfiguration));
         let mut context = self.default_tera_context(target.kubernetes, target.environment);
 
+        validate_k8s_required_memory(&self.service_type(), self.resources.memory_request_mib, event_details.clone())?;
+
         let cpu_limits = match validate_k8s_required_cpu_and_burstable(
             &ListenersHelper::new(&self.listeners),
             self.context.execution_id(),
             &self.id,
-            self.total_cpus(),
-            self.cpu_burst(),
+            self.resources.cpu_request.clone(),
+            self.resources.cpu_limit.clone(),
             event_details.clone(),
             self.logger(),
         ) {
@@ -27,13 +29,13 @@ impl ToTeraContext for Application<AWS> {
             Err(e) => {
                 return Err(EngineError::new_k8s_validate_required_cpu_and_burstable_error(
                     event_details,
-                    self.total_cpus(),
-                    self.cpu_burst(),
+                    self.resources.cpu_request.clone(),
+                    self.resources.cpu_limit.clone(),
                     e,
                 ));
             }
         };
-        context.insert("cpu_burst", &cpu_limits.cpu_limit);
+        context.insert("cpu_limit", &cpu_limits.cpu_limit);
 
         let storage = self
             .storage
@@ -51,6 +53,7 @@ impl ToTeraContext for Application<AWS> {
                 size_in_gib: s.size_in_gib,
                 mount_point: s.mount_point.clone(),
                 snapshot_retention_in_days: s.snapshot_retention_in_days,
+                reclaim_policy: s.reclaim_policy,
             })
             .collect::<Vec<_>>();
 
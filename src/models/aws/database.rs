@@ -225,6 +225,9 @@ where
         context.insert("database_disk_size_in_gib", &options.disk_size_in_gib);
         context.insert("database_instance_type", &self.database_instance_type);
         context.insert("database_disk_type", &options.database_disk_type);
+        if let Some(disk_iops) = options.disk_iops {
+            context.insert("database_disk_iops", &disk_iops);
+        }
         context.insert("encrypt_disk", &options.encrypt_disk);
         context.insert("database_ram_size_in_mib", &self.total_ram_in_mib);
         context.insert("database_total_cpus", &self.total_cpus);
@@ -236,6 +239,8 @@ where
         context.insert("final_snapshot_name", &format!("qovery-{}-final-snap", self.id));
         context.insert("delete_automated_backups", &self.context().is_test_cluster());
         context.insert("publicly_accessible", &options.publicly_accessible);
+        context.insert("allowed_cidrs", &options.allowed_cidrs());
+        context.insert("deletion_protection", &options.deletion_protection);
 
         if self.context.resource_expiration_in_seconds().is_some() {
             context.insert("resource_expiration_in_seconds", &self.context.resource_expiration_in_seconds())
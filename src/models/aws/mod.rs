@@ -1,10 +1,10 @@
 mod application;
 mod database;
-mod database_utils;
+pub(crate) mod database_utils;
 mod router;
 
-use crate::models::types::CloudProvider;
 use crate::models::types::AWS;
+use crate::models::types::{CloudProvider, StorageType};
 
 pub struct AwsAppExtraSettings {}
 pub struct AwsDbExtraSettings {}
@@ -44,3 +44,10 @@ pub enum AwsStorageType {
     GP2,
     IO1,
 }
+
+impl StorageType for AwsStorageType {
+    fn supports_snapshots(&self) -> bool {
+        // every EBS volume type can be snapshotted
+        true
+    }
+}
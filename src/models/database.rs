@@ -1,3 +1,4 @@
+use crate::cloud_provider::kubernetes::validate_k8s_required_memory;
 use crate::cloud_provider::service::{
     check_service_version, default_tera_context, delete_stateful_service, deploy_stateful_service, get_tfstate_name,
     get_tfstate_suffix, scale_down_database, send_progress_on_long_task, Action, Create, DatabaseOptions,
@@ -45,6 +46,38 @@ impl DatabaseMode for Container {
     }
 }
 
+/// Checks the database can be paused, only container databases can be as they are scaled down to 0.
+fn check_pause_is_supported<M: DatabaseMode>(
+    db_type: service::DatabaseType,
+    event_details: EventDetails,
+) -> Result<(), EngineError> {
+    if M::is_managed() {
+        return Err(EngineError::new_cannot_pause_managed_database(
+            event_details,
+            db_type.to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks the database can be deleted, a deletion protected database is only deleted when the deletion is forced.
+fn check_delete_is_allowed(
+    database_name: &str,
+    deletion_protection: bool,
+    forced_deletion: bool,
+    event_details: EventDetails,
+) -> Result<(), EngineError> {
+    if deletion_protection && !forced_deletion {
+        return Err(EngineError::new_cannot_delete_protected_database(
+            event_details,
+            database_name.to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /////////////////////////////////////////////////////////////////
 // Database types, will be only used as a marker
 pub struct PostgresSQL {}
@@ -83,6 +116,7 @@ pub struct Database<C: CloudProvider, M: DatabaseMode, T: DatabaseType<C, M>> {
     pub(super) total_ram_in_mib: u32,
     pub(super) database_instance_type: String,
     pub(super) publicly_accessible: bool,
+    pub(super) deletion_protection: bool,
     pub(super) private_port: u16,
     pub(super) options: T::DatabaseOptions,
     pub(super) listeners: Listeners,
@@ -102,6 +136,7 @@ impl<C: CloudProvider, M: DatabaseMode, T: DatabaseType<C, M>> Database<C, M, T>
         total_ram_in_mib: u32,
         database_instance_type: &str,
         publicly_accessible: bool,
+        deletion_protection: bool,
         private_port: u16,
         options: T::DatabaseOptions,
         listeners: Listeners,
@@ -123,6 +158,7 @@ impl<C: CloudProvider, M: DatabaseMode, T: DatabaseType<C, M>> Database<C, M, T>
             total_ram_in_mib,
             database_instance_type: database_instance_type.to_string(),
             publicly_accessible,
+            deletion_protection,
             private_port,
             options,
             listeners,
@@ -289,7 +325,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
         print_action(
             C::short_name(),
-            T::db_type().to_string().as_str(),
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
@@ -306,7 +342,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
         print_action(
             C::short_name(),
-            T::db_type().to_string().as_str(),
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
@@ -319,6 +355,7 @@ where
                 vec![&self.fqdn],
                 self.context.execution_id(),
                 self.context.execution_id(),
+                &self.context.dns_check_tld_budgets(),
                 event_details,
                 self.logger(),
             )?;
@@ -331,7 +368,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
         print_action(
             C::short_name(),
-            T::db_type().to_string().as_str(),
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -351,13 +388,16 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Pause));
         print_action(
             C::short_name(),
-            T::db_type().to_string().as_str(),
+            self.kind_label(),
             function_name!(),
             self.name(),
-            event_details,
+            event_details.clone(),
             self.logger(),
         );
 
+        // managed databases have no statefulset to scale down
+        check_pause_is_supported::<M>(T::db_type(), event_details)?;
+
         send_progress_on_long_task(self, Action::Pause, || scale_down_database(target, self, 0))
     }
 
@@ -370,7 +410,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Pause));
         print_action(
             C::short_name(),
-            T::db_type().to_string().as_str(),
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -390,12 +430,18 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Delete));
         print_action(
             C::short_name(),
-            T::db_type().to_string().as_str(),
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
             self.logger(),
         );
+        check_delete_is_allowed(
+            self.name(),
+            self.deletion_protection,
+            self.context.requires_forced_deletion(),
+            event_details.clone(),
+        )?;
 
         send_progress_on_long_task(self, Action::Delete, || {
             delete_stateful_service(target, self, event_details.clone(), self.logger())
@@ -411,7 +457,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Delete));
         print_action(
             C::short_name(),
-            T::db_type().to_string().as_str(),
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -461,6 +507,8 @@ where
         options: &DatabaseOptions,
     ) -> Result<TeraContext, EngineError> {
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::LoadConfiguration));
+        validate_k8s_required_memory(&self.service_type(), self.total_ram_in_mib, event_details.clone())?;
+
         let kubernetes = target.kubernetes;
         let environment = target.environment;
         let mut context = default_tera_context(self, kubernetes, environment);
@@ -497,6 +545,9 @@ where
         context.insert("database_disk_size_in_gib", &options.disk_size_in_gib);
         context.insert("database_instance_type", &self.database_instance_type);
         context.insert("database_disk_type", &options.database_disk_type);
+        if let Some(storage_class_name) = &options.storage_class_name {
+            context.insert("storage_class_name", storage_class_name);
+        }
         context.insert("database_ram_size_in_mib", &self.total_ram_in_mib);
         context.insert("database_total_cpus", &self.total_cpus);
         context.insert("database_fqdn", &options.host.as_str());
@@ -512,3 +563,179 @@ where
         Ok(context)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::service::{insert_common_labels, DatabaseType};
+    use crate::errors::Tag;
+    use crate::events::{EnvironmentStep, EventDetails, Stage, Transmitter};
+    use crate::io_models::QoveryIdentifier;
+    use crate::models::database::{check_delete_is_allowed, check_pause_is_supported, Container, Managed};
+    use tera::{Context as TeraContext, Tera};
+
+    #[test]
+    fn test_check_pause_is_supported() {
+        // setup:
+        let event_details = EventDetails::new(
+            None,
+            QoveryIdentifier::new_from_long_id("organization_id".to_string()),
+            QoveryIdentifier::new_from_long_id("cluster_id".to_string()),
+            QoveryIdentifier::new_from_long_id("execution_id".to_string()),
+            None,
+            Stage::Environment(EnvironmentStep::Pause),
+            Transmitter::Database("database_id".to_string(), "postgresql".to_string(), "database".to_string()),
+        );
+
+        // execute & verify: container databases are scaled down
+        assert!(check_pause_is_supported::<Container>(DatabaseType::PostgreSQL, event_details.clone()).is_ok());
+
+        // execute & verify: managed databases cannot be paused
+        let error = check_pause_is_supported::<Managed>(DatabaseType::PostgreSQL, event_details)
+            .expect_err("managed database pause should fail");
+        assert_eq!(Tag::CannotPauseManagedDatabase, *error.tag());
+        assert!(error.tag().is_user_error());
+    }
+
+    #[test]
+    fn test_check_delete_is_allowed() {
+        // setup:
+        let event_details = EventDetails::new(
+            None,
+            QoveryIdentifier::new_from_long_id("organization_id".to_string()),
+            QoveryIdentifier::new_from_long_id("cluster_id".to_string()),
+            QoveryIdentifier::new_from_long_id("execution_id".to_string()),
+            None,
+            Stage::Environment(EnvironmentStep::Delete),
+            Transmitter::Database("database_id".to_string(), "postgresql".to_string(), "database".to_string()),
+        );
+
+        // execute & verify: unprotected databases are deleted
+        assert!(check_delete_is_allowed("database", false, false, event_details.clone()).is_ok());
+
+        // execute & verify: protected databases are not deleted
+        let error = check_delete_is_allowed("database", true, false, event_details.clone())
+            .expect_err("protected database deletion should fail");
+        assert_eq!(Tag::CannotDeleteProtectedDatabase, *error.tag());
+        assert!(error.tag().is_user_error());
+
+        // execute & verify: protected databases are deleted when the deletion is forced
+        assert!(check_delete_is_allowed("database", true, true, event_details).is_ok());
+    }
+
+    const SELF_HOSTED_DATABASE_VALUES: &[(&str, &str)] = &[
+        (
+            include_str!("../../lib/aws/chart_values/mongodb/q-values.j2.yaml"),
+            "aws-ebs-gp2-0",
+        ),
+        (
+            include_str!("../../lib/aws/chart_values/mysql/q-values.j2.yaml"),
+            "aws-ebs-gp2-0",
+        ),
+        (
+            include_str!("../../lib/aws/chart_values/postgresql/q-values.j2.yaml"),
+            "aws-ebs-gp2-0",
+        ),
+        (
+            include_str!("../../lib/aws/chart_values/redis/q-values.j2.yaml"),
+            "aws-ebs-gp2-0",
+        ),
+        (
+            include_str!("../../lib/digitalocean/chart_values/mongodb/q-values.j2.yaml"),
+            "scw-sbv-ssd-0",
+        ),
+        (
+            include_str!("../../lib/digitalocean/chart_values/mysql/q-values.j2.yaml"),
+            "scw-sbv-ssd-0",
+        ),
+        (
+            include_str!("../../lib/digitalocean/chart_values/postgresql/q-values.j2.yaml"),
+            "scw-sbv-ssd-0",
+        ),
+        (
+            include_str!("../../lib/digitalocean/chart_values/redis/q-values.j2.yaml"),
+            "scw-sbv-ssd-0",
+        ),
+        (
+            include_str!("../../lib/scaleway/chart_values/mongodb/q-values.j2.yaml"),
+            "scw-sbv-ssd-0",
+        ),
+        (
+            include_str!("../../lib/scaleway/chart_values/mysql/q-values.j2.yaml"),
+            "scw-sbv-ssd-0",
+        ),
+        (
+            include_str!("../../lib/scaleway/chart_values/postgresql/q-values.j2.yaml"),
+            "scw-sbv-ssd-0",
+        ),
+        (
+            include_str!("../../lib/scaleway/chart_values/redis/q-values.j2.yaml"),
+            "scw-sbv-ssd-0",
+        ),
+    ];
+
+    fn render_database_values(template: &str, storage_class_name: Option<&str>) -> String {
+        let mut context = TeraContext::new();
+        for (key, value) in &[
+            ("id", "z1234abc"),
+            ("long_id", "1b8f8c2e-1c8a-4b64-9b4e-7d4b8d4c3e21"),
+            ("owner_id", "owner-id"),
+            ("environment_id", "environment-id"),
+            ("sanitized_name", "postgresql-z1234abc"),
+            ("service_name", "postgresql-z1234abc"),
+            ("fqdn", "postgresql-z1234abc.example.com"),
+            ("version", "13"),
+            ("database_db_name", "postgres"),
+            ("database_login", "superuser"),
+            ("database_password", "password"),
+            ("database_disk_type", "scw-sbv-ssd-0"),
+            ("database_total_cpus", "500m"),
+            ("total_cpus", "500m"),
+        ] {
+            context.insert(*key, value);
+        }
+        context.insert("database_disk_size_in_gib", &10);
+        context.insert("database_ram_size_in_mib", &512);
+        context.insert("total_ram_in_mib", &512);
+        context.insert("private_port", &5432);
+        context.insert("publicly_accessible", &false);
+        insert_common_labels(&mut context, "organization-id", "cluster-id");
+        if let Some(storage_class_name) = storage_class_name {
+            context.insert("storage_class_name", storage_class_name);
+        }
+
+        Tera::one_off(template, &context, false).expect("database values template should render")
+    }
+
+    #[test]
+    fn test_self_hosted_database_storage_class_name() {
+        let uses_storage_class = |rendered: &str, storage_class: &str| {
+            rendered.contains(&format!("storageClass: {}\n", storage_class))
+                || rendered.contains(&format!("storageClass: \"{}\"\n", storage_class))
+        };
+
+        for (template, default_storage_class) in SELF_HOSTED_DATABASE_VALUES {
+            // execute & verify: provider storage class is used by default
+            let rendered = render_database_values(template, None);
+            assert!(uses_storage_class(&rendered, default_storage_class));
+
+            // execute & verify: storage class override
+            let rendered = render_database_values(template, Some("custom-storage-class"));
+            assert!(uses_storage_class(&rendered, "custom-storage-class"));
+            assert!(!uses_storage_class(&rendered, default_storage_class));
+        }
+    }
+
+    #[test]
+    fn test_self_hosted_database_common_labels() {
+        // execute:
+        let rendered = render_database_values(
+            include_str!("../../lib/scaleway/chart_values/postgresql/q-values.j2.yaml"),
+            None,
+        );
+
+        // verify: labels are set next to the database ones
+        assert!(rendered.contains(
+            "    databaseLongId: 1b8f8c2e-1c8a-4b64-9b4e-7d4b8d4c3e21\n    qovery.com/cluster: \"cluster-id\"\n    qovery.com/organization: \"organization-id\"\n"
+        ));
+    }
+}
@@ -1,9 +1,13 @@
+use crate::cloud_provider::service::DatabaseType;
 use crate::errors::CommandError;
+use crate::models::aws::database_utils::{
+    managed_mongodb_versions, managed_mysql_versions, managed_postgres_versions, managed_redis_versions,
+};
 use crate::models::types::VersionsNumber;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-pub fn get_self_hosted_postgres_version(requested_version: String) -> Result<String, CommandError> {
+fn self_hosted_postgres_versions() -> HashMap<String, String> {
     let mut supported_postgres_versions = HashMap::new();
 
     // https://hub.docker.com/r/bitnami/postgresql/tags?page=1&ordering=last_updated
@@ -24,10 +28,14 @@ pub fn get_self_hosted_postgres_version(requested_version: String) -> Result<Str
     let v13 = generate_supported_version(13, 1, 4, Some(0), Some(0), None);
     supported_postgres_versions.extend(v13);
 
-    get_supported_version_to_use("Postgresql", supported_postgres_versions, requested_version)
+    supported_postgres_versions
 }
 
-pub fn get_self_hosted_mysql_version(requested_version: String) -> Result<String, CommandError> {
+pub fn get_self_hosted_postgres_version(requested_version: String) -> Result<String, CommandError> {
+    get_supported_version_to_use("Postgresql", self_hosted_postgres_versions(), requested_version)
+}
+
+fn self_hosted_mysql_versions() -> HashMap<String, String> {
     let mut supported_mysql_versions = HashMap::new();
     // https://hub.docker.com/r/bitnami/mysql/tags?page=1&ordering=last_updated
 
@@ -39,10 +47,14 @@ pub fn get_self_hosted_mysql_version(requested_version: String) -> Result<String
     let v8 = generate_supported_version(8, 0, 0, Some(11), Some(24), None);
     supported_mysql_versions.extend(v8);
 
-    get_supported_version_to_use("MySQL", supported_mysql_versions, requested_version)
+    supported_mysql_versions
 }
 
-pub fn get_self_hosted_mongodb_version(requested_version: String) -> Result<String, CommandError> {
+pub fn get_self_hosted_mysql_version(requested_version: String) -> Result<String, CommandError> {
+    get_supported_version_to_use("MySQL", self_hosted_mysql_versions(), requested_version)
+}
+
+fn self_hosted_mongodb_versions() -> HashMap<String, String> {
     let mut supported_mongodb_versions = HashMap::new();
 
     // https://hub.docker.com/r/bitnami/mongodb/tags?page=1&ordering=last_updated
@@ -63,10 +75,42 @@ pub fn get_self_hosted_mongodb_version(requested_version: String) -> Result<Stri
     let mongo_version = generate_supported_version(4, 4, 4, Some(0), Some(4), None);
     supported_mongodb_versions.extend(mongo_version);
 
-    get_supported_version_to_use("MongoDB", supported_mongodb_versions, requested_version)
+    supported_mongodb_versions
 }
 
-pub fn get_self_hosted_redis_version(requested_version: String) -> Result<String, CommandError> {
+pub fn get_self_hosted_mongodb_version(requested_version: String) -> Result<String, CommandError> {
+    get_supported_version_to_use("MongoDB", self_hosted_mongodb_versions(), requested_version)
+}
+
+fn self_hosted_mariadb_versions() -> HashMap<String, String> {
+    let mut supported_mariadb_versions = HashMap::new();
+
+    // https://hub.docker.com/r/bitnami/mariadb/tags?page=1&ordering=last_updated
+
+    // v10.3
+    let mariadb_version = generate_supported_version(10, 3, 3, Some(0), Some(32), None);
+    supported_mariadb_versions.extend(mariadb_version);
+
+    // v10.4
+    let mariadb_version = generate_supported_version(10, 4, 4, Some(0), Some(22), None);
+    supported_mariadb_versions.extend(mariadb_version);
+
+    // v10.5
+    let mariadb_version = generate_supported_version(10, 5, 5, Some(0), Some(13), None);
+    supported_mariadb_versions.extend(mariadb_version);
+
+    // v10.6
+    let mariadb_version = generate_supported_version(10, 6, 6, Some(0), Some(5), None);
+    supported_mariadb_versions.extend(mariadb_version);
+
+    supported_mariadb_versions
+}
+
+pub fn get_self_hosted_mariadb_version(requested_version: String) -> Result<String, CommandError> {
+    get_supported_version_to_use("MariaDB", self_hosted_mariadb_versions(), requested_version)
+}
+
+fn self_hosted_redis_versions() -> HashMap<String, String> {
     let mut supported_redis_versions = HashMap::with_capacity(4);
     // https://hub.docker.com/r/bitnami/redis/tags?page=1&ordering=last_updated
 
@@ -75,7 +119,43 @@ pub fn get_self_hosted_redis_version(requested_version: String) -> Result<String
     supported_redis_versions.insert("5".to_string(), "5.0.10".to_string());
     supported_redis_versions.insert("5.0".to_string(), "5.0.10".to_string());
 
-    get_supported_version_to_use("Redis", supported_redis_versions, requested_version)
+    supported_redis_versions
+}
+
+pub fn get_self_hosted_redis_version(requested_version: String) -> Result<String, CommandError> {
+    get_supported_version_to_use("Redis", self_hosted_redis_versions(), requested_version)
+}
+
+/// Returns the sorted major and major.minor versions a user can pick for a database type.
+/// Managed versions are the AWS ones, the only provider offering every database type as a managed service.
+pub fn supported_versions(db_type: DatabaseType, managed: bool) -> Vec<VersionsNumber> {
+    let all_supported_versions = match (db_type, managed) {
+        (DatabaseType::PostgreSQL, false) => self_hosted_postgres_versions(),
+        (DatabaseType::PostgreSQL, true) => managed_postgres_versions(),
+        (DatabaseType::MySQL, false) => self_hosted_mysql_versions(),
+        (DatabaseType::MySQL, true) => managed_mysql_versions(),
+        (DatabaseType::MongoDB, false) => self_hosted_mongodb_versions(),
+        (DatabaseType::MongoDB, true) => managed_mongodb_versions(),
+        (DatabaseType::Redis, false) => self_hosted_redis_versions(),
+        (DatabaseType::Redis, true) => managed_redis_versions(),
+    };
+
+    let mut versions: Vec<VersionsNumber> = all_supported_versions
+        .keys()
+        .filter_map(|version| VersionsNumber::from_str(version).ok())
+        .filter(|version| version.patch.is_none())
+        .collect();
+
+    let sort_key = |version: &VersionsNumber| {
+        (
+            version.major.parse::<u32>().unwrap_or(0),
+            version.minor.as_ref().map(|minor| minor.parse::<u32>().unwrap_or(0)),
+        )
+    };
+    versions.sort_by_key(sort_key);
+    versions.dedup();
+
+    versions
 }
 
 pub fn get_supported_version_to_use(
@@ -197,3 +277,39 @@ pub fn generate_supported_version(
 
     supported_versions
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::service::DatabaseType;
+    use crate::models::database_utils::{get_self_hosted_mariadb_version, supported_versions};
+
+    #[test]
+    fn test_supported_versions_self_hosted_postgres() {
+        // execute:
+        let versions = supported_versions(DatabaseType::PostgreSQL, false);
+
+        // verify:
+        let majors: Vec<String> = versions
+            .iter()
+            .filter(|version| version.minor.is_none())
+            .map(|version| version.major.clone())
+            .collect();
+        assert_eq!(majors, vec!["10", "11", "12", "13"]);
+        assert_eq!(versions.first().map(|v| v.to_string()), Some("10".to_string()));
+        assert!(versions.iter().all(|version| version.patch.is_none()));
+    }
+
+    #[test]
+    fn test_get_self_hosted_mariadb_version() {
+        // execute & verify: major only targets the latest minor and patch
+        assert_eq!(get_self_hosted_mariadb_version("10".to_string()), Ok("10.6.5".to_string()));
+
+        // execute & verify: major.minor targets the latest patch of this minor
+        assert_eq!(get_self_hosted_mariadb_version("10.5".to_string()), Ok("10.5.13".to_string()));
+        assert_eq!(get_self_hosted_mariadb_version("10.3.4".to_string()), Ok("10.3.4".to_string()));
+
+        // execute & verify: unsupported version
+        assert!(get_self_hosted_mariadb_version("9".to_string()).is_err());
+        assert!(get_self_hosted_mariadb_version("10.2".to_string()).is_err());
+    }
+}
@@ -1,11 +1,10 @@
-use crate::cloud_provider::kubernetes::validate_k8s_required_cpu_and_burstable;
+use crate::cloud_provider::kubernetes::{validate_k8s_required_cpu_and_burstable, validate_k8s_required_memory};
 use crate::cloud_provider::models::StorageDataTemplate;
 use crate::cloud_provider::DeploymentTarget;
 use crate::errors::EngineError;
 use crate::events::{EnvironmentStep, Stage};
 use crate::io_models::ListenersHelper;
 use crate::models::application::Application;
-use crate::models::digital_ocean::DoStorageType;
 use crate::models::types::{ToTeraContext, DO};
 use tera::Context as TeraContext;
 
@@ -16,12 +15,14 @@ impl ToTeraContext for Application<DO> {
         let environment = target.environment;
         let mut context = self.default_tera_context(kubernetes, environment);
 
+        validate_k8s_required_memory(&self.service_type(), self.resources.memory_request_mib, event_details.clone())?;
+
         let cpu_limits = match validate_k8s_required_cpu_and_burstable(
             &ListenersHelper::new(&self.listeners),
             self.context.execution_id(),
             &self.id,
-            self.total_cpus(),
-            self.cpu_burst(),
+            self.resources.cpu_request.clone(),
+            self.resources.cpu_limit.clone(),
             event_details.clone(),
             self.logger(),
         ) {
@@ -29,13 +30,13 @@ impl ToTeraContext for Application<DO> {
             Err(e) => {
                 return Err(EngineError::new_k8s_validate_required_cpu_and_burstable_error(
                     event_details,
-                    self.total_cpus(),
-                    self.cpu_burst(),
+                    self.resources.cpu_request.clone(),
+                    self.resources.cpu_limit.clone(),
                     e,
                 ));
             }
         };
-        context.insert("cpu_burst", &cpu_limits.cpu_limit);
+        context.insert("cpu_limit", &cpu_limits.cpu_limit);
 
         // This is specific to digital ocean as it is them that create the registry secret
         // we don't have the hand on it
@@ -47,13 +48,11 @@ impl ToTeraContext for Application<DO> {
             .map(|s| StorageDataTemplate {
                 id: s.id.clone(),
                 name: s.name.clone(),
-                storage_type: match s.storage_type {
-                    DoStorageType::Standard => "do-block-storage",
-                }
-                .to_string(),
+                storage_type: s.storage_type.to_string(),
                 size_in_gib: s.size_in_gib,
                 mount_point: s.mount_point.clone(),
                 snapshot_retention_in_days: s.snapshot_retention_in_days,
+                reclaim_policy: s.reclaim_policy,
             })
             .collect::<Vec<_>>();
 
@@ -3,8 +3,8 @@ mod database;
 mod router;
 
 use crate::errors::CommandError;
-use crate::models::types::CloudProvider;
 use crate::models::types::DO;
+use crate::models::types::{CloudProvider, StorageType};
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
@@ -40,11 +40,41 @@ impl CloudProvider for DO {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum DoStorageType {
     Standard,
 }
 
+impl StorageType for DoStorageType {
+    fn supports_snapshots(&self) -> bool {
+        match self {
+            DoStorageType::Standard => true,
+        }
+    }
+}
+
+impl Display for DoStorageType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            DoStorageType::Standard => write!(f, "do-block-storage"),
+        }
+    }
+}
+
+impl FromStr for DoStorageType {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<DoStorageType, CommandError> {
+        match s {
+            "do-block-storage" => Ok(DoStorageType::Standard),
+            _ => Err(CommandError::new_from_safe_message(format!(
+                "`{}` storage type is not supported",
+                s
+            ))),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum DoRegion {
     NewYorkCity1,
@@ -126,3 +156,20 @@ impl FromStr for DoRegion {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::digital_ocean::DoStorageType;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_storage_type_round_trip() {
+        for storage_type in vec![DoStorageType::Standard] {
+            assert_eq!(DoStorageType::from_str(&storage_type.to_string()), Ok(storage_type));
+        }
+
+        assert_eq!(DoStorageType::Standard.to_string(), "do-block-storage");
+        assert!(DoStorageType::from_str("gp2").is_err());
+        assert!(DoStorageType::from_str("").is_err());
+    }
+}
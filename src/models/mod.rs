@@ -1,7 +1,7 @@
 pub mod application;
 pub mod aws;
 pub mod database;
-pub(crate) mod database_utils;
+pub mod database_utils;
 pub mod digital_ocean;
 pub mod router;
 pub mod scaleway;
@@ -4,13 +4,20 @@ use crate::cloud_provider::service::{
     default_tera_context, delete_stateless_service, deploy_stateless_service_error, send_progress_on_long_task, Action,
     Create, Delete, Helm, Pause, RouterService, Service, ServiceType, StatelessService,
 };
-use crate::cloud_provider::utilities::{check_cname_for, print_action, sanitize_name};
+use crate::cloud_provider::utilities::{
+    print_action, sanitize_k8s_label_value, sanitize_name, validate_cidr, DnsCheck, DnsCheckKind,
+};
 use crate::cloud_provider::DeploymentTarget;
 use crate::cmd::helm;
 use crate::cmd::helm::to_engine_error;
-use crate::errors::EngineError;
-use crate::events::{EngineEvent, EnvironmentStep, EventMessage, Stage, ToTransmitter, Transmitter};
-use crate::io_models::{Context, Listen, Listener, Listeners};
+use crate::cmd::kubectl::{
+    kubectl_exec_get_external_ingress_hostname, kubectl_exec_is_certificate_ready, KubectlExecError,
+};
+use crate::errors::{CommandError, EngineError};
+use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage, ToTransmitter, Transmitter};
+use crate::io_models::{
+    BlueGreenSlot, BlueGreenStrategy, Context, HstsConfig, Listen, Listener, Listeners, TlsVersion,
+};
 use crate::logger::Logger;
 use crate::models::types::CloudProvider;
 use crate::models::types::ToTeraContext;
@@ -18,6 +25,8 @@ use crate::utilities::to_short_id;
 use function_name::named;
 use std::borrow::Borrow;
 use std::marker::PhantomData;
+use std::thread;
+use std::time::{Duration, Instant};
 use tera::Context as TeraContext;
 use uuid::Uuid;
 
@@ -38,6 +47,12 @@ pub struct Router<T: CloudProvider> {
     pub(crate) custom_domains: Vec<CustomDomain>,
     pub(crate) sticky_sessions_enabled: bool,
     pub(crate) routes: Vec<Route>,
+    pub(crate) proxy_read_timeout_seconds: Option<u32>,
+    pub(crate) proxy_body_size_mb: Option<u32>,
+    pub(crate) certificate_ready_timeout: Option<Duration>,
+    pub(crate) whitelist_source_ranges: Vec<String>,
+    pub(crate) hsts: Option<HstsConfig>,
+    pub(crate) blue_green: Option<BlueGreenStrategy>,
     pub(crate) listeners: Listeners,
     pub(crate) logger: Box<dyn Logger>,
     pub(crate) _extra_settings: T::RouterExtraSettings,
@@ -53,10 +68,38 @@ impl<T: CloudProvider> Router<T> {
         custom_domains: Vec<CustomDomain>,
         routes: Vec<Route>,
         sticky_sessions_enabled: bool,
+        proxy_read_timeout_seconds: Option<u32>,
+        proxy_body_size_mb: Option<u32>,
+        certificate_ready_timeout_seconds: Option<u32>,
+        whitelist_source_ranges: Vec<String>,
+        min_tls_version: TlsVersion,
+        hsts: Option<HstsConfig>,
+        blue_green: Option<BlueGreenStrategy>,
         extra_settings: T::RouterExtraSettings,
         listeners: Listeners,
         logger: Box<dyn Logger>,
     ) -> Result<Self, RouterError> {
+        validate_default_domain(default_domain)?;
+
+        validate_positive_proxy_setting("proxy_read_timeout_seconds", proxy_read_timeout_seconds)?;
+        validate_positive_proxy_setting("proxy_body_size_mb", proxy_body_size_mb)?;
+        validate_whitelist_source_ranges(&whitelist_source_ranges)?;
+        validate_min_tls_version(min_tls_version)?;
+        validate_hsts(&hsts)?;
+        validate_blue_green(&blue_green)?;
+
+        let mut domains: Vec<&str> = Vec::with_capacity(custom_domains.len());
+        for custom_domain in &custom_domains {
+            if domains.contains(&custom_domain.domain.as_str()) {
+                return Err(RouterError::InvalidConfig(format!(
+                    "custom domain `{}` is declared several times",
+                    custom_domain.domain
+                )));
+            }
+            domains.push(custom_domain.domain.as_str());
+        }
+        validate_custom_domains_differ_from_default(default_domain, &custom_domains)?;
+
         Ok(Self {
             _marker: PhantomData,
             context,
@@ -68,6 +111,12 @@ impl<T: CloudProvider> Router<T> {
             custom_domains,
             sticky_sessions_enabled,
             routes,
+            proxy_read_timeout_seconds,
+            proxy_body_size_mb,
+            certificate_ready_timeout: certificate_ready_timeout_seconds.map(|x| Duration::from_secs(x as u64)),
+            whitelist_source_ranges,
+            hsts,
+            blue_green,
             listeners,
             logger,
             _extra_settings: extra_settings,
@@ -97,7 +146,7 @@ impl<T: CloudProvider> Router<T> {
             .custom_domains
             .iter()
             .map(|cd| {
-                let domain_hash = crate::crypto::to_sha1_truncate_16(cd.domain.as_str());
+                let domain_hash = custom_domain_hash(self.id.as_str(), cd.domain.as_str());
                 CustomDomainDataTemplate {
                     domain: cd.domain.clone(),
                     domain_hash,
@@ -106,23 +155,46 @@ impl<T: CloudProvider> Router<T> {
             })
             .collect::<Vec<_>>();
 
-        let route_data_templates = self
-            .routes
-            .iter()
-            .filter_map(|r| {
-                match applications
-                    .iter()
-                    .find(|app| app.name() == r.application_name.as_str())
-                {
-                    Some(application) => application.private_port().map(|private_port| RouteDataTemplate {
-                        path: r.path.clone(),
-                        application_name: application.sanitized_name(),
-                        application_port: private_port,
-                    }),
-                    _ => None,
+        let routes = match &self.blue_green {
+            Some(blue_green) => {
+                for slot in &[BlueGreenSlot::Blue, BlueGreenSlot::Green] {
+                    let application_name = blue_green.application_name(*slot);
+                    if !applications.iter().any(|app| app.name() == application_name) {
+                        self.logger().log(EngineEvent::Warning(
+                            event_details.clone(),
+                            EventMessage::new_from_safe(format!(
+                                "Blue/green application `{}` is not part of the environment, router `{}` can't send traffic to it.",
+                                application_name, self.name
+                            )),
+                        ));
+                    }
                 }
-            })
-            .collect::<Vec<_>>();
+                to_blue_green_routes(&self.routes, blue_green)
+            }
+            None => self
+                .routes
+                .iter()
+                .map(|route| Route {
+                    path: route.path.clone(),
+                    application_name: route.application_name.clone(),
+                })
+                .collect(),
+        };
+        let (route_data_templates, applications_without_private_port) = to_route_data_templates(&routes, |name| {
+            applications
+                .iter()
+                .find(|app| app.name() == name)
+                .map(|app| (app.sanitized_name(), app.private_port()))
+        });
+        for application_name in applications_without_private_port {
+            self.logger().log(EngineEvent::Warning(
+                event_details.clone(),
+                EventMessage::new_from_safe(format!(
+                    "Application `{}` has no private port, its routes can't be set on router `{}`.",
+                    application_name, self.name
+                )),
+            ));
+        }
 
         // autoscaler
         context.insert("nginx_enable_horizontal_autoscaler", "false");
@@ -137,11 +209,18 @@ impl<T: CloudProvider> Router<T> {
         let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
 
         // Default domain
-        match crate::cmd::kubectl::kubectl_exec_get_external_ingress_hostname(
-            kubernetes_config_file_path,
-            "nginx-ingress",
-            "nginx-ingress-ingress-nginx-controller",
-            kubernetes.cloud_provider().credentials_environment_variables(),
+        let get_external_ingress_hostname = || {
+            kubectl_exec_get_external_ingress_hostname(
+                kubernetes_config_file_path.as_str(),
+                "nginx-ingress",
+                "nginx-ingress-ingress-nginx-controller",
+                kubernetes.cloud_provider().credentials_environment_variables(),
+                EXTERNAL_INGRESS_HOSTNAME_TIMEOUT,
+            )
+        };
+        match get_external_ingress_hostname_with_retry(
+            get_external_ingress_hostname,
+            EXTERNAL_INGRESS_HOSTNAME_MAX_ATTEMPTS,
         ) {
             Ok(external_ingress_hostname_default) => match external_ingress_hostname_default {
                 Some(hostname) => context.insert("external_ingress_hostname_default", hostname.as_str()),
@@ -155,7 +234,17 @@ impl<T: CloudProvider> Router<T> {
                     ));
                 }
             },
-            _ => {
+            Err(KubectlExecError::Timeout(timeout)) => {
+                self.logger().log(EngineEvent::Warning(
+                    event_details,
+                    EventMessage::new_from_safe(format!(
+                        "Can't fetch external ingress hostname, Kubernetes API didn't answer within {} seconds ({} attempts).",
+                        timeout.as_secs(),
+                        EXTERNAL_INGRESS_HOSTNAME_MAX_ATTEMPTS
+                    )),
+                ));
+            }
+            Err(KubectlExecError::Command(_)) => {
                 // FIXME really?
                 // TODO(benjaminch): Handle better this one via a proper error eventually
                 self.logger().log(EngineEvent::Warning(
@@ -170,11 +259,16 @@ impl<T: CloudProvider> Router<T> {
         let tls_domain = kubernetes.dns_provider().domain().wildcarded();
         context.insert("router_tls_domain", tls_domain.to_string().as_str());
         context.insert("router_default_domain", self.default_domain.as_str());
+        context.insert(
+            "router_default_domain_label",
+            &sanitize_k8s_label_value(self.default_domain.as_str()),
+        );
         context.insert("router_default_domain_hash", router_default_domain_hash.as_str());
         context.insert("custom_domains", &custom_domain_data_templates);
         context.insert("routes", &route_data_templates);
         context.insert("spec_acme_email", "tls@qovery.com"); // TODO CHANGE ME
         context.insert("metadata_annotations_cert_manager_cluster_issuer", "letsencrypt-qovery");
+        context.insert("cert_manager_enabled", &kubernetes.is_cert_manager_deployed());
 
         let lets_encrypt_url = match self.context.is_test_cluster() {
             true => "https://acme-staging-v02.api.letsencrypt.org/directory",
@@ -184,11 +278,189 @@ impl<T: CloudProvider> Router<T> {
 
         // Nginx
         context.insert("sticky_sessions_enabled", &self.sticky_sessions_enabled);
+        // nginx defaults are kept when the router doesn't override them
+        if let Some(proxy_read_timeout_seconds) = self.proxy_read_timeout_seconds {
+            context.insert("proxy_read_timeout_seconds", &proxy_read_timeout_seconds);
+        }
+        if let Some(proxy_body_size_mb) = self.proxy_body_size_mb {
+            context.insert("proxy_body_size_mb", &proxy_body_size_mb);
+        }
+        context.insert("whitelist_source_ranges", &self.whitelist_source_ranges);
+        if let Some(hsts) = &self.hsts {
+            context.insert("hsts_header", &hsts.header_value());
+        }
 
         Ok(context)
     }
 }
 
+fn validate_positive_proxy_setting(name: &str, value: Option<u32>) -> Result<(), RouterError> {
+    match value {
+        Some(0) => Err(RouterError::InvalidConfig(format!("`{}` must be greater than 0", name))),
+        _ => Ok(()),
+    }
+}
+
+/// TLS versions are negotiated by the ingress controller for every router at once (`ssl-protocols` config), a router
+/// can't require a newer one without a server snippet.
+fn validate_min_tls_version(min_tls_version: TlsVersion) -> Result<(), RouterError> {
+    let controller_min_tls_version = TlsVersion::default();
+    match min_tls_version == controller_min_tls_version {
+        true => Ok(()),
+        false => Err(RouterError::InvalidConfig(format!(
+            "minimum TLS version {} can't be enforced per router, the ingress controller accepts TLS {} and above",
+            min_tls_version.version(),
+            controller_min_tls_version.version()
+        ))),
+    }
+}
+
+fn validate_hsts(hsts: &Option<HstsConfig>) -> Result<(), RouterError> {
+    match hsts {
+        Some(hsts) if hsts.max_age_seconds == 0 => Err(RouterError::InvalidConfig(
+            "HSTS `max_age_seconds` must be greater than 0".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn validate_blue_green(blue_green: &Option<BlueGreenStrategy>) -> Result<(), RouterError> {
+    match blue_green {
+        Some(blue_green) if blue_green.blue_application_name == blue_green.green_application_name => {
+            Err(RouterError::InvalidConfig(format!(
+                "blue/green strategy requires two different applications, `{}` is used for both",
+                blue_green.blue_application_name
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_whitelist_source_ranges(whitelist_source_ranges: &[String]) -> Result<(), RouterError> {
+    for source_range in whitelist_source_ranges {
+        validate_cidr(source_range).map_err(|_| {
+            RouterError::InvalidConfig(format!("whitelist source range `{}` is not a valid CIDR block", source_range))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Returns the hash of a custom domain, used to name its Kubernetes resources.
+/// The full SHA1 is kept (a truncated one is prone to collisions) and the router id is part of the hashed input,
+/// so two routers of a cluster sharing a custom domain don't get the same hash.
+fn custom_domain_hash(router_id: &str, domain: &str) -> String {
+    crate::crypto::to_sha1(format!("{}/{}", router_id, domain).as_str())
+}
+
+/// Timeout of a single attempt to get the external ingress hostname, Kubernetes API may be slow to answer.
+const EXTERNAL_INGRESS_HOSTNAME_TIMEOUT: Duration = Duration::from_secs(30);
+const EXTERNAL_INGRESS_HOSTNAME_MAX_ATTEMPTS: usize = 3;
+
+const CERTIFICATE_READY_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Polls the certificate readiness until it's ready or the timeout is reached, errors being considered as not ready
+/// yet (cert-manager may not have created the certificate). Returns whether the certificate is ready.
+fn wait_for_certificate_ready<F, L>(
+    is_ready: F,
+    timeout: Duration,
+    poll_interval: Duration,
+    mut log_progress: L,
+) -> bool
+where
+    F: Fn() -> Result<bool, CommandError>,
+    L: FnMut(usize),
+{
+    let start = Instant::now();
+    let mut attempt = 1;
+    loop {
+        if let Ok(true) = is_ready() {
+            return true;
+        }
+        if start.elapsed() + poll_interval >= timeout {
+            return false;
+        }
+
+        log_progress(attempt);
+        thread::sleep(poll_interval);
+        attempt += 1;
+    }
+}
+
+/// Gets the external ingress hostname, retrying on timeouts only: a missing hostname or a failing command is returned
+/// as is.
+fn get_external_ingress_hostname_with_retry<F>(
+    get_hostname: F,
+    max_attempts: usize,
+) -> Result<Option<String>, KubectlExecError>
+where
+    F: Fn() -> Result<Option<String>, KubectlExecError>,
+{
+    let mut attempt = 1;
+    loop {
+        match get_hostname() {
+            Err(KubectlExecError::Timeout(_)) if attempt < max_attempts => attempt += 1,
+            result => return result,
+        }
+    }
+}
+
+/// Returns routes with blue or green application targets replaced by the live one, so the ingress backend of every
+/// route is switched in a single update.
+fn to_blue_green_routes(routes: &[Route], blue_green: &BlueGreenStrategy) -> Vec<Route> {
+    routes
+        .iter()
+        .map(|route| {
+            let is_blue_green_route = route.application_name == blue_green.blue_application_name
+                || route.application_name == blue_green.green_application_name;
+            Route {
+                path: route.path.clone(),
+                application_name: match is_blue_green_route {
+                    true => blue_green.live_application_name().to_string(),
+                    false => route.application_name.clone(),
+                },
+            }
+        })
+        .collect()
+}
+
+/// Builds routes templates from router routes. All paths targeting an application are kept, ordered from the most
+/// specific (longest) path to the least specific one. Also returns applications which can't be routed because they
+/// don't have any private port.
+fn to_route_data_templates<F>(routes: &[Route], find_application: F) -> (Vec<RouteDataTemplate>, Vec<String>)
+where
+    F: Fn(&str) -> Option<(String, Option<u16>)>,
+{
+    let mut route_data_templates: Vec<RouteDataTemplate> = Vec::with_capacity(routes.len());
+    let mut applications_without_private_port: Vec<String> = vec![];
+
+    for route in routes {
+        match find_application(route.application_name.as_str()) {
+            Some((application_sanitized_name, Some(private_port))) => {
+                let route_data_template = RouteDataTemplate {
+                    path: route.path.clone(),
+                    application_name: application_sanitized_name,
+                    application_port: private_port,
+                };
+                if !route_data_templates.contains(&route_data_template) {
+                    route_data_templates.push(route_data_template);
+                }
+            }
+            Some((_, None)) => {
+                if !applications_without_private_port.contains(&route.application_name) {
+                    applications_without_private_port.push(route.application_name.clone());
+                }
+            }
+            None => {}
+        }
+    }
+
+    // stable sort: routes having the same path length keep their declaration order
+    route_data_templates.sort_by(|a, b| b.path.len().cmp(&a.path.len()));
+
+    (route_data_templates, applications_without_private_port)
+}
+
 impl<T: CloudProvider> ToTransmitter for Router<T> {
     fn to_transmitter(&self) -> Transmitter {
         Transmitter::Router(self.id.to_string(), self.name.to_string())
@@ -317,7 +589,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
@@ -333,6 +605,7 @@ where
         // respect order - getting the context here and not before is mandatory
         // the nginx-ingress must be available to get the external dns target if necessary
         let context = self.tera_context(target)?;
+        self.dump_tera_context(&context, event_details.clone());
 
         let from_dir = format!(
             "{}/{}/charts/q-ingress-tls",
@@ -371,7 +644,51 @@ where
         );
 
         helm.upgrade(&chart, &[])
-            .map_err(|e| EngineError::new_helm_error(event_details.clone(), e))
+            .map_err(|e| EngineError::new_helm_error(event_details.clone(), e))?;
+
+        // custom domains certificate is issued by cert-manager once the ingress is created, the default domain
+        // relies on the cluster wildcard certificate
+        if let (Some(timeout), false, true) = (
+            self.certificate_ready_timeout,
+            self.custom_domains.is_empty(),
+            kubernetes.is_cert_manager_deployed(),
+        ) {
+            let certificate_name = format!("router-tls-{}", self.id);
+            let is_ready = wait_for_certificate_ready(
+                || {
+                    kubectl_exec_is_certificate_ready(
+                        &kubernetes_config_file_path,
+                        environment.namespace(),
+                        &certificate_name,
+                        kubernetes.cloud_provider().credentials_environment_variables(),
+                    )
+                },
+                timeout,
+                CERTIFICATE_READY_POLL_INTERVAL,
+                |attempt| {
+                    self.logger().log(EngineEvent::Info(
+                        event_details.clone(),
+                        EventMessage::new_from_safe(format!(
+                            "Waiting for certificate `{}` of router `{}` to be issued (attempt {}).",
+                            certificate_name, self.name, attempt
+                        )),
+                    ))
+                },
+            );
+            if !is_ready {
+                self.logger().log(EngineEvent::Warning(
+                    event_details,
+                    EventMessage::new_from_safe(format!(
+                        "Certificate `{}` of router `{}` is still not issued after {} seconds, HTTPS requests on custom domains may fail until it is.",
+                        certificate_name,
+                        self.name,
+                        timeout.as_secs()
+                    )),
+                ));
+            }
+        }
+
+        Ok(())
     }
 
     #[named]
@@ -379,41 +696,15 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
             self.logger(),
         );
 
-        // check non custom domains
-        self.check_domains(event_details.clone(), self.logger())?;
-
-        // Wait/Check that custom domain is a CNAME targeting qovery
-        for domain_to_check in self.custom_domains.iter() {
-            match check_cname_for(
-                self.progress_scope(),
-                self.listeners(),
-                &domain_to_check.domain,
-                self.context.execution_id(),
-            ) {
-                Ok(cname) if cname.trim_end_matches('.') == domain_to_check.target_domain.trim_end_matches('.') => {
-                    continue;
-                }
-                Ok(err) | Err(err) => {
-                    // TODO(benjaminch): Handle better this one via a proper error eventually
-                    self.logger().log(EngineEvent::Warning(
-                        event_details.clone(),
-                        EventMessage::new(
-                            format!(
-                                "Invalid CNAME for {}. Might not be an issue if user is using a CDN.",
-                                domain_to_check.domain,
-                            ),
-                            Some(err.to_string()),
-                        ),
-                    ));
-                }
-            }
+        for dns_check in self.dns_checks(event_details) {
+            dns_check.run(self.logger())?;
         }
 
         Ok(())
@@ -424,7 +715,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Deploy));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -446,7 +737,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Pause));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -460,7 +751,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Pause));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -475,7 +766,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Pause));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -494,7 +785,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Delete));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
@@ -511,7 +802,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Delete));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details,
@@ -526,7 +817,7 @@ where
         let event_details = self.get_event_details(Stage::Environment(EnvironmentStep::Delete));
         print_action(
             T::short_name(),
-            "router",
+            self.kind_label(),
             function_name!(),
             self.name(),
             event_details.clone(),
@@ -566,4 +857,574 @@ where
     fn has_custom_domains(&self) -> bool {
         !self.custom_domains.is_empty()
     }
+
+    fn dns_checks(&self, event_details: EventDetails) -> Vec<DnsCheck> {
+        // check domains resolution, then wait/check that custom domains are CNAME targeting qovery
+        let mut dns_checks = Vec::with_capacity(1 + 2 * self.custom_domains.len());
+        dns_checks.extend(self.domains().into_iter().map(|domain| DnsCheckKind::Resolution {
+            domain: domain.to_string(),
+        }));
+        dns_checks.extend(self.custom_domains.iter().map(|custom_domain| DnsCheckKind::Cname {
+            domain: custom_domain.domain.clone(),
+            target_domain: custom_domain.target_domain.clone(),
+        }));
+
+        let tld_budgets = self.context.dns_check_tld_budgets();
+        dns_checks
+            .into_iter()
+            .map(|kind| DnsCheck {
+                kind,
+                scope: self.progress_scope(),
+                listeners: self.listeners(),
+                service_id: self.id().to_string(),
+                execution_id: self.context.execution_id().to_string(),
+                tld_budgets: tld_budgets.clone(),
+                event_details: event_details.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Checks the default domain is a DNS valid hostname (RFC 1123), otherwise the ingress can't be created.
+fn validate_default_domain(default_domain: &str) -> Result<(), RouterError> {
+    if default_domain.is_empty() {
+        return Err(RouterError::InvalidConfig("default domain can't be empty".to_string()));
+    }
+
+    let is_valid_hostname = default_domain.len() <= 253
+        && default_domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    if !is_valid_hostname {
+        return Err(RouterError::InvalidConfig(format!(
+            "default domain `{}` is not a valid hostname",
+            default_domain
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks no custom domain is the default domain, otherwise two ingress rules would target the same host.
+/// Domains are compared case and trailing dot insensitively, as DNS does.
+fn validate_custom_domains_differ_from_default(
+    default_domain: &str,
+    custom_domains: &[CustomDomain],
+) -> Result<(), RouterError> {
+    let normalize = |domain: &str| domain.trim_end_matches('.').to_lowercase();
+    let default_domain = normalize(default_domain);
+
+    match custom_domains
+        .iter()
+        .find(|custom_domain| normalize(&custom_domain.domain) == default_domain)
+    {
+        Some(custom_domain) => Err(RouterError::InvalidConfig(format!(
+            "custom domain `{}` is the router default domain",
+            custom_domain.domain
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud_provider::models::{CustomDomain, CustomDomainDataTemplate, Route, RouteDataTemplate};
+    use crate::cloud_provider::service::insert_common_labels;
+    use crate::cmd::kubectl::KubectlExecError;
+    use crate::crypto::to_sha1_truncate_16;
+    use crate::errors::CommandError;
+    use crate::io_models::{BlueGreenSlot, BlueGreenStrategy, HstsConfig, TlsVersion};
+    use crate::models::router::{
+        custom_domain_hash, get_external_ingress_hostname_with_retry, to_blue_green_routes, to_route_data_templates,
+        validate_blue_green, validate_custom_domains_differ_from_default, validate_default_domain, validate_hsts,
+        validate_min_tls_version, validate_positive_proxy_setting, validate_whitelist_source_ranges,
+        wait_for_certificate_ready,
+    };
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_custom_domain_hash_is_collision_resistant() {
+        // setup: craft two domains colliding on their truncated SHA1 prefix
+        // (4 chars prefix here, a 16 chars one is too long to be brute forced in a test)
+        let mut prefixes: HashMap<String, String> = HashMap::new();
+        let mut colliding_domains = None;
+        for i in 0.. {
+            let domain = format!("app-{}.example.com", i);
+            let prefix = to_sha1_truncate_16(domain.as_str())[..4].to_string();
+            if let Some(other_domain) = prefixes.insert(prefix, domain.clone()) {
+                colliding_domains = Some((other_domain, domain));
+                break;
+            }
+        }
+        let (domain_1, domain_2) = colliding_domains.expect("colliding domains should be found");
+        assert_eq!(
+            to_sha1_truncate_16(domain_1.as_str())[..4],
+            to_sha1_truncate_16(domain_2.as_str())[..4]
+        );
+
+        // execute:
+        let hash_1 = custom_domain_hash("z1234abc", domain_1.as_str());
+        let hash_2 = custom_domain_hash("z1234abc", domain_2.as_str());
+
+        // verify:
+        assert_ne!(hash_1, hash_2);
+        assert_ne!(hash_1[..16], hash_2[..16]);
+        assert_eq!(40, hash_1.len());
+        // same domain on two routers doesn't collide either
+        assert_ne!(hash_1, custom_domain_hash("z5678def", domain_1.as_str()));
+    }
+
+    #[test]
+    fn test_route_data_templates_with_several_paths_for_one_application() {
+        // setup:
+        let routes = vec![
+            Route {
+                path: "/".to_string(),
+                application_name: "my-app".to_string(),
+            },
+            Route {
+                path: "/api/v1".to_string(),
+                application_name: "my-app".to_string(),
+            },
+            Route {
+                path: "/admin".to_string(),
+                application_name: "no-port-app".to_string(),
+            },
+            Route {
+                path: "/unknown".to_string(),
+                application_name: "unknown-app".to_string(),
+            },
+        ];
+
+        // execute:
+        let (route_data_templates, applications_without_private_port) =
+            to_route_data_templates(&routes, |name| match name {
+                "my-app" => Some(("app-z123".to_string(), Some(8080))),
+                "no-port-app" => Some(("app-z456".to_string(), None)),
+                _ => None,
+            });
+
+        // verify:
+        assert_eq!(
+            vec![
+                RouteDataTemplate {
+                    path: "/api/v1".to_string(),
+                    application_name: "app-z123".to_string(),
+                    application_port: 8080,
+                },
+                RouteDataTemplate {
+                    path: "/".to_string(),
+                    application_name: "app-z123".to_string(),
+                    application_port: 8080,
+                },
+            ],
+            route_data_templates
+        );
+        assert_eq!(vec!["no-port-app".to_string()], applications_without_private_port);
+    }
+
+    #[test]
+    fn test_validate_default_domain() {
+        // execute & verify: empty
+        assert!(validate_default_domain("").is_err());
+
+        // execute & verify: invalid
+        let too_long_label_domain = format!("{}.example.com", "a".repeat(64));
+        for domain in &[
+            "app..example.com",
+            "-app.example.com",
+            "app-.example.com",
+            "app_1.example.com",
+            "app.example.com.",
+            "https://app.example.com",
+            too_long_label_domain.as_str(),
+        ] {
+            assert!(validate_default_domain(domain).is_err(), "{} should be invalid", domain);
+        }
+
+        // execute & verify: valid
+        for domain in &[
+            "app.example.com",
+            "z1234abc-main.qovery.io",
+            "localhost",
+            "app-1.b2.example.com",
+        ] {
+            assert!(validate_default_domain(domain).is_ok(), "{} should be valid", domain);
+        }
+    }
+
+    #[test]
+    fn test_external_ingress_hostname_retries_on_timeout() {
+        // setup: mocked kubectl timing out once
+        let calls = Cell::new(0);
+        let get_hostname = || {
+            calls.set(calls.get() + 1);
+            match calls.get() {
+                1 => Err(KubectlExecError::Timeout(Duration::from_secs(30))),
+                _ => Ok(Some("lb.example.com".to_string())),
+            }
+        };
+
+        // execute:
+        let hostname = get_external_ingress_hostname_with_retry(get_hostname, 3);
+
+        // verify:
+        assert_eq!(Some("lb.example.com".to_string()), hostname.expect("hostname should be found"));
+        assert_eq!(2, calls.get());
+
+        // execute & verify: genuine absence and errors are not retried
+        calls.set(0);
+        let no_hostname = || -> Result<Option<String>, KubectlExecError> {
+            calls.set(calls.get() + 1);
+            Ok(None)
+        };
+        assert!(matches!(get_external_ingress_hostname_with_retry(no_hostname, 3), Ok(None)));
+        assert_eq!(1, calls.get());
+
+        calls.set(0);
+        let failing = || -> Result<Option<String>, KubectlExecError> {
+            calls.set(calls.get() + 1);
+            Err(KubectlExecError::Command(CommandError::new_from_safe_message(
+                "error".to_string(),
+            )))
+        };
+        assert!(matches!(
+            get_external_ingress_hostname_with_retry(failing, 3),
+            Err(KubectlExecError::Command(_))
+        ));
+        assert_eq!(1, calls.get());
+
+        // execute & verify: timeouts are retried a limited number of times
+        calls.set(0);
+        let always_timing_out = || -> Result<Option<String>, KubectlExecError> {
+            calls.set(calls.get() + 1);
+            Err(KubectlExecError::Timeout(Duration::from_secs(30)))
+        };
+        assert!(matches!(
+            get_external_ingress_hostname_with_retry(always_timing_out, 3),
+            Err(KubectlExecError::Timeout(_))
+        ));
+        assert_eq!(3, calls.get());
+    }
+
+    /// Router ingress template context with a single route, tests insert the settings they are about.
+    fn ingress_test_context() -> tera::Context {
+        let mut context = tera::Context::new();
+        context.insert("sanitized_name", "router-abc");
+        context.insert("namespace", "env");
+        context.insert("id", "abc");
+        context.insert("long_id", "d9b8c2c4-6a2f-4a1a-9c1a-6f6f0b5c3f00");
+        context.insert("environment_id", "env");
+        context.insert("router_default_domain", "abc.example.com");
+        context.insert("router_default_domain_label", "abc.example.com");
+        context.insert("metadata_annotations_cert_manager_cluster_issuer", "letsencrypt-qovery");
+        context.insert("cert_manager_enabled", &true);
+        context.insert("custom_domains", &Vec::<CustomDomainDataTemplate>::new());
+        context.insert(
+            "routes",
+            &vec![RouteDataTemplate {
+                path: "/".to_string(),
+                application_name: "app".to_string(),
+                application_port: 8080,
+            }],
+        );
+        context.insert("sticky_sessions_enabled", &false);
+        insert_common_labels(&mut context, "organization-id", "cluster-id");
+
+        context
+    }
+
+    fn render_ingress(context: &tera::Context) -> String {
+        tera::Tera::one_off(
+            include_str!("../../lib/digitalocean/charts/q-ingress-tls/templates/ingress-qovery.j2.yaml"),
+            context,
+            false,
+        )
+        .expect("ingress template should render")
+    }
+
+    #[test]
+    fn test_proxy_timeout_and_body_size_annotations() {
+        // setup:
+        let mut context = ingress_test_context();
+
+        // execute & verify: nginx defaults are kept when not overridden
+        let rendered = render_ingress(&context);
+        assert!(!rendered.contains("nginx.ingress.kubernetes.io/proxy-read-timeout"));
+        assert!(!rendered.contains("nginx.ingress.kubernetes.io/proxy-body-size"));
+
+        // execute & verify:
+        context.insert("proxy_read_timeout_seconds", &600);
+        context.insert("proxy_body_size_mb", &100);
+        let rendered = render_ingress(&context);
+        assert!(rendered.contains("nginx.ingress.kubernetes.io/proxy-read-timeout: \"600\""));
+        assert!(rendered.contains("nginx.ingress.kubernetes.io/proxy-body-size: \"100m\""));
+        assert!(validate_positive_proxy_setting("proxy_read_timeout_seconds", None).is_ok());
+        assert!(validate_positive_proxy_setting("proxy_read_timeout_seconds", Some(600)).is_ok());
+        assert!(validate_positive_proxy_setting("proxy_body_size_mb", Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_validate_custom_domains_differ_from_default() {
+        // setup:
+        let custom_domain = |domain: &str| CustomDomain {
+            domain: domain.to_string(),
+            target_domain: "abc.example.com".to_string(),
+        };
+
+        // execute & verify: distinct domains
+        assert!(validate_custom_domains_differ_from_default(
+            "abc.example.com",
+            &[custom_domain("www.acme.com"), custom_domain("acme.com")]
+        )
+        .is_ok());
+        assert!(validate_custom_domains_differ_from_default("abc.example.com", &[]).is_ok());
+
+        // execute & verify: custom domain duplicating the default one
+        for domain in &["abc.example.com", "ABC.Example.com", "abc.example.com."] {
+            assert!(
+                validate_custom_domains_differ_from_default("abc.example.com", &[custom_domain(domain)]).is_err(),
+                "{} should be rejected",
+                domain
+            );
+        }
+    }
+
+    #[test]
+    fn test_wait_for_certificate_ready() {
+        // setup:
+        let polls = Cell::new(0);
+        let progress = Cell::new(0);
+
+        // execute: certificate becomes ready on the second poll
+        let is_ready = wait_for_certificate_ready(
+            || {
+                polls.set(polls.get() + 1);
+                Ok(polls.get() == 2)
+            },
+            Duration::from_secs(60),
+            Duration::from_millis(0),
+            |_| progress.set(progress.get() + 1),
+        );
+
+        // verify:
+        assert!(is_ready);
+        assert_eq!(2, polls.get());
+        assert_eq!(1, progress.get());
+
+        // execute & verify: certificate never ready, errors included
+        let is_ready = wait_for_certificate_ready(
+            || Err(CommandError::new_from_safe_message("certificate not found".to_string())),
+            Duration::from_millis(0),
+            Duration::from_millis(0),
+            |_| {},
+        );
+        assert!(!is_ready);
+    }
+
+    #[test]
+    fn test_no_certificate_is_requested_without_cert_manager() {
+        // setup:
+        let mut context = ingress_test_context();
+        context.insert(
+            "custom_domains",
+            &vec![CustomDomainDataTemplate {
+                domain: "www.acme.com".to_string(),
+                domain_hash: to_sha1_truncate_16("www.acme.com"),
+                target_domain: "abc.example.com".to_string(),
+            }],
+        );
+        context.insert("spec_acme_server", "https://acme-v02.api.letsencrypt.org/directory");
+        context.insert("spec_acme_email", "tls@qovery.com");
+        let render = |context: &tera::Context| {
+            let ingress = render_ingress(context);
+            let issuer = tera::Tera::one_off(
+                include_str!("../../lib/digitalocean/charts/q-ingress-tls/templates/cert-issuer.j2.yaml"),
+                context,
+                false,
+            )
+            .expect("issuer template should render");
+            format!("{}{}", ingress, issuer)
+        };
+
+        // execute:
+        context.insert("cert_manager_enabled", &true);
+        let with_cert_manager = render(&context);
+        context.insert("cert_manager_enabled", &false);
+        let without_cert_manager = render(&context);
+
+        // verify:
+        assert!(with_cert_manager.contains("cert-manager.io/issuer: abc"));
+        assert!(with_cert_manager.contains("secretName: \"router-tls-abc\""));
+        assert!(with_cert_manager.contains("kind: Issuer"));
+        assert_eq!(
+            2,
+            with_cert_manager
+                .matches("    qovery.com/organization: \"organization-id\"\n")
+                .count()
+        );
+        assert!(!without_cert_manager.contains("kubernetes.io/tls-acme"));
+        assert!(!without_cert_manager.contains("cert-manager.io/issuer:"));
+        assert!(!without_cert_manager.contains("cert-manager.io/cluster-issuer:"));
+        assert!(!without_cert_manager.contains("kind: Issuer"));
+        assert!(!without_cert_manager.contains("router-tls-abc"));
+        assert!(without_cert_manager.contains("- host: \"www.acme.com\""));
+    }
+
+    #[test]
+    fn test_whitelist_source_ranges() {
+        // setup:
+        let whitelist_source_ranges = vec!["10.0.0.0/16".to_string(), "203.0.113.12/32".to_string()];
+        let mut context = ingress_test_context();
+        context.insert("whitelist_source_ranges", &whitelist_source_ranges);
+
+        // execute:
+        let rendered = render_ingress(&context);
+
+        // verify:
+        assert!(
+            rendered.contains("nginx.ingress.kubernetes.io/whitelist-source-range: \"10.0.0.0/16,203.0.113.12/32\"")
+        );
+        assert!(validate_whitelist_source_ranges(&whitelist_source_ranges).is_ok());
+        assert!(validate_whitelist_source_ranges(&[]).is_ok());
+        assert!(validate_whitelist_source_ranges(&["10.0.0.0/33".to_string()]).is_err());
+        assert!(validate_whitelist_source_ranges(&["10.0.0.0".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_min_tls_version() {
+        // setup:
+        let nginx_ingress_values = vec![
+            include_str!("../../lib/aws/bootstrap/chart_values/nginx-ingress.yaml"),
+            include_str!("../../lib/digitalocean/bootstrap/chart_values/nginx-ingress.j2.yaml"),
+            include_str!("../../lib/scaleway/bootstrap/chart_values/nginx-ingress.yaml"),
+        ];
+
+        // verify: the default version is the one ingress controllers enforce, without any snippet
+        let ssl_protocols = format!("    ssl-protocols: \"{}\"\n", TlsVersion::default().ssl_protocols());
+        for values in nginx_ingress_values {
+            assert!(values.contains(&ssl_protocols));
+            assert!(!values.contains("allow-snippet-annotations"));
+        }
+        assert!(!render_ingress(&ingress_test_context()).contains("ssl_protocols"));
+        assert_eq!(TlsVersion::Tls12.ssl_protocols(), "TLSv1.2 TLSv1.3");
+        assert_eq!(TlsVersion::Tls13.ssl_protocols(), "TLSv1.3");
+
+        // execute & verify: a newer version can't be enforced per router
+        assert!(validate_min_tls_version(TlsVersion::Tls12).is_ok());
+        assert!(validate_min_tls_version(TlsVersion::Tls13).is_err());
+    }
+
+    #[test]
+    fn test_hsts() {
+        // setup:
+        let hsts = HstsConfig {
+            max_age_seconds: 31536000,
+            include_subdomains: true,
+            preload: false,
+        };
+        let mut context = ingress_test_context();
+
+        // execute & verify: HSTS is off by default
+        assert!(!render_ingress(&context).contains("Strict-Transport-Security"));
+
+        // execute & verify: HSTS configured
+        context.insert("hsts_header", &hsts.header_value());
+        assert!(render_ingress(&context)
+            .contains("more_set_headers \"Strict-Transport-Security: max-age=31536000; includeSubDomains\";"));
+        assert_eq!(
+            HstsConfig {
+                preload: true,
+                ..hsts.clone()
+            }
+            .header_value(),
+            "max-age=31536000; includeSubDomains; preload"
+        );
+        assert!(validate_hsts(&Some(hsts.clone())).is_ok());
+        assert!(validate_hsts(&None).is_ok());
+        assert!(validate_hsts(&Some(HstsConfig {
+            max_age_seconds: 0,
+            ..hsts
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_blue_green_flip_and_rollback() {
+        // setup:
+        let routes = vec![
+            Route {
+                path: "/".to_string(),
+                application_name: "front-blue".to_string(),
+            },
+            Route {
+                path: "/api".to_string(),
+                application_name: "api".to_string(),
+            },
+        ];
+        let mut blue_green = BlueGreenStrategy {
+            blue_application_name: "front-blue".to_string(),
+            green_application_name: "front-green".to_string(),
+            active: BlueGreenSlot::Blue,
+            rollback: false,
+        };
+        let backends = |blue_green: &BlueGreenStrategy| {
+            let (route_data_templates, _) =
+                to_route_data_templates(&to_blue_green_routes(&routes, blue_green), |name| match name {
+                    "front-blue" => Some(("app-zblue".to_string(), Some(8080))),
+                    "front-green" => Some(("app-zgreen".to_string(), Some(8080))),
+                    "api" => Some(("app-zapi".to_string(), Some(3000))),
+                    _ => None,
+                });
+            route_data_templates
+                .into_iter()
+                .map(|route| (route.path, route.application_name))
+                .collect::<Vec<_>>()
+        };
+
+        // execute & verify: blue is live
+        assert_eq!(
+            vec![
+                ("/api".to_string(), "app-zapi".to_string()),
+                ("/".to_string(), "app-zblue".to_string())
+            ],
+            backends(&blue_green)
+        );
+
+        // execute & verify: flip to green
+        blue_green.active = BlueGreenSlot::Green;
+        assert_eq!(
+            vec![
+                ("/api".to_string(), "app-zapi".to_string()),
+                ("/".to_string(), "app-zgreen".to_string())
+            ],
+            backends(&blue_green)
+        );
+
+        // execute & verify: rollback sends traffic back to blue
+        blue_green.rollback = true;
+        assert_eq!(BlueGreenSlot::Blue, blue_green.live_slot());
+        assert_eq!(
+            vec![
+                ("/api".to_string(), "app-zapi".to_string()),
+                ("/".to_string(), "app-zblue".to_string())
+            ],
+            backends(&blue_green)
+        );
+
+        // execute & verify: both slots must be different applications
+        assert!(validate_blue_green(&Some(blue_green.clone())).is_ok());
+        assert!(validate_blue_green(&None).is_ok());
+        assert!(validate_blue_green(&Some(BlueGreenStrategy {
+            green_application_name: "front-blue".to_string(),
+            ..blue_green
+        }))
+        .is_err());
+    }
 }
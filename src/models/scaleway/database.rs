@@ -4,7 +4,7 @@ use crate::cloud_provider::service::{
 };
 use crate::cloud_provider::{service, DeploymentTarget};
 use crate::cmd::kubectl;
-use crate::errors::EngineError;
+use crate::errors::{CommandError, EngineError};
 use crate::events::{EnvironmentStep, EventDetails, Stage};
 use crate::models::database::{
     Container, Database, DatabaseMode, DatabaseType, Managed, MongoDB, MySQL, PostgresSQL, Redis,
@@ -14,7 +14,9 @@ use crate::models::database_utils::{
     get_self_hosted_redis_version,
 };
 use crate::models::scaleway::database_utils::{pick_managed_mysql_version, pick_managed_postgres_version};
+use crate::models::scaleway::{ScwDiskType, ScwRegion, ScwZone};
 use crate::models::types::{ToTeraContext, SCW};
+use std::str::FromStr;
 use tera::Context as TeraContext;
 
 /////////////////////////////////////////////////////////////////
@@ -112,6 +114,35 @@ impl DatabaseType<SCW, Managed> for MySQL {
 
 // Redis and MongoDB are not supported managed db yet
 
+/// Validates the location overrides of a managed database, the region being the zone one when only the zone is set.
+fn scaleway_database_location(
+    region: Option<&str>,
+    zone: Option<&str>,
+) -> Result<(Option<ScwRegion>, Option<ScwZone>), CommandError> {
+    let region = match region {
+        Some(region) => Some(ScwRegion::from_str(region).map_err(|_| {
+            CommandError::new_from_safe_message(format!(
+                "`{}` is not a known Scaleway region, known ones are `fr-par`, `nl-ams` and `pl-waw`",
+                region
+            ))
+        })?),
+        None => None,
+    };
+    let zone = match zone {
+        Some(zone) => Some(ScwZone::from_str(zone)?),
+        None => None,
+    };
+
+    match (region, zone) {
+        (Some(region), Some(zone)) if zone.region() != region => Err(CommandError::new_from_safe_message(format!(
+            "Scaleway zone `{}` is not in region `{}`",
+            zone, region
+        ))),
+        (None, Some(zone)) => Ok((Some(zone.region()), Some(zone))),
+        location => Ok(location),
+    }
+}
+
 impl<M: DatabaseMode, T: DatabaseType<SCW, M>> Database<SCW, M, T> {
     fn to_tera_context_for_scaleway_managed(
         &self,
@@ -140,6 +171,31 @@ impl<M: DatabaseMode, T: DatabaseType<SCW, M>> Database<SCW, M, T> {
 
         context.insert("namespace", environment.namespace());
 
+        let disk_type = ScwDiskType::from_str(options.database_disk_type.as_str()).map_err(|e| {
+            EngineError::new_terraform_unsupported_context_parameter_value(
+                event_details.clone(),
+                T::short_name().to_string(),
+                "database_disk_type".to_string(),
+                options.database_disk_type.to_string(),
+                Some(e),
+            )
+        })?;
+
+        let (database_region, database_zone) =
+            scaleway_database_location(options.region.as_deref(), options.zone.as_deref()).map_err(|e| {
+                EngineError::new_terraform_unsupported_context_parameter_value(
+                    event_details.clone(),
+                    T::short_name().to_string(),
+                    "region".to_string(),
+                    format!(
+                        "{}/{}",
+                        options.region.as_deref().unwrap_or_default(),
+                        options.zone.as_deref().unwrap_or_default()
+                    ),
+                    Some(e),
+                )
+            })?;
+
         let version = get_version(event_details)?.matched_version();
         context.insert("version_major", &version.to_major_version_string());
         context.insert("version", &version.to_string()); // Scaleway needs to have major version only
@@ -150,6 +206,12 @@ impl<M: DatabaseMode, T: DatabaseType<SCW, M>> Database<SCW, M, T> {
 
         context.insert("kubernetes_cluster_id", kubernetes.id());
         context.insert("kubernetes_cluster_name", kubernetes.name());
+        if let Some(database_region) = database_region {
+            context.insert("database_region", database_region.as_str());
+        }
+        if let Some(database_zone) = database_zone {
+            context.insert("database_zone", database_zone.as_str());
+        }
 
         context.insert("fqdn_id", self.fqdn_id.as_str());
         context.insert("fqdn", self.fqdn(target, &self.fqdn, M::is_managed()).as_str());
@@ -161,7 +223,7 @@ impl<M: DatabaseMode, T: DatabaseType<SCW, M>> Database<SCW, M, T> {
         context.insert("database_port", &self.private_port());
         context.insert("database_disk_size_in_gib", &options.disk_size_in_gib);
         context.insert("database_instance_type", &self.database_instance_type);
-        context.insert("database_disk_type", &options.database_disk_type);
+        context.insert("database_disk_type", disk_type.as_str());
         context.insert("database_ram_size_in_mib", &self.total_ram_in_mib);
         context.insert("database_total_cpus", &self.total_cpus);
         context.insert("database_fqdn", &options.host.as_str());
@@ -170,9 +232,11 @@ impl<M: DatabaseMode, T: DatabaseType<SCW, M>> Database<SCW, M, T> {
         context.insert("tfstate_name", &get_tfstate_name(self));
 
         context.insert("publicly_accessible", &options.publicly_accessible);
+        context.insert("allowed_cidrs", &options.allowed_cidrs());
         context.insert("activate_high_availability", &options.activate_high_availability);
         context.insert("activate_backups", &options.activate_backups);
         context.insert("delete_automated_backups", &self.context().is_test_cluster());
+        context.insert("deletion_protection", &options.deletion_protection);
         if self.context.resource_expiration_in_seconds().is_some() {
             context.insert("resource_expiration_in_seconds", &self.context.resource_expiration_in_seconds())
         }
@@ -291,3 +355,63 @@ where
         self.to_tera_context_for_container(target, &self.options)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::scaleway::database::scaleway_database_location;
+    use crate::models::scaleway::{ScwRegion, ScwZone};
+    use tera::Context as TeraContext;
+    use tera::Tera;
+
+    #[test]
+    fn test_scaleway_database_location() {
+        // execute & verify:
+        assert_eq!(scaleway_database_location(None, None).unwrap(), (None, None));
+        assert_eq!(
+            scaleway_database_location(Some("nl-ams"), None).unwrap(),
+            (Some(ScwRegion::Amsterdam), None)
+        );
+        assert_eq!(
+            scaleway_database_location(None, Some("fr-par-2")).unwrap(),
+            (Some(ScwRegion::Paris), Some(ScwZone::Paris2))
+        );
+        assert!(scaleway_database_location(Some("us-east-1"), None).is_err());
+        assert!(scaleway_database_location(None, Some("fr-par-9")).is_err());
+        assert!(scaleway_database_location(Some("pl-waw"), Some("fr-par-1")).is_err());
+    }
+
+    #[test]
+    fn test_database_location_override_is_rendered() {
+        // setup:
+        let mut context = TeraContext::new();
+        context.insert("scaleway_access_key", "access_key");
+        context.insert("scaleway_secret_key", "secret_key");
+        context.insert("scaleway_project_id", "project_id");
+        context.insert("kubernetes_cluster_id", "z1234abc");
+        context.insert("region", "fr-par");
+        context.insert("zone", "fr-par-1");
+
+        // execute & verify: cluster location is used by default
+        let rendered = Tera::one_off(
+            include_str!("../../../lib/scaleway/services/common/providers.j2.tf"),
+            &context,
+            false,
+        )
+        .expect("providers should render");
+        assert!(rendered.contains("zone            = \"fr-par-1\""));
+        assert!(rendered.contains("region          = \"fr-par\""));
+
+        // execute & verify: database location override
+        let (database_region, database_zone) = scaleway_database_location(None, Some("nl-ams-1")).unwrap();
+        context.insert("database_region", database_region.unwrap().as_str());
+        context.insert("database_zone", database_zone.unwrap().as_str());
+        let rendered = Tera::one_off(
+            include_str!("../../../lib/scaleway/services/common/providers.j2.tf"),
+            &context,
+            false,
+        )
+        .expect("providers should render");
+        assert!(rendered.contains("zone            = \"nl-ams-1\""));
+        assert!(rendered.contains("region          = \"nl-ams\""));
+    }
+}
@@ -4,8 +4,8 @@ mod database_utils;
 mod router;
 
 use crate::errors::CommandError;
-use crate::models::types::CloudProvider;
 use crate::models::types::SCW;
+use crate::models::types::{CloudProvider, StorageType};
 use std::fmt;
 use std::str::FromStr;
 
@@ -48,6 +48,53 @@ pub enum ScwStorageType {
     LocalSsd,
 }
 
+impl StorageType for ScwStorageType {
+    fn supports_snapshots(&self) -> bool {
+        match self {
+            ScwStorageType::BlockSsd => true,
+            // local volumes are bound to their instance and can't be snapshotted
+            ScwStorageType::LocalSsd => false,
+        }
+    }
+}
+
+/// Scaleway managed database volume types.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ScwDiskType {
+    LocalSsd,
+    BlockSsd,
+}
+
+impl ScwDiskType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ScwDiskType::LocalSsd => "lssd",
+            ScwDiskType::BlockSsd => "bssd",
+        }
+    }
+}
+
+impl fmt::Display for ScwDiskType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ScwDiskType {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<ScwDiskType, CommandError> {
+        match s {
+            "lssd" => Ok(ScwDiskType::LocalSsd),
+            "bssd" => Ok(ScwDiskType::BlockSsd),
+            _ => Err(CommandError::new_from_safe_message(format!(
+                "`{}` disk type is not supported, supported ones are `lssd` and `bssd`",
+                s
+            ))),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum ScwRegion {
     Paris,
@@ -164,9 +211,19 @@ impl FromStr for ScwZone {
 
 #[cfg(test)]
 mod tests {
-    use super::{ScwRegion, ScwZone};
+    use super::{ScwDiskType, ScwRegion, ScwZone};
     use std::str::FromStr;
 
+    #[test]
+    fn test_disk_type_from_str() {
+        assert_eq!(ScwDiskType::from_str("lssd"), Ok(ScwDiskType::LocalSsd));
+        assert_eq!(ScwDiskType::from_str("bssd"), Ok(ScwDiskType::BlockSsd));
+        assert_eq!("bssd", ScwDiskType::BlockSsd.to_string());
+        assert!(ScwDiskType::from_str("b_ssd").is_err());
+        assert!(ScwDiskType::from_str("scw-sbv-ssd-0").is_err());
+        assert!(ScwDiskType::from_str("").is_err());
+    }
+
     #[test]
     fn test_region_to_str() {
         assert_eq!("fr-par", ScwRegion::Paris.as_str());
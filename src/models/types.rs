@@ -18,7 +18,7 @@ pub trait CloudProvider {
     type AppExtraSettings;
     type DbExtraSettings;
     type RouterExtraSettings;
-    type StorageTypes;
+    type StorageTypes: StorageType;
 
     fn short_name() -> &'static str;
     fn full_name() -> &'static str;
@@ -27,6 +27,11 @@ pub trait CloudProvider {
     fn lib_directory_name() -> &'static str;
 }
 
+// Capabilities of a provider storage (volume) type
+pub trait StorageType {
+    fn supports_snapshots(&self) -> bool;
+}
+
 pub(crate) trait ToTeraContext {
     fn to_tera_context(&self, target: &DeploymentTarget) -> Result<TeraContext, EngineError>;
 }
@@ -89,6 +94,13 @@ impl FromStr for VersionsNumber {
             }
         };
 
+        if major.is_empty() || !major.chars().all(|c| c.is_ascii_digit()) {
+            return Err(CommandError::new_from_safe_message(format!(
+                "please check the version you've sent ({}), major version `{}` is not a number",
+                version, major
+            )));
+        }
+
         let minor = version_split.next().map(|minor| {
             let minor = minor.to_string();
             minor.replace('+', "")
@@ -177,3 +177,110 @@ impl RenderedTemplate {
         }
     }
 }
+
+/// When set, the tera context of each deployed service is dumped as JSON into its workspace directory.
+pub const DUMP_TERA_CONTEXT_ENV_VAR: &str = "QOVERY_ENGINE_DUMP_TERA_CONTEXT";
+pub const TERA_CONTEXT_DUMP_FILE_NAME: &str = "tera_context.json";
+const REDACTED_VALUE: &str = "********";
+const SENSITIVE_KEY_PATTERNS: [&str; 7] = [
+    "password",
+    "secret",
+    "token",
+    "access_key",
+    "private_key",
+    "credentials",
+    // user environment variables may hold any secret
+    "environment_variables",
+];
+
+pub fn is_tera_context_dump_enabled() -> bool {
+    matches!(std::env::var(DUMP_TERA_CONTEXT_ENV_VAR), Ok(value) if value == "true" || value == "1")
+}
+
+/// Returns the context as JSON, values of keys looking like secrets being redacted at any depth.
+pub fn redacted_tera_context_json(context: &Context) -> serde_json::Value {
+    redact_json_value(context.clone().into_json(), false)
+}
+
+fn redact_json_value(value: serde_json::Value, sensitive: bool) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let key_lowercase = key.to_lowercase();
+                    let is_sensitive_key = SENSITIVE_KEY_PATTERNS
+                        .iter()
+                        .any(|pattern| key_lowercase.contains(pattern));
+                    (key, redact_json_value(value, sensitive || is_sensitive_key))
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(values) => serde_json::Value::Array(
+            values
+                .into_iter()
+                .map(|value| redact_json_value(value, sensitive))
+                .collect(),
+        ),
+        serde_json::Value::Null => serde_json::Value::Null,
+        _ if sensitive => serde_json::Value::String(REDACTED_VALUE.to_string()),
+        value => value,
+    }
+}
+
+/// Writes the redacted context as JSON into the given directory, to debug template rendering.
+pub fn dump_tera_context<P>(context: &Context, to_dir: P) -> Result<String, CommandError>
+where
+    P: AsRef<Path>,
+{
+    let dest = to_dir.as_ref().join(TERA_CONTEXT_DUMP_FILE_NAME);
+    let content = serde_json::to_string_pretty(&redacted_tera_context_json(context)).map_err(|e| {
+        CommandError::new("Error while serializing tera context.".to_string(), Some(e.to_string()), None)
+    })?;
+
+    fs::create_dir_all(to_dir.as_ref())
+        .and_then(|_| fs::write(&dest, content))
+        .map_err(|e| {
+            CommandError::new("Error while writing tera context dump.".to_string(), Some(e.to_string()), None)
+        })?;
+
+    Ok(dest.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::template::{dump_tera_context, TERA_CONTEXT_DUMP_FILE_NAME};
+    use tempdir::TempDir;
+    use tera::Context;
+
+    #[test]
+    fn test_dump_tera_context_redacts_secrets() {
+        // setup:
+        let tmp_dir = TempDir::new("tera_context_dump").expect("error creating temporary dir");
+        let mut context = Context::new();
+        context.insert("sanitized_name", "postgresql-abc");
+        context.insert("database_login", "superuser");
+        context.insert("database_password", "p4ssw0rd");
+        context.insert("database_port", &5432);
+        context.insert(
+            "environment_variables",
+            &vec![serde_json::json!({"key": "API_TOKEN", "value": "not-that-secret"})],
+        );
+        context.insert("aws_secret_key", "s3cr3t");
+
+        // execute:
+        let dest = dump_tera_context(&context, tmp_dir.path()).expect("tera context should be dumped");
+
+        // verify:
+        assert!(dest.ends_with(TERA_CONTEXT_DUMP_FILE_NAME));
+        let dump = std::fs::read_to_string(dest).expect("dump should be readable");
+        let json: serde_json::Value = serde_json::from_str(&dump).expect("dump should be valid JSON");
+        assert_eq!("postgresql-abc", json["sanitized_name"]);
+        assert_eq!("superuser", json["database_login"]);
+        assert_eq!(5432, json["database_port"]);
+        assert_eq!("********", json["database_password"]);
+        assert_eq!("********", json["aws_secret_key"]);
+        assert!(!dump.contains("p4ssw0rd"));
+        assert!(!dump.contains("s3cr3t"));
+        assert!(!dump.contains("not-that-secret"));
+    }
+}
@@ -1,15 +1,22 @@
-use crate::build_platform::BuildError;
+use crate::build_platform::{Build, BuildError};
 use crate::cloud_provider::environment::Environment;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::cloud_provider::kubernetes::Kubernetes;
 use crate::cloud_provider::service::{Action, Service};
+use crate::cmd::kubectl::kubectl_exec_is_pod_ready;
 use crate::container_registry::errors::ContainerRegistryError;
 use crate::container_registry::to_engine_error;
 use crate::engine::{EngineConfig, EngineConfigError};
-use crate::errors::{EngineError, Tag};
-use crate::events::{EngineEvent, EnvironmentStep, EventDetails, EventMessage, Stage, Transmitter};
+use crate::errors::{CommandError, EngineError, Tag};
+use crate::events::{
+    AuditOutcome, EngineEvent, EnvironmentStep, EventDetails, EventMessage, GeneralStep, Stage, Transmitter,
+};
 use crate::io_models::{
     EnvironmentError, ListenersHelper, ProgressInfo, ProgressLevel, ProgressScope, QoveryIdentifier,
 };
@@ -22,7 +29,7 @@ pub struct Transaction<'a> {
     steps: Vec<Step>,
     executed_steps: Vec<Step>,
     current_step: StepName,
-    is_transaction_aborted: Box<dyn Fn() -> bool>,
+    is_transaction_aborted: Box<dyn Fn() -> bool + Send + Sync>,
     on_step_change: Box<dyn Fn(&StepName)>,
 }
 
@@ -30,7 +37,7 @@ impl<'a> Transaction<'a> {
     pub fn new(
         engine: &'a EngineConfig,
         logger: Box<dyn Logger>,
-        is_transaction_aborted: Box<dyn Fn() -> bool>,
+        is_transaction_aborted: Box<dyn Fn() -> bool + Send + Sync>,
         on_step_change: Box<dyn Fn(&StepName)>,
     ) -> Result<Self, EngineConfigError> {
         let _ = engine.is_valid()?;
@@ -65,6 +72,19 @@ impl<'a> Transaction<'a> {
         )
     }
 
+    /// Transaction start and end are logged along with who or what triggered the request, when known.
+    fn log_audit_event(&self, outcome: AuditOutcome) {
+        if let Some(audit_context) = self.engine.context().audit_context() {
+            let kubernetes = self.engine.kubernetes();
+            let event_details = self.get_event_details(
+                Stage::General(GeneralStep::Audit),
+                Transmitter::Kubernetes(kubernetes.id().to_string(), kubernetes.name().to_string()),
+            );
+            self.logger
+                .log(EngineEvent::Audit(event_details, audit_context.clone(), outcome));
+        }
+    }
+
     pub fn set_current_step(&mut self, step: StepName) {
         (self.on_step_change)(&step);
         self.current_step = step;
@@ -91,6 +111,8 @@ impl<'a> Transaction<'a> {
             DeploymentOption {
                 force_build: false,
                 force_push: false,
+                max_parallel_builds: None,
+                readiness_gate: None,
             },
         )
     }
@@ -110,11 +132,17 @@ impl<'a> Transaction<'a> {
         environment: &Rc<RefCell<Environment>>,
         option: DeploymentOption,
     ) -> Result<(), EnvironmentError> {
+        // services have to be deployed after the ones they depend on
+        environment.borrow_mut().sort_services_by_dependencies()?;
+
+        let readiness_gate = option.readiness_gate.clone();
+
         // add build step
         self.build_environment(environment, option)?;
 
         // add deployment step
-        self.steps.push(Step::DeployEnvironment(environment.clone()));
+        self.steps
+            .push(Step::DeployEnvironment(environment.clone(), readiness_gate));
 
         Ok(())
     }
@@ -158,20 +186,11 @@ impl<'a> Transaction<'a> {
             to_engine_error(event_details, err)
         };
 
-        let build_event_details = || -> EventDetails {
-            self.get_event_details(
-                Stage::Environment(EnvironmentStep::Build),
-                Transmitter::BuildPlatform(
-                    self.engine.build_platform().id().to_string(),
-                    self.engine.build_platform().name().to_string(),
-                ),
-            )
-        };
-
         // Do setup of registry and be sure we are login to the registry
         let cr_registry = self.engine.container_registry();
         let _ = cr_registry.create_registry().map_err(cr_to_engine_error)?;
 
+        let mut builds: Vec<(String, &mut Build)> = Vec::with_capacity(apps_to_build.len());
         for app in apps_to_build.iter_mut() {
             // If image already exist in the registry, skip the build
             if !option.force_build && cr_registry.does_image_exists(&app.get_build().image) {
@@ -185,14 +204,30 @@ impl<'a> Transaction<'a> {
                 .create_repository(app.get_build().image.repository_name())
                 .map_err(cr_to_engine_error)?;
 
-            // Ok now everything is setup, we can try to build the app
-            let build_result = self
-                .engine
-                .build_platform()
-                .build(app.get_build_mut(), &self.is_transaction_aborted);
+            builds.push((app.id().to_string(), app.get_build_mut()));
+        }
+
+        // Ok now everything is setup, we can try to build the apps
+        let build_platform = self.engine.build_platform();
+        let is_transaction_aborted = &self.is_transaction_aborted;
+        let logger = &self.logger;
+        let execution_id = self.engine.context().execution_id();
+        let event_details = self.get_event_details(
+            Stage::Environment(EnvironmentStep::Build),
+            Transmitter::BuildPlatform(build_platform.id().to_string(), build_platform.name().to_string()),
+        );
+        let has_build_failed = AtomicBool::new(false);
+
+        let build_app = |(app_id, build): (String, &mut Build)| -> Option<(String, Result<(), EngineError>)> {
+            // Do not start new builds once one of them failed, the deployment will be aborted anyway
+            if has_build_failed.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            let build_result = build_platform.build(build, is_transaction_aborted.as_ref());
 
             // logging
-            let image_name = app.get_build().image.full_image_name_with_tag();
+            let image_name = build.image.full_image_name_with_tag();
             let msg = match &build_result {
                 Ok(_) => format!("✅ Container image {} is built and ready to use", &image_name),
                 Err(BuildError::Aborted(_)) => format!("🚫 Container image {} build has been canceled", &image_name),
@@ -200,27 +235,38 @@ impl<'a> Transaction<'a> {
             };
 
             let progress_info = ProgressInfo::new(
-                ProgressScope::Application {
-                    id: app.id().to_string(),
-                },
+                ProgressScope::Application { id: app_id.clone() },
                 match build_result.is_ok() {
                     true => ProgressLevel::Info,
                     false => ProgressLevel::Error,
                 },
                 Some(msg.to_string()),
-                self.engine.context().execution_id(),
+                execution_id,
             );
-            ListenersHelper::new(self.engine.build_platform().listeners()).deployment_in_progress(progress_info);
+            ListenersHelper::new(build_platform.listeners()).deployment_in_progress(progress_info);
 
-            let event_details = build_event_details();
-            self.logger
-                .log(EngineEvent::Info(event_details.clone(), EventMessage::new_from_safe(msg)));
+            logger.log(EngineEvent::Info(event_details.clone(), EventMessage::new_from_safe(msg)));
 
-            // Abort if it was an error
-            let _ = build_result.map_err(|err| crate::build_platform::to_engine_error(event_details, err))?;
-        }
+            if build_result.is_err() {
+                has_build_failed.store(true, Ordering::SeqCst);
+            }
 
-        Ok(())
+            Some((
+                app_id,
+                build_result
+                    .map(|_| ())
+                    .map_err(|err| crate::build_platform::to_engine_error(event_details.clone(), err)),
+            ))
+        };
+
+        // Serial by default, abort with the first error by application id whatever the builds completion order is
+        let max_parallel_builds = option.max_parallel_builds.unwrap_or(1);
+        let build_results = run_with_bounded_parallelism(builds, max_parallel_builds, &build_app)
+            .into_iter()
+            .flatten()
+            .collect();
+
+        first_error_by_service_id(build_results)
     }
 
     pub fn rollback(&self) -> Result<(), RollbackError> {
@@ -247,7 +293,7 @@ impl<'a> Transaction<'a> {
                 Step::BuildEnvironment(_environment_action, _option) => {
                     // revert build applications
                 }
-                Step::DeployEnvironment(environment_action) => {
+                Step::DeployEnvironment(environment_action, _readiness_gate) => {
                     // revert environment deployment
                     self.rollback_environment(&(environment_action.as_ref().borrow()))?;
                 }
@@ -282,6 +328,14 @@ impl<'a> Transaction<'a> {
     }
 
     pub fn commit(mut self) -> TransactionResult {
+        self.log_audit_event(AuditOutcome::Started);
+        let result = self.commit_steps();
+        self.log_audit_event(audit_outcome(&result));
+
+        result
+    }
+
+    fn commit_steps(&mut self) -> TransactionResult {
         for step in self.steps.clone().into_iter() {
             // execution loop
             self.executed_steps.push(step.clone());
@@ -341,14 +395,20 @@ impl<'a> Transaction<'a> {
                         }
                     };
                 }
-                Step::DeployEnvironment(environment_action) => {
+                Step::DeployEnvironment(environment_action, readiness_gate) => {
                     if (self.is_transaction_aborted)() {
                         return TransactionResult::Canceled;
                     }
 
                     // deploy complete environment
                     match self.commit_environment(&(environment_action.as_ref().borrow()), |qe_env| {
-                        self.engine.kubernetes().deploy_environment(qe_env)
+                        self.engine.kubernetes().deploy_environment(qe_env)?;
+
+                        // helm being done does not mean applications are serving, wait for them if asked
+                        match &readiness_gate {
+                            Some(readiness_gate) => self.wait_for_applications_readiness(qe_env, readiness_gate),
+                            None => Ok(()),
+                        }
                     }) {
                         TransactionResult::Ok => {}
                         err => {
@@ -395,6 +455,47 @@ impl<'a> Transaction<'a> {
         TransactionResult::Ok
     }
 
+    fn wait_for_applications_readiness(
+        &self,
+        environment: &Environment,
+        readiness_gate: &ReadinessGate,
+    ) -> Result<(), EngineError> {
+        let kubernetes = self.engine.kubernetes();
+        let kubernetes_config_file_path = kubernetes.get_kubeconfig_file_path()?;
+        let envs = kubernetes.cloud_provider().credentials_environment_variables();
+
+        for application in environment
+            .applications
+            .iter()
+            .filter(|app| *app.action() == Action::Create)
+        {
+            let selector = match application.selector() {
+                Some(selector) => selector,
+                None => continue,
+            };
+
+            let _ = wait_until_ready(readiness_gate, || {
+                kubectl_exec_is_pod_ready(
+                    &kubernetes_config_file_path,
+                    environment.namespace(),
+                    &selector,
+                    envs.clone(),
+                )
+                .map(|is_ready| is_ready == Some(true))
+            })
+            .map_err(|err| {
+                EngineError::new_k8s_pod_not_ready(
+                    application.get_event_details(Stage::Environment(EnvironmentStep::Deploy)),
+                    selector.to_string(),
+                    environment.namespace().to_string(),
+                    err,
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
     fn commit_infrastructure(&self, action: Action, result: Result<(), EngineError>) -> TransactionResult {
         // send back the right progress status
         fn send_progress(lh: &ListenersHelper, action: Action, execution_id: &str, is_error: bool) {
@@ -533,6 +634,95 @@ impl<'a> Transaction<'a> {
 pub struct DeploymentOption {
     pub force_build: bool,
     pub force_push: bool,
+    /// Maximum number of applications built at the same time, builds are done one by one if not set
+    pub max_parallel_builds: Option<usize>,
+    /// Wait for applications to be ready once deployed, the deployment is rolled back if they are not in time
+    pub readiness_gate: Option<ReadinessGate>,
+}
+
+#[derive(Clone)]
+pub struct ReadinessGate {
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+/// Poll `is_ready` until it returns true, or fail once the readiness gate timeout is reached.
+fn wait_until_ready<F>(readiness_gate: &ReadinessGate, is_ready: F) -> Result<(), CommandError>
+where
+    F: Fn() -> Result<bool, CommandError>,
+{
+    let started_at = Instant::now();
+
+    loop {
+        if is_ready()? {
+            return Ok(());
+        }
+
+        if started_at.elapsed() + readiness_gate.poll_interval > readiness_gate.timeout {
+            return Err(CommandError::new_from_safe_message(format!(
+                "not ready after {} seconds",
+                readiness_gate.timeout.as_secs()
+            )));
+        }
+
+        thread::sleep(readiness_gate.poll_interval);
+    }
+}
+
+/// Returns the error of the service with the lowest id, so the reported error doesn't depend on the order services
+/// have been processed in.
+fn first_error_by_service_id<E>(results: Vec<(String, Result<(), E>)>) -> Result<(), E> {
+    let mut errors = results
+        .into_iter()
+        .filter_map(|(service_id, result)| result.err().map(|err| (service_id, err)))
+        .collect::<Vec<_>>();
+    errors.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    match errors.into_iter().next() {
+        Some((_, err)) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Apply `f` to every item, with at most `max_parallelism` items processed at the same time.
+/// Results are returned in the same order as the items, whatever the order they have been processed in.
+fn run_with_bounded_parallelism<T, R, F>(items: Vec<T>, max_parallelism: usize, f: &F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let nb_workers = max_parallelism.min(items.len());
+    if nb_workers <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let nb_items = items.len();
+    let pending_items = Mutex::new(items.into_iter().enumerate());
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..nb_items).map(|_| None).collect());
+
+    crossbeam_utils::thread::scope(|scope| {
+        for _ in 0..nb_workers {
+            scope.spawn(|_| loop {
+                let next_item = pending_items.lock().unwrap().next();
+                match next_item {
+                    Some((idx, item)) => {
+                        let result = f(item);
+                        results.lock().unwrap()[idx] = Some(result);
+                    }
+                    None => break,
+                }
+            });
+        }
+    })
+    .expect("a worker thread panicked");
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item should have been processed"))
+        .collect()
 }
 
 #[derive(Clone)]
@@ -568,7 +758,7 @@ pub enum Step {
     DeleteKubernetes,
     PauseKubernetes,
     BuildEnvironment(Rc<RefCell<Environment>>, DeploymentOption),
-    DeployEnvironment(Rc<RefCell<Environment>>),
+    DeployEnvironment(Rc<RefCell<Environment>>, Option<ReadinessGate>),
     PauseEnvironment(Rc<RefCell<Environment>>),
     DeleteEnvironment(Rc<RefCell<Environment>>),
 }
@@ -580,7 +770,7 @@ impl Step {
             Step::DeleteKubernetes => StepName::DeleteKubernetes,
             Step::PauseKubernetes => StepName::PauseKubernetes,
             Step::BuildEnvironment(_, _) => StepName::BuildEnvironment,
-            Step::DeployEnvironment(_) => StepName::DeployEnvironment,
+            Step::DeployEnvironment(_, _) => StepName::DeployEnvironment,
             Step::PauseEnvironment(_) => StepName::PauseEnvironment,
             Step::DeleteEnvironment(_) => StepName::DeleteEnvironment,
         }
@@ -594,7 +784,7 @@ impl Clone for Step {
             Step::DeleteKubernetes => Step::DeleteKubernetes,
             Step::PauseKubernetes => Step::PauseKubernetes,
             Step::BuildEnvironment(e, option) => Step::BuildEnvironment(e.clone(), option.clone()),
-            Step::DeployEnvironment(e) => Step::DeployEnvironment(e.clone()),
+            Step::DeployEnvironment(e, readiness_gate) => Step::DeployEnvironment(e.clone(), readiness_gate.clone()),
             Step::PauseEnvironment(e) => Step::PauseEnvironment(e.clone()),
             Step::DeleteEnvironment(e) => Step::DeleteEnvironment(e.clone()),
         }
@@ -615,3 +805,341 @@ pub enum TransactionResult {
     Rollback(EngineError),
     UnrecoverableError(EngineError, RollbackError),
 }
+
+fn audit_outcome(result: &TransactionResult) -> AuditOutcome {
+    match result {
+        TransactionResult::Ok => AuditOutcome::Succeeded,
+        TransactionResult::Canceled => AuditOutcome::Canceled,
+        TransactionResult::Rollback(_) => AuditOutcome::RolledBack,
+        TransactionResult::UnrecoverableError(_, _) => AuditOutcome::Failed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build_platform::local_docker::LocalDocker;
+    use crate::build_platform::Image;
+    use crate::cloud_provider::digitalocean::DO;
+    use crate::cloud_provider::kubernetes::test_utils::TestKubernetes;
+    use crate::cloud_provider::kubernetes::Kind as KubernetesKind;
+    use crate::cloud_provider::{CloudProvider, TerraformStateCredentials};
+    use crate::cmd::docker::Docker;
+    use crate::container_registry::errors::ContainerRegistryError;
+    use crate::container_registry::{ContainerRegistry, ContainerRegistryInfo, Kind as ContainerRegistryKind};
+    use crate::dns_provider::cloudflare::Cloudflare;
+    use crate::dns_provider::DnsProvider;
+    use crate::engine::EngineConfig;
+    use crate::errors::CommandError;
+    use crate::events::{AuditOutcome, EngineEvent, EventMessageVerbosity, GeneralStep, Stage};
+    use crate::io_models::{AuditContext, Context, Domain, Listen, Listener, Listeners, Metadata};
+    use crate::logger::{Logger, StdIoLogger};
+    use crate::transaction::{
+        audit_outcome, first_error_by_service_id, run_with_bounded_parallelism, wait_until_ready, ReadinessGate,
+        Transaction, TransactionResult,
+    };
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+    use url::Url;
+    use uuid::Uuid;
+
+    struct MockBuilder {
+        running_builds: AtomicUsize,
+        max_running_builds: AtomicUsize,
+    }
+
+    impl MockBuilder {
+        fn build(&self, app_id: &str) -> Result<String, String> {
+            let running_builds = self.running_builds.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running_builds.fetch_max(running_builds, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            self.running_builds.fetch_sub(1, Ordering::SeqCst);
+
+            match app_id.starts_with("failing") {
+                true => Err(format!("{} failed", app_id)),
+                false => Ok(format!("{} built", app_id)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_with_bounded_parallelism() {
+        let app_ids = vec![
+            "app-1",
+            "failing-app-2",
+            "app-3",
+            "failing-app-4",
+            "app-5",
+            "app-6",
+            "app-7",
+        ];
+
+        for max_parallelism in vec![1, 3, 10] {
+            // setup:
+            let builder = MockBuilder {
+                running_builds: AtomicUsize::new(0),
+                max_running_builds: AtomicUsize::new(0),
+            };
+
+            // execute:
+            let results =
+                run_with_bounded_parallelism(app_ids.clone(), max_parallelism, &|app_id| builder.build(app_id));
+
+            // verify:
+            let max_running_builds = builder.max_running_builds.load(Ordering::SeqCst);
+            assert!(max_running_builds <= max_parallelism);
+            assert!(max_running_builds >= 1);
+            assert_eq!(results.len(), app_ids.len());
+            for (app_id, result) in app_ids.iter().zip(results.iter()) {
+                assert_eq!(result.is_err(), app_id.starts_with("failing"));
+            }
+            assert_eq!(
+                results.into_iter().find(|r| r.is_err()),
+                Some(Err("failing-app-2 failed".to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_first_error_by_service_id() {
+        // setup: results in completion order
+        let results = vec![
+            ("app-3".to_string(), Ok(())),
+            ("app-4".to_string(), Err("app-4 failed")),
+            ("app-1".to_string(), Ok(())),
+            ("app-2".to_string(), Err("app-2 failed")),
+        ];
+
+        // execute & verify:
+        assert_eq!(Err("app-2 failed"), first_error_by_service_id(results.clone()));
+        assert_eq!(
+            Err("app-2 failed"),
+            first_error_by_service_id(results.iter().rev().cloned().collect())
+        );
+        assert_eq!(
+            Ok(()),
+            first_error_by_service_id::<&str>(vec![("app-1".to_string(), Ok(())), ("app-3".to_string(), Ok(()))])
+        );
+    }
+
+    #[test]
+    fn test_wait_until_ready() {
+        // setup:
+        let readiness_gate = ReadinessGate {
+            timeout: Duration::from_millis(100),
+            poll_interval: Duration::from_millis(10),
+        };
+        let polls = Cell::new(0);
+        let ready_on_second_poll = || -> Result<bool, CommandError> {
+            polls.set(polls.get() + 1);
+            Ok(polls.get() >= 2)
+        };
+
+        // execute:
+        let result = wait_until_ready(&readiness_gate, ready_on_second_poll);
+
+        // verify:
+        assert!(result.is_ok());
+        assert_eq!(polls.get(), 2);
+
+        // never ready, timeout is reached
+        assert!(wait_until_ready(&readiness_gate, || Ok(false)).is_err());
+
+        // polling error is returned as is
+        let polling_error = CommandError::new_from_safe_message("kubectl error".to_string());
+        assert_eq!(
+            wait_until_ready(&readiness_gate, || Err(polling_error.clone())),
+            Err(polling_error)
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct RecordingLogger {
+        events: Arc<Mutex<Vec<EngineEvent>>>,
+    }
+
+    impl Logger for RecordingLogger {
+        fn log(&self, event: EngineEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn clone_dyn(&self) -> Box<dyn Logger> {
+            Box::new(self.clone())
+        }
+    }
+
+    /// Registry never reached, DOCR logs in to docker as soon as it is created.
+    struct TestContainerRegistry {
+        context: Context,
+        listeners: Listeners,
+        registry_info: ContainerRegistryInfo,
+    }
+
+    impl Listen for TestContainerRegistry {
+        fn listeners(&self) -> &Listeners {
+            &self.listeners
+        }
+
+        fn add_listener(&mut self, listener: Listener) {
+            self.listeners.push(listener);
+        }
+    }
+
+    impl ContainerRegistry for TestContainerRegistry {
+        fn context(&self) -> &Context {
+            &self.context
+        }
+
+        fn kind(&self) -> ContainerRegistryKind {
+            ContainerRegistryKind::Docr
+        }
+
+        fn id(&self) -> &str {
+            "registry-id"
+        }
+
+        fn name(&self) -> &str {
+            "test-registry"
+        }
+
+        fn registry_info(&self) -> &ContainerRegistryInfo {
+            &self.registry_info
+        }
+
+        fn create_registry(&self) -> Result<(), ContainerRegistryError> {
+            unimplemented!()
+        }
+
+        fn create_repository(&self, _repository_name: &str) -> Result<(), ContainerRegistryError> {
+            unimplemented!()
+        }
+
+        fn does_image_exists(&self, _image: &Image) -> bool {
+            unimplemented!()
+        }
+    }
+
+    fn test_engine_config(audit_context: Option<AuditContext>) -> EngineConfig {
+        let context = Context::new(
+            "organization-id".to_string(),
+            "cluster-id".to_string(),
+            "execution-id".to_string(),
+            "/tmp".to_string(),
+            "lib".to_string(),
+            true,
+            None,
+            vec![],
+            Some(Metadata::new(None, None, None, None, None, None, audit_context)),
+            Docker::new_with_options(false, None).expect("docker without buildkit should be created"),
+        );
+        let build_platform = LocalDocker::new(
+            context.clone(),
+            "build-platform-id",
+            "test-build-platform",
+            Box::new(StdIoLogger::new()),
+        )
+        .expect("local docker should be created");
+        let container_registry = TestContainerRegistry {
+            context: context.clone(),
+            listeners: vec![],
+            registry_info: ContainerRegistryInfo {
+                endpoint: Url::parse("https://registry.digitalocean.com").expect("registry url should be valid"),
+                registry_name: "test-registry".to_string(),
+                registry_docker_json_config: None,
+                get_image_name: Box::new(|image_name| image_name.to_string()),
+                get_repository_name: Box::new(|image_name| image_name.to_string()),
+            },
+        };
+        let cloud_provider: Box<dyn CloudProvider> = Box::new(DO::new(
+            context.clone(),
+            "cloud-provider-id",
+            "organization-id",
+            Uuid::nil(),
+            "do-token",
+            "spaces-access-id",
+            "spaces-secret-key",
+            "test-cloud-provider",
+            TerraformStateCredentials::new("access-key-id", "secret-access-key", "eu-west-3"),
+        ));
+        let dns_provider: Box<dyn DnsProvider> = Box::new(Cloudflare::new(
+            context.clone(),
+            "dns-provider-id",
+            "test-dns-provider",
+            Domain::new("example.com".to_string()),
+            "cloudflare-api-token",
+            "jane@qovery.com",
+        ));
+        let kubernetes = TestKubernetes::new(context.clone(), KubernetesKind::Doks);
+
+        EngineConfig::new(
+            context,
+            Box::new(build_platform),
+            Box::new(container_registry),
+            Arc::new(cloud_provider),
+            Arc::new(dns_provider),
+            Box::new(kubernetes),
+        )
+    }
+
+    #[test]
+    fn test_commit_logs_audit_events() {
+        // setup:
+        let audit_context = AuditContext {
+            actor: "jane@qovery.com".to_string(),
+            trigger: "git-push".to_string(),
+            request_id: "f1a2b3c4".to_string(),
+        };
+        let engine_config = test_engine_config(Some(audit_context.clone()));
+        let logger = RecordingLogger::default();
+        let tx = Transaction::new(&engine_config, Box::new(logger.clone()), Box::new(|| false), Box::new(|_| {}))
+            .expect("transaction should be created");
+
+        // execute:
+        let result = tx.commit();
+
+        // verify:
+        assert!(matches!(result, TransactionResult::Ok));
+        let events = logger.events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        let outcomes = events
+            .iter()
+            .map(|event| match event {
+                EngineEvent::Audit(details, event_audit_context, outcome) => {
+                    assert_eq!(details.stage(), &Stage::General(GeneralStep::Audit));
+                    assert_eq!(event_audit_context, &audit_context);
+                    *outcome
+                }
+                _ => panic!("only audit events are expected, got {:?}", event),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(outcomes, vec![AuditOutcome::Started, AuditOutcome::Succeeded]);
+        assert_eq!(
+            events[0].message(EventMessageVerbosity::SafeOnly),
+            "Transaction started: actor=`jane@qovery.com`, trigger=`git-push`, request_id=`f1a2b3c4`"
+        );
+    }
+
+    #[test]
+    fn test_commit_without_audit_context() {
+        // setup:
+        let engine_config = test_engine_config(None);
+        let logger = RecordingLogger::default();
+        let tx = Transaction::new(&engine_config, Box::new(logger.clone()), Box::new(|| false), Box::new(|_| {}))
+            .expect("transaction should be created");
+
+        // execute:
+        let result = tx.commit();
+
+        // verify:
+        assert!(matches!(result, TransactionResult::Ok));
+        assert!(logger.events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_audit_outcome() {
+        assert_eq!(audit_outcome(&TransactionResult::Ok), AuditOutcome::Succeeded);
+        assert_eq!(audit_outcome(&TransactionResult::Canceled), AuditOutcome::Canceled);
+    }
+}
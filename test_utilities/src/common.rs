@@ -8,7 +8,7 @@ use qovery_engine::cloud_provider::utilities::sanitize_name;
 use qovery_engine::dns_provider::DnsProvider;
 use qovery_engine::io_models::{
     Action, Application, CloneForTest, Context, Database, DatabaseKind, DatabaseMode, EnvironmentRequest,
-    GitCredentials, Port, Protocol, Route, Router, Storage, StorageType,
+    GitCredentials, Port, Protocol, ReclaimPolicy, Route, Router, Storage, StorageType, TlsVersion,
 };
 
 use crate::aws::{AWS_KUBERNETES_VERSION, AWS_TEST_REGION};
@@ -125,6 +125,8 @@ impl Infrastructure for EnvironmentRequest {
             DeploymentOption {
                 force_build: true,
                 force_push: true,
+                max_parallel_builds: None,
+                readiness_gate: None,
             },
         );
 
@@ -154,6 +156,8 @@ impl Infrastructure for EnvironmentRequest {
             DeploymentOption {
                 force_build: true,
                 force_push: true,
+                max_parallel_builds: None,
+                readiness_gate: None,
             },
         );
 
@@ -278,6 +282,7 @@ pub fn environment_3_apps_3_routers_3_databases(
                     size_in_gib: 10,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }],
                 environment_vars: btreemap! {
                      "PG_DBNAME".to_string() => base64::encode(database_name.clone()),
@@ -302,6 +307,19 @@ pub fn environment_3_apps_3_routers_3_databases(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advance_settings: Default::default(),
+                depends_on: vec![],
+                network_policy: None,
+                lifecycle: None,
+                security_context: None,
+                config_mounts: vec![],
+                host_aliases: vec![],
+                topology_spread: vec![],
+                startup_probe: None,
+                metrics: None,
+                cpu_request: None,
+                cpu_limit: None,
+                memory_request_mib: None,
+                memory_limit_mib: None,
             },
             Application {
                 long_id: Uuid::new_v4(),
@@ -325,6 +343,7 @@ pub fn environment_3_apps_3_routers_3_databases(
                     size_in_gib: 10,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }],
                 environment_vars: btreemap! {
                      "PG_DBNAME".to_string() => base64::encode(database_name_2.clone()),
@@ -349,6 +368,19 @@ pub fn environment_3_apps_3_routers_3_databases(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advance_settings: Default::default(),
+                depends_on: vec![],
+                network_policy: None,
+                lifecycle: None,
+                security_context: None,
+                config_mounts: vec![],
+                host_aliases: vec![],
+                topology_spread: vec![],
+                startup_probe: None,
+                metrics: None,
+                cpu_request: None,
+                cpu_limit: None,
+                memory_request_mib: None,
+                memory_limit_mib: None,
             },
             Application {
                 long_id: Uuid::new_v4(),
@@ -372,6 +404,7 @@ pub fn environment_3_apps_3_routers_3_databases(
                     size_in_gib: 10,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }],
                 environment_vars: btreemap! {
                     "IS_DOCUMENTDB".to_string() => base64::encode("false"),
@@ -398,6 +431,19 @@ pub fn environment_3_apps_3_routers_3_databases(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advance_settings: Default::default(),
+                depends_on: vec![],
+                network_policy: None,
+                lifecycle: None,
+                security_context: None,
+                config_mounts: vec![],
+                host_aliases: vec![],
+                topology_spread: vec![],
+                startup_probe: None,
+                metrics: None,
+                cpu_request: None,
+                cpu_limit: None,
+                memory_request_mib: None,
+                memory_limit_mib: None,
             },
         ],
         routers: vec![
@@ -413,6 +459,13 @@ pub fn environment_3_apps_3_routers_3_databases(
                     application_name: app_name_1.clone(),
                 }],
                 sticky_sessions_enabled: false,
+                proxy_read_timeout_seconds: None,
+                proxy_body_size_mb: None,
+                certificate_ready_timeout_seconds: None,
+                whitelist_source_ranges: vec![],
+                min_tls_version: TlsVersion::default(),
+                hsts: None,
+                blue_green: None,
             },
             Router {
                 long_id: Uuid::new_v4(),
@@ -426,6 +479,13 @@ pub fn environment_3_apps_3_routers_3_databases(
                     application_name: app_name_2.clone(),
                 }],
                 sticky_sessions_enabled: false,
+                proxy_read_timeout_seconds: None,
+                proxy_body_size_mb: None,
+                certificate_ready_timeout_seconds: None,
+                whitelist_source_ranges: vec![],
+                min_tls_version: TlsVersion::default(),
+                hsts: None,
+                blue_green: None,
             },
             Router {
                 long_id: Uuid::new_v4(),
@@ -439,6 +499,13 @@ pub fn environment_3_apps_3_routers_3_databases(
                     application_name: app_name_3.clone(),
                 }],
                 sticky_sessions_enabled: false,
+                proxy_read_timeout_seconds: None,
+                proxy_body_size_mb: None,
+                certificate_ready_timeout_seconds: None,
+                whitelist_source_ranges: vec![],
+                min_tls_version: TlsVersion::default(),
+                hsts: None,
+                blue_green: None,
             },
         ],
         databases: vec![
@@ -463,6 +530,13 @@ pub fn environment_3_apps_3_routers_3_databases(
                 activate_backups: false,
                 publicly_accessible: false,
                 mode: CONTAINER,
+                managed: None,
+                allowed_cidrs: vec![],
+                storage_class_name: None,
+                disk_iops: None,
+                deletion_protection: false,
+                region: None,
+                zone: None,
             },
             Database {
                 kind: DatabaseKind::Postgresql,
@@ -485,6 +559,13 @@ pub fn environment_3_apps_3_routers_3_databases(
                 activate_backups: false,
                 publicly_accessible: false,
                 mode: CONTAINER,
+                managed: None,
+                allowed_cidrs: vec![],
+                storage_class_name: None,
+                disk_iops: None,
+                deletion_protection: false,
+                region: None,
+                zone: None,
             },
             Database {
                 kind: DatabaseKind::Mongodb,
@@ -507,9 +588,18 @@ pub fn environment_3_apps_3_routers_3_databases(
                 activate_backups: false,
                 publicly_accessible: false,
                 mode: CONTAINER,
+                managed: None,
+                allowed_cidrs: vec![],
+                storage_class_name: None,
+                disk_iops: None,
+                deletion_protection: false,
+                region: None,
+                zone: None,
             },
         ],
         clone_from_environment_id: None,
+        resource_quota: None,
+        dns_check: None,
     }
 }
 
@@ -558,6 +648,19 @@ pub fn working_minimal_environment(context: &Context, test_domain: &str) -> Envi
             max_instances: 2,
             cpu_burst: "100m".to_string(),
             advance_settings: Default::default(),
+            depends_on: vec![],
+            network_policy: None,
+            lifecycle: None,
+            security_context: None,
+            config_mounts: vec![],
+            host_aliases: vec![],
+            topology_spread: vec![],
+            startup_probe: None,
+            metrics: None,
+            cpu_request: None,
+            cpu_limit: None,
+            memory_request_mib: None,
+            memory_limit_mib: None,
         }],
         routers: vec![Router {
             long_id: Uuid::new_v4(),
@@ -571,9 +674,18 @@ pub fn working_minimal_environment(context: &Context, test_domain: &str) -> Envi
                 application_name: format!("{}-{}", "simple-app".to_string(), &suffix),
             }],
             sticky_sessions_enabled: false,
+            proxy_read_timeout_seconds: None,
+            proxy_body_size_mb: None,
+            certificate_ready_timeout_seconds: None,
+            whitelist_source_ranges: vec![],
+            min_tls_version: TlsVersion::default(),
+            hsts: None,
+            blue_green: None,
         }],
         databases: vec![],
         clone_from_environment_id: None,
+        resource_quota: None,
+        dns_check: None,
     }
 }
 
@@ -612,10 +724,25 @@ pub fn database_test_environment(context: &Context) -> EnvironmentRequest {
             max_instances: 1,
             cpu_burst: "100m".to_string(),
             advance_settings: Default::default(),
+            depends_on: vec![],
+            network_policy: None,
+            lifecycle: None,
+            security_context: None,
+            config_mounts: vec![],
+            host_aliases: vec![],
+            topology_spread: vec![],
+            startup_probe: None,
+            metrics: None,
+            cpu_request: None,
+            cpu_limit: None,
+            memory_request_mib: None,
+            memory_limit_mib: None,
         }],
         routers: vec![],
         databases: vec![],
         clone_from_environment_id: None,
+        resource_quota: None,
+        dns_check: None,
     }
 }
 
@@ -678,6 +805,13 @@ pub fn environnement_2_app_2_routers_1_psql(
             activate_backups: false,
             publicly_accessible: false,
             mode: CONTAINER,
+            managed: None,
+            allowed_cidrs: vec![],
+            storage_class_name: None,
+            disk_iops: None,
+            deletion_protection: false,
+            region: None,
+            zone: None,
         }],
         applications: vec![
             Application {
@@ -702,6 +836,7 @@ pub fn environnement_2_app_2_routers_1_psql(
                     size_in_gib: 10,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }],
                 environment_vars: btreemap! {
                      "PG_DBNAME".to_string() => base64::encode(database_name.clone()),
@@ -726,6 +861,19 @@ pub fn environnement_2_app_2_routers_1_psql(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advance_settings: Default::default(),
+                depends_on: vec![],
+                network_policy: None,
+                lifecycle: None,
+                security_context: None,
+                config_mounts: vec![],
+                host_aliases: vec![],
+                topology_spread: vec![],
+                startup_probe: None,
+                metrics: None,
+                cpu_request: None,
+                cpu_limit: None,
+                memory_request_mib: None,
+                memory_limit_mib: None,
             },
             Application {
                 long_id: Uuid::new_v4(),
@@ -749,6 +897,7 @@ pub fn environnement_2_app_2_routers_1_psql(
                     size_in_gib: 10,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }],
                 environment_vars: btreemap! {
                      "PG_DBNAME".to_string() => base64::encode(database_name.clone()),
@@ -773,6 +922,19 @@ pub fn environnement_2_app_2_routers_1_psql(
                 max_instances: 2,
                 cpu_burst: "100m".to_string(),
                 advance_settings: Default::default(),
+                depends_on: vec![],
+                network_policy: None,
+                lifecycle: None,
+                security_context: None,
+                config_mounts: vec![],
+                host_aliases: vec![],
+                topology_spread: vec![],
+                startup_probe: None,
+                metrics: None,
+                cpu_request: None,
+                cpu_limit: None,
+                memory_request_mib: None,
+                memory_limit_mib: None,
             },
         ],
         routers: vec![
@@ -788,6 +950,13 @@ pub fn environnement_2_app_2_routers_1_psql(
                     application_name: application_name1.to_string(),
                 }],
                 sticky_sessions_enabled: false,
+                proxy_read_timeout_seconds: None,
+                proxy_body_size_mb: None,
+                certificate_ready_timeout_seconds: None,
+                whitelist_source_ranges: vec![],
+                min_tls_version: TlsVersion::default(),
+                hsts: None,
+                blue_green: None,
             },
             Router {
                 long_id: Uuid::new_v4(),
@@ -801,9 +970,18 @@ pub fn environnement_2_app_2_routers_1_psql(
                     application_name: application_name2.to_string(),
                 }],
                 sticky_sessions_enabled: false,
+                proxy_read_timeout_seconds: None,
+                proxy_body_size_mb: None,
+                certificate_ready_timeout_seconds: None,
+                whitelist_source_ranges: vec![],
+                min_tls_version: TlsVersion::default(),
+                hsts: None,
+                blue_green: None,
             },
         ],
         clone_from_environment_id: None,
+        resource_quota: None,
+        dns_check: None,
     }
 }
 
@@ -870,6 +1048,19 @@ pub fn echo_app_environment(context: &Context, test_domain: &str) -> Environment
             max_instances: 2,
             cpu_burst: "100m".to_string(),
             advance_settings: Default::default(),
+            depends_on: vec![],
+            network_policy: None,
+            lifecycle: None,
+            security_context: None,
+            config_mounts: vec![],
+            host_aliases: vec![],
+            topology_spread: vec![],
+            startup_probe: None,
+            metrics: None,
+            cpu_request: None,
+            cpu_limit: None,
+            memory_request_mib: None,
+            memory_limit_mib: None,
         }],
         routers: vec![Router {
             long_id: Uuid::new_v4(),
@@ -883,9 +1074,18 @@ pub fn echo_app_environment(context: &Context, test_domain: &str) -> Environment
                 application_name: format!("{}-{}", "echo-app".to_string(), &suffix),
             }],
             sticky_sessions_enabled: false,
+            proxy_read_timeout_seconds: None,
+            proxy_body_size_mb: None,
+            certificate_ready_timeout_seconds: None,
+            whitelist_source_ranges: vec![],
+            min_tls_version: TlsVersion::default(),
+            hsts: None,
+            blue_green: None,
         }],
         databases: vec![],
         clone_from_environment_id: None,
+        resource_quota: None,
+        dns_check: None,
     }
 }
 
@@ -931,10 +1131,25 @@ pub fn environment_only_http_server(context: &Context) -> EnvironmentRequest {
             max_instances: 2,
             cpu_burst: "100m".to_string(),
             advance_settings: Default::default(),
+            depends_on: vec![],
+            network_policy: None,
+            lifecycle: None,
+            security_context: None,
+            config_mounts: vec![],
+            host_aliases: vec![],
+            topology_spread: vec![],
+            startup_probe: None,
+            metrics: None,
+            cpu_request: None,
+            cpu_limit: None,
+            memory_request_mib: None,
+            memory_limit_mib: None,
         }],
         routers: vec![],
         databases: vec![],
         clone_from_environment_id: None,
+        resource_quota: None,
+        dns_check: None,
     }
 }
 
@@ -981,6 +1196,19 @@ pub fn environment_only_http_server_router(context: &Context, test_domain: &str)
             max_instances: 2,
             cpu_burst: "100m".to_string(),
             advance_settings: Default::default(),
+            depends_on: vec![],
+            network_policy: None,
+            lifecycle: None,
+            security_context: None,
+            config_mounts: vec![],
+            host_aliases: vec![],
+            topology_spread: vec![],
+            startup_probe: None,
+            metrics: None,
+            cpu_request: None,
+            cpu_limit: None,
+            memory_request_mib: None,
+            memory_limit_mib: None,
         }],
         routers: vec![Router {
             long_id: Uuid::new_v4(),
@@ -994,9 +1222,18 @@ pub fn environment_only_http_server_router(context: &Context, test_domain: &str)
                 application_name: format!("{}-{}", "mini-http".to_string(), &suffix),
             }],
             sticky_sessions_enabled: false,
+            proxy_read_timeout_seconds: None,
+            proxy_body_size_mb: None,
+            certificate_ready_timeout_seconds: None,
+            whitelist_source_ranges: vec![],
+            min_tls_version: TlsVersion::default(),
+            hsts: None,
+            blue_green: None,
         }],
         databases: vec![],
         clone_from_environment_id: None,
+        resource_quota: None,
+        dns_check: None,
     }
 }
 
@@ -1104,6 +1341,19 @@ pub fn test_db(
         activate_backups: false,
         publicly_accessible: is_public.clone(),
         mode: database_mode.clone(),
+        managed: None,
+        allowed_cidrs: match is_public
+            && database_mode == DatabaseMode::MANAGED
+            && matches!(db_kind, DatabaseKind::Postgresql | DatabaseKind::Mysql)
+        {
+            true => vec!["0.0.0.0/0".to_string()],
+            false => vec![],
+        },
+        storage_class_name: None,
+        disk_iops: None,
+        deletion_protection: false,
+        region: None,
+        zone: None,
     };
 
     environment.databases = vec![db.clone()];
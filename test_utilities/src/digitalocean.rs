@@ -1,5 +1,8 @@
 use const_format::formatcp;
 use qovery_engine::cloud_provider::aws::kubernetes::VpcQoveryNetworkMode;
+use qovery_engine::cloud_provider::digitalocean::kubernetes::helm_charts::{
+    DoksDnsOptions, DoksEngineOptions, DoksIngressOptions, DoksMaintenanceOptions, DoksMonitoringOptions,
+};
 use qovery_engine::cloud_provider::digitalocean::kubernetes::DoksOptions;
 use qovery_engine::cloud_provider::digitalocean::network::vpc::VpcInitKind;
 use qovery_engine::cloud_provider::digitalocean::DO;
@@ -150,13 +153,20 @@ impl Cluster<DO, DoksOptions> for DO {
             engine_version_controller_token: secrets.QOVERY_ENGINE_CONTROLLER_TOKEN.unwrap(),
             agent_version_controller_token: secrets.QOVERY_AGENT_CONTROLLER_TOKEN.unwrap(),
             grafana_admin_user: "admin".to_string(),
-            grafana_admin_password: "qovery".to_string(),
             discord_api_key: secrets.DISCORD_API_URL.unwrap(),
             qovery_nats_url: secrets.QOVERY_NATS_URL.unwrap(),
             qovery_nats_user: secrets.QOVERY_NATS_USERNAME.unwrap(),
             qovery_nats_password: secrets.QOVERY_NATS_PASSWORD.unwrap(),
             qovery_ssh_key: secrets.QOVERY_SSH_USER.unwrap(),
             tls_email_report: secrets.LETS_ENCRYPT_EMAIL_REPORT.unwrap(),
+            engine: DoksEngineOptions::default(),
+            monitoring: DoksMonitoringOptions {
+                grafana_admin_password: "qovery".to_string(),
+                ..Default::default()
+            },
+            ingress: DoksIngressOptions::default(),
+            dns: DoksDnsOptions::default(),
+            maintenance: DoksMaintenanceOptions::default(),
         }
     }
 }
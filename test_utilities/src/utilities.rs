@@ -90,6 +90,9 @@ pub fn context(organization_id: &str, cluster_id: &str) -> Context {
             }
         }),
         disable_pleco: Some(true),
+        forced_deletion: None,
+        dns_check_tld_budgets: None,
+        audit_context: None,
     };
 
     let enabled_features = vec![Features::LogsHistory];
@@ -281,6 +281,13 @@ fn postgresql_deploy_a_working_environment_and_redeploy() {
             activate_backups: false,
             publicly_accessible: false,
             mode: CONTAINER,
+            managed: None,
+            allowed_cidrs: vec![],
+            storage_class_name: None,
+            disk_iops: None,
+            deletion_protection: false,
+            region: None,
+            zone: None,
         }];
         environment.applications = environment
             .applications
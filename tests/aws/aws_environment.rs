@@ -7,7 +7,7 @@ use self::test_utilities::utilities::{
 use ::function_name::named;
 use qovery_engine::cloud_provider::Kind;
 use qovery_engine::cmd::kubectl::kubernetes_get_all_pdbs;
-use qovery_engine::io_models::{Action, CloneForTest, Port, Protocol, Storage, StorageType};
+use qovery_engine::io_models::{Action, CloneForTest, Port, Protocol, ReclaimPolicy, Storage, StorageType};
 use qovery_engine::transaction::TransactionResult;
 use qovery_engine::utilities::to_short_id;
 use std::collections::BTreeMap;
@@ -592,6 +592,7 @@ fn deploy_a_working_environment_with_storage_on_aws_eks() {
                     size_in_gib: storage_size,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }];
                 app
             })
@@ -675,6 +676,7 @@ fn redeploy_same_app_with_ebs() {
                     size_in_gib: storage_size,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }];
                 app
             })
@@ -7,7 +7,7 @@ use self::test_utilities::utilities::{
 };
 use ::function_name::named;
 use qovery_engine::cloud_provider::Kind;
-use qovery_engine::io_models::{Action, CloneForTest, Port, Protocol, Storage, StorageType};
+use qovery_engine::io_models::{Action, CloneForTest, Port, Protocol, ReclaimPolicy, Storage, StorageType};
 use qovery_engine::transaction::TransactionResult;
 use qovery_engine::utilities::to_short_id;
 use std::collections::BTreeMap;
@@ -459,6 +459,7 @@ fn digitalocean_doks_deploy_a_working_environment_with_storage() {
                     size_in_gib: storage_size,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }];
                 app
             })
@@ -543,6 +544,7 @@ fn digitalocean_doks_redeploy_same_app() {
                     size_in_gib: storage_size,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }];
                 app
             })
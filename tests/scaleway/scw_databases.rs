@@ -298,6 +298,13 @@ fn postgresql_deploy_a_working_environment_and_redeploy() {
             total_ram_in_mib: 512,
             disk_size_in_gib: 10,
             mode: database_mode.clone(),
+            managed: None,
+            allowed_cidrs: vec![],
+            storage_class_name: None,
+            disk_iops: None,
+            deletion_protection: false,
+            region: None,
+            zone: None,
             database_instance_type: if database_mode == MANAGED {
                 SCW_MANAGED_DATABASE_INSTANCE_TYPE
             } else {
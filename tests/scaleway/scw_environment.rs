@@ -7,7 +7,7 @@ use self::test_utilities::utilities::{
 };
 use ::function_name::named;
 use qovery_engine::cloud_provider::Kind;
-use qovery_engine::io_models::{Action, CloneForTest, Port, Protocol, Storage, StorageType};
+use qovery_engine::io_models::{Action, CloneForTest, Port, Protocol, ReclaimPolicy, Storage, StorageType};
 use qovery_engine::transaction::TransactionResult;
 use qovery_engine::utilities::to_short_id;
 use std::collections::BTreeMap;
@@ -474,6 +474,7 @@ fn scaleway_kapsule_deploy_a_working_environment_with_storage() {
                     size_in_gib: storage_size,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }];
                 app
             })
@@ -643,6 +644,7 @@ fn scaleway_kapsule_redeploy_same_app() {
                     size_in_gib: storage_size,
                     mount_point: "/mnt/photos".to_string(),
                     snapshot_retention_in_days: 0,
+                    reclaim_policy: ReclaimPolicy::Delete,
                 }];
                 app
             })